@@ -99,4 +99,10 @@ class MamuApplication : Application() {
      * @return 初始化是否成功
      */
     private external fun initMamuCore(): Boolean
+
+    /**
+     * 各 Java 类的本地方法注册结果
+     * @return JSON 数组，每项包含 class、registered、methods、error
+     */
+    private external fun nativeGetJniRegistrationReport(): String
 }
\ No newline at end of file
@@ -8,10 +8,13 @@ import moe.fuqiuluo.mamu.floating.data.model.DisplayValueType
 import moe.fuqiuluo.mamu.floating.data.model.MemoryRange
 import java.nio.ByteBuffer
 import java.nio.ByteOrder
+import kotlin.text.HexFormat
 
 private const val TAG = "SearchEngine"
 
 object SearchEngine {
+    private val hexFormat = HexFormat { upperCase = true }
+
     /**
      * Shared buffer size in bytes.
      * Memory layout:
@@ -278,6 +281,59 @@ object SearchEngine {
         }
     }
 
+    /**
+     * Gets search results through a single packed byte blob instead of one JNI object per item.
+     *
+     * Layout (little-endian): 8-byte header `[mode:u8][version:u8][reserved:u16][count:u32]`,
+     * followed by `count` 32-byte records
+     * `[address:u64][index:u64][type:u16][flags:u8][valueLen:u8][reserved:4][value:8]`.
+     * `flags` bit 0 marks the value as valid.
+     */
+    fun getResultsPacked(start: Int, count: Int): Array<SearchResultItem> {
+        val buffer = ByteBuffer.wrap(nativePackResults(start, count)).order(ByteOrder.LITTLE_ENDIAN)
+        val mode = buffer.get(0).toInt()
+        val size = buffer.getInt(4)
+        return Array(size) { i ->
+            val offset = 8 + i * 32
+            val address = buffer.getLong(offset)
+            val index = buffer.getLong(offset + 8)
+            val type = buffer.getShort(offset + 16).toInt() and 0xFFFF
+            val valid = (buffer.get(offset + 18).toInt() and 1) != 0
+            val valueLen = buffer.get(offset + 19).toInt() and 0xFF
+            val value = if (valid) formatPackedValue(buffer, offset + 24, type, valueLen) else "N/A"
+            if (mode == 1) {
+                FuzzySearchResultItem(index, address, value, type)
+            } else {
+                ExactSearchResultItem(index, address, type, value)
+            }
+        }
+    }
+
+    /**
+     * Formats the first `valueLen` bytes of a packed record value.
+     *
+     * Oword and byte patterns are shown as hex. Text types carry their encoded length in the
+     * low 7 bits of the type id; only the first 8 bytes are transferred, so longer strings
+     * end with an ellipsis.
+     */
+    private fun formatPackedValue(buffer: ByteBuffer, offset: Int, type: Int, valueLen: Int): String {
+        val bytes = ByteArray(valueLen.coerceAtMost(8)) { buffer.get(offset + it) }
+        return when {
+            type == 8 || (type and 0x180) == 0x80 -> bytes.toHexString(hexFormat)
+            (type and 0x100) != 0 -> {
+                val charset = if ((type and 0x80) != 0) Charsets.UTF_16LE else Charsets.UTF_8
+                val text = String(bytes, charset)
+                if (bytes.size < (type and 0x7F)) "$text…" else text
+            }
+            type == 0 -> buffer.get(offset).toUByte().toString()
+            type == 1 -> buffer.getShort(offset).toUShort().toString()
+            type == 3 -> buffer.getLong(offset).toULong().toString()
+            type == 4 -> buffer.getFloat(offset).toString()
+            type == 5 -> buffer.getDouble(offset).toString()
+            else -> buffer.getInt(offset).toUInt().toString()
+        }
+    }
+
     /**
      * Gets total result count.
      */
@@ -369,6 +425,16 @@ object SearchEngine {
         return SearchMode.fromNativeValue(nativeValue)
     }
 
+    /**
+     * Switches the result storage mode.
+     * Changing the mode clears the current results and the refine history.
+     * @param mode EXACT stores address and type only, FUZZY also stores the value for later comparisons.
+     * @return true on success.
+     */
+    fun setResultMode(mode: SearchMode): Boolean {
+        return nativeSetResultMode(mode.nativeValue)
+    }
+
     /**
      * Sets compatibility mode.
      * When enabled, all search results are stored in fuzzy format,
@@ -387,6 +453,14 @@ object SearchEngine {
         return nativeGetCompatibilityMode()
     }
 
+    /**
+     * Gets the memory access mode that scanned the bound process fastest so far.
+     * @return Memory access mode id, the driver default when there are not enough scans yet.
+     */
+    fun getRecommendedMemoryMode(): Int {
+        return nativeGetRecommendedMemoryMode()
+    }
+
     /**
      * Starts an async fuzzy initial search. Records all values in memory regions.
      * @param type Data type to search for.
@@ -539,6 +613,7 @@ object SearchEngine {
     ): Long
 
     private external fun nativeGetResults(start: Int, count: Int): Array<SearchResultItem>
+    private external fun nativePackResults(start: Int, count: Int): ByteArray
     private external fun nativeGetTotalResultCount(): Long
     private external fun nativeClearSearchResults()
     private external fun nativeRemoveResult(index: Int): Boolean
@@ -554,8 +629,10 @@ object SearchEngine {
 
     private external fun nativeClearFilter()
     private external fun nativeGetCurrentSearchMode(): Int
+    private external fun nativeSetResultMode(mode: Int): Boolean
     private external fun nativeSetCompatibilityMode(enabled: Boolean)
     private external fun nativeGetCompatibilityMode(): Boolean
+    private external fun nativeGetRecommendedMemoryMode(): Int
     @Deprecated("同步搜索版本已废弃")
     private external fun nativeRefineSearch(
         query: String,
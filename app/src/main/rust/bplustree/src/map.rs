@@ -1,12 +1,14 @@
 use std::collections::VecDeque;
 use std::mem::ManuallyDrop;
+use std::ops::{Bound, RangeBounds};
 use std::{mem, ptr};
 use std::ptr::NonNull;
 use crate::node::{InternalNode, LeafNode, TreeNode};
 
 pub struct BPlusTreeMap<K, V> {
     pub(crate) root: Option<TreeNode<K, V>>,
-    pub(crate) order: u16,
+    pub(crate) leaf_order: u16,
+    pub(crate) internal_order: u16,
     pub(crate) length: usize,
     pub(crate) head: Option<NonNull<LeafNode<K, V>>>
 }
@@ -17,11 +19,19 @@ unsafe impl<K: Sync, V: Sync> Sync for BPlusTreeMap<K, V> {}
 // K must be Clone because keys are copied up to internal nodes from leaves.
 impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
     pub fn new(order: u16) -> Self {
-        // The order must be at least 3 to allow for splitting.
-        assert!(order >= 3, "B+ Tree order must be at least 3");
+        Self::with_orders(order, order)
+    }
+
+    /// Creates a map whose leaves hold up to `leaf_order` entries and whose internal nodes
+    /// hold up to `internal_order` separator keys, so each level can be sized independently.
+    pub fn with_orders(leaf_order: u16, internal_order: u16) -> Self {
+        // Both orders must be at least 3 to allow for splitting.
+        assert!(leaf_order >= 3, "B+ Tree leaf order must be at least 3");
+        assert!(internal_order >= 3, "B+ Tree internal order must be at least 3");
         Self {
             root: None,
-            order,
+            leaf_order,
+            internal_order,
             length: 0,
             head: None
         }
@@ -33,7 +43,7 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
         // The tree is empty.
         if self.root.is_none() {
-            let leaf_box = LeafNode::new(self.order);
+            let leaf_box = LeafNode::new(self.leaf_order);
             let leaked_ref: &mut LeafNode<K, V> = Box::leak(leaf_box);
             let mut leaf_ptr = NonNull::from(leaked_ref);
             let leaf = unsafe { leaf_ptr.as_mut() };
@@ -84,9 +94,9 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
         }
 
         // The leaf is full and needs to be split.
-        if leaf_node.len > self.order {
+        if leaf_node.len > self.leaf_order {
             let midpoint = (leaf_node.len as usize) / 2; // 用order / 2作为分裂点也可以
-            let new_sibling_box = LeafNode::new(self.order);
+            let new_sibling_box = LeafNode::new(self.leaf_order);
             let mut new_sibling_ptr = NonNull::from(Box::leak(new_sibling_box));
             let sibling = unsafe { new_sibling_ptr.as_mut() };
 
@@ -116,7 +126,7 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
                 self.insert_into_parent(parent_ptr, promoted_key, TreeNode::LeafNode(new_sibling_ptr));
             } else {
                 // The leaf was the root, so we must create a new root.
-                let mut new_root = InternalNode::new(self.order);
+                let mut new_root = InternalNode::new(self.internal_order);
                 //new_root.keys.push(first_key);
                 new_root.keys.push(promoted_key);
                 new_root.children.push(TreeNode::LeafNode(leaf_node_ptr));
@@ -190,12 +200,12 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
 
     /// Returns the minimum number of keys allowed in a leaf node (except root).
     fn min_keys_for_leaf(&self) -> u16 {
-        (self.order + 1) / 2
+        (self.leaf_order + 1) / 2
     }
 
     /// Returns the minimum number of keys allowed in an internal node (except root).
     fn min_keys_for_internal(&self) -> u16 {
-        self.order / 2
+        self.internal_order / 2
     }
 
     /// Finds the leaf node that should contain the given key.
@@ -746,13 +756,13 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
         parent.children.insert(pos + 1, child);
         parent.len += 1;
 
-        if parent.len > self.order {
+        if parent.len > self.internal_order {
             let midpoint = (parent.len as usize) / 2;
 
             // The key at the midpoint is moved up to the grandparent.
             let promoted_key = parent.keys.remove(midpoint);
 
-            let new_sibling_box = InternalNode::new(self.order);
+            let new_sibling_box = InternalNode::new(self.internal_order);
             let mut new_sibling_ptr = NonNull::from(Box::leak(new_sibling_box));
             let sibling = unsafe { new_sibling_ptr.as_mut() };
 
@@ -776,7 +786,7 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
                 self.insert_into_parent(grandparent_ptr, promoted_key, TreeNode::Internal(new_sibling_ptr));
             } else {
                 // The parent was the root, create a new root.
-                let mut new_root = InternalNode::new(self.order);
+                let mut new_root = InternalNode::new(self.internal_order);
                 new_root.keys.push(promoted_key);
                 new_root.children.push(TreeNode::Internal(parent_ptr));
                 new_root.children.push(TreeNode::Internal(new_sibling_ptr));
@@ -789,6 +799,425 @@ impl<K: Ord + Clone, V> BPlusTreeMap<K, V> {
             }
         }
     }
+
+    /// Removes every key within `range` and returns the number of removed entries.
+    ///
+    /// Descends once to the lower bound and walks the leaf list to count the spanning entries.
+    /// Small ranges are removed key by key; larger ones are cut out of the leaves in place,
+    /// leaves fully inside the range are freed and the untouched ones are relinked as they are,
+    /// so only the internal levels are rebuilt instead of rebalancing after every removal.
+    pub fn remove_range<R: RangeBounds<K>>(&mut self, range: R) -> usize {
+        let mut removed = 0;
+        self.for_each_range_leaf(&range, |keys| removed += keys.len());
+        if removed == 0 {
+            return 0;
+        }
+
+        if removed < self.leaf_order as usize {
+            let mut keys = Vec::with_capacity(removed);
+            self.for_each_range_leaf(&range, |span| keys.extend_from_slice(span));
+            for key in &keys {
+                self.remove(key);
+            }
+            return removed;
+        }
+
+        let mut leaves = self.take_leaves();
+        for leaf_ptr in &mut leaves {
+            let leaf = unsafe { leaf_ptr.as_mut() };
+            let span = Self::range_in_leaf(&leaf.keys, &range);
+            leaf.keys.drain(span.clone());
+            leaf.vals.drain(span);
+        }
+        self.relink_leaves(leaves);
+        removed
+    }
+
+    /// Removes every entry for which `f(key, value)` returns true and returns the number removed.
+    ///
+    /// Walks the leaf list once to find the matching entries without touching the structure.
+    /// Few matches are removed key by key; otherwise the tree is drained and rebuilt once,
+    /// so filtering by value never rebalances after each removal.
+    pub fn remove_if_value<F: Fn(&K, &V) -> bool>(&mut self, f: F) -> usize {
+        let keys: Vec<K> = self.iter().filter(|(k, v)| f(k, v)).map(|(k, _)| k.clone()).collect();
+        if keys.is_empty() {
+            return 0;
+        }
+
+        let removed = keys.len();
+        if removed < self.leaf_order as usize {
+            for key in &keys {
+                self.remove(key);
+            }
+            return removed;
+        }
+
+        // Both sides are in key order, so a single pass drops the collected keys.
+        let mut doomed = keys.iter().peekable();
+        let entries: Vec<(K, V)> = self
+            .drain_sorted()
+            .into_iter()
+            .filter(|(k, _)| doomed.next_if(|d| *d == k).is_none())
+            .collect();
+        self.build_from_sorted(entries);
+        removed
+    }
+
+    /// Keeps only the entries whose key satisfies `f` and returns the number removed.
+    ///
+    /// Walks the leaf list once to count the kept keys of every leaf. Few removals go key by key;
+    /// otherwise fully kept leaves stay in place, fully removed ones are unlinked and freed, and
+    /// only the mixed leaves run `f` again to filter in place. For an address-window predicate
+    /// those are at most the two boundary leaves. The internal levels are rebuilt once on top.
+    pub fn retain_keys<F: Fn(&K) -> bool>(&mut self, f: F) -> usize {
+        // (kept, len) of every leaf in list order
+        let mut counts = Vec::new();
+        let mut current = self.head;
+        while let Some(leaf_ptr) = current {
+            let leaf = unsafe { leaf_ptr.as_ref() };
+            counts.push((leaf.keys.iter().filter(|k| f(k)).count(), leaf.keys.len()));
+            current = leaf.next;
+        }
+
+        let removed: usize = counts.iter().map(|(kept, len)| len - kept).sum();
+        if removed == 0 {
+            return 0;
+        }
+
+        if removed < self.leaf_order as usize {
+            let mut keys = Vec::with_capacity(removed);
+            let mut current = self.head;
+            for &(kept, len) in &counts {
+                let leaf = unsafe { current.unwrap().as_ref() };
+                if kept < len {
+                    keys.extend(leaf.keys.iter().filter(|k| !f(k)).cloned());
+                }
+                current = leaf.next;
+            }
+            for key in &keys {
+                self.remove(key);
+            }
+            return removed;
+        }
+
+        let mut leaves = self.take_leaves();
+        for (leaf_ptr, &(kept, len)) in leaves.iter_mut().zip(&counts) {
+            let leaf = unsafe { leaf_ptr.as_mut() };
+            if kept == 0 {
+                leaf.keys.clear();
+                leaf.vals.clear();
+            } else if kept < len {
+                let flags: Vec<bool> = leaf.keys.iter().map(&f).collect();
+                let mut flag = flags.iter();
+                leaf.keys.retain(|_| *flag.next().unwrap());
+                let mut flag = flags.iter();
+                leaf.vals.retain(|_| *flag.next().unwrap());
+            }
+        }
+        self.relink_leaves(leaves);
+        removed
+    }
+
+    /// Follows the leaf list from the lower bound of `range` and calls `visit` with the keys
+    /// of every leaf that fall inside the range.
+    fn for_each_range_leaf<R: RangeBounds<K>, F: FnMut(&[K])>(&self, range: &R, mut visit: F) {
+        let mut current = match range.start_bound() {
+            Bound::Included(start) | Bound::Excluded(start) => self.find_leaf(start),
+            Bound::Unbounded => self.head,
+        };
+
+        while let Some(leaf_ptr) = current {
+            let leaf = unsafe { leaf_ptr.as_ref() };
+            let span = Self::range_in_leaf(&leaf.keys, range);
+            if !span.is_empty() {
+                visit(&leaf.keys[span.clone()]);
+            }
+            if span.end < leaf.keys.len() {
+                return;
+            }
+            current = leaf.next;
+        }
+    }
+
+    /// Positions of the sorted `keys` that fall inside `range`.
+    fn range_in_leaf<R: RangeBounds<K>>(keys: &[K], range: &R) -> std::ops::Range<usize> {
+        let begin = match range.start_bound() {
+            Bound::Included(start) => keys.partition_point(|k| k < start),
+            Bound::Excluded(start) => keys.partition_point(|k| k <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => keys.partition_point(|k| k <= end),
+            Bound::Excluded(end) => keys.partition_point(|k| k < end),
+            Bound::Unbounded => keys.len(),
+        };
+        begin..end.max(begin)
+    }
+
+    /// Takes every entry out of the tree in key order, leaving the tree empty.
+    fn drain_sorted(&mut self) -> Vec<(K, V)> {
+        let mut entries = Vec::with_capacity(self.length);
+        for leaf_ptr in self.take_leaves() {
+            let mut leaf = unsafe { Box::from_raw(leaf_ptr.as_ptr()) };
+            entries.extend(leaf.keys.drain(..).zip(leaf.vals.drain(..)));
+        }
+        entries
+    }
+
+    /// Frees the internal nodes and hands back the leaves in list order, leaving the tree empty.
+    /// The leaves stay allocated; the caller must relink or free them.
+    fn take_leaves(&mut self) -> Vec<NonNull<LeafNode<K, V>>> {
+        // Free internal nodes first; leaves are reclaimed through the linked list below.
+        if let Some(root) = self.root.take() {
+            let mut queue = VecDeque::new();
+            queue.push_back(root);
+            while let Some(node) = queue.pop_front() {
+                if let TreeNode::Internal(internal_ptr) = node {
+                    let mut internal_node = unsafe { Box::from_raw(internal_ptr.as_ptr()) };
+                    queue.extend(internal_node.children.drain(..));
+                }
+            }
+        }
+
+        let mut leaves = Vec::new();
+        let mut current = self.head.take();
+        while let Some(leaf_ptr) = current {
+            current = unsafe { leaf_ptr.as_ref() }.next;
+            leaves.push(leaf_ptr);
+        }
+
+        self.length = 0;
+        leaves
+    }
+
+    /// Rebuilds the tree on top of leaves taken by `take_leaves` whose contents were
+    /// filtered in place. Empty leaves are freed, and an underfull leaf is merged with or
+    /// borrows from its left neighbour, so kept leaves are reused instead of copied.
+    fn relink_leaves(&mut self, leaves: Vec<NonNull<LeafNode<K, V>>>) {
+        debug_assert!(self.root.is_none() && self.head.is_none());
+        let order = self.leaf_order as usize;
+        let min = self.min_keys_for_leaf() as usize;
+
+        let mut kept: Vec<NonNull<LeafNode<K, V>>> = Vec::with_capacity(leaves.len());
+        for leaf_ptr in leaves {
+            if unsafe { leaf_ptr.as_ref() }.keys.is_empty() {
+                drop(unsafe { Box::from_raw(leaf_ptr.as_ptr()) });
+                continue;
+            }
+            kept.push(leaf_ptr);
+
+            // Every leaf but the last is at least half full, so fixing the last pair suffices;
+            // a merge may leave the last leaf underfull again, hence the loop. The underfull
+            // side is the one merged away, so a fully kept neighbour survives.
+            while let [.., mut left_ptr, mut right_ptr] = kept[..] {
+                let (left, right) = unsafe { (left_ptr.as_mut(), right_ptr.as_mut()) };
+                if left.keys.len() >= min && right.keys.len() >= min {
+                    break;
+                }
+                let total = left.keys.len() + right.keys.len();
+                if total <= order {
+                    if right.keys.len() < min {
+                        left.keys.append(&mut right.keys);
+                        left.vals.append(&mut right.vals);
+                        kept.pop();
+                        drop(unsafe { Box::from_raw(right_ptr.as_ptr()) });
+                    } else {
+                        right.keys.splice(0..0, left.keys.drain(..));
+                        right.vals.splice(0..0, left.vals.drain(..));
+                        kept.remove(kept.len() - 2);
+                        drop(unsafe { Box::from_raw(left_ptr.as_ptr()) });
+                    }
+                    continue;
+                }
+                let left_len = total / 2;
+                if left.keys.len() > left_len {
+                    let keys = left.keys.split_off(left_len);
+                    let vals = left.vals.split_off(left_len);
+                    right.keys.splice(0..0, keys);
+                    right.vals.splice(0..0, vals);
+                } else {
+                    let moved = left_len - left.keys.len();
+                    left.keys.extend(right.keys.drain(..moved));
+                    left.vals.extend(right.vals.drain(..moved));
+                }
+                break;
+            }
+        }
+
+        let mut level: Vec<(K, TreeNode<K, V>)> = Vec::with_capacity(kept.len());
+        let mut prev: Option<NonNull<LeafNode<K, V>>> = None;
+        for mut leaf_ptr in kept {
+            let leaf = unsafe { leaf_ptr.as_mut() };
+            leaf.len = leaf.keys.len() as u16;
+            leaf.parent = None;
+            leaf.prev = prev;
+            leaf.next = None;
+            self.length += leaf.keys.len();
+            match prev {
+                Some(mut prev_ptr) => unsafe { prev_ptr.as_mut().next = Some(leaf_ptr) },
+                None => self.head = Some(leaf_ptr),
+            }
+            prev = Some(leaf_ptr);
+            level.push((leaf.keys[0].clone(), TreeNode::LeafNode(leaf_ptr)));
+        }
+        self.build_internal_levels(level);
+    }
+
+    /// Builds the tree bottom-up from entries sorted by key, spreading them evenly so that
+    /// every non-root node stays within the fill bounds used by insert/remove.
+    fn build_from_sorted(&mut self, entries: Vec<(K, V)>) {
+        debug_assert!(self.root.is_none() && self.head.is_none());
+        if entries.is_empty() {
+            return;
+        }
+        self.length = entries.len();
+
+        // Leaf level: (first key, node)
+        let leaf_count = entries.len().div_ceil(self.leaf_order as usize);
+        let mut level: Vec<(K, TreeNode<K, V>)> = Vec::with_capacity(leaf_count);
+        let mut prev: Option<NonNull<LeafNode<K, V>>> = None;
+        let mut iter = entries.into_iter();
+        for size in Self::even_sizes(self.length, leaf_count) {
+            let mut leaf = LeafNode::new(self.leaf_order);
+            for (k, v) in iter.by_ref().take(size) {
+                leaf.keys.push(k);
+                leaf.vals.push(v);
+            }
+            leaf.len = size as u16;
+            leaf.prev = prev;
+            let first_key = leaf.keys[0].clone();
+            let mut leaf_ptr = NonNull::from(Box::leak(leaf));
+            match prev {
+                Some(mut prev_ptr) => unsafe { prev_ptr.as_mut().next = Some(leaf_ptr) },
+                None => self.head = Some(leaf_ptr),
+            }
+            prev = Some(leaf_ptr);
+            unsafe { leaf_ptr.as_mut().next = None };
+            level.push((first_key, TreeNode::LeafNode(leaf_ptr)));
+        }
+        self.build_internal_levels(level);
+    }
+
+    /// Builds the internal levels over `(first key, node)` pairs of one level until a single
+    /// root remains, spreading the children evenly and fixing their parent pointers.
+    fn build_internal_levels(&mut self, mut level: Vec<(K, TreeNode<K, V>)>) {
+        let max_children = self.internal_order as usize + 1;
+        while level.len() > 1 {
+            let parent_count = level.len().div_ceil(max_children);
+            let mut parents = Vec::with_capacity(parent_count);
+            let mut iter = level.into_iter();
+            for size in Self::even_sizes(iter.len(), parent_count) {
+                let mut internal = InternalNode::new(self.internal_order);
+                let mut first_key = None;
+                for (key, child) in iter.by_ref().take(size) {
+                    if first_key.is_none() {
+                        first_key = Some(key);
+                    } else {
+                        internal.keys.push(key);
+                    }
+                    internal.children.push(child);
+                }
+                internal.len = internal.keys.len() as u16;
+                let internal_ptr = NonNull::from(Box::leak(internal));
+                for child in unsafe { &mut (*internal_ptr.as_ptr()).children } {
+                    match child {
+                        TreeNode::Internal(ptr) => unsafe { ptr.as_mut().parent = Some(internal_ptr) },
+                        TreeNode::LeafNode(ptr) => unsafe { ptr.as_mut().parent = Some(internal_ptr) },
+                    }
+                }
+                parents.push((first_key.unwrap(), TreeNode::Internal(internal_ptr)));
+            }
+            level = parents;
+        }
+
+        self.root = level.pop().map(|(_, node)| node);
+    }
+
+    /// Splits `total` items into `parts` sizes that differ by at most one.
+    fn even_sizes(total: usize, parts: usize) -> impl Iterator<Item = usize> {
+        let base = total / parts;
+        let extra = total % parts;
+        (0..parts).map(move |i| base + usize::from(i < extra))
+    }
+
+    /// Checks structural invariants: sorted keys, fill bounds, separators, parent pointers,
+    /// uniform leaf depth and the leaf linked list. Panics on the first violation.
+    #[cfg(test)]
+    pub(crate) fn check_invariants(&self) {
+        let Some(root) = self.root.as_ref() else {
+            assert!(self.head.is_none(), "empty tree must not have a head leaf");
+            assert_eq!(self.length, 0, "empty tree must have length 0");
+            return;
+        };
+
+        let mut leaves = Vec::new();
+        let mut leaf_depth = None;
+        self.check_node(root, None, None, None, 0, &mut leaf_depth, &mut leaves);
+
+        assert_eq!(self.head, leaves.first().copied(), "head must be the leftmost leaf");
+        let mut count = 0;
+        for (i, leaf_ptr) in leaves.iter().enumerate() {
+            let leaf = unsafe { leaf_ptr.as_ref() };
+            assert_eq!(leaf.prev, if i == 0 { None } else { Some(leaves[i - 1]) }, "broken prev link");
+            assert_eq!(leaf.next, leaves.get(i + 1).copied(), "broken next link");
+            count += leaf.keys.len();
+        }
+        assert_eq!(count, self.length, "length does not match leaf contents");
+    }
+
+    #[cfg(test)]
+    #[allow(clippy::too_many_arguments)]
+    fn check_node(
+        &self,
+        node: &TreeNode<K, V>,
+        parent: Option<NonNull<InternalNode<K, V>>>,
+        lower: Option<&K>,
+        upper: Option<&K>,
+        depth: usize,
+        leaf_depth: &mut Option<usize>,
+        leaves: &mut Vec<NonNull<LeafNode<K, V>>>,
+    ) {
+        let is_root = parent.is_none();
+        let in_bounds = |k: &K| lower.is_none_or(|l| k >= l) && upper.is_none_or(|u| k < u);
+        match node {
+            TreeNode::LeafNode(ptr) => {
+                let leaf = unsafe { ptr.as_ref() };
+                assert_eq!(leaf.parent, parent, "leaf has wrong parent pointer");
+                assert_eq!(leaf.len as usize, leaf.keys.len());
+                assert_eq!(leaf.keys.len(), leaf.vals.len());
+                assert!(leaf.len <= self.leaf_order, "leaf overflow");
+                if !is_root {
+                    assert!(leaf.len >= self.min_keys_for_leaf(), "leaf underflow");
+                }
+                assert!(leaf.keys.windows(2).all(|w| w[0] < w[1]), "leaf keys not sorted");
+                assert!(leaf.keys.iter().all(in_bounds), "leaf key outside separator bounds");
+                match *leaf_depth {
+                    Some(d) => assert_eq!(d, depth, "leaves at different depths"),
+                    None => *leaf_depth = Some(depth),
+                }
+                leaves.push(*ptr);
+            }
+            TreeNode::Internal(ptr) => {
+                let internal = unsafe { ptr.as_ref() };
+                assert_eq!(internal.parent, parent, "internal node has wrong parent pointer");
+                assert_eq!(internal.len as usize, internal.keys.len());
+                assert_eq!(internal.children.len(), internal.keys.len() + 1);
+                assert!(internal.len <= self.internal_order, "internal overflow");
+                if !is_root {
+                    assert!(internal.len >= self.min_keys_for_internal(), "internal underflow");
+                } else {
+                    assert!(internal.len >= 1, "internal root without separators");
+                }
+                assert!(internal.keys.windows(2).all(|w| w[0] < w[1]), "separators not sorted");
+                assert!(internal.keys.iter().all(in_bounds), "separator outside parent bounds");
+                for (i, child) in internal.children.iter().enumerate() {
+                    let lo = if i == 0 { lower } else { Some(&internal.keys[i - 1]) };
+                    let hi = internal.keys.get(i).or(upper);
+                    self.check_node(child, Some(*ptr), lo, hi, depth + 1, leaf_depth, leaves);
+                }
+            }
+        }
+    }
 }
 
 /// Iterator over the key-value pairs in a B+ tree map.
@@ -2010,6 +2439,45 @@ mod tests {
         }
     }
 
+    /// 测试叶子与内部节点使用不同 order 时的一致性
+    #[test]
+    fn test_consistency_across_leaf_internal_orders() {
+        for (leaf_order, internal_order) in [(3, 16), (16, 3), (4, 7), (64, 4), (5, 5)] {
+            let mut map = BPlusTreeMap::<i32, i32>::with_orders(leaf_order, internal_order);
+
+            // 插入相同数据
+            for i in 0..500 {
+                map.insert(i, i * 2);
+            }
+            map.check_invariants();
+
+            // 删除一半，触发借位与合并
+            for i in (0..500).step_by(2) {
+                map.remove(&i);
+            }
+            map.check_invariants();
+
+            // 验证剩余键
+            for i in 0..500 {
+                let expected = if i % 2 == 0 { None } else { Some(i * 2) };
+                assert_eq!(
+                    map.get(&i).copied(),
+                    expected,
+                    "Orders ({}, {}): wrong value for key {}",
+                    leaf_order,
+                    internal_order,
+                    i
+                );
+            }
+
+            // 批量删除走整体重建
+            assert_eq!(map.remove_range(100..300), 100);
+            map.check_invariants();
+
+            assert_eq!(map.len(), 150, "Orders ({}, {}): wrong length", leaf_order, internal_order);
+        }
+    }
+
     /// 压力测试：大量随机操作
     #[test]
     fn test_stress_random_operations_extended() {
@@ -2594,4 +3062,193 @@ mod tests {
 
         assert_eq!(map.len(), 20);
     }
-}
\ No newline at end of file
+
+    /// remove_range 在不同阶数下跨多个叶子删除，之后结构仍然合法
+    #[test]
+    fn test_remove_range_keeps_invariants() {
+        for order in [3u16, 4, 5, 8, 16] {
+            let mut map = BPlusTreeMap::<i32, i32>::new(order);
+            for i in 0..500 {
+                map.insert(i, i * 10);
+            }
+            map.check_invariants();
+
+            assert_eq!(map.remove_range(100..300), 200);
+            map.check_invariants();
+            assert_eq!(map.remove_range(..=10), 11);
+            map.check_invariants();
+            assert_eq!(map.remove_range(450..), 50);
+            map.check_invariants();
+            assert_eq!(map.remove_range((std::ops::Bound::Excluded(300), std::ops::Bound::Excluded(303))), 2);
+            map.check_invariants();
+
+            let keys: Vec<i32> = map.iter().map(|(k, _)| *k).collect();
+            let expected: Vec<i32> = (11..100).chain([300]).chain(303..450).collect();
+            assert_eq!(keys, expected, "order {}", order);
+            assert!(map.iter().all(|(k, v)| *v == *k * 10));
+
+            // 重建后的树仍可正常插入和删除
+            for i in 100..300 {
+                map.insert(i, i * 10);
+            }
+            map.check_invariants();
+            for i in (0..500).step_by(3) {
+                map.remove(&i);
+            }
+            map.check_invariants();
+        }
+    }
+
+    /// remove_range 批量删除时范围外的叶子原地复用，只有范围两端的叶子被裁剪
+    #[test]
+    fn test_remove_range_splices_leaves() {
+        for order in [3u16, 4, 5, 8, 16] {
+            let mut map = BPlusTreeMap::<u32, u32>::new(order);
+            for i in 0..2000u32 {
+                map.insert(i, i + 1);
+            }
+
+            let outside: Vec<_> = leaf_ptrs(&map)
+                .into_iter()
+                .filter(|ptr| unsafe { ptr.as_ref() }.keys.iter().all(|k| !(700..1300).contains(k)))
+                .collect();
+            assert_eq!(map.remove_range(700..1300), 600);
+            map.check_invariants();
+            let after = leaf_ptrs(&map);
+            assert!(outside.iter().all(|ptr| after.contains(ptr)), "order {}", order);
+            let expected: Vec<u32> = (0..700).chain(1300..2000).collect();
+            assert_eq!(map.iter().map(|(k, _)| *k).collect::<Vec<_>>(), expected, "order {}", order);
+            assert!(map.iter().all(|(k, v)| *v == *k + 1));
+
+            // 少于 order 个键时逐个删除
+            assert_eq!(map.remove_range(1301..=1301), 1);
+            assert_eq!(map.remove_range(..), 1399);
+            map.check_invariants();
+            assert!(map.is_empty());
+            map.insert(5, 6);
+            map.check_invariants();
+        }
+    }
+
+    /// remove_if_value 按值过滤，少量删除和批量重建两条路径都保持结构合法
+    #[test]
+    fn test_remove_if_value_keeps_invariants() {
+        for order in [3u16, 4, 5, 8, 16] {
+            let mut map = BPlusTreeMap::<u32, u32>::new(order);
+            // 值与键无关，命中的键散布在所有叶子里
+            for i in 0..1000u32 {
+                map.insert(i, i * 7 % 1000);
+            }
+
+            assert_eq!(map.remove_if_value(|_, v| *v < 300), 300);
+            map.check_invariants();
+            assert_eq!(map.len(), 700);
+            let keys: Vec<u32> = map.iter().map(|(k, _)| *k).collect();
+            let expected: Vec<u32> = (0..1000).filter(|i| i * 7 % 1000 >= 300).collect();
+            assert_eq!(keys, expected, "order {}", order);
+            assert!(map.iter().all(|(k, v)| *v == *k * 7 % 1000));
+
+            // 少于 order 个命中时逐个删除
+            assert_eq!(map.remove_if_value(|k, v| *v >= 998 && *k % 2 == 0), 1);
+            map.check_invariants();
+            assert_eq!(map.remove_if_value(|_, _| false), 0);
+            assert_eq!(map.len(), 699);
+
+            assert_eq!(map.remove_if_value(|_, _| true), 699);
+            map.check_invariants();
+            assert!(map.is_empty());
+            map.insert(1, 1);
+            map.check_invariants();
+        }
+    }
+
+    /// retain_keys 按地址窗口保留，整叶保留/删除的叶子不再调用谓词，结果与结构都正确
+    #[test]
+    fn test_retain_keys_address_window() {
+        use std::cell::Cell;
+
+        for order in [3u16, 4, 5, 8, 16] {
+            let mut map = BPlusTreeMap::<u64, u64>::new(order);
+            for i in 0..1000u64 {
+                map.insert(0x7000_0000 + i * 4, i);
+            }
+
+            let (lo, hi) = (0x7000_0000 + 0x400, 0x7000_0000 + 0xA00);
+            let calls = Cell::new(0usize);
+            let removed = map.retain_keys(|k| {
+                calls.set(calls.get() + 1);
+                (lo..hi).contains(k)
+            });
+            assert_eq!(removed, 1000 - 0x180);
+            map.check_invariants();
+            // 每个键判定一次，只有窗口两端的混合叶子再判定一次
+            assert!(calls.get() <= 1000 + 2 * order as usize, "order {}: {} calls", order, calls.get());
+            let keys: Vec<u64> = map.iter().map(|(k, _)| *k).collect();
+            let expected: Vec<u64> = (0x100..0x280u64).map(|i| 0x7000_0000 + i * 4).collect();
+            assert_eq!(keys, expected, "order {}", order);
+            assert!(map.iter().all(|(k, v)| *k == 0x7000_0000 + *v * 4));
+
+            // 少于 order 个删除时逐个删除
+            assert_eq!(map.retain_keys(|k| *k != lo), 1);
+            map.check_invariants();
+            assert_eq!(map.retain_keys(|_| true), 0);
+            assert_eq!(map.len(), 0x17F);
+
+            assert_eq!(map.retain_keys(|_| false), 0x17F);
+            map.check_invariants();
+            assert!(map.is_empty());
+            map.insert(1, 1);
+            map.check_invariants();
+        }
+    }
+
+    /// 按叶子链表顺序返回所有叶子节点
+    fn leaf_ptrs<K, V>(map: &BPlusTreeMap<K, V>) -> Vec<NonNull<LeafNode<K, V>>> {
+        let mut ptrs = Vec::new();
+        let mut current = map.head;
+        while let Some(leaf_ptr) = current {
+            ptrs.push(leaf_ptr);
+            current = unsafe { leaf_ptr.as_ref() }.next;
+        }
+        ptrs
+    }
+
+    /// retain_keys 批量删除时整叶保留的叶子原地复用，不会被拷贝到新节点
+    #[test]
+    fn test_retain_keys_reuses_kept_leaves() {
+        for order in [3u16, 4, 5, 8, 16] {
+            let mut map = BPlusTreeMap::<u32, u32>::new(order);
+            for i in 0..2000u32 {
+                map.insert(i, i * 3);
+            }
+
+            let window = 500..1500u32;
+            let inside: Vec<_> = leaf_ptrs(&map)
+                .into_iter()
+                .filter(|ptr| unsafe { ptr.as_ref() }.keys.iter().all(|k| window.contains(k)))
+                .collect();
+            assert!(!inside.is_empty());
+
+            assert_eq!(map.retain_keys(|k| window.contains(k)), 1000);
+            map.check_invariants();
+            let after = leaf_ptrs(&map);
+            assert!(inside.iter().all(|ptr| after.contains(ptr)), "order {}", order);
+            let keys: Vec<u32> = map.iter().map(|(k, _)| *k).collect();
+            assert_eq!(keys, window.clone().collect::<Vec<_>>(), "order {}", order);
+            assert!(map.iter().all(|(k, v)| *v == *k * 3));
+
+            // 零散删除让每个叶子都变成混合叶子，合并和借位后结构仍然合法
+            assert_eq!(map.retain_keys(|k| k % 3 != 0), 333);
+            map.check_invariants();
+            let expected: Vec<u32> = window.clone().filter(|k| k % 3 != 0).collect();
+            assert_eq!(map.iter().map(|(k, _)| *k).collect::<Vec<_>>(), expected, "order {}", order);
+            assert_eq!(map.retain_keys(|k| k % 7 == 0), 572);
+            map.check_invariants();
+            for i in 0..2000u32 {
+                map.insert(i, i * 3);
+            }
+            map.check_invariants();
+            assert_eq!(map.len(), 2000);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use core::cmp::Ordering::{self, Equal, Greater, Less};
 use core::fmt::{self, Debug};
 use core::iter::{FusedIterator, Peekable};
-use core::ops::{BitAnd, BitOr, BitXor, Sub};
+use core::ops::{BitAnd, BitOr, BitXor, RangeBounds, Sub};
 use crate::BPlusTreeMap;
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Default, Copy)]
@@ -52,9 +52,17 @@ impl<T: Ord + Clone> BPlusTreeSet<T> {
         }
     }
 
+    /// Creates a new empty set with separate leaf and internal node orders.
+    /// Both orders must be at least 3.
+    pub fn with_orders(leaf_order: u16, internal_order: u16) -> Self {
+        BPlusTreeSet {
+            map: BPlusTreeMap::with_orders(leaf_order, internal_order),
+        }
+    }
+
     /// Clears the set, removing all elements.
     pub fn clear(&mut self) {
-        *self = BPlusTreeSet::new(self.map.order);
+        *self = BPlusTreeSet::with_orders(self.map.leaf_order, self.map.internal_order);
     }
 
     /// Returns true if the set contains the specified value.
@@ -74,6 +82,11 @@ impl<T: Ord + Clone> BPlusTreeSet<T> {
         self.map.remove(value).is_some()
     }
 
+    /// Removes all values within the range and returns how many were removed.
+    pub fn remove_range<R: RangeBounds<T>>(&mut self, range: R) -> usize {
+        self.map.remove_range(range)
+    }
+
     /// Returns the number of elements in the set.
     pub fn len(&self) -> usize {
         self.map.len()
@@ -724,4 +737,39 @@ mod tests {
         let expected2: Vec<_> = result2.iter().copied().collect();
         assert_eq!(expected2, (1..=9).collect::<Vec<_>>());
     }
+
+    #[test]
+    fn test_remove_range_spanning_leaves() {
+        let mut set: BPlusTreeSet<u64> = BPlusTreeSet::new(4);
+        for i in 0..1000u64 {
+            set.insert(i * 4);
+        }
+
+        // 跨越大量叶子，走批量重建
+        assert_eq!(set.remove_range(400..2000), 400);
+        set.map.check_invariants();
+        assert_eq!(set.len(), 600);
+        assert!(set.contains(&396));
+        assert!(!set.contains(&400));
+        assert!(!set.contains(&1996));
+        assert!(set.contains(&2000));
+
+        // 小范围，逐个删除
+        assert_eq!(set.remove_range(2000..=2004), 2);
+        set.map.check_invariants();
+
+        // 区间内没有元素
+        assert_eq!(set.remove_range(401..404), 0);
+        set.map.check_invariants();
+
+        let expected: Vec<u64> = (0..1000u64).map(|i| i * 4).filter(|v| *v < 400 || *v > 2004).collect();
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), expected);
+
+        // 删除剩余全部
+        assert_eq!(set.remove_range(..), expected.len());
+        set.map.check_invariants();
+        assert!(set.is_empty());
+        assert!(set.insert(8));
+        set.map.check_invariants();
+    }
 }
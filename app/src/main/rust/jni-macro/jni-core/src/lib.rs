@@ -1,39 +1,437 @@
+use jni::objects::GlobalRef;
 use jni::{JNIEnv, JavaVM};
+use std::collections::HashMap;
 use std::ffi::c_void;
+use std::sync::{LazyLock, Mutex};
 
 /// JNI initialization function type
 pub type JniInitFn = fn(&mut JNIEnv, &JavaVM);
 
+/// Fallible JNI initialization function type; the error is already formatted for the log
+pub type JniFallibleInitFn = fn(&mut JNIEnv, &JavaVM) -> Result<(), String>;
+
+/// JNI teardown function type
+///
+/// Only the `JavaVM` is passed: `JNI_OnUnload` may run while no `JNIEnv` is attached to the
+/// current thread, so a finalizer that needs one must attach it through the VM itself.
+pub type JniFinalizerFn = fn(&JavaVM);
+
 /// JNI initialization function registry entry
 pub struct JniInitializer {
-    pub priority: u32,
+    pub priority: i32,
     pub name: &'static str,
     pub init_fn: JniInitFn,
 }
 
+/// Registry entry for a `#[jni_onload]` function returning `Result<(), E>`
+pub struct JniFallibleInitializer {
+    pub priority: i32,
+    pub name: &'static str,
+    pub init_fn: JniFallibleInitFn,
+}
+
+/// JNI teardown function registry entry, collected by `#[jni_onunload]`
+pub struct JniFinalizer {
+    pub priority: i32,
+    pub name: &'static str,
+    pub finalize_fn: JniFinalizerFn,
+}
+
 /// JNI method registration entry
 pub struct JniMethodRegistration {
-    pub priority: u32,
+    pub priority: i32,
     pub class_path: &'static str,
     pub method_name: &'static str,
     pub signature: &'static str,
     pub fn_ptr: *mut c_void,
 }
 
+impl JniMethodRegistration {
+    /// Build a registration entry; `#[jni_method]` expands to this so every entry goes through one place.
+    ///
+    /// `fn_ptr` must point to an `extern "system"` function matching `signature`, which cannot be
+    /// checked here. Debug builds panic on a null pointer, which in the macro's `static` is a compile error.
+    pub const fn new(priority: i32, class_path: &'static str, method_name: &'static str, signature: &'static str, fn_ptr: *mut c_void) -> Self {
+        debug_assert!(!fn_ptr.is_null(), "JNI method registration with null fn_ptr");
+        Self {
+            priority,
+            class_path,
+            method_name,
+            signature,
+            fn_ptr,
+        }
+    }
+}
+
 // Safety: fn pointers are safe to send across threads
 unsafe impl Send for JniMethodRegistration {}
 unsafe impl Sync for JniMethodRegistration {}
 
 // Collect all JNI initializers using inventory
 inventory::collect!(JniInitializer);
+inventory::collect!(JniFallibleInitializer);
+
+// Collect all JNI finalizers using inventory
+inventory::collect!(JniFinalizer);
 
 // Collect all JNI method registrations using inventory
 inventory::collect!(JniMethodRegistration);
 
+/// One entry of the `JNI_OnLoad` initialization sequence
+#[derive(Clone, Copy)]
+pub enum InitStep<'a> {
+    Infallible(&'a JniInitializer),
+    Fallible(&'a JniFallibleInitializer),
+}
+
+impl InitStep<'_> {
+    pub fn priority(&self) -> i32 {
+        match self {
+            InitStep::Infallible(init) => init.priority,
+            InitStep::Fallible(init) => init.priority,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InitStep::Infallible(init) => init.name,
+            InitStep::Fallible(init) => init.name,
+        }
+    }
+}
+
+/// A fallible initializer failed and the sequence was aborted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitError {
+    pub name: &'static str,
+    pub error: String,
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JNI initializer {} failed: {}", self.name, self.error)
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// All initializers collected by `#[jni_onload]`, both forms, in execution order (priority descending)
+pub fn init_sequence() -> Vec<InitStep<'static>> {
+    let mut steps: Vec<InitStep> = inventory::iter::<JniInitializer>()
+        .map(InitStep::Infallible)
+        .chain(inventory::iter::<JniFallibleInitializer>().map(InitStep::Fallible))
+        .collect();
+    steps.sort_by_key(|step| std::cmp::Reverse(step.priority()));
+    steps
+}
+
+/// Run `steps` in order through `run`, logging every failure with the initializer name
+///
+/// With `abort_on_error` the first failure stops the sequence and is returned; otherwise the
+/// remaining initializers still run. `run` performs the actual call so the control flow can be
+/// exercised without a live VM.
+pub fn run_steps<F>(steps: &[InitStep], abort_on_error: bool, mut run: F) -> Result<(), InitError>
+where
+    F: FnMut(&InitStep) -> Result<(), String>,
+{
+    for step in steps {
+        log::info!("Executing JNI initializer: {} (priority: {})", step.name(), step.priority());
+        if let Err(error) = run(step) {
+            log::error!("JNI initializer {} failed: {}", step.name(), error);
+            if abort_on_error {
+                return Err(InitError { name: step.name(), error });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Execute every `#[jni_onload]` initializer, see [`run_steps`] for how failures are handled
+pub fn run_initializers(env: &mut JNIEnv, vm: &JavaVM, abort_on_error: bool) -> Result<(), InitError> {
+    run_steps(&init_sequence(), abort_on_error, |step| match step {
+        InitStep::Infallible(init) => {
+            (init.init_fn)(env, vm);
+            Ok(())
+        },
+        InitStep::Fallible(init) => (init.init_fn)(env, vm),
+    })
+}
+
+/// Order finalizers for `JNI_OnUnload`: priority ascending, the reverse of initialization
+///
+/// Subsystems with a high priority initialize first, so they are torn down last, after everything
+/// that may depend on them. Equal priorities keep their collection order.
+pub fn finalizer_sequence<'a, I>(finalizers: I) -> Vec<&'a JniFinalizer>
+where
+    I: IntoIterator<Item = &'a JniFinalizer>,
+{
+    let mut finalizers: Vec<&JniFinalizer> = finalizers.into_iter().collect();
+    finalizers.sort_by_key(|finalizer| finalizer.priority);
+    finalizers
+}
+
+/// Run every `#[jni_onunload]` finalizer in [`finalizer_sequence`] order
+///
+/// Call this from `JNI_OnUnload`. No `JNIEnv` is available there in general, see [`JniFinalizerFn`].
+pub fn run_all_jni_finalizers(vm: &JavaVM) {
+    for finalizer in finalizer_sequence(inventory::iter::<JniFinalizer>()) {
+        log::info!("Executing JNI finalizer: {} (priority: {})", finalizer.name, finalizer.priority);
+        (finalizer.finalize_fn)(vm);
+    }
+}
+
+/// Registration plan for a single class: the methods to register in one `RegisterNatives` call
+pub struct ClassPlan<'a> {
+    pub class_path: &'a str,
+    /// Methods in registration order (priority descending)
+    pub methods: Vec<&'a JniMethodRegistration>,
+}
+
+impl ClassPlan<'_> {
+    /// Highest priority among the methods of this class
+    pub fn priority(&self) -> i32 {
+        self.methods.iter().map(|m| m.priority).max().unwrap_or(0)
+    }
+}
+
+/// Build the registration plan from all methods collected by `#[jni_method]`
+///
+/// Pure function, no JNI calls, so the plan can be inspected without a live VM.
+pub fn plan_registrations() -> Vec<ClassPlan<'static>> {
+    plan_from(inventory::iter::<JniMethodRegistration>())
+}
+
+/// Every method collected by `#[jni_method]`, in no particular order, duplicates included
+pub fn iter_method_registrations() -> impl Iterator<Item = &'static JniMethodRegistration> {
+    inventory::iter::<JniMethodRegistration>()
+}
+
+/// `(class_path, method_name, signature, priority)` of every method in the order
+/// [`register_all_jni_methods`] registers them
+///
+/// Read-only, so it can be called before registration. Duplicates that would be skipped are left
+/// out; see [`conflicts_from`] for those.
+pub fn describe_registrations() -> Vec<(String, String, String, i32)> {
+    describe_from(iter_method_registrations())
+}
+
+/// [`describe_registrations`] over an arbitrary set of registrations
+pub fn describe_from<'a, I>(registrations: I) -> Vec<(String, String, String, i32)>
+where
+    I: IntoIterator<Item = &'a JniMethodRegistration>,
+{
+    plan_from(registrations)
+        .iter()
+        .flat_map(|class| class.methods.iter())
+        .map(|m| (m.class_path.to_string(), m.method_name.to_string(), m.signature.to_string(), m.priority))
+        .collect()
+}
+
+/// Registration order: priority descending, then name and signature
+///
+/// Exact duplicates with equal priority are ordered by function address so the same one wins on every load.
+fn registration_order(a: &&JniMethodRegistration, b: &&JniMethodRegistration) -> std::cmp::Ordering {
+    b.priority
+        .cmp(&a.priority)
+        .then_with(|| a.method_name.cmp(b.method_name))
+        .then_with(|| a.signature.cmp(b.signature))
+        .then_with(|| (a.fn_ptr as usize).cmp(&(b.fn_ptr as usize)))
+}
+
+/// Several registrations of the same `(class, name, signature)`; only the first one is registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub class_path: String,
+    pub method_name: String,
+    pub signature: String,
+    /// Priorities of the colliding registrations, in registration order (the kept one first)
+    pub priorities: Vec<i32>,
+}
+
+/// Find registrations colliding on `(class, name, signature)`, ordered by class, name and signature
+///
+/// Every collision is logged as an error. [`plan_from`] keeps the first registration of each
+/// group in [`registration_order`] and drops the rest.
+pub fn conflicts_from<'a, I>(registrations: I) -> Vec<Conflict>
+where
+    I: IntoIterator<Item = &'a JniMethodRegistration>,
+{
+    let mut groups: HashMap<(&str, &str, &str), Vec<&JniMethodRegistration>> = HashMap::new();
+    for registration in registrations {
+        groups
+            .entry((registration.class_path, registration.method_name, registration.signature))
+            .or_default()
+            .push(registration);
+    }
+
+    let mut conflicts: Vec<Conflict> = groups
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|((class_path, method_name, signature), mut group)| {
+            group.sort_by(registration_order);
+            let priorities: Vec<i32> = group.iter().map(|m| m.priority).collect();
+            log::error!(
+                "JNI method {}.{}{} registered {} times (priorities {:?}), keeping priority {}",
+                class_path,
+                method_name,
+                signature,
+                group.len(),
+                priorities,
+                priorities[0]
+            );
+            Conflict {
+                class_path: class_path.to_string(),
+                method_name: method_name.to_string(),
+                signature: signature.to_string(),
+                priorities,
+            }
+        })
+        .collect();
+    conflicts.sort_by(|a, b| (&a.class_path, &a.method_name, &a.signature).cmp(&(&b.class_path, &b.method_name, &b.signature)));
+    conflicts
+}
+
+/// Build a registration plan from an arbitrary set of registrations
+///
+/// Methods are sorted by priority (higher first, ties by name and signature), grouped by class,
+/// and duplicates of the same `(class, name, signature)` are dropped, keeping the highest priority one.
+/// Classes are ordered by their highest method priority, then by class path.
+pub fn plan_from<'a, I>(registrations: I) -> Vec<ClassPlan<'a>>
+where
+    I: IntoIterator<Item = &'a JniMethodRegistration>,
+{
+    use std::collections::{HashMap, HashSet};
+
+    let mut methods: Vec<&JniMethodRegistration> = registrations.into_iter().collect();
+
+    // Sort by priority (descending - higher priority first)
+    methods.sort_by(registration_order);
+
+    // Group methods by class path while preserving priority order
+    let mut seen = HashSet::new();
+    let mut class_methods: HashMap<&str, Vec<&JniMethodRegistration>> = HashMap::new();
+    for method in methods {
+        if !seen.insert((method.class_path, method.method_name, method.signature)) {
+            log::warn!(
+                "Duplicate JNI method {}.{}{} (priority {}) ignored",
+                method.class_path,
+                method.method_name,
+                method.signature,
+                method.priority
+            );
+            continue;
+        }
+        class_methods.entry(method.class_path).or_default().push(method);
+    }
+
+    let mut plan: Vec<ClassPlan> = class_methods
+        .into_iter()
+        .map(|(class_path, methods)| ClassPlan { class_path, methods })
+        .collect();
+    plan.sort_by(|a, b| b.priority().cmp(&a.priority()).then_with(|| a.class_path.cmp(b.class_path)));
+    plan
+}
+
+/// Outcome of registering one class of a plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassRegistrationStatus {
+    pub class_path: String,
+    pub method_count: usize,
+    pub registered: bool,
+    /// `FindClass` or `RegisterNatives` error text when registration failed
+    pub error: Option<String>,
+}
+
+/// Why a class in the plan could not be registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassRegistrationError {
+    ClassNotFound(String),
+    RegisterFailed(String),
+}
+
+impl std::fmt::Display for ClassRegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassRegistrationError::ClassNotFound(e) => write!(f, "class not found: {}", e),
+            ClassRegistrationError::RegisterFailed(e) => write!(f, "RegisterNatives failed: {}", e),
+        }
+    }
+}
+
+static LAST_REPORT: Mutex<Vec<ClassRegistrationStatus>> = Mutex::new(Vec::new());
+
+/// Run `register` for every class of the plan and collect the outcomes, in plan order
+///
+/// `register` performs the actual registration of one class; keeping it abstract lets the
+/// report be built without a live VM.
+pub fn report_from<F>(plan: &[ClassPlan], mut register: F) -> Vec<ClassRegistrationStatus>
+where
+    F: FnMut(&ClassPlan) -> Result<(), ClassRegistrationError>,
+{
+    plan.iter()
+        .map(|class_plan| {
+            let result = register(class_plan);
+            if let Err(e) = &result {
+                log::error!("Failed to register methods for class {}: {}", class_plan.class_path, e);
+            }
+            ClassRegistrationStatus {
+                class_path: class_plan.class_path.to_string(),
+                method_count: class_plan.methods.len(),
+                registered: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Perform the JNI calls for a registration plan
+///
+/// The per-class outcome is returned and also kept for [`registration_report`].
+pub fn apply_plan(env: &mut JNIEnv, plan: &[ClassPlan]) -> Vec<ClassRegistrationStatus> {
+    use jni::NativeMethod;
+    use log::info;
+
+    let report = report_from(plan, |class_plan| {
+        let class_path = class_plan.class_path;
+        let methods = &class_plan.methods;
+        let class = env
+            .find_class(class_path)
+            .map_err(|e| ClassRegistrationError::ClassNotFound(format!("{:?}", e)))?;
+
+        let native_methods: Vec<NativeMethod> = methods
+            .iter()
+            .map(|m| NativeMethod {
+                name: m.method_name.into(),
+                sig: m.signature.into(),
+                fn_ptr: m.fn_ptr,
+            })
+            .collect();
+
+        env.register_native_methods(&class, &native_methods)
+            .map_err(|e| ClassRegistrationError::RegisterFailed(format!("{:?}", e)))?;
+        info!(
+            "Registered {} methods for class {} (priorities: {:?})",
+            native_methods.len(),
+            class_path,
+            methods.iter().map(|m| m.priority).collect::<Vec<_>>()
+        );
+        Ok(())
+    });
+
+    *LAST_REPORT.lock().unwrap_or_else(|e| e.into_inner()) = report.clone();
+    report
+}
+
+/// Per-class outcome of the last [`apply_plan`] call, empty before registration ran
+pub fn registration_report() -> Vec<ClassRegistrationStatus> {
+    LAST_REPORT.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
 /// Helper function to register all collected JNI methods
 ///
 /// This function collects all methods registered via `#[jni_method]`,
 /// sorts them by priority (higher first), groups by class, and registers them.
+/// See [`plan_registrations`] and [`apply_plan`] for the two halves.
 ///
 /// # Example
 /// ```
@@ -47,56 +445,375 @@ inventory::collect!(JniMethodRegistration);
 /// }
 /// ```
 pub fn register_all_jni_methods(env: &mut JNIEnv) {
-    use jni::NativeMethod;
-    use log::info;
-    use std::collections::HashMap;
+    register_all_jni_methods_checked(env);
+}
 
-    // Collect all method registrations
-    let mut methods: Vec<_> = inventory::iter::<JniMethodRegistration>().collect();
+/// [`register_all_jni_methods`] that also returns the duplicate registrations it skipped
+///
+/// An empty result means every `(class, name, signature)` was registered exactly once.
+pub fn register_all_jni_methods_checked(env: &mut JNIEnv) -> Vec<Conflict> {
+    let conflicts = conflicts_from(inventory::iter::<JniMethodRegistration>());
+    let plan = plan_registrations();
+    apply_plan(env, &plan);
+    conflicts
+}
 
-    // Sort by priority (descending - higher priority first)
-    methods.sort_by_key(|m| std::cmp::Reverse(m.priority));
+/// Registry of values resolved once per class path
+///
+/// Generic over the cached value so the caching rules can be exercised without a live VM;
+/// [`register_global_class`] and [`get_cached_class`] use it with [`GlobalRef`].
+pub struct ClassRegistry<T> {
+    entries: Mutex<HashMap<String, T>>,
+}
 
-    // Group methods by class path while preserving priority order
-    let mut class_methods: HashMap<&str, Vec<&JniMethodRegistration>> = HashMap::new();
-    for method in methods.iter() {
-        class_methods
-            .entry(method.class_path)
-            .or_insert_with(Vec::new)
-            .push(method);
-    }
-
-    // Register methods for each class
-    for (class_path, methods) in class_methods.iter() {
-        let class = match env.find_class(class_path) {
-            Ok(c) => c,
-            Err(e) => {
-                log::error!("Failed to find class {}: {:?}", class_path, e);
-                continue;
-            },
-        };
+impl<T: Clone> ClassRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
 
-        let native_methods: Vec<NativeMethod> = methods
+    /// Return the cached value for `class_path`, resolving it with `resolve` on first use
+    ///
+    /// A failed resolution is not cached, so a later call may retry.
+    pub fn get_or_try_insert_with<E, F>(&self, class_path: &str, resolve: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = entries.get(class_path) {
+            return Ok(value.clone());
+        }
+        let value = resolve()?;
+        entries.insert(class_path.to_string(), value.clone());
+        Ok(value)
+    }
+
+    pub fn get(&self, class_path: &str) -> Option<T> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).get(class_path).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> Default for ClassRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static GLOBAL_CLASSES: LazyLock<ClassRegistry<GlobalRef>> = LazyLock::new(ClassRegistry::new);
+
+/// Resolve `class_path` and keep a global reference to it
+///
+/// Call this from a `#[jni_onload]` initializer: `FindClass` on threads attached later uses the
+/// system class loader and cannot see application classes. Registering the same class again
+/// returns the cached reference without another `FindClass`/`NewGlobalRef`.
+///
+/// # Example
+/// ```ignore
+/// #[jni_onload(90)]
+/// fn cache_classes(env: &mut JNIEnv, _vm: &JavaVM) {
+///     register_global_class(env, "moe/fuqiuluo/mamu/driver/CProcInfo").unwrap();
+/// }
+///
+/// // Later, on any thread:
+/// let class = get_cached_class("moe/fuqiuluo/mamu/driver/CProcInfo").unwrap();
+/// ```
+pub fn register_global_class(env: &mut JNIEnv, class_path: &str) -> jni::errors::Result<GlobalRef> {
+    GLOBAL_CLASSES.get_or_try_insert_with(class_path, || {
+        let class = env.find_class(class_path)?;
+        let global = env.new_global_ref(&class)?;
+        env.delete_local_ref(class)?;
+        Ok(global)
+    })
+}
+
+/// Global reference registered earlier with [`register_global_class`]
+pub fn get_cached_class(class_path: &str) -> Option<GlobalRef> {
+    GLOBAL_CLASSES.get(class_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "system" fn native_stub() {}
+
+    fn reg(priority: i32, class_path: &'static str, method_name: &'static str, signature: &'static str) -> JniMethodRegistration {
+        JniMethodRegistration::new(priority, class_path, method_name, signature, native_stub as *mut c_void)
+    }
+
+    fn names(plan: &[ClassPlan]) -> Vec<(String, Vec<&'static str>)> {
+        plan.iter()
+            .map(|c| (c.class_path.to_string(), c.methods.iter().map(|m| m.method_name).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_groups_and_orders_by_priority() {
+        let regs = [
+            reg(0, "a/Search", "nativeSearch", "()V"),
+            reg(10, "a/Driver", "nativeOpen", "(I)Z"),
+            reg(5, "a/Search", "nativeInit", "()Z"),
+            reg(0, "a/Driver", "nativeClose", "()V"),
+            reg(0, "a/Freeze", "nativeFreeze", "(J)V"),
+            reg(5, "a/Search", "nativeCancel", "()V"),
+        ];
+
+        let plan = plan_from(regs.iter());
+        assert_eq!(
+            names(&plan),
+            vec![
+                ("a/Driver".to_string(), vec!["nativeOpen", "nativeClose"]),
+                ("a/Search".to_string(), vec!["nativeCancel", "nativeInit", "nativeSearch"]),
+                ("a/Freeze".to_string(), vec!["nativeFreeze"]),
+            ]
+        );
+        assert_eq!(plan[0].priority(), 10);
+        assert_eq!(plan[2].priority(), 0);
+    }
+
+    #[test]
+    fn test_describe_follows_registration_order() {
+        let regs = [
+            reg(0, "a/Search", "nativeSearch", "()V"),
+            reg(10, "a/Driver", "nativeOpen", "(I)Z"),
+            reg(5, "a/Search", "nativeInit", "()Z"),
+            reg(1, "a/Search", "nativeInit", "()Z"),
+        ];
+
+        let described = describe_from(regs.iter());
+        let expected = [
+            ("a/Driver", "nativeOpen", "(I)Z", 10),
+            ("a/Search", "nativeInit", "()Z", 5),
+            ("a/Search", "nativeSearch", "()V", 0),
+        ];
+        assert_eq!(described.len(), expected.len());
+        for ((class_path, method_name, signature, priority), expected) in described.iter().zip(expected) {
+            assert_eq!((class_path.as_str(), method_name.as_str(), signature.as_str(), *priority), expected);
+        }
+    }
+
+    #[test]
+    fn test_plan_dedups_keeping_highest_priority() {
+        let regs = [
+            reg(1, "a/Search", "nativeSearch", "()V"),
+            reg(7, "a/Search", "nativeSearch", "()V"),
+            // Same name, different signature is an overload: kept
+            reg(0, "a/Search", "nativeSearch", "(I)V"),
+            // Same name and signature on another class: kept
+            reg(0, "a/Other", "nativeSearch", "()V"),
+        ];
+
+        let plan = plan_from(regs.iter());
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].class_path, "a/Search");
+        let search: Vec<(i32, &str)> = plan[0].methods.iter().map(|m| (m.priority, m.signature)).collect();
+        assert_eq!(search, vec![(7, "()V"), (0, "(I)V")]);
+        assert_eq!(names(&plan[1..]), vec![("a/Other".to_string(), vec!["nativeSearch"])]);
+    }
+
+    #[test]
+    fn test_conflicts_list_colliding_priorities() {
+        let regs = [
+            reg(1, "a/Search", "nativeRead", "(J)I"),
+            reg(7, "a/Search", "nativeRead", "(J)I"),
+            reg(3, "a/Search", "nativeRead", "(J)I"),
+            // Overload and same method on another class are not conflicts
+            reg(0, "a/Search", "nativeRead", "(JI)I"),
+            reg(0, "a/Driver", "nativeRead", "(J)I"),
+            reg(2, "a/Driver", "nativeOpen", "()Z"),
+            reg(2, "a/Driver", "nativeOpen", "()Z"),
+        ];
+
+        let conflicts = conflicts_from(regs.iter());
+        let summary: Vec<(&str, &str, &[i32])> = conflicts
             .iter()
-            .map(|m| NativeMethod {
-                name: m.method_name.into(),
-                sig: m.signature.into(),
-                fn_ptr: m.fn_ptr,
-            })
+            .map(|c| (c.class_path.as_str(), c.method_name.as_str(), c.priorities.as_slice()))
             .collect();
+        assert_eq!(
+            summary,
+            vec![("a/Driver", "nativeOpen", &[2, 2][..]), ("a/Search", "nativeRead", &[7, 3, 1][..])]
+        );
 
-        match env.register_native_methods(&class, &native_methods) {
-            Ok(_) => {
-                info!(
-                    "Registered {} methods for class {} (priorities: {:?})",
-                    native_methods.len(),
-                    class_path,
-                    methods.iter().map(|m| m.priority).collect::<Vec<_>>()
-                );
-            },
-            Err(e) => {
-                log::error!("Failed to register methods for class {}: {:?}", class_path, e);
-            },
-        }
+        // The plan keeps the highest priority registration of each conflict
+        let plan = plan_from(regs.iter());
+        let search = plan.iter().find(|c| c.class_path == "a/Search").unwrap();
+        let kept: Vec<(i32, &str)> = search.methods.iter().map(|m| (m.priority, m.signature)).collect();
+        assert_eq!(kept, vec![(7, "(J)I"), (0, "(JI)I")]);
+        assert!(conflicts_from(plan.iter().flat_map(|c| c.methods.iter().copied())).is_empty());
+    }
+
+    #[test]
+    fn test_plan_orders_negative_priorities_last() {
+        let regs = [
+            reg(-10, "a/Late", "nativeLate", "()V"),
+            reg(0, "a/Search", "nativeSearch", "()V"),
+            reg(-1, "a/Search", "nativeTeardown", "()V"),
+            reg(3, "a/Driver", "nativeOpen", "(I)Z"),
+        ];
+
+        let plan = plan_from(regs.iter());
+        assert_eq!(
+            names(&plan),
+            vec![
+                ("a/Driver".to_string(), vec!["nativeOpen"]),
+                ("a/Search".to_string(), vec!["nativeSearch", "nativeTeardown"]),
+                ("a/Late".to_string(), vec!["nativeLate"]),
+            ]
+        );
+        assert_eq!(plan[2].priority(), -10);
+    }
+
+    #[test]
+    fn test_plan_empty() {
+        assert!(plan_from(std::iter::empty()).is_empty());
+    }
+    fn init_stub(_env: &mut JNIEnv, _vm: &JavaVM) {}
+
+    fn fallible_init_stub(_env: &mut JNIEnv, _vm: &JavaVM) -> Result<(), String> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_steps_abort_on_error() {
+        let logger = JniInitializer {
+            priority: 100,
+            name: "init_logger",
+            init_fn: init_stub,
+        };
+        let driver = JniFallibleInitializer {
+            priority: 50,
+            name: "init_driver",
+            init_fn: fallible_init_stub,
+        };
+        let late = JniInitializer {
+            priority: -10,
+            name: "init_late",
+            init_fn: init_stub,
+        };
+        let steps = [InitStep::Infallible(&logger), InitStep::Fallible(&driver), InitStep::Infallible(&late)];
+
+        let fail_driver = |step: &InitStep| match step.name() {
+            "init_driver" => Err("device not found".to_string()),
+            _ => Ok(()),
+        };
+
+        let mut ran = Vec::new();
+        let result = run_steps(&steps, true, |step| {
+            ran.push(step.name());
+            fail_driver(step)
+        });
+        assert_eq!(
+            result,
+            Err(InitError {
+                name: "init_driver",
+                error: "device not found".to_string()
+            })
+        );
+        assert_eq!(ran, vec!["init_logger", "init_driver"]);
+
+        // Without abort the remaining initializers still run
+        ran.clear();
+        let result = run_steps(&steps, false, |step| {
+            ran.push(step.name());
+            fail_driver(step)
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(ran, vec!["init_logger", "init_driver", "init_late"]);
+    }
+
+    fn finalizer_stub(_vm: &JavaVM) {}
+
+    #[test]
+    fn test_finalizers_run_in_ascending_priority() {
+        let finalizer = |priority: i32, name: &'static str| JniFinalizer {
+            priority,
+            name,
+            finalize_fn: finalizer_stub,
+        };
+        let finalizers = [
+            finalizer(100, "close_logger"),
+            finalizer(-10, "flush_late"),
+            finalizer(50, "close_driver"),
+            finalizer(50, "close_search"),
+        ];
+
+        let names: Vec<&str> = finalizer_sequence(finalizers.iter()).iter().map(|f| f.name).collect();
+        assert_eq!(names, vec!["flush_late", "close_driver", "close_search", "close_logger"]);
+    }
+
+    #[test]
+    fn test_report_records_missing_class() {
+        let regs = [
+            reg(10, "a/Driver", "nativeOpen", "(I)Z"),
+            reg(0, "a/Missing", "nativeA", "()V"),
+            reg(0, "a/Missing", "nativeB", "()V"),
+            reg(0, "a/Search", "nativeSearch", "()V"),
+        ];
+        let plan = plan_from(regs.iter());
+
+        let report = report_from(&plan, |class_plan| match class_plan.class_path {
+            "a/Missing" => Err(ClassRegistrationError::ClassNotFound("java.lang.ClassNotFoundException: a.Missing".to_string())),
+            "a/Search" => Err(ClassRegistrationError::RegisterFailed("NoSuchMethodError".to_string())),
+            _ => Ok(()),
+        });
+
+        let summary: Vec<(&str, usize, bool)> = report.iter().map(|s| (s.class_path.as_str(), s.method_count, s.registered)).collect();
+        assert_eq!(summary, vec![("a/Driver", 1, true), ("a/Missing", 2, false), ("a/Search", 1, false)]);
+        assert_eq!(report[0].error, None);
+        assert_eq!(report[1].error.as_deref(), Some("class not found: java.lang.ClassNotFoundException: a.Missing"));
+        assert_eq!(report[2].error.as_deref(), Some("RegisterNatives failed: NoSuchMethodError"));
+    }
+
+    #[test]
+    fn test_class_registry_resolves_once() {
+        let registry: ClassRegistry<u32> = ClassRegistry::new();
+        let mut resolved = 0;
+
+        assert_eq!(registry.get("a/Search"), None);
+        let first = registry.get_or_try_insert_with("a/Search", || -> Result<u32, ()> {
+            resolved += 1;
+            Ok(7)
+        });
+        assert_eq!(first, Ok(7));
+
+        let second = registry.get_or_try_insert_with("a/Search", || -> Result<u32, ()> {
+            resolved += 1;
+            Ok(8)
+        });
+        assert_eq!(second, Ok(7));
+        assert_eq!(resolved, 1);
+        assert_eq!(registry.get("a/Search"), Some(7));
+
+        // Failures are not cached
+        assert_eq!(registry.get_or_try_insert_with("a/Missing", || Err("not found")), Err("not found"));
+        assert_eq!(registry.get("a/Missing"), None);
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_new_registration_fields() {
+        static REG: JniMethodRegistration = JniMethodRegistration::new(7, "a/Search", "nativeSearch", "(J)Z", native_stub as *mut c_void);
+
+        assert_eq!(REG.priority, 7);
+        assert_eq!(REG.class_path, "a/Search");
+        assert_eq!(REG.method_name, "nativeSearch");
+        assert_eq!(REG.signature, "(J)Z");
+        assert!(!REG.fn_ptr.is_null());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "null fn_ptr")]
+    fn test_new_rejects_null_fn_ptr() {
+        JniMethodRegistration::new(0, "a/Search", "nativeSearch", "()V", std::ptr::null_mut());
     }
 }
@@ -0,0 +1,155 @@
+//! JNI type descriptors derived from Rust function signatures
+
+use syn::{FnArg, GenericArgument, PathArguments, ReturnType, Signature, Type};
+
+/// Descriptor for a Rust parameter or return type, matched on the last path segment
+///
+/// Both the `jni::sys` aliases and the `jni::objects` wrappers are recognized. `JObject` and
+/// `jobject` can't say which class they hold, so they map to `java/lang/Object`.
+fn type_descriptor(ty: &Type) -> Option<&'static str> {
+    let ident = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path.segments.last()?.ident,
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return Some("V"),
+        Type::Paren(paren) => return type_descriptor(&paren.elem),
+        Type::Group(group) => return type_descriptor(&group.elem),
+        _ => return None,
+    };
+
+    let descriptor = match ident.to_string().as_str() {
+        "jboolean" | "bool" => "Z",
+        "jbyte" | "i8" => "B",
+        "jchar" | "u16" => "C",
+        "jshort" | "i16" => "S",
+        "jint" | "i32" | "jsize" => "I",
+        "jlong" | "i64" => "J",
+        "jfloat" | "f32" => "F",
+        "jdouble" | "f64" => "D",
+        "JObject" | "jobject" => "Ljava/lang/Object;",
+        "JString" | "jstring" => "Ljava/lang/String;",
+        "JClass" | "jclass" => "Ljava/lang/Class;",
+        "JThrowable" | "jthrowable" => "Ljava/lang/Throwable;",
+        "JByteBuffer" => "Ljava/nio/ByteBuffer;",
+        "JBooleanArray" | "jbooleanArray" => "[Z",
+        "JByteArray" | "jbyteArray" => "[B",
+        "JCharArray" | "jcharArray" => "[C",
+        "JShortArray" | "jshortArray" => "[S",
+        "JIntArray" | "jintArray" => "[I",
+        "JLongArray" | "jlongArray" => "[J",
+        "JFloatArray" | "jfloatArray" => "[F",
+        "JDoubleArray" | "jdoubleArray" => "[D",
+        "JObjectArray" | "jobjectArray" => "[Ljava/lang/Object;",
+        _ => return None,
+    };
+
+    // `JObject<'l>` etc. only carry a lifetime; anything else is not a JNI type
+    let generic = match ty {
+        Type::Path(path) => &path.path.segments.last()?.arguments,
+        _ => return Some(descriptor),
+    };
+    match generic {
+        PathArguments::None => Some(descriptor),
+        PathArguments::AngleBracketed(args) if args.args.iter().all(|arg| matches!(arg, GenericArgument::Lifetime(_))) => Some(descriptor),
+        _ => None,
+    }
+}
+
+fn return_descriptor(output: &ReturnType) -> Result<&'static str, &Type> {
+    match output {
+        ReturnType::Default => Ok("V"),
+        ReturnType::Type(_, ty) => type_descriptor(ty).ok_or(ty),
+    }
+}
+
+/// Build the descriptor for a native method, e.g. `(ILjava/lang/String;)Z`
+///
+/// The first two inputs are `JNIEnv` and the receiver (`JObject` or `JClass`) and are not part
+/// of the Java signature. A type without a known mapping is reported as an error on that type.
+pub(crate) fn method_descriptor(sig: &Signature) -> syn::Result<String> {
+    if sig.inputs.len() < 2 {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            "JNI native methods take `JNIEnv` and the receiver (`JObject`/`JClass`) as their first two parameters",
+        ));
+    }
+
+    let mut descriptor = String::from("(");
+    for input in sig.inputs.iter().skip(2) {
+        let ty = match input {
+            FnArg::Typed(pat) => &pat.ty,
+            FnArg::Receiver(receiver) => return Err(syn::Error::new_spanned(receiver, "JNI native methods can't take `self`")),
+        };
+        let param =
+            type_descriptor(ty).ok_or_else(|| syn::Error::new_spanned(ty, "Cannot derive a JNI descriptor for this type, pass the signature explicitly"))?;
+        descriptor.push_str(param);
+    }
+    descriptor.push(')');
+
+    let ret = return_descriptor(&sig.output)
+        .map_err(|ty| syn::Error::new_spanned(ty, "Cannot derive a JNI descriptor for this return type, pass the signature explicitly"))?;
+    descriptor.push_str(ret);
+    Ok(descriptor)
+}
+
+/// Return type part of an explicit signature, `Z` for `(I)Z`
+fn explicit_return(signature: &str) -> Option<&str> {
+    let (params, ret) = signature.strip_prefix('(')?.split_once(')')?;
+    if params.contains('(') || ret.is_empty() {
+        return None;
+    }
+    Some(ret)
+}
+
+/// Check an explicit signature against the Rust return type
+///
+/// Only mismatches that would certainly break at call time are reported. Return types without a
+/// known mapping are not checked. `JObject` accepts any reference type and `JObjectArray` any
+/// array of references, since the Java side may declare a concrete class.
+pub(crate) fn check_explicit(signature: &str, output: &ReturnType) -> Result<(), String> {
+    let ret = explicit_return(signature).ok_or_else(|| format!("Malformed JNI signature `{}`", signature))?;
+    let Ok(expected) = return_descriptor(output) else {
+        return Ok(());
+    };
+
+    let compatible = match expected {
+        "Ljava/lang/Object;" => ret.starts_with('L') || ret.starts_with('['),
+        "[Ljava/lang/Object;" => ret.starts_with("[L") || ret.starts_with("[["),
+        _ => ret == expected,
+    };
+    if compatible {
+        return Ok(());
+    }
+    Err(format!(
+        "JNI signature `{}` returns `{}`, but the function returns `{}`",
+        signature, ret, expected
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_descriptor_from_signature() {
+        let func: syn::ItemFn = syn::parse_quote! {
+            fn native(mut env: JNIEnv<'l>, _obj: JObject, pid: jint, name: JString, addrs: JLongArray<'l>) -> jboolean {}
+        };
+        assert_eq!(method_descriptor(&func.sig).unwrap(), "(ILjava/lang/String;[J)Z");
+
+        let func: syn::ItemFn = syn::parse_quote! { fn native(env: JNIEnv, _class: JClass) {} };
+        assert_eq!(method_descriptor(&func.sig).unwrap(), "()V");
+
+        let func: syn::ItemFn = syn::parse_quote! { fn native(env: JNIEnv, _obj: JObject, value: Vec<u8>) {} };
+        assert!(method_descriptor(&func.sig).is_err());
+
+        let ret: ReturnType = syn::parse_quote! { -> jboolean };
+        assert!(check_explicit("(I)Z", &ret).is_ok());
+        assert!(check_explicit("(I)V", &ret).is_err());
+        let ret: ReturnType = syn::parse_quote! { -> JObject<'l> };
+        assert!(check_explicit("()Lmoe/fuqiuluo/mamu/data/ProcessInfo;", &ret).is_ok());
+        assert!(check_explicit("()I", &ret).is_err());
+        let ret: ReturnType = syn::parse_quote! { -> jobjectArray };
+        assert!(check_explicit("(II)[[B", &ret).is_ok());
+        assert!(check_explicit("(II)[B", &ret).is_err());
+        assert!(check_explicit("I)Z", &ReturnType::Default).is_err());
+    }
+}
@@ -1,6 +1,8 @@
+mod descriptor;
+
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, ItemFn, LitInt, LitStr, Token};
+use syn::{parse_macro_input, ItemFn, LitInt, LitStr, ReturnType, Token};
 use syn::parse::{Parse, ParseStream};
 
 /// Attribute macro to register JNI initialization functions
@@ -13,14 +15,23 @@ use syn::parse::{Parse, ParseStream};
 /// fn init_driver(env: &mut JNIEnv, vm: &JavaVM) {
 ///     // initialization code
 /// }
+///
+/// #[jni_onload(-10)]  // Negative priorities run after every non-negative one
+/// fn init_late(env: &mut JNIEnv, vm: &JavaVM) {
+///     // initialization code
+/// }
+///
+/// #[jni_onload(90)]  // Errors are logged with the function name and may abort JNI_OnLoad
+/// fn init_config(env: &mut JNIEnv, vm: &JavaVM) -> anyhow::Result<()> {
+///     Ok(())
+/// }
 /// ```
 #[proc_macro_attribute]
 pub fn jni_onload(args: TokenStream, input: TokenStream) -> TokenStream {
-    let priority = parse_macro_input!(args as LitInt);
+    let priority = parse_macro_input!(args as Priority);
     let func = parse_macro_input!(input as ItemFn);
 
-    let priority_value: u32 = priority.base10_parse()
-        .expect("Priority must be a valid u32 number");
+    let priority_value = priority.value();
 
     let func_name = &func.sig.ident;
     let func_vis = &func.vis;
@@ -28,15 +39,73 @@ pub fn jni_onload(args: TokenStream, input: TokenStream) -> TokenStream {
     let func_sig = &func.sig;
     let func_block = &func.block;
 
+    // A function returning `Result<(), E>` is registered as fallible; `E` only needs `Display`
+    let registration = match func_sig.output {
+        ReturnType::Default => quote! {
+            ::jni_macro::JniInitializer {
+                priority: #priority_value,
+                name: stringify!(#func_name),
+                init_fn: #func_name,
+            }
+        },
+        ReturnType::Type(..) => quote! {
+            ::jni_macro::JniFallibleInitializer {
+                priority: #priority_value,
+                name: stringify!(#func_name),
+                init_fn: |env, vm| #func_name(env, vm).map_err(|error| ::std::format!("{:#}", error)),
+            }
+        },
+    };
+
     let expanded = quote! {
         #(#func_attrs)*
         #func_vis #func_sig #func_block
 
         ::jni_macro::inventory::submit! {
-            ::jni_macro::JniInitializer {
+            #registration
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Attribute macro to register JNI teardown functions, run by `run_all_jni_finalizers`
+///
+/// Finalizers run in ascending priority, the reverse of `#[jni_onload]`, so a subsystem that
+/// initialized first is torn down last. `JNIEnv` may be unavailable during unload, so only the
+/// `JavaVM` is passed.
+///
+/// # Example
+/// ```
+/// use jni_macro::jni_onunload;
+///
+/// #[jni_onunload(100)]  // Priority: higher number = execute last
+/// fn close_driver(vm: &JavaVM) {
+///     // cleanup code
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn jni_onunload(args: TokenStream, input: TokenStream) -> TokenStream {
+    let priority = parse_macro_input!(args as Priority);
+    let func = parse_macro_input!(input as ItemFn);
+
+    let priority_value = priority.value();
+
+    let func_name = &func.sig.ident;
+    let func_vis = &func.vis;
+    let func_attrs = &func.attrs;
+    let func_sig = &func.sig;
+    let func_block = &func.block;
+
+    let expanded = quote! {
+        #(#func_attrs)*
+        #func_vis #func_sig #func_block
+
+        ::jni_macro::inventory::submit! {
+            ::jni_macro::JniFinalizer {
                 priority: #priority_value,
                 name: stringify!(#func_name),
-                init_fn: #func_name,
+                finalize_fn: #func_name,
             }
         }
     };
@@ -44,23 +113,53 @@ pub fn jni_onload(args: TokenStream, input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
-/// Parse arguments for jni_method: (priority, class_path, method_name, signature)
+/// Priority literal, optionally negated: `100`, `-10`
+///
+/// A negative literal is two tokens (`-` and the integer), so it can't be parsed as a plain `LitInt`.
+struct Priority {
+    negative: bool,
+    literal: LitInt,
+}
+
+impl Priority {
+    fn value(&self) -> i32 {
+        let magnitude: i64 = self.literal.base10_parse()
+            .expect("Priority must be a valid i32 number");
+        let value = if self.negative { -magnitude } else { magnitude };
+        i32::try_from(value).expect("Priority must be a valid i32 number")
+    }
+}
+
+impl Parse for Priority {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let negative = input.parse::<Option<Token![-]>>()?.is_some();
+        let literal: LitInt = input.parse()?;
+        Ok(Priority { negative, literal })
+    }
+}
+
+/// Parse arguments for jni_method: (priority, class_path, method_name[, signature])
 struct JniMethodArgs {
-    priority: LitInt,
+    priority: Priority,
     class_path: LitStr,
     method_name: LitStr,
-    signature: LitStr,
+    /// Derived from the Rust signature when omitted
+    signature: Option<LitStr>,
 }
 
 impl Parse for JniMethodArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let priority: LitInt = input.parse()?;
+        let priority: Priority = input.parse()?;
         input.parse::<Token![,]>()?;
         let class_path: LitStr = input.parse()?;
         input.parse::<Token![,]>()?;
         let method_name: LitStr = input.parse()?;
-        input.parse::<Token![,]>()?;
-        let signature: LitStr = input.parse()?;
+        let signature = if input.is_empty() {
+            None
+        } else {
+            input.parse::<Token![,]>()?;
+            Some(input.parse()?)
+        };
 
         Ok(JniMethodArgs {
             priority,
@@ -83,17 +182,41 @@ impl Parse for JniMethodArgs {
 ///     // implementation
 ///     1
 /// }
+///
+/// #[jni_method(-1, "com/example/MyClass", "lateMethod", "()V")]  // Registered after non-negative priorities
+/// pub fn late_method(mut env: JNIEnv, obj: JObject) {}
+///
+/// #[jni_method(90, "com/example/MyClass", "nativeBind")]  // Signature derived as "(ILjava/lang/String;)Z"
+/// pub fn bind(mut env: JNIEnv, obj: JObject, pid: jint, name: JString) -> jboolean {
+///     1
+/// }
 /// ```
+///
+/// When the signature is omitted it is built from the parameters after `JNIEnv` and the receiver
+/// and from the return type. `JObject` maps to `java/lang/Object`, so methods taking or returning
+/// a concrete class still need an explicit signature. An explicit signature whose return type
+/// contradicts the Rust return type is a compile error.
 #[proc_macro_attribute]
 pub fn jni_method(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as JniMethodArgs);
     let func = parse_macro_input!(input as ItemFn);
 
-    let priority_value: u32 = args.priority.base10_parse()
-        .expect("Priority must be a valid u32 number");
+    let priority_value = args.priority.value();
     let class_path = args.class_path.value();
     let method_name = args.method_name.value();
-    let signature = args.signature.value();
+    let signature = match &args.signature {
+        Some(literal) => {
+            let signature = literal.value();
+            if let Err(message) = descriptor::check_explicit(&signature, &func.sig.output) {
+                return syn::Error::new_spanned(literal, message).to_compile_error().into();
+            }
+            signature
+        },
+        None => match descriptor::method_descriptor(&func.sig) {
+            Ok(signature) => signature,
+            Err(error) => return error.to_compile_error().into(),
+        },
+    };
 
     let func_name = &func.sig.ident;
     let func_vis = &func.vis;
@@ -106,13 +229,13 @@ pub fn jni_method(args: TokenStream, input: TokenStream) -> TokenStream {
         #func_vis #func_sig #func_block
 
         ::jni_macro::inventory::submit! {
-            ::jni_macro::JniMethodRegistration {
-                priority: #priority_value,
-                class_path: #class_path,
-                method_name: #method_name,
-                signature: #signature,
-                fn_ptr: #func_name as *mut ::std::ffi::c_void,
-            }
+            ::jni_macro::JniMethodRegistration::new(
+                #priority_value,
+                #class_path,
+                #method_name,
+                #signature,
+                #func_name as *mut ::std::ffi::c_void,
+            )
         }
     };
 
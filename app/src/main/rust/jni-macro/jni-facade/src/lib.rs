@@ -5,7 +5,8 @@
 //! ## Features
 //!
 //! - **Distributed initialization**: Register JNI init functions across multiple modules
-//! - **Priority-based ordering**: Control execution order with numeric priorities (higher = first)
+//! - **Priority-based ordering**: Control execution order with signed priorities (higher = first, negative = after all others)
+//! - **Symmetric teardown**: `#[jni_onunload]` finalizers run from `JNI_OnUnload` in reverse priority order
 //! - **Zero boilerplate**: Automatic collection via `inventory` crate
 //!
 //! ## Example
@@ -23,13 +24,28 @@
 //! fn init_logging(env: &mut JNIEnv, vm: &JavaVM) {
 //!     // Initialize logging
 //! }
+//!
+//! #[jni_onload(-10)]  // Negative priority - runs after all non-negative ones
+//! fn init_late(env: &mut JNIEnv, vm: &JavaVM) {
+//!     // Late initialization
+//! }
+//!
+//! #[jni_onload(90)]   // Returning `Result` reports failures to `run_initializers`
+//! fn init_config(env: &mut JNIEnv, vm: &JavaVM) -> anyhow::Result<()> {
+//!     Ok(())
+//! }
 //! ```
 
 // Re-export core types and functions
-pub use jni_macro_core::{JniInitFn, JniInitializer, JniMethodRegistration, register_all_jni_methods};
+pub use jni_macro_core::{
+    ClassPlan, ClassRegistrationError, ClassRegistrationStatus, ClassRegistry, Conflict, InitError, InitStep, JniFallibleInitFn, JniFallibleInitializer,
+    JniFinalizer, JniFinalizerFn, JniInitFn, JniInitializer, JniMethodRegistration, apply_plan, conflicts_from, describe_from, describe_registrations,
+    finalizer_sequence, get_cached_class, init_sequence, iter_method_registrations, plan_from, plan_registrations, register_all_jni_methods,
+    register_all_jni_methods_checked, register_global_class, registration_report, report_from, run_all_jni_finalizers, run_initializers, run_steps,
+};
 
 // Re-export the procedural macros
-pub use jni_macro_derive::{jni_onload, jni_method};
+pub use jni_macro_derive::{jni_onload, jni_onunload, jni_method};
 
 // Re-export inventory for macro expansion
 #[doc(hidden)]
@@ -1,8 +1,56 @@
 //! Driver manager implementation
 
+use crate::core::globals::{bump_memory_generation, PAGE_SIZE};
+use crate::core::maps_watcher;
+use crate::core::memory_source::MemorySource;
 use crate::core::memory_mode::MemoryAccessMode;
-use crate::wuwa::{BindProc, PageStatusBitmap, WuWaDriver, WuwaMemoryType};
-use log::error;
+use crate::core::value_guess;
+use crate::search::parser::parse_address;
+use crate::search::ValueType;
+use crate::wuwa::{BindProc, PageStatusBitmap, WuWaDriver, WuwaMemRegionEntry, WuwaMemoryType, MEM_READABLE};
+use log::{debug, error, warn};
+use nix::sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+use std::num::NonZeroUsize;
+use std::os::fd::{FromRawFd, OwnedFd};
+
+/// AArch64 `NOP` 指令（0xD503201F，小端）
+pub const AARCH64_NOP: [u8; 4] = [0x1F, 0x20, 0x03, 0xD5];
+
+/// [`DriverManager::fill_memory`] 与 [`DriverManager::nop_memory`] 的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FillReport {
+    /// 成功写入的字节数
+    pub bytes_written: usize,
+    /// 写入失败的页起始地址
+    pub failed_pages: Vec<u64>,
+}
+
+/// 从 `start` 开始重复写入 `pattern` 直到 `end`，按页分批，某页写入失败不影响其余页
+///
+/// `pattern` 的相位以 `start` 为准，跨页后保持连续
+pub(crate) fn fill_with<W>(start: u64, end: u64, pattern: &[u8], mut write: W) -> FillReport
+where
+    W: FnMut(u64, &[u8]) -> anyhow::Result<()>,
+{
+    let page_size = *PAGE_SIZE as u64;
+    let mut report = FillReport::default();
+    let mut buf = Vec::with_capacity(*PAGE_SIZE);
+    let mut addr = start;
+    while addr < end {
+        let page_start = addr & !(page_size - 1);
+        let chunk_end = page_start.saturating_add(page_size).min(end);
+        let phase = ((addr - start) % pattern.len() as u64) as usize;
+        buf.clear();
+        buf.extend(pattern.iter().cycle().skip(phase).take((chunk_end - addr) as usize));
+
+        match write(addr, &buf) {
+            Ok(()) => report.bytes_written += buf.len(),
+            Err(_) => report.failed_pages.push(page_start),
+        }
+        addr = chunk_end;
+    }
+    report
+}
 
 pub struct DriverManager {
     driver: Option<WuWaDriver>,
@@ -79,6 +127,7 @@ impl DriverManager {
         // 缺页模式和物理模式不需要设置内存类型，这个时候不走bindproc去读写内存
         self.bound_process = Some(bind_proc);
         self.bound_pid = pid;
+        bump_memory_generation();
         Ok(())
     }
 
@@ -86,6 +135,7 @@ impl DriverManager {
     pub fn unbind_process(&mut self) {
         self.bound_process = None;
         self.bound_pid = 0;
+        bump_memory_generation();
     }
 
     pub fn is_process_bound(&self) -> bool {
@@ -100,6 +150,88 @@ impl DriverManager {
         self.bound_process.as_ref()
     }
 
+    /// 当前绑定进程可读区域列表（见 [`Self::readable_regions`]）的指纹，映射增减时改变
+    ///
+    /// 未绑定进程或驱动枚举失败时返回 [`maps_watcher::UNAVAILABLE_FINGERPRINT`]；
+    /// 与上一次的值比较见 [`maps_watcher::poll_fingerprint`]，后台监视见 [`maps_watcher::MapsWatcher`]
+    pub fn maps_fingerprint(&self) -> u64 {
+        match self.readable_regions() {
+            Ok(regions) => maps_watcher::fingerprint_regions(&regions),
+            Err(e) => {
+                debug!("Failed to query regions for fingerprint: {}", e);
+                maps_watcher::UNAVAILABLE_FINGERPRINT
+            },
+        }
+    }
+
+    /// 通过驱动枚举当前绑定进程的可读内存区域 `[start, end)`
+    pub fn readable_regions(&self) -> anyhow::Result<Vec<(u64, u64)>> {
+        let driver = self.get_driver().ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+        if !self.is_process_bound() {
+            return Err(anyhow::anyhow!("Process not bound"));
+        }
+
+        let result = driver.query_mem_regions(self.bound_pid, 0, 0)?;
+        // fd 随 OwnedFd 一起关闭
+        let fd = unsafe { OwnedFd::from_raw_fd(result.fd) };
+        if result.entry_count == 0 {
+            return Ok(Vec::new());
+        }
+        let size = NonZeroUsize::new(result.buffer_size).ok_or_else(|| anyhow::anyhow!("Invalid buffer size"))?;
+        let mapped = unsafe { mmap(None, size, ProtFlags::PROT_READ, MapFlags::MAP_PRIVATE, &fd, 0)? };
+
+        let entries = unsafe { std::slice::from_raw_parts(mapped.as_ptr() as *const WuwaMemRegionEntry, result.entry_count) };
+        let regions = entries
+            .iter()
+            .filter(|entry| entry.type_ & MEM_READABLE != 0)
+            .map(|entry| (entry.start, entry.end))
+            .collect();
+
+        unsafe {
+            let _ = munmap(mapped, result.buffer_size);
+        }
+        Ok(regions)
+    }
+
+    /// 解析地址字符串，支持 `libil2cpp.so+0x1A2B3C` 这类相对模块基址的写法
+    ///
+    /// 模块基址通过驱动在当前绑定进程中查询，不过滤 VM 标志
+    pub fn resolve_address(&self, input: &str) -> anyhow::Result<u64> {
+        let expr = parse_address(input).map_err(|e| anyhow::anyhow!("Invalid address '{}': {}", input, e))?;
+        match &expr.module {
+            None => expr.resolve(|_| None).map_err(|e| anyhow::anyhow!(e)),
+            Some(_) => {
+                let driver = self
+                    .get_driver()
+                    .ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+                if !self.is_process_bound() {
+                    return Err(anyhow::anyhow!("Process not bound"));
+                }
+                let pid = self.get_bound_pid();
+                expr.resolve(|name| driver.get_module_base(pid, name, 0).ok().map(|base| base as u64))
+                    .map_err(|e| anyhow::anyhow!(e))
+            },
+        }
+    }
+
+    /// 猜测 `addr` 处的值类型，返回按置信度降序排列的 (类型, 置信度)，读取失败时返回空列表
+    ///
+    /// 读取 8 字节按各类型解释打分，指针通过尝试读取目标地址判断是否已映射，见 [`value_guess::guess_value_type`]
+    pub fn guess_value_type(&self, addr: u64) -> Vec<(ValueType, f64)> {
+        let mut bytes = [0u8; 8];
+        if !self.is_readable(addr, &mut bytes) {
+            return Vec::new();
+        }
+        value_guess::guess_value_type(&bytes, |target| self.is_readable(target, &mut [0u8; 1]))
+    }
+
+    /// 读取 `buf.len()` 字节，涉及的页都读取成功时返回 true
+    fn is_readable(&self, addr: u64, buf: &mut [u8]) -> bool {
+        let mut page_status = PageStatusBitmap::new(buf.len(), addr as usize);
+        self.read_memory_unified(addr, buf, Some(&mut page_status)).is_ok()
+            && (0..page_status.num_pages()).all(|i| page_status.is_page_success(i))
+    }
+
     /// 统一的内存读取方法，使用当前配置的 access_mode
     ///
     /// # Arguments
@@ -116,7 +248,29 @@ impl DriverManager {
         buf: &mut [u8],
         page_status: Option<&mut PageStatusBitmap>,
     ) -> anyhow::Result<()> {
-        match self.access_mode {
+        self.read_memory_with_mode(self.access_mode, addr, buf, page_status)
+    }
+
+    /// [`Self::read_memory_with_mode`] 能否使用该模式读取
+    pub fn can_read_with_mode(&self, mode: MemoryAccessMode) -> bool {
+        match mode {
+            MemoryAccessMode::None | MemoryAccessMode::PageFault => true,
+            MemoryAccessMode::NonCacheable | MemoryAccessMode::WriteThrough | MemoryAccessMode::Normal => mode == self.access_mode,
+        }
+    }
+
+    /// 使用指定的 access_mode 读取内存，不修改当前配置
+    ///
+    /// NonCacheable/WriteThrough/Normal 依赖 bind_proc 上已设置的内存类型，临时切换会影响并发读取，
+    /// 因此这三种模式只有与当前配置一致时才能使用，否则返回错误
+    pub fn read_memory_with_mode(
+        &self,
+        mode: MemoryAccessMode,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
+    ) -> anyhow::Result<()> {
+        match mode {
             MemoryAccessMode::None => {
                 // 物理内存读取（绕过 access_mode）
                 let driver = self
@@ -160,6 +314,13 @@ impl DriverManager {
                 Ok(())
             },
             MemoryAccessMode::NonCacheable | MemoryAccessMode::WriteThrough | MemoryAccessMode::Normal => {
+                if !self.can_read_with_mode(mode) {
+                    return Err(anyhow::anyhow!(
+                        "Memory mode {:?} requires it to be the configured access mode (current: {:?})",
+                        mode,
+                        self.access_mode
+                    ));
+                }
                 // 使用 bind_proc 和配置的 access_mode
                 let bind_proc = self
                     .get_bound_process()
@@ -183,7 +344,26 @@ impl DriverManager {
         addr: u64,
         buf: &[u8],
     ) -> anyhow::Result<()> {
-        match self.access_mode {
+        self.write_memory_with_mode(self.access_mode, addr, buf)
+    }
+
+    /// 使用指定的 access_mode 写入内存，限制同 [`Self::read_memory_with_mode`]
+    pub fn write_memory_with_mode(
+        &self,
+        mode: MemoryAccessMode,
+        addr: u64,
+        buf: &[u8],
+    ) -> anyhow::Result<()> {
+        if !self.can_read_with_mode(mode) {
+            return Err(anyhow::anyhow!(
+                "Memory mode {:?} requires it to be the configured access mode (current: {:?})",
+                mode,
+                self.access_mode
+            ));
+        }
+        // 写入失败也可能已部分写入，一律视为内存已改变
+        bump_memory_generation();
+        match mode {
             MemoryAccessMode::None => {
                 // 物理内存写入（绕过 access_mode）
                 let driver = self
@@ -221,4 +401,132 @@ impl DriverManager {
             },
         }
     }
+
+    /// 用 `pattern` 重复填充 `[start, end)`，返回写入的字节数和不可写的页
+    ///
+    /// 按页分批写入，不可写的页跳过并记录到日志；没有任何一页写入成功时返回错误
+    pub fn fill_memory(&self, start: u64, end: u64, pattern: &[u8], mode: i32) -> anyhow::Result<FillReport> {
+        let mode = MemoryAccessMode::from_id(mode).ok_or_else(|| anyhow::anyhow!("Invalid memory mode: {}", mode))?;
+        Self::fill_memory_with(start, end, pattern, |addr, buf| self.write_memory_with_mode(mode, addr, buf))
+    }
+
+    /// [`Self::fill_memory`] 的实现，`write` 负责写入一页内的数据
+    pub(crate) fn fill_memory_with<W>(start: u64, end: u64, pattern: &[u8], write: W) -> anyhow::Result<FillReport>
+    where
+        W: FnMut(u64, &[u8]) -> anyhow::Result<()>,
+    {
+        if pattern.is_empty() {
+            return Err(anyhow::anyhow!("Fill pattern is empty"));
+        }
+        if start >= end {
+            return Err(anyhow::anyhow!("Invalid fill range 0x{:X} - 0x{:X}", start, end));
+        }
+
+        let report = fill_with(start, end, pattern, write);
+        if !report.failed_pages.is_empty() {
+            if report.bytes_written == 0 {
+                return Err(anyhow::anyhow!("No writable page in 0x{:X} - 0x{:X}", start, end));
+            }
+            warn!(
+                "fill_memory: {} unwritable pages in 0x{:X} - 0x{:X}: {:X?}",
+                report.failed_pages.len(),
+                start,
+                end,
+                report.failed_pages
+            );
+        }
+        Ok(report)
+    }
+
+    /// 用 AArch64 `NOP` 覆盖 `[start, end)` 内的指令，两端必须 4 字节对齐
+    pub fn nop_memory(&self, start: u64, end: u64, mode: i32) -> anyhow::Result<FillReport> {
+        if start % 4 != 0 || end % 4 != 0 {
+            return Err(anyhow::anyhow!("NOP range 0x{:X} - 0x{:X} is not 4-byte aligned", start, end));
+        }
+        self.fill_memory(start, end, &AARCH64_NOP, mode)
+    }
+}
+
+impl MemorySource for DriverManager {
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> anyhow::Result<()> {
+        self.read_memory_unified(addr, buf, Some(page_status))
+    }
+
+    /// 枚举失败时返回空列表
+    fn regions(&self) -> Vec<(u64, u64)> {
+        self.readable_regions().unwrap_or_else(|e| {
+            warn!("Failed to query memory regions: {:?}", e);
+            Vec::new()
+        })
+    }
+
+    fn write(&self, addr: u64, buf: &[u8]) -> anyhow::Result<()> {
+        self.write_memory_unified(addr, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RamSource;
+
+    #[test]
+    fn test_fill_reports_unwritable_pages() {
+        let page = *PAGE_SIZE as u64;
+        let base = 0x7000000000u64;
+        // 第二页未映射
+        let ram = RamSource::new();
+        ram.add_segment(base, vec![0u8; page as usize]).unwrap();
+        ram.add_segment(base + 2 * page, vec![0u8; page as usize]).unwrap();
+
+        let (start, end) = (base + page - 6, base + 2 * page + 10);
+        let report = fill_with(start, end, &[0xAA, 0xBB, 0xCC], |addr, buf| ram.write(addr, buf));
+        assert_eq!(report.failed_pages, vec![base + page]);
+        assert_eq!(report.bytes_written, 16);
+
+        let mut page_status = PageStatusBitmap::new(page as usize, base as usize);
+        let mut head = vec![0u8; 8];
+        ram.read(start - 2, &mut head, &mut page_status).unwrap();
+        assert_eq!(head, [0x00, 0x00, 0xAA, 0xBB, 0xCC, 0xAA, 0xBB, 0xCC]);
+        // 跨过未映射的页后 pattern 相位保持连续
+        let mut tail = vec![0u8; 12];
+        ram.read(base + 2 * page, &mut tail, &mut page_status).unwrap();
+        let phase = ((base + 2 * page - start) % 3) as usize;
+        let expected: Vec<u8> = [0xAA, 0xBB, 0xCC].iter().cycle().skip(phase).take(10).copied().chain([0, 0]).collect();
+        assert_eq!(tail, expected);
+
+        let nops = fill_with(base, base + 8, &AARCH64_NOP, |addr, buf| ram.write(addr, buf));
+        assert_eq!(nops.bytes_written, 8);
+        let mut code = vec![0u8; 8];
+        ram.read(base, &mut code, &mut page_status).unwrap();
+        assert_eq!(u32::from_le_bytes(code[4..].try_into().unwrap()), 0xD503201F);
+    }
+
+    #[test]
+    fn test_fill_memory_returns_report() {
+        let page = *PAGE_SIZE as u64;
+        let base = 0x7000000000u64;
+        let ram = RamSource::new();
+        ram.add_segment(base, vec![0u8; page as usize]).unwrap();
+
+        // 第二页未映射：部分写入成功时返回报告，由调用方决定如何提示
+        let report = DriverManager::fill_memory_with(base + page - 4, base + page + 4, &[0x90], |addr, buf| ram.write(addr, buf)).unwrap();
+        assert_eq!(
+            report,
+            FillReport {
+                bytes_written: 4,
+                failed_pages: vec![base + page],
+            }
+        );
+
+        let fill = |start: u64, end: u64, pattern: &[u8]| DriverManager::fill_memory_with(start, end, pattern, |addr, buf| ram.write(addr, buf));
+        assert!(fill(base + page, base + 2 * page, &[0x90]).is_err(), "no writable page");
+        assert!(fill(base, base + 4, &[]).is_err(), "empty pattern");
+        assert!(fill(base + 4, base, &[0x90]).is_err(), "reversed range");
+
+        let manager = DriverManager::new();
+        assert!(manager.fill_memory(base, base + 4, &[0x90], -1).is_err(), "invalid memory mode");
+        let mode = MemoryAccessMode::None.to_id();
+        assert!(manager.nop_memory(base + 2, base + 8, mode).is_err(), "unaligned NOP range");
+    }
 }
@@ -1,13 +1,21 @@
 //! Freeze Manager - 内存值冻结管理器
 //!
 //! 使用 tokio 实现高精度定时写入，将冻结的地址值持续写入目标进程内存。
+//!
+//! 所有冻结条目共用一个后台定时任务：调度器用最小堆维护每个条目的下次到期时间，
+//! 任务只在最近的到期时刻醒来，同一页内同时到期的写入会被合并。
+//!
+//! 监视条目（见 [`FreezeManager::watch_and_lock`]）与冻结条目共用调度器：到期时读取并与上次的值比较，
+//! 首次发现变化就转为冻结条目，冻结值为变化前的值。
 
-use crate::core::globals::DRIVER_MANAGER;
+use crate::core::globals::{driver_manager_read, PAGE_SIZE};
 use dashmap::DashMap;
 use log::{debug, error, warn};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 
@@ -18,17 +26,165 @@ pub struct FrozenEntry {
     pub value: Vec<u8>,
     /// 值类型 ID（用于调试/日志）
     pub value_type: i32,
+    /// 所属冻结组名称，单独添加的冻结为 None
+    pub group: Option<String>,
+    /// 该条目的写入间隔（微秒），None 表示使用全局间隔
+    pub interval_us: Option<u64>,
+}
+
+/// 变化监视条目：记录上一次读到的值，第一次读到不同的值时给出变化前的值
+#[derive(Clone)]
+pub struct ChangeWatch {
+    /// 每次读取的字节数
+    pub size: usize,
+    /// 值类型 ID，转为冻结条目时沿用
+    pub value_type: i32,
+    /// 转为冻结条目后的写入间隔，None 表示使用全局间隔
+    pub interval_us: Option<u64>,
+    last: Option<Vec<u8>>,
+}
+
+impl ChangeWatch {
+    pub fn new(size: usize, value_type: i32, interval_us: Option<u64>) -> Self {
+        Self {
+            size,
+            value_type,
+            interval_us,
+            last: None,
+        }
+    }
+
+    /// 记录一次读取，值与上一次不同时返回上一次的值；第一次读取只作为基准
+    pub fn observe(&mut self, current: &[u8]) -> Option<Vec<u8>> {
+        match &self.last {
+            Some(previous) if previous.as_slice() != current => Some(previous.clone()),
+            Some(_) => None,
+            None => {
+                self.last = Some(current.to_vec());
+                None
+            },
+        }
+    }
+}
+
+/// 相位槽数量，新条目按所在页分配到不同槽，避免所有条目在同一时刻写入
+const PHASE_SLOTS: u64 = 16;
+
+/// 冻结调度器
+///
+/// 维护 (下次到期时间, 地址) 的最小堆。时间由调用方传入（微秒），
+/// 后台任务使用单调时钟，测试可以直接传入模拟时间。
+#[derive(Default)]
+pub struct FreezeScheduler {
+    heap: BinaryHeap<Reverse<(u64, u64)>>,
+    /// 地址 -> 当前有效的到期时间，堆中与之不符的项视为过期
+    due: HashMap<u64, u64>,
+}
+
+impl FreezeScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 调度一个地址，首次到期时间为 now 加上按页计算的相位偏移
+    ///
+    /// 同一页的地址得到相同的相位，因此会在同一时刻到期并被合并写入。
+    pub fn schedule(&mut self, addr: u64, interval_us: u64, now_us: u64) {
+        let interval_us = interval_us.max(1);
+        let page = addr / *PAGE_SIZE as u64;
+        let phase = (page % PHASE_SLOTS) * interval_us / PHASE_SLOTS;
+        let due = now_us.saturating_add(phase);
+        self.due.insert(addr, due);
+        self.heap.push(Reverse((due, addr)));
+    }
+
+    /// 取消地址的调度，堆中残留的项会在弹出时被跳过
+    pub fn unschedule(&mut self, addr: u64) {
+        self.due.remove(&addr);
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.due.clear();
+    }
+
+    /// 最近一次到期时间
+    pub fn next_due(&mut self) -> Option<u64> {
+        while let Some(&Reverse((due, addr))) = self.heap.peek() {
+            if self.due.get(&addr) == Some(&due) {
+                return Some(due);
+            }
+            self.heap.pop();
+        }
+        None
+    }
+
+    /// 弹出所有在 now 之前到期的地址，并按各自的间隔重新调度
+    ///
+    /// `interval_of` 返回地址当前的间隔，返回 None 表示条目已被移除。
+    /// 如果任务落后了多个周期，只补写一次，下次到期时间对齐到原有节拍上。
+    pub fn pop_due<F>(&mut self, now_us: u64, interval_of: F) -> Vec<u64>
+    where
+        F: Fn(u64) -> Option<u64>,
+    {
+        let mut ready = Vec::new();
+        while let Some(&Reverse((due, addr))) = self.heap.peek() {
+            if due > now_us {
+                break;
+            }
+            self.heap.pop();
+            if self.due.get(&addr) != Some(&due) {
+                continue;
+            }
+            let Some(interval_us) = interval_of(addr) else {
+                self.due.remove(&addr);
+                continue;
+            };
+            let interval_us = interval_us.max(1);
+            let periods = (now_us - due) / interval_us + 1;
+            let next = due.saturating_add(periods.saturating_mul(interval_us));
+            self.due.insert(addr, next);
+            self.heap.push(Reverse((next, addr)));
+            ready.push(addr);
+        }
+        ready
+    }
+}
+
+/// 合并同一页内相邻的写入，返回按地址排序的写入列表
+pub fn coalesce_writes(mut writes: Vec<(u64, Vec<u8>)>) -> Vec<(u64, Vec<u8>)> {
+    writes.sort_by_key(|(addr, _)| *addr);
+    let page_size = *PAGE_SIZE as u64;
+    let mut merged: Vec<(u64, Vec<u8>)> = Vec::with_capacity(writes.len());
+    for (addr, bytes) in writes {
+        if let Some((last_addr, last_bytes)) = merged.last_mut() {
+            let last_end = *last_addr + last_bytes.len() as u64;
+            let end = addr + bytes.len() as u64;
+            if last_end == addr && !bytes.is_empty() && *last_addr / page_size == (end - 1) / page_size {
+                last_bytes.extend_from_slice(&bytes);
+                continue;
+            }
+        }
+        merged.push((addr, bytes));
+    }
+    merged
 }
 
 /// 冻结管理器
 pub struct FreezeManager {
     /// 冻结地址映射表：地址 -> 冻结条目
     frozen_entries: Arc<DashMap<u64, FrozenEntry>>,
+    /// 监视地址映射表：地址 -> 监视条目，发现变化后移入 `frozen_entries`
+    watches: Arc<DashMap<u64, ChangeWatch>>,
     /// 冻结间隔（微秒）
     interval_us: Arc<AtomicU64>,
+    /// 共享的写入调度器
+    scheduler: Arc<Mutex<FreezeScheduler>>,
+    /// 调度器时间基准
+    epoch: Instant,
     /// 是否正在运行
     running: Arc<AtomicBool>,
-    /// 用于通知任务停止
+    /// 用于通知任务停止或调度变化
     stop_notify: Arc<Notify>,
     /// 后台任务句柄
     task_handle: Option<JoinHandle<()>>,
@@ -38,7 +194,10 @@ impl FreezeManager {
     pub fn new() -> Self {
         Self {
             frozen_entries: Arc::new(DashMap::new()),
+            watches: Arc::new(DashMap::new()),
             interval_us: Arc::new(AtomicU64::new(33000)), // 默认 33ms
+            scheduler: Arc::new(Mutex::new(FreezeScheduler::new())),
+            epoch: Instant::now(),
             running: Arc::new(AtomicBool::new(false)),
             stop_notify: Arc::new(Notify::new()),
             task_handle: None,
@@ -54,7 +213,10 @@ impl FreezeManager {
         self.running.store(true, Ordering::SeqCst);
 
         let entries = Arc::clone(&self.frozen_entries);
+        let watches = Arc::clone(&self.watches);
         let interval_us = Arc::clone(&self.interval_us);
+        let scheduler = Arc::clone(&self.scheduler);
+        let epoch = self.epoch;
         let running = Arc::clone(&self.running);
         let stop_notify = Arc::clone(&self.stop_notify);
 
@@ -67,17 +229,29 @@ impl FreezeManager {
                     break;
                 }
 
-                // 获取当前间隔
-                let interval = Duration::from_micros(interval_us.load(Ordering::Relaxed));
+                let global_interval = interval_us.load(Ordering::Relaxed);
+                let now = epoch.elapsed().as_micros() as u64;
 
-                // 执行冻结写入
-                if !entries.is_empty() {
-                    Self::write_frozen_values(&entries);
+                // 弹出到期的地址并执行冻结写入
+                let due = match scheduler.lock() {
+                    Ok(mut s) => s.pop_due(now, |addr| Self::interval_of(&entries, &watches, addr, global_interval)),
+                    Err(e) => {
+                        error!("FreezeManager: 调度器锁已损坏: {}", e);
+                        break;
+                    },
+                };
+                if !due.is_empty() {
+                    Self::write_due_values(&entries, &watches, &due);
                 }
 
-                // 等待间隔或停止信号
+                // 睡到下一个到期时刻，最长不超过全局间隔
+                let next = scheduler.lock().ok().and_then(|mut s| s.next_due());
+                let now = epoch.elapsed().as_micros() as u64;
+                let wait_us = next.map_or(global_interval, |t| t.saturating_sub(now)).min(global_interval);
+
+                // 等待到期或停止/调度变化信号
                 tokio::select! {
-                    _ = tokio::time::sleep(interval) => {},
+                    _ = tokio::time::sleep(Duration::from_micros(wait_us)) => {},
                     _ = stop_notify.notified() => {
                         if !running.load(Ordering::SeqCst) {
                             break;
@@ -110,46 +284,239 @@ impl FreezeManager {
         }
     }
 
-    /// 写入所有冻结值
-    fn write_frozen_values(entries: &DashMap<u64, FrozenEntry>) {
-        let manager = match DRIVER_MANAGER.read() {
-            Ok(m) => m,
-            Err(e) => {
-                error!("FreezeManager: 无法获取 DRIVER_MANAGER 读锁: {}", e);
-                return;
-            },
+    /// 地址当前的调度间隔，既不是冻结条目也不是监视条目时返回 None
+    fn interval_of(entries: &DashMap<u64, FrozenEntry>, watches: &DashMap<u64, ChangeWatch>, addr: u64, global_interval: u64) -> Option<u64> {
+        let interval = match entries.get(&addr) {
+            Some(entry) => entry.interval_us,
+            None => watches.get(&addr)?.interval_us,
         };
+        Some(interval.unwrap_or(global_interval))
+    }
+
+    /// 写入到期的冻结值，同一页内相邻的写入合并为一次
+    fn write_due_values(entries: &DashMap<u64, FrozenEntry>, watches: &DashMap<u64, ChangeWatch>, due: &[u64]) {
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return;
         }
 
-        for entry in entries.iter() {
-            let addr = *entry.key();
-            let frozen = entry.value();
+        Self::process_due(
+            entries,
+            watches,
+            due,
+            |addr, buf| manager.read_memory_unified(addr, buf, None),
+            |addr, bytes| manager.write_memory_unified(addr, bytes),
+        );
+    }
+
+    /// 处理到期的地址：监视条目读取比较，发现变化即转为冻结条目；冻结条目（含刚转换的）写入
+    fn process_due<R, W>(entries: &DashMap<u64, FrozenEntry>, watches: &DashMap<u64, ChangeWatch>, due: &[u64], mut read: R, mut write: W)
+    where
+        R: FnMut(u64, &mut [u8]) -> anyhow::Result<()>,
+        W: FnMut(u64, &[u8]) -> anyhow::Result<()>,
+    {
+        for &addr in due {
+            // 读取失败时保留基准，下次到期再比较
+            let changed = watches.get_mut(&addr).and_then(|mut watch| {
+                let mut buf = vec![0u8; watch.size];
+                read(addr, &mut buf).ok()?;
+                watch.observe(&buf)
+            });
+            let Some(previous) = changed else {
+                continue;
+            };
+            let Some((_, watch)) = watches.remove(&addr) else {
+                continue;
+            };
+            debug!("FreezeManager: 监视地址 0x{:X} 发生变化，锁定为变化前的值", addr);
+            entries.insert(
+                addr,
+                FrozenEntry {
+                    value: previous,
+                    value_type: watch.value_type,
+                    group: None,
+                    interval_us: watch.interval_us,
+                },
+            );
+        }
+
+        let writes: Vec<(u64, Vec<u8>)> = due
+            .iter()
+            .filter_map(|addr| entries.get(addr).map(|e| (*addr, e.value.clone())))
+            .collect();
 
-            if let Err(e) = manager.write_memory_unified(addr, &frozen.value) {
+        for (addr, bytes) in coalesce_writes(writes) {
+            if let Err(e) = write(addr, &bytes) {
                 warn!("FreezeManager: 写入地址 0x{:X} 失败: {}", addr, e);
             }
         }
     }
 
+    /// 使用给定的时刻和读写函数执行一次调度，代替后台任务
+    #[cfg(test)]
+    fn tick_with<R, W>(&self, now_us: u64, read: R, write: W)
+    where
+        R: FnMut(u64, &mut [u8]) -> anyhow::Result<()>,
+        W: FnMut(u64, &[u8]) -> anyhow::Result<()>,
+    {
+        let global_interval = self.interval_us.load(Ordering::Relaxed);
+        let due = self
+            .scheduler
+            .lock()
+            .unwrap()
+            .pop_due(now_us, |addr| Self::interval_of(&self.frozen_entries, &self.watches, addr, global_interval));
+        Self::process_due(&self.frozen_entries, &self.watches, &due, read, write);
+    }
+
+    /// 将地址加入调度器并唤醒后台任务
+    fn schedule_entry(&self, address: u64, interval_us: Option<u64>) {
+        let interval_us = interval_us.unwrap_or_else(|| self.interval_us.load(Ordering::Relaxed));
+        let now = self.epoch.elapsed().as_micros() as u64;
+        if let Ok(mut scheduler) = self.scheduler.lock() {
+            scheduler.schedule(address, interval_us, now);
+        }
+        self.stop_notify.notify_one();
+    }
+
     /// 添加冻结地址
     pub fn add_frozen(&self, address: u64, value: Vec<u8>, value_type: i32) {
-        debug!("FreezeManager: 添加冻结 addr=0x{:X}, type={}, len={}", address, value_type, value.len());
-        self.frozen_entries.insert(address, FrozenEntry { value, value_type });
+        self.add_frozen_with_interval(address, value, value_type, None);
+    }
+
+    /// 添加冻结地址并指定独立的写入间隔（微秒），None 表示使用全局间隔
+    pub fn add_frozen_with_interval(&self, address: u64, value: Vec<u8>, value_type: i32, interval_us: Option<u64>) {
+        debug!(
+            "FreezeManager: 添加冻结 addr=0x{:X}, type={}, len={}, interval={:?}",
+            address,
+            value_type,
+            value.len(),
+            interval_us
+        );
+        self.watches.remove(&address);
+        self.frozen_entries.insert(
+            address,
+            FrozenEntry {
+                value,
+                value_type,
+                group: None,
+                interval_us,
+            },
+        );
+        self.schedule_entry(address, interval_us);
+    }
+
+    /// 监视地址，值第一次发生变化时锁定为变化前的值
+    ///
+    /// 第一次读取作为基准，之后每个间隔读取比较；发现变化后转为普通冻结条目并立即写回。
+    /// 已被冻结的地址保持冻结，不再监视。
+    pub fn watch_and_lock(&self, address: u64, size: usize, value_type: i32, interval_us: Option<u64>) {
+        if self.frozen_entries.contains_key(&address) {
+            return;
+        }
+        debug!("FreezeManager: 监视变化 addr=0x{:X}, type={}, len={}", address, value_type, size);
+        self.watches.insert(address, ChangeWatch::new(size, value_type, interval_us));
+        self.schedule_entry(address, interval_us);
     }
 
-    /// 移除冻结地址
+    /// 检查地址是否处于监视中（尚未发现变化）
+    pub fn is_watching(&self, address: u64) -> bool {
+        self.watches.contains_key(&address)
+    }
+
+    /// 以组的形式冻结一批地址，所有地址写入相同的值
+    ///
+    /// 已被冻结的地址会被覆盖并归入该组
+    pub fn freeze_group(&self, name: &str, addrs: &[u64], value_bytes: &[u8], value_type: i32) {
+        debug!(
+            "FreezeManager: 添加冻结组 name={}, count={}, type={}, len={}",
+            name,
+            addrs.len(),
+            value_type,
+            value_bytes.len()
+        );
+        for &address in addrs {
+            self.watches.remove(&address);
+            self.frozen_entries.insert(
+                address,
+                FrozenEntry {
+                    value: value_bytes.to_vec(),
+                    value_type,
+                    group: Some(name.to_string()),
+                    interval_us: None,
+                },
+            );
+            self.schedule_entry(address, None);
+        }
+    }
+
+    /// 解除指定组的所有冻结，返回释放的地址数量
+    pub fn unfreeze_group(&self, name: &str) -> usize {
+        // 在 retain 内计数，避免两次读取长度之间的并发插入导致下溢
+        let mut removed_addrs = Vec::new();
+        self.frozen_entries.retain(|addr, entry| {
+            let keep = entry.group.as_deref() != Some(name);
+            if !keep {
+                removed_addrs.push(*addr);
+            }
+            keep
+        });
+        if let Ok(mut scheduler) = self.scheduler.lock() {
+            for addr in &removed_addrs {
+                scheduler.unschedule(*addr);
+            }
+        }
+        let removed = removed_addrs.len();
+        debug!("FreezeManager: 移除冻结组 name={}, count={}", name, removed);
+        removed
+    }
+
+    /// 列出所有冻结地址及其所属组
+    pub fn list_frozen(&self) -> Vec<(u64, Option<String>)> {
+        self.frozen_entries.iter().map(|e| (*e.key(), e.value().group.clone())).collect()
+    }
+
+    /// 所有冻结条目的快照，按地址排序
+    pub fn frozen_entries(&self) -> Vec<(u64, FrozenEntry)> {
+        let mut entries: Vec<(u64, FrozenEntry)> = self.frozen_entries.iter().map(|e| (*e.key(), e.value().clone())).collect();
+        entries.sort_unstable_by_key(|(address, _)| *address);
+        entries
+    }
+
+    /// 按原样恢复一个冻结条目（包括所属组和写入间隔），已存在的地址会被覆盖
+    pub fn restore_entry(&self, address: u64, entry: FrozenEntry) {
+        let interval_us = entry.interval_us;
+        self.watches.remove(&address);
+        self.frozen_entries.insert(address, entry);
+        self.schedule_entry(address, interval_us);
+    }
+
+    /// 获取所有冻结组名称（去重、排序）
+    pub fn get_group_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.frozen_entries.iter().filter_map(|e| e.value().group.clone()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// 移除冻结地址，监视中的地址同样取消监视
     pub fn remove_frozen(&self, address: u64) -> bool {
         debug!("FreezeManager: 移除冻结 addr=0x{:X}", address);
-        self.frozen_entries.remove(&address).is_some()
+        if let Ok(mut scheduler) = self.scheduler.lock() {
+            scheduler.unschedule(address);
+        }
+        let watched = self.watches.remove(&address).is_some();
+        self.frozen_entries.remove(&address).is_some() || watched
     }
 
     /// 清空所有冻结
     pub fn clear_all(&self) {
         debug!("FreezeManager: 清空所有冻结");
         self.frozen_entries.clear();
+        self.watches.clear();
+        if let Ok(mut scheduler) = self.scheduler.lock() {
+            scheduler.clear();
+        }
     }
 
     /// 设置冻结间隔（微秒）
@@ -179,3 +546,199 @@ impl Drop for FreezeManager {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_freeze_group_unfreeze_by_name() {
+        let manager = FreezeManager::new();
+        manager.freeze_group("infinite_hp", &[0x1000, 0x1004, 0x1008], &100i32.to_le_bytes(), 2);
+        manager.freeze_group("infinite_ammo", &[0x2000, 0x2004], &99i32.to_le_bytes(), 2);
+        manager.add_frozen(0x3000, vec![1], 0);
+
+        assert_eq!(manager.get_frozen_count(), 6);
+        assert_eq!(manager.get_group_names(), vec!["infinite_ammo".to_string(), "infinite_hp".to_string()]);
+
+        assert_eq!(manager.unfreeze_group("infinite_hp"), 3);
+
+        for addr in [0x1000, 0x1004, 0x1008] {
+            assert!(!manager.is_frozen(addr));
+        }
+        for addr in [0x2000, 0x2004, 0x3000] {
+            assert!(manager.is_frozen(addr));
+        }
+
+        let mut listed = manager.list_frozen();
+        listed.sort();
+        assert_eq!(
+            listed,
+            vec![
+                (0x2000, Some("infinite_ammo".to_string())),
+                (0x2004, Some("infinite_ammo".to_string())),
+                (0x3000, None),
+            ]
+        );
+        assert_eq!(manager.unfreeze_group("missing"), 0);
+    }
+
+    #[test]
+    fn test_unfreeze_group_with_concurrent_inserts() {
+        let manager = Arc::new(FreezeManager::new());
+        let addrs: Vec<u64> = (0..2000u64).map(|i| 0x10_0000 + i * 4).collect();
+        manager.freeze_group("hp", &addrs, &1i32.to_le_bytes(), 2);
+
+        let writer = {
+            let manager = Arc::clone(&manager);
+            std::thread::spawn(move || {
+                for i in 0..2000u64 {
+                    manager.add_frozen(0x80_0000 + i * 4, vec![0; 4], 2);
+                }
+            })
+        };
+
+        // 并发插入不影响移除计数
+        assert_eq!(manager.unfreeze_group("hp"), addrs.len());
+        writer.join().unwrap();
+        assert_eq!(manager.get_frozen_count(), 2000);
+    }
+
+    #[test]
+    fn test_scheduler_cadence_with_mock_clock() {
+        let page = *PAGE_SIZE as u64;
+        // 三个条目位于不同页，间隔分别为 10ms / 20ms / 30ms
+        let intervals: HashMap<u64, u64> = [(page, 10_000), (page * 2, 20_000), (page * 3, 30_000)].into_iter().collect();
+
+        let mut scheduler = FreezeScheduler::new();
+        let mut clock = 0u64;
+        for (&addr, &interval) in &intervals {
+            scheduler.schedule(addr, interval, clock);
+        }
+
+        let mut writes: HashMap<u64, Vec<u64>> = HashMap::new();
+        let mut wakeups = 0;
+        // 模拟时钟：每次直接跳到下一个到期时刻
+        while let Some(next) = scheduler.next_due() {
+            if next > 180_000 {
+                break;
+            }
+            clock = next;
+            let due = scheduler.pop_due(clock, |addr| intervals.get(&addr).copied());
+            assert!(!due.is_empty());
+            assert!(due.len() < intervals.len(), "所有条目在 {} μs 同时写入", clock);
+            for addr in due {
+                writes.entry(addr).or_default().push(clock);
+            }
+            wakeups += 1;
+        }
+
+        for (addr, interval) in &intervals {
+            let times = &writes[addr];
+            assert_eq!(times.len() as u64, 180_000 / interval);
+            assert!(times.windows(2).all(|w| w[1] - w[0] == *interval));
+        }
+        assert!(wakeups > 0);
+    }
+
+    #[test]
+    fn test_scheduler_skips_removed_and_catches_up() {
+        let mut scheduler = FreezeScheduler::new();
+        scheduler.schedule(0x10, 1_000, 0);
+        scheduler.schedule(0x20, 1_000, 0);
+        scheduler.unschedule(0x20);
+
+        // 落后多个周期时只写一次，下次到期仍在原节拍上
+        assert_eq!(scheduler.pop_due(5_500, |_| Some(1_000)), vec![0x10]);
+        assert_eq!(scheduler.next_due(), Some(6_000));
+        assert!(scheduler.pop_due(5_999, |_| Some(1_000)).is_empty());
+
+        // 条目已被移除时不再调度
+        assert!(scheduler.pop_due(6_000, |_| None).is_empty());
+        assert_eq!(scheduler.next_due(), None);
+    }
+
+    #[test]
+    fn test_coalesce_same_page_writes() {
+        let page = *PAGE_SIZE as u64;
+        let writes = vec![
+            (page + 4, vec![2, 2, 2, 2]),
+            (page, vec![1, 1, 1, 1]),
+            (page + 16, vec![3]),
+            // 跨页的相邻写入不合并
+            (page * 2 - 2, vec![4, 4]),
+            (page * 2, vec![5, 5]),
+        ];
+        let merged = coalesce_writes(writes);
+        assert_eq!(
+            merged,
+            vec![
+                (page, vec![1, 1, 1, 1, 2, 2, 2, 2]),
+                (page + 16, vec![3]),
+                (page * 2 - 2, vec![4, 4]),
+                (page * 2, vec![5, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_locks_to_pre_change_value() {
+        let page = *PAGE_SIZE as u64;
+        let addr = page + 0x40;
+        let memory: RefCell<HashMap<u64, Vec<u8>>> = RefCell::new([(addr, 100i32.to_le_bytes().to_vec())].into_iter().collect());
+        let writes = RefCell::new(Vec::new());
+        let set = |value: i32| {
+            memory.borrow_mut().insert(addr, value.to_le_bytes().to_vec());
+        };
+        let current = || i32::from_le_bytes(memory.borrow()[&addr].as_slice().try_into().unwrap());
+
+        let manager = FreezeManager::new();
+        manager.watch_and_lock(addr, 4, 2, Some(10_000));
+        assert!(manager.is_watching(addr));
+
+        // 模拟时钟：每次前进一个间隔
+        let mut clock = 0u64;
+        let mut tick = || {
+            clock += 10_000;
+            manager.tick_with(
+                clock,
+                |a, buf| {
+                    buf.copy_from_slice(&memory.borrow()[&a]);
+                    Ok(())
+                },
+                |a, bytes| {
+                    writes.borrow_mut().push(a);
+                    memory.borrow_mut().insert(a, bytes.to_vec());
+                    Ok(())
+                },
+            )
+        };
+
+        // 第一次读取只记录基准，值未变时不写入
+        tick();
+        tick();
+        assert!(writes.borrow().is_empty());
+
+        // 值开始衰减：立即锁回变化前的值
+        set(90);
+        tick();
+        assert_eq!(current(), 100);
+        assert_eq!(*writes.borrow(), vec![addr]);
+        assert!(!manager.is_watching(addr));
+        assert!(manager.is_frozen(addr));
+
+        // 之后按普通冻结持续写回
+        set(80);
+        tick();
+        assert_eq!(current(), 100);
+        assert_eq!(writes.borrow().len(), 2);
+
+        // 取消后不再写入
+        assert!(manager.remove_frozen(addr));
+        set(70);
+        tick();
+        assert_eq!(current(), 70);
+        assert_eq!(writes.borrow().len(), 2);
+    }
+}
@@ -3,7 +3,9 @@
 use crate::core::driver_manager::DriverManager;
 use crate::core::freeze_manager::FreezeManager;
 use lazy_static::lazy_static;
-use std::sync::RwLock;
+use log::warn;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tokio::runtime::Runtime;
 
 lazy_static! {
@@ -27,4 +29,90 @@ lazy_static! {
             .unwrap_or(4096)
     };
     pub static ref PAGE_MASK: usize = !(*PAGE_SIZE - 1);
-}
\ No newline at end of file
+}
+
+/// 获取 [`DRIVER_MANAGER`] 的读锁，锁已中毒时恢复后继续使用，见 [`read_recovering`]
+pub fn driver_manager_read() -> RwLockReadGuard<'static, DriverManager> {
+    read_recovering(&DRIVER_MANAGER, "DriverManager")
+}
+
+/// 获取 [`DRIVER_MANAGER`] 的写锁，锁已中毒时恢复后继续使用，见 [`read_recovering`]
+pub fn driver_manager_write() -> RwLockWriteGuard<'static, DriverManager> {
+    write_recovering(&DRIVER_MANAGER, "DriverManager")
+}
+
+/// 获取读锁，锁已中毒时记录日志、清除中毒标记并沿用锁内的状态
+///
+/// 持有写锁的线程 panic 后，标准库的锁之后每次加锁都会失败，管理器从此不可用。
+/// DriverManager 只保存驱动句柄、绑定的进程和访问模式，沿用 panic 时的状态比永久失效更合理
+pub(crate) fn read_recovering<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockReadGuard<'a, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        warn!("{} lock was poisoned by a panicked thread, recovering", name);
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// 同 [`read_recovering`]，获取写锁
+pub(crate) fn write_recovering<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockWriteGuard<'a, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        warn!("{} lock was poisoned by a panicked thread, recovering", name);
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// 目标进程内存的修改代数：每次写入、绑定/解绑进程时递增，用于判断缓存的搜索结果是否仍然有效
+static MEMORY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn memory_generation() -> u64 {
+    MEMORY_GENERATION.load(Ordering::Acquire)
+}
+
+pub fn bump_memory_generation() {
+    MEMORY_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MemoryAccessMode;
+    use std::sync::Arc;
+
+    fn poison<T: Send + Sync + 'static>(lock: Arc<RwLock<T>>) {
+        let result = std::thread::spawn(move || {
+            let _guard = lock.write().unwrap();
+            panic!("panicking while holding the write lock");
+        })
+        .join();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_poisoned_lock_recovers() {
+        let lock = Arc::new(RwLock::new(DriverManager::new()));
+        lock.write().unwrap().set_access_mode(MemoryAccessMode::PageFault).unwrap();
+        poison(Arc::clone(&lock));
+        assert!(lock.read().is_err());
+
+        // 恢复后沿用原状态，之后普通的加锁也能成功
+        assert_eq!(read_recovering(&lock, "test").get_access_mode(), MemoryAccessMode::PageFault);
+        assert!(!lock.is_poisoned());
+        assert!(lock.read().is_ok());
+
+        poison(Arc::clone(&lock));
+        write_recovering(&lock, "test").set_access_mode(MemoryAccessMode::None).unwrap();
+        assert_eq!(lock.read().unwrap().get_access_mode(), MemoryAccessMode::None);
+    }
+
+    #[test]
+    fn test_poisoned_driver_manager_serves_reads() {
+        // 使用局部锁，不污染其他测试共用的全局 DRIVER_MANAGER
+        let lock = Arc::new(RwLock::new(DriverManager::new()));
+        poison(Arc::clone(&lock));
+
+        assert!(!read_recovering(&lock, "DriverManager").is_driver_loaded());
+        assert!(!lock.is_poisoned());
+        assert!(lock.read().is_ok());
+    }
+}
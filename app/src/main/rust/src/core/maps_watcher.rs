@@ -0,0 +1,149 @@
+//! 内存映射变化检测：对驱动枚举出的可读区域列表取指纹，指纹变化说明有新的映射或映射被释放
+//!
+//! UI 可以轮询 [`DriverManager::maps_fingerprint`](crate::core::DriverManager::maps_fingerprint)，
+//! 也可以启动 [`MapsWatcher`] 后台线程，在指纹变化时收到回调，刷新区域列表或重新枚举扫描区域。
+
+use crate::core::globals::driver_manager_read;
+use log::debug;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 无法枚举区域（未绑定进程、进程已退出等）时的指纹
+pub const UNAVAILABLE_FINGERPRINT: u64 = 0;
+
+/// 区域列表 `[start, end)` 的指纹，同一进程内对相同的列表稳定
+pub fn fingerprint_regions(regions: &[(u64, u64)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for &(start, end) in regions {
+        hasher.write_u64(start);
+        hasher.write_u64(end);
+    }
+    hasher.finish()
+}
+
+/// 与上一次记录的指纹比较，变化时更新 `last` 并返回 true
+pub fn poll_fingerprint(last: &mut u64, current: u64) -> bool {
+    if *last == current {
+        return false;
+    }
+    *last = current;
+    true
+}
+
+/// 后台轮询映射指纹的线程，drop 时停止
+pub struct MapsWatcher {
+    stop_tx: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MapsWatcher {
+    /// 监视当前绑定进程的内存区域，每隔 `interval` 检查一次，变化时以新指纹调用 `on_change`
+    pub fn watch_bound_process<C>(interval: Duration, on_change: C) -> Self
+    where
+        C: FnMut(u64) + Send + 'static,
+    {
+        Self::start(interval, || driver_manager_read().maps_fingerprint(), on_change)
+    }
+
+    /// 以 `fingerprint` 为指纹来源启动监视线程，启动时的指纹作为基准，不触发回调
+    pub fn start<F, C>(interval: Duration, mut fingerprint: F, mut on_change: C) -> Self
+    where
+        F: FnMut() -> u64 + Send + 'static,
+        C: FnMut(u64) + Send + 'static,
+    {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let handle = std::thread::spawn(move || {
+            let mut last = fingerprint();
+            loop {
+                // 收到停止信号或发送端被 drop 时退出
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {},
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if poll_fingerprint(&mut last, fingerprint()) {
+                    debug!("Memory maps changed, fingerprint={:#x}", last);
+                    on_change(last);
+                }
+            }
+        });
+
+        Self {
+            stop_tx: Some(stop_tx),
+            handle: Some(handle),
+        }
+    }
+
+    /// 停止监视线程并等待其退出
+    pub fn stop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MapsWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_fingerprint_changes_with_regions() {
+        let mut regions = vec![(0x7000000000u64, 0x7000100000u64)];
+        let before = fingerprint_regions(&regions);
+        assert_ne!(before, UNAVAILABLE_FINGERPRINT);
+        assert_eq!(fingerprint_regions(&regions), before);
+
+        // 新增一段映射
+        regions.push((0x7100000000, 0x7100004000));
+        let after = fingerprint_regions(&regions);
+        assert_ne!(after, before);
+
+        let mut last = before;
+        assert!(poll_fingerprint(&mut last, after));
+        assert_eq!(last, after);
+        assert!(!poll_fingerprint(&mut last, after));
+
+        // 映射扩展也会改变指纹
+        regions[1].1 = 0x7100008000;
+        assert_ne!(fingerprint_regions(&regions), after);
+    }
+
+    #[test]
+    fn test_watcher_reports_changes() {
+        let maps = Arc::new(Mutex::new(vec![(0x7000000000u64, 0x7000100000u64)]));
+        let (change_tx, change_rx) = mpsc::channel();
+
+        let source = Arc::clone(&maps);
+        let mut watcher = MapsWatcher::start(
+            Duration::from_millis(5),
+            move || fingerprint_regions(&source.lock().unwrap()),
+            move |fingerprint| {
+                let _ = change_tx.send(fingerprint);
+            },
+        );
+
+        // 内容不变时不回调
+        assert!(change_rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        maps.lock().unwrap().push((0x7100000000, 0x7100004000));
+        let expected = fingerprint_regions(&maps.lock().unwrap());
+        assert_eq!(change_rx.recv_timeout(Duration::from_secs(5)).unwrap(), expected);
+
+        watcher.stop();
+        maps.lock().unwrap().clear();
+        assert!(change_rx.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+}
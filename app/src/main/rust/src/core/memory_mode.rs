@@ -23,4 +23,15 @@ impl MemoryAccessMode {
             _ => None,
         }
     }
+
+    #[inline]
+    pub fn to_id(&self) -> i32 {
+        match self {
+            MemoryAccessMode::None => 0,
+            MemoryAccessMode::NonCacheable => 1,
+            MemoryAccessMode::WriteThrough => 2,
+            MemoryAccessMode::Normal => 3,
+            MemoryAccessMode::PageFault => 4,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+//! 内存后端抽象：搜索引擎通过 [`MemorySource`] 读写目标内存，不直接依赖驱动
+//!
+//! [`DriverManager`](crate::core::DriverManager) 是实时进程的实现；模拟器的 RAM、core dump
+//! 等离线数据可以用 [`RamSource`] 或自行实现该 trait 后交给 `SearchEngineManager` 的 `*_from` 方法：
+//! 首次搜索 [`search_memory_from`](crate::search::SearchEngineManager::search_memory_from)、
+//! [`find_first_n_from`](crate::search::SearchEngineManager::find_first_n_from)、
+//! [`search_streaming_from`](crate::search::SearchEngineManager::search_streaming_from) 和
+//! [`matches_at_from`](crate::search::SearchEngineManager::matches_at_from)。
+//!
+//! 改善搜索（refine、模糊细化）和冻结、写入仍只通过驱动读写当前绑定的进程。
+
+use crate::core::globals::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// 可被搜索的内存后端，多个 rayon 线程会同时调用 [`Self::read`]
+pub trait MemorySource: Send + Sync {
+    /// 读取 `buf.len()` 字节，并在 `page_status` 中标记读取成功的页（第 0 页是 `addr` 所在的页）
+    ///
+    /// 部分页不可读时应返回 `Ok` 并只标记可读的页；整段都无法读取时可以返回错误
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()>;
+
+    /// 可读的内存区域 `[start, end)`
+    fn regions(&self) -> Vec<(u64, u64)>;
+
+    fn write(&self, addr: u64, buf: &[u8]) -> Result<()>;
+}
+
+/// 由若干段内存组成的后端，适合加载 core dump 或模拟器 RAM
+///
+/// 段之间的空洞视为未映射，读取时对应的页标记为失败
+#[derive(Default)]
+pub struct RamSource {
+    /// 段起始地址 -> 数据
+    segments: RwLock<BTreeMap<u64, Vec<u8>>>,
+}
+
+impl RamSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一段从 `start` 开始的内存，不能与已有的段重叠
+    pub fn add_segment(&self, start: u64, data: Vec<u8>) -> Result<()> {
+        let end = start + data.len() as u64;
+        let mut segments = self.segments.write().map_err(|_| anyhow!("RamSource lock poisoned"))?;
+        let overlaps = segments
+            .range(..end)
+            .next_back()
+            .is_some_and(|(&seg_start, seg)| seg_start + seg.len() as u64 > start);
+        if overlaps {
+            return Err(anyhow!("Segment 0x{:X} - 0x{:X} overlaps an existing segment", start, end));
+        }
+        segments.insert(start, data);
+        Ok(())
+    }
+}
+
+impl MemorySource for RamSource {
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()> {
+        let segments = self.segments.read().map_err(|_| anyhow!("RamSource lock poisoned"))?;
+        let end = addr + buf.len() as u64;
+        let page_base = addr & !(*PAGE_SIZE as u64 - 1);
+        buf.fill(0);
+
+        // 逐页检查：页内请求的字节全部落在同一段内才算可读
+        let pages = (end - page_base).div_ceil(*PAGE_SIZE as u64) as usize;
+        for page in 0..pages {
+            let chunk_start = (page_base + (page * *PAGE_SIZE) as u64).max(addr);
+            let chunk_end = (page_base + ((page + 1) * *PAGE_SIZE) as u64).min(end);
+            let Some((&seg_start, seg)) = segments.range(..=chunk_start).next_back() else {
+                continue;
+            };
+            if seg_start + (seg.len() as u64) < chunk_end {
+                continue;
+            }
+
+            let src = (chunk_start - seg_start) as usize..(chunk_end - seg_start) as usize;
+            buf[(chunk_start - addr) as usize..(chunk_end - addr) as usize].copy_from_slice(&seg[src]);
+            page_status.mark_success(page);
+        }
+        Ok(())
+    }
+
+    fn regions(&self) -> Vec<(u64, u64)> {
+        self.segments.read().map_or_else(
+            |_| Vec::new(),
+            |segments| segments.iter().map(|(&start, seg)| (start, start + seg.len() as u64)).collect(),
+        )
+    }
+
+    fn write(&self, addr: u64, buf: &[u8]) -> Result<()> {
+        let mut segments = self.segments.write().map_err(|_| anyhow!("RamSource lock poisoned"))?;
+        let (&seg_start, seg) = segments
+            .range_mut(..=addr)
+            .next_back()
+            .ok_or_else(|| anyhow!("Address 0x{:X} is not mapped", addr))?;
+        let offset = (addr - seg_start) as usize;
+        let dst = seg
+            .get_mut(offset..offset + buf.len())
+            .ok_or_else(|| anyhow!("Write 0x{:X} + {} crosses a segment boundary", addr, buf.len()))?;
+        dst.copy_from_slice(buf);
+        Ok(())
+    }
+}
@@ -6,9 +6,14 @@ pub mod memory_mode;
 pub mod driver_manager;
 pub mod globals;
 pub mod freeze_manager;
+pub mod value_guess;
+pub mod maps_watcher;
+pub mod memory_source;
 
 // Re-export commonly used items
 pub use memory_mode::MemoryAccessMode;
-pub use driver_manager::DriverManager;
+pub use driver_manager::{DriverManager, FillReport};
 pub use globals::DRIVER_MANAGER;
-pub use freeze_manager::FreezeManager;
\ No newline at end of file
+pub use freeze_manager::FreezeManager;
+pub use maps_watcher::MapsWatcher;
+pub use memory_source::{MemorySource, RamSource};
\ No newline at end of file
@@ -0,0 +1,117 @@
+//! 未知地址的值类型猜测，供 UI 的"这是什么"功能使用
+//!
+//! 对同一段 8 字节按各类型解释并打分：小整数、数量级合理且尾数简短的浮点数、可打印 ASCII 字符、
+//! 指向已映射内存的指针。分数只用于排序，不是概率。
+
+use crate::search::ValueType;
+
+/// 各类解释的基础分
+const POINTER_SCORE: f64 = 0.9;
+const UNALIGNED_POINTER_SCORE: f64 = 0.6;
+const SMALL_DWORD_SCORE: f64 = 0.8;
+const SMALL_QWORD_SCORE: f64 = 0.6;
+const ROUND_FLOAT_SCORE: f64 = 0.85;
+const FLOAT_SCORE: f64 = 0.7;
+const SMALL_WORD_SCORE: f64 = 0.4;
+const ASCII_BYTE_SCORE: f64 = 0.35;
+/// 0 可以是任何类型，各类型都给低分
+const ZERO_SCORE: f64 = 0.3;
+
+/// 小整数上限，血量、金币、计数等通常在此范围内
+const SMALL_INT_LIMIT: i64 = 100_000;
+/// 合理浮点数的数量级范围
+const FLOAT_MIN_MAGNITUDE: f64 = 1e-4;
+const FLOAT_MAX_MAGNITUDE: f64 = 1e9;
+/// 尾数低位为 0 的位数达到该值视为"整齐"的浮点数，例如 1.0、0.5、100.0
+const ROUND_FLOAT_ZERO_BITS: u32 = 12;
+
+/// 按 `bytes` 猜测值类型，返回按置信度降序排列的 (类型, 置信度)，每个类型至多出现一次
+///
+/// `is_mapped` 判断一个地址是否位于可读的已映射内存，用于识别指针
+pub fn guess_value_type<M>(bytes: &[u8; 8], is_mapped: M) -> Vec<(ValueType, f64)>
+where
+    M: Fn(u64) -> bool,
+{
+    let qword = u64::from_le_bytes(*bytes);
+    if qword == 0 {
+        return vec![(ValueType::Dword, ZERO_SCORE), (ValueType::Qword, ZERO_SCORE)];
+    }
+
+    let dword = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+    let word = u16::from_le_bytes(bytes[..2].try_into().unwrap());
+    let mut guesses: Vec<(ValueType, f64)> = Vec::new();
+    let mut add = |value_type: ValueType, score: f64| match guesses.iter_mut().find(|(t, _)| *t == value_type) {
+        Some(entry) => entry.1 = entry.1.max(score),
+        None => guesses.push((value_type, score)),
+    };
+
+    if is_mapped(qword) {
+        let score = if qword.is_multiple_of(4) { POINTER_SCORE } else { UNALIGNED_POINTER_SCORE };
+        add(ValueType::Qword, score);
+    }
+
+    if let Some(score) = small_int_score(dword as i32 as i64, SMALL_DWORD_SCORE) {
+        add(ValueType::Dword, score);
+    }
+    if let Some(score) = small_int_score(qword as i64, SMALL_QWORD_SCORE) {
+        add(ValueType::Qword, score);
+    }
+    if let Some(score) = float_score(f32::from_bits(dword) as f64, f32::MANTISSA_DIGITS - 1, dword as u64) {
+        add(ValueType::Float, score);
+    }
+    if let Some(score) = float_score(f64::from_bits(qword), f64::MANTISSA_DIGITS - 1, qword) {
+        add(ValueType::Double, score);
+    }
+    if (word as i16).unsigned_abs() <= 1000 && word != 0 {
+        add(ValueType::Word, SMALL_WORD_SCORE);
+    }
+    if (0x20..0x7F).contains(&bytes[0]) {
+        add(ValueType::Byte, ASCII_BYTE_SCORE);
+    }
+
+    guesses.sort_by(|a, b| b.1.total_cmp(&a.1));
+    guesses
+}
+
+fn small_int_score(value: i64, score: f64) -> Option<f64> {
+    (value != 0 && value.unsigned_abs() <= SMALL_INT_LIMIT as u64).then_some(score)
+}
+
+/// 正规、数量级合理的浮点数才计分；尾数低位大量为 0 的（整齐的数）分数更高
+fn float_score(value: f64, mantissa_bits: u32, bits: u64) -> Option<f64> {
+    if !value.is_normal() || !(FLOAT_MIN_MAGNITUDE..=FLOAT_MAX_MAGNITUDE).contains(&value.abs()) {
+        return None;
+    }
+    let mantissa = bits & ((1u64 << mantissa_bits) - 1);
+    let zero_bits = if mantissa == 0 { mantissa_bits } else { mantissa.trailing_zeros() };
+    Some(if zero_bits >= ROUND_FLOAT_ZERO_BITS { ROUND_FLOAT_SCORE } else { FLOAT_SCORE })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top(bytes: [u8; 8], is_mapped: impl Fn(u64) -> bool) -> ValueType {
+        guess_value_type(&bytes, is_mapped)[0].0
+    }
+
+    #[test]
+    fn test_guess_top_type() {
+        let unmapped = |_: u64| false;
+        let heap = |addr: u64| (0x7000000000..0x7000100000).contains(&addr);
+
+        assert_eq!(top(100u64.to_le_bytes(), unmapped), ValueType::Dword);
+        assert_eq!(top(((1.0f32.to_bits() as u64) | (7u64 << 32)).to_le_bytes(), unmapped), ValueType::Float);
+        assert_eq!(top(2.5f64.to_le_bytes(), unmapped), ValueType::Double);
+        assert_eq!(top(0x7000001000u64.to_le_bytes(), heap), ValueType::Qword);
+
+        // 同样的值不指向已映射内存时不是指针
+        let guesses = guess_value_type(&0x7000001000u64.to_le_bytes(), unmapped);
+        assert_eq!(guesses[0].0, ValueType::Dword);
+        assert!(guesses.iter().all(|&(t, _)| t != ValueType::Qword));
+
+        let guesses = guess_value_type(&u64::from(b'A').to_le_bytes(), unmapped);
+        assert!(guesses.contains(&(ValueType::Byte, ASCII_BYTE_SCORE)));
+        assert!(guesses.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+}
@@ -3,8 +3,8 @@
 use crate::ext::jni::{JniResult, JniResultExt};
 use jni::JNIEnv;
 use jni::objects::JObject;
-use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean};
-use jni_macro::jni_method;
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jstring};
+use jni_macro::{ClassRegistrationStatus, jni_method, registration_report};
 use log::info;
 use obfstr::obfstr as s;
 
@@ -30,4 +30,34 @@ pub fn jni_init_core(mut env: JNIEnv, obj: JObject) -> jboolean {
         Ok(JNI_TRUE)
     })()
     .or_throw(&mut env)
-}
\ No newline at end of file
+}
+
+/// 各 Java 类的本地方法注册结果，JSON 数组，每项包含类名、是否注册成功、方法数和错误信息
+///
+/// 用于排查某个功能的按钮没有反应：通常是它的类没有被找到，本地方法没有注册上
+#[jni_method(90, "moe/fuqiuluo/mamu/MamuApplication", "nativeGetJniRegistrationReport", "()Ljava/lang/String;")]
+pub fn jni_get_registration_report(mut env: JNIEnv, _obj: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let report = registration_report_json(&registration_report());
+        Ok(env.new_string(report)?.into_raw())
+    })()
+    .unwrap_or_else(|e| {
+        let _ = env.throw(format!("{:#}", e));
+        std::ptr::null_mut()
+    })
+}
+
+fn registration_report_json(report: &[ClassRegistrationStatus]) -> String {
+    let classes: Vec<serde_json::Value> = report
+        .iter()
+        .map(|status| {
+            serde_json::json!({
+                "class": status.class_path,
+                "registered": status.registered,
+                "methods": status.method_count,
+                "error": status.error,
+            })
+        })
+        .collect();
+    serde_json::Value::Array(classes).to_string()
+}
@@ -1,7 +1,9 @@
 //! JNI methods for WuwaDriver
 
-use crate::core::{MemoryAccessMode, DRIVER_MANAGER};
+use crate::core::globals::{driver_manager_read, driver_manager_write};
+use crate::core::MemoryAccessMode;
 use crate::ext::jni::{JniResult, JniResultExt};
+use crate::search::SEARCH_ENGINE_MANAGER;
 use crate::wuwa::{WuWaDriver, WuwaMemRegionEntry};
 use anyhow::anyhow;
 use jni::JNIEnv;
@@ -78,13 +80,7 @@ mod conversions {
 #[jni_method(90, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeSetDriverFd", "(I)Z")]
 pub fn jni_set_driver_fd(mut env: JNIEnv, _obj: JObject, fd: i32) -> jboolean {
     (|| -> JniResult<jboolean> {
-        if DRIVER_MANAGER.is_poisoned() {
-            return Err(anyhow!("DriverManager is poisoned"));
-        }
-
-        let mut manager = DRIVER_MANAGER
-            .write()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+        let mut manager = driver_manager_write();
 
         if !manager.is_driver_loaded() {
             manager.set_driver(WuWaDriver::from_fd(fd));
@@ -117,12 +113,8 @@ pub fn jni_set_driver_fd(mut env: JNIEnv, _obj: JObject, fd: i32) -> jboolean {
 
 #[jni_method(90, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeIsLoaded", "()Z")]
 pub fn jni_is_loaded(_env: JNIEnv, _obj: JObject) -> jboolean {
-    if let Ok(manager) = DRIVER_MANAGER.read() {
-        if manager.is_driver_loaded() {
-            JNI_TRUE
-        } else {
-            JNI_FALSE
-        }
+    if driver_manager_read().is_driver_loaded() {
+        JNI_TRUE
     } else {
         JNI_FALSE
     }
@@ -131,12 +123,7 @@ pub fn jni_is_loaded(_env: JNIEnv, _obj: JObject) -> jboolean {
 #[jni_method(90, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeSetMemoryAccessMode", "(I)V")]
 pub fn jni_set_memory_access_mode(mut env: JNIEnv, _obj: JObject, mode_id: i32) {
     (|| -> JniResult<()> {
-        if DRIVER_MANAGER.is_poisoned() {
-            return Err(anyhow!("DriverManager is poisoned"));
-        }
-        let mut manager = DRIVER_MANAGER
-            .write()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+        let mut manager = driver_manager_write();
         let mode =
             MemoryAccessMode::from_id(mode_id).ok_or_else(|| anyhow!("Invalid memory access mode id: {}", mode_id))?;
         manager.set_access_mode(mode)?;
@@ -151,8 +138,7 @@ pub fn jni_set_memory_access_mode(mut env: JNIEnv, _obj: JObject, mode_id: i32)
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeIsProcessAlive", "(I)Z")]
 pub fn jni_is_proc_alive(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
     (|| -> JniResult<jboolean> {
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if let Some(driver) = manager.get_driver() {
             if let Ok(alive) = driver.is_process_alive(pid) {
@@ -169,8 +155,7 @@ pub fn jni_is_proc_alive(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetProcessList", "()[I")]
 pub fn jni_get_proc_list<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JIntArray<'l> {
     (|| -> JniResult<JIntArray<'l>> {
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         let driver = manager.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
@@ -187,8 +172,7 @@ pub fn jni_get_proc_list<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JIntArray<'l
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetProcessInfo", "(I)Lmoe/fuqiuluo/mamu/driver/CProcInfo;")]
 pub fn jni_get_proc_info<'l>(mut env: JNIEnv<'l>, _obj: JObject, pid: jint) -> JObject<'l> {
     (|| -> JniResult<JObject<'l>> {
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
         let driver = manager.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
 
@@ -204,8 +188,7 @@ pub fn jni_get_proc_info<'l>(mut env: JNIEnv<'l>, _obj: JObject, pid: jint) -> J
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetProcessListWithInfo", "()[Lmoe/fuqiuluo/mamu/driver/CProcInfo;")]
 pub fn jni_get_proc_list_with_info<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JObjectArray<'l> {
     (|| -> JniResult<JObjectArray<'l>> {
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
         let driver = manager.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
 
@@ -230,19 +213,24 @@ pub fn jni_get_proc_list_with_info<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JO
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeBindProcess", "(I)Z")]
 pub fn jni_bind_proc(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
     (|| -> JniResult<jboolean> {
-        let manager_read = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager_read = driver_manager_read();
         let driver = manager_read.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
 
         let Ok(bind_proc) = driver.bind_process(pid) else {
             return Ok(JNI_FALSE);
         };
+        let package = driver.get_process_info(pid).ok().map(|info| conversions::extract_cstring(&info.name));
         drop(manager_read);
 
-        let mut manager_write = DRIVER_MANAGER.write()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+        let mut manager_write = driver_manager_write();
         manager_write.bind_process(bind_proc, pid)?;
+        drop(manager_write);
+
+        // 扫描统计按目标包名归档，见 SearchEngineManager::recommend_memory_mode
+        if let Ok(mut search_manager) = SEARCH_ENGINE_MANAGER.write() {
+            search_manager.set_stats_target(package);
+        }
 
         debug!("{}: {}", s!("绑定进程成功，PID"), pid);
         Ok(JNI_TRUE)
@@ -252,21 +240,13 @@ pub fn jni_bind_proc(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
 
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetCurrentBindPid", "()I")]
 pub fn jni_get_current_bind_pid(_env: JNIEnv, _obj: JObject) -> jint {
-    if let Ok(manager) = DRIVER_MANAGER.read() {
-        manager.get_bound_pid()
-    } else {
-        0
-    }
+    driver_manager_read().get_bound_pid()
 }
 
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeIsProcessBound", "()Z")]
 pub fn jni_is_proc_bound(_env: JNIEnv, _obj: JObject) -> jboolean {
-    if let Ok(manager) = DRIVER_MANAGER.read() {
-        if manager.is_process_bound() {
-            JNI_TRUE
-        } else {
-            JNI_FALSE
-        }
+    if driver_manager_read().is_process_bound() {
+        JNI_TRUE
     } else {
         JNI_FALSE
     }
@@ -275,9 +255,13 @@ pub fn jni_is_proc_bound(_env: JNIEnv, _obj: JObject) -> jboolean {
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeUnbindProcess", "()Z")]
 pub fn jni_unbind_proc(mut env: JNIEnv, _obj: JObject) -> jboolean {
     (|| -> JniResult<jboolean> {
-        let mut manager = DRIVER_MANAGER.write()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+        let mut manager = driver_manager_write();
         manager.unbind_process();
+        drop(manager);
+
+        if let Ok(mut search_manager) = SEARCH_ENGINE_MANAGER.write() {
+            search_manager.set_stats_target(None);
+        }
         debug!("{}", s!("释放进程绑定成功"));
         Ok(JNI_TRUE)
     })()
@@ -291,8 +275,7 @@ pub fn jni_query_mem_regions<'l>(
     pid: jint,
 ) -> JObjectArray<'l> {
     (|| -> JniResult<JObjectArray<'l>> {
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return Err(anyhow!("No process is bound. Please bind a process before querying memory regions."));
@@ -393,8 +376,7 @@ pub fn jni_read_memory<'l>(
             return Err(anyhow!("Invalid size: {}", size));
         }
 
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return Err(anyhow!("No process is bound. Please bind a process first."));
@@ -445,8 +427,7 @@ pub fn jni_batch_read_memory<'l>(
         env.get_int_array_region(&sizes, 0, &mut read_sizes)
             .map_err(|e| anyhow!("Failed to get size array region: {}", e))?;
 
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return Err(anyhow!("No process is bound. Please bind a process first."));
@@ -501,8 +482,7 @@ pub fn jni_write_memory(
             return Err(anyhow!("Cannot write zero bytes"));
         }
 
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return Err(anyhow!("No process is bound. Please bind a process first."));
@@ -553,8 +533,7 @@ pub fn jni_batch_write_memory<'l>(
         env.get_long_array_region(&addrs, 0, &mut addresses)
             .map_err(|e| anyhow!("Failed to get address array region: {}", e))?;
 
-        let manager = DRIVER_MANAGER.read()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let manager = driver_manager_read();
 
         if !manager.is_process_bound() {
             return Err(anyhow!("No process is bound. Please bind a process first."));
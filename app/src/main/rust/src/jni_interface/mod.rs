@@ -9,4 +9,7 @@ pub mod mem_ops;
 pub mod disassembler;
 pub mod driver_installer;
 pub mod pointer_scan;
-pub mod freeze;
\ No newline at end of file
+pub mod freeze;
+
+#[cfg(test)]
+mod registration_tests;
@@ -0,0 +1,90 @@
+//! Registration self-check: `#[jni_method]` and `#[jni_onload]` functions must reach the inventory
+//!
+//! Runs the macro expansion and inventory collection end to end without a VM, so a build that
+//! collects zero registrations fails in CI instead of on a device.
+
+#[cfg(test)]
+mod tests {
+    use jni::objects::JObject;
+    use jni::sys::{JNI_TRUE, jboolean, jint};
+    use jni::{JNIEnv, JavaVM};
+    use jni_macro::{conflicts_from, describe_registrations, init_sequence, iter_method_registrations, jni_method, jni_onload, plan_registrations};
+
+    const CHECK_CLASS: &str = "moe/fuqiuluo/mamu/test/RegistrationCheck";
+
+    #[jni_method(7, "moe/fuqiuluo/mamu/test/RegistrationCheck", "nativeAdd", "(II)I")]
+    fn check_add(_env: JNIEnv, _obj: JObject, a: jint, b: jint) -> jint {
+        a + b
+    }
+
+    // Same class, name and signature at a lower priority; planning must keep the one above
+    #[jni_method(1, "moe/fuqiuluo/mamu/test/RegistrationCheck", "nativeAdd", "(II)I")]
+    fn check_add_shadowed(_env: JNIEnv, _obj: JObject, a: jint, b: jint) -> jint {
+        a - b
+    }
+
+    // Signature derived from the Rust types
+    #[jni_method(3, "moe/fuqiuluo/mamu/test/RegistrationCheck", "nativePing")]
+    fn check_ping(_env: JNIEnv, _obj: JObject, _token: jint) -> jboolean {
+        JNI_TRUE
+    }
+
+    #[jni_onload(-41)]
+    fn check_onload(_env: &mut JNIEnv, _vm: &JavaVM) {}
+
+    #[jni_onload(-42)]
+    fn check_onload_fallible(_env: &mut JNIEnv, _vm: &JavaVM) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_methods_reach_inventory() {
+        let collected = iter_method_registrations().filter(|m| m.class_path == CHECK_CLASS).count();
+        assert_eq!(collected, 3);
+
+        // The real JNI interface registers too; an empty inventory means the macros are not linked in
+        assert!(
+            describe_registrations()
+                .iter()
+                .any(|(class, ..)| class == "moe/fuqiuluo/mamu/driver/SearchEngine")
+        );
+
+        let described: Vec<(String, String, String, i32)> = describe_registrations().into_iter().filter(|(class, ..)| class == CHECK_CLASS).collect();
+        assert_eq!(
+            described,
+            vec![
+                (CHECK_CLASS.to_string(), "nativeAdd".to_string(), "(II)I".to_string(), 7),
+                (CHECK_CLASS.to_string(), "nativePing".to_string(), "(I)Z".to_string(), 3),
+            ]
+        );
+
+        let plan = plan_registrations();
+        let class = plan.iter().find(|class| class.class_path == CHECK_CLASS).unwrap();
+        assert_eq!(class.priority(), 7);
+        assert_eq!(class.methods.len(), 2);
+        assert_eq!((class.methods[0].method_name, class.methods[0].priority), ("nativeAdd", 7));
+    }
+
+    #[test]
+    fn test_duplicate_registration_reported() {
+        let conflicts: Vec<_> = conflicts_from(iter_method_registrations())
+            .into_iter()
+            .filter(|conflict| conflict.class_path == CHECK_CLASS)
+            .collect();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].method_name, "nativeAdd");
+        assert_eq!(conflicts[0].priorities, vec![7, 1]);
+    }
+
+    #[test]
+    fn test_onload_reaches_inventory() {
+        let steps: Vec<(&str, i32)> = init_sequence().iter().map(|step| (step.name(), step.priority())).collect();
+        let position = |name: &str| steps.iter().position(|&(step, _)| step == name).unwrap();
+
+        assert_eq!(steps[position("check_onload")].1, -41);
+        assert_eq!(steps[position("check_onload_fallible")].1, -42);
+        // Negative priorities run after every non-negative initializer, such as the logger
+        assert!(position("init_logger") < position("check_onload"));
+        assert!(position("check_onload") < position("check_onload_fallible"));
+    }
+}
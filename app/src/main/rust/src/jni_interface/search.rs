@@ -1,9 +1,9 @@
 //! JNI methods for SearchEngine.
 
-use crate::core::DRIVER_MANAGER;
+use crate::core::globals::driver_manager_read;
 use crate::ext::jni::{JniResult, JniResultExt};
 use crate::search::SearchResultItem;
-use crate::search::engine::{SEARCH_ENGINE_MANAGER, SHARED_BUFFER_SIZE, SearchProgressCallback};
+use crate::search::engine::{SEARCH_ENGINE_MANAGER, SHARED_BUFFER_SIZE, SearchEngineManager, SearchProgressCallback};
 use crate::search::parser::parse_search_query;
 use crate::search::result_manager::SearchResultMode;
 use crate::search::types::ValueType;
@@ -52,6 +52,7 @@ fn jint_to_value_type(value: jint) -> Option<ValueType> {
         5 => Some(ValueType::Double),
         6 => Some(ValueType::Auto),
         7 => Some(ValueType::Xor),
+        8 => Some(ValueType::Oword),
         _ => None,
     }
 }
@@ -105,6 +106,18 @@ fn format_value(bytes: &[u8], typ: ValueType) -> String {
                 "N/A".to_string()
             }
         },
+        ValueType::Oword => match bytes.get(..16) {
+            Some(bytes) => format!("0x{:032X}", u128::from_le_bytes(bytes.try_into().unwrap())),
+            None => "N/A".to_string(),
+        },
+        ValueType::Bytes(len) => match bytes.get(..len as usize) {
+            Some(bytes) => bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+            None => "N/A".to_string(),
+        },
+        ValueType::Text(len, encoding) => match bytes.get(..len as usize) {
+            Some(bytes) => encoding.decode(bytes),
+            None => "N/A".to_string(),
+        },
     }
 }
 
@@ -157,7 +170,7 @@ pub fn jni_clear_shared_buffer(mut env: JNIEnv, _class: JObject) {
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        manager.clear_shared_buffer();
+        manager.clear_shared_buffer()?;
         Ok(())
     })()
     .or_throw(&mut env)
@@ -291,13 +304,16 @@ pub fn jni_search(
             Some(Arc::new(JniCallback { vm, callback: global_ref }))
         };
 
-        let mut manager = SEARCH_ENGINE_MANAGER
-            .write()
-            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
-
-        let count = manager.search_memory(&search_query, &memory_regions, use_deep_search != JNI_FALSE, callback)?;
+        // 扫描期间不持有管理器的锁，进度查询和取消不会被阻塞
+        let outcome = SearchEngineManager::search_memory_shared(
+            &*SEARCH_ENGINE_MANAGER,
+            &search_query,
+            &memory_regions,
+            use_deep_search != JNI_FALSE,
+            callback,
+        )?;
 
-        Ok(count as jlong)
+        Ok(outcome.count as jlong)
     })()
     .or_throw(&mut env)
 }
@@ -318,7 +334,7 @@ pub fn jni_get_results(mut env: JNIEnv, _class: JObject, start: jint, size: jint
             .read()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
 
-        let current_mode = search_manager.get_current_mode()?;
+        let current_mode = search_manager.get_result_mode()?;
 
         if log_enabled!(Level::Debug) {
             let total_count = search_manager.get_total_count().unwrap_or(0);
@@ -377,7 +393,7 @@ pub fn jni_get_results(mut env: JNIEnv, _class: JObject, start: jint, size: jint
 
         let array = env.new_object_array(results.len() as jint, &class, JObject::null())?;
 
-        let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let driver_manager = driver_manager_read();
 
         for (i, (native_position, item)) in results.into_iter().enumerate() {
             let obj = match item {
@@ -438,6 +454,27 @@ pub fn jni_get_results(mut env: JNIEnv, _class: JObject, start: jint, size: jint
     .or_throw(&mut env)
 }
 
+/// Packs a page of results into one byte blob, see `search::result_manager::packed` for the layout.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativePackResults", "(II)[B")]
+pub fn jni_pack_results<'l>(mut env: JNIEnv<'l>, _class: JObject, start: jint, size: jint) -> JObject<'l> {
+    (|| -> JniResult<JObject<'l>> {
+        if start < 0 || size < 0 {
+            return Err(anyhow!("Invalid range: start={}, size={}", start, size));
+        }
+
+        let packed = {
+            let manager = SEARCH_ENGINE_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+            manager.pack_results(start as usize, size as usize)?
+        };
+
+        let array = env.byte_array_from_slice(&packed)?;
+        Ok(array.into())
+    })()
+    .or_throw(&mut env)
+}
+
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetTotalResultCount", "()J")]
 pub fn jni_get_total_result_count(mut env: JNIEnv, _class: JObject) -> jlong {
     (|| -> JniResult<jlong> {
@@ -579,13 +616,21 @@ pub fn jni_get_current_search_mode(mut env: JNIEnv, _class: JObject) -> jint {
             .read()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
 
-        let mode = manager.get_current_mode()?;
-        let mode_value = match mode {
-            crate::search::result_manager::SearchResultMode::Exact => 0,
-            crate::search::result_manager::SearchResultMode::Fuzzy => 1,
-        };
+        Ok(manager.get_result_mode()?.to_id())
+    })()
+    .or_throw(&mut env)
+}
 
-        Ok(mode_value)
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetResultMode", "(I)Z")]
+pub fn jni_set_result_mode(mut env: JNIEnv, _class: JObject, mode: jint) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let mode = SearchResultMode::from_id(mode).ok_or_else(|| anyhow!("Invalid result mode: {}", mode))?;
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_result_mode(mode)?;
+        Ok(JNI_TRUE)
     })()
     .or_throw(&mut env)
 }
@@ -619,6 +664,19 @@ pub fn jni_get_compatibility_mode(mut env: JNIEnv, _class: JObject) -> jboolean
     .or_throw(&mut env)
 }
 
+/// Gets the memory access mode id that scanned the bound process fastest within the fault limit.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetRecommendedMemoryMode", "()I")]
+pub fn jni_get_recommended_memory_mode(mut env: JNIEnv, _class: JObject) -> jint {
+    (|| -> JniResult<jint> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        Ok(manager.recommend_memory_mode().to_id())
+    })()
+    .or_throw(&mut env)
+}
+
 /// Legacy synchronous refine search method.
 #[jni_method(
     70,
@@ -642,11 +700,14 @@ pub fn jni_refine_search(mut env: JNIEnv, _class: JObject, query_str: JString, d
             Some(Arc::new(JniCallback { vm, callback: global_ref }))
         };
 
+        // 使用当前配置的访问模式读取，先于搜索管理器的写锁获取驱动读锁
+        let memory_mode = driver_manager_read().get_access_mode().to_id();
+
         let mut manager = SEARCH_ENGINE_MANAGER
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        let count = manager.refine_search(&search_query, callback)?;
+        let count = manager.refine_search(&search_query, memory_mode, callback)?;
 
         Ok(count as jlong)
     })()
@@ -8,9 +8,9 @@ pub mod search;
 pub mod wuwa;
 
 use android_logger::Config;
-use jni::sys::{jint, JNI_VERSION_1_6};
+use jni::sys::{jint, JNI_ERR, JNI_VERSION_1_6};
 use jni::{JNIEnv, JavaVM};
-use log::{info, LevelFilter};
+use log::{error, info, LevelFilter};
 use obfstr::obfstr as s;
 use std::ffi::c_void;
 use std::path::Path;
@@ -32,19 +32,18 @@ fn init_logger(_env: &mut JNIEnv, _vm: &JavaVM) {
     android_logger::init_once(Config::default().with_max_level(log_level).with_tag(s!("MamuCore")));
 }
 
+/// Whether a failing `#[jni_onload]` initializer makes `JNI_OnLoad` return `JNI_ERR` instead of continuing
+const ABORT_ON_INIT_ERROR: bool = true;
+
 #[allow(non_snake_case)]
 #[unsafe(no_mangle)]
 pub extern "system" fn JNI_OnLoad(vm: JavaVM, _: *mut c_void) -> jint {
     let mut env = vm.get_env().expect(s!("Cannot get reference to the JNIEnv"));
 
-    // Collect all registered initializers and sort by priority (descending)
-    let mut initializers: Vec<_> = jni_macro::inventory::iter::<jni_macro::JniInitializer>().collect();
-    initializers.sort_by_key(|init| std::cmp::Reverse(init.priority));
-
-    // Execute all initializers in priority order
-    for init in initializers {
-        info!("Executing JNI initializer: {} (priority: {})", init.name, init.priority);
-        (init.init_fn)(&mut env, &vm);
+    // Execute all registered initializers in priority order (descending)
+    if let Err(e) = jni_macro::run_initializers(&mut env, &vm, ABORT_ON_INIT_ERROR) {
+        error!("Aborting JNI_OnLoad: {}", e);
+        return JNI_ERR;
     }
 
     // Automatically register all methods marked with #[jni_method]
@@ -55,3 +54,10 @@ pub extern "system" fn JNI_OnLoad(vm: JavaVM, _: *mut c_void) -> jint {
 
     JNI_VERSION_1_6
 }
+
+#[allow(non_snake_case)]
+#[unsafe(no_mangle)]
+pub extern "system" fn JNI_OnUnload(vm: JavaVM, _: *mut c_void) {
+    // Tear down in ascending priority, the reverse of JNI_OnLoad
+    jni_macro::run_all_jni_finalizers(&vm);
+}
@@ -13,7 +13,7 @@ mod recursive_dfs;
 use crate::pointer_scan::chain_builder::layer_bfs::build_pointer_chains_layered_bfs;
 use crate::pointer_scan::chain_builder::recursive_dfs::build_pointer_chains_dfs;
 use crate::pointer_scan::storage::MmapQueue;
-use crate::pointer_scan::types::{PointerChain, PointerChainStep, PointerData, PointerScanConfig, VmStaticData};
+use crate::pointer_scan::types::{ChainBuildStats, ChainScoreWeights, PointerChain, PointerChainStep, PointerData, PointerScanConfig, VmStaticData};
 use anyhow::Result;
 use log::{debug, info, log_enabled, warn, Level};
 use rayon::prelude::*;
@@ -65,7 +65,8 @@ fn find_range_in_pointer_queue(queue: &MmapQueue<PointerData>, min_value: u64, m
 /// 返回 Vec<(指针地址, 有符号偏移)>，其中 有符号偏移 = target - 指针值。
 /// 正偏移：指针指向target下方
 /// 负偏移：指针指向target上方
-fn find_pointers_to_range(pointer_lib: &MmapQueue<PointerData>, target: u64, max_offset: u32) -> Vec<(u64, i64)> {
+/// 偏移不是 `offset_step` 整数倍的指针被跳过，`offset_step` 为 0 时视为 1。
+fn find_pointers_to_range(pointer_lib: &MmapQueue<PointerData>, target: u64, max_offset: u32, offset_step: u32) -> Vec<(u64, i64)> {
     let min_value = target.saturating_sub(max_offset as u64);
     let max_value = target + 1; // 上界不包含，所以 target+1 表示搜索到 target
 
@@ -90,7 +91,9 @@ fn find_pointers_to_range(pointer_lib: &MmapQueue<PointerData>, target: u64, max
             // 验证偏移在范围内
             if offset >= 0 && offset <= max_offset as i64 {
                 // ptr_address这个位置有个指针值，把它读出来然后加上offset得到target
-                results.push((ptr_address, offset));
+                if offset % offset_step.max(1) as i64 == 0 {
+                    results.push((ptr_address, offset));
+                }
             } else if log_enabled!(Level::Debug) {
                 debug!(
                     "跳过超出范围的指针: 地址=0x{:X}, 值=0x{:X}, 偏移={}, max_offset={}",
@@ -107,6 +110,47 @@ fn find_pointers_to_range(pointer_lib: &MmapQueue<PointerData>, target: u64, max
     results
 }
 
+/// 节点访问预算：统计在指针库中查找过引用者的节点数，达到 `max_visited_nodes` 后拒绝继续扩展。
+struct NodeBudget {
+    limit: Option<usize>,
+    visited: AtomicUsize,
+    limit_hit: AtomicBool,
+}
+
+impl NodeBudget {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            visited: AtomicUsize::new(0),
+            limit_hit: AtomicBool::new(false),
+        }
+    }
+
+    /// 占用一个节点，预算耗尽时返回 false。
+    fn try_visit(&self) -> bool {
+        let visited = self.visited.fetch_add(1, AtomicOrdering::Relaxed);
+        match self.limit {
+            Some(limit) if visited >= limit => {
+                self.limit_hit.store(true, AtomicOrdering::Relaxed);
+                false
+            },
+            _ => true,
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.limit_hit.load(AtomicOrdering::Relaxed)
+    }
+
+    fn stats(&self) -> ChainBuildStats {
+        let visited = self.visited.load(AtomicOrdering::Relaxed);
+        ChainBuildStats {
+            visited_nodes: self.limit.map_or(visited, |limit| visited.min(limit)),
+            node_limit_hit: self.is_exhausted(),
+        }
+    }
+}
+
 /// 检查地址是否属于静态模块。
 /// 如果找到，返回 (模块名, 模块索引, 基址偏移)。
 ///
@@ -159,21 +203,164 @@ fn classify_pointer(
 /// * `check_cancelled` - 检查是否取消的函数
 ///
 /// # 返回
-/// 完整指针链的向量
+/// 完整指针链的向量，以及访问节点数等统计；`node_limit_hit` 为 true 时链不完整
 pub fn build_pointer_chains<F, C>(
     pointer_lib: &MmapQueue<PointerData>,
     static_modules: &[VmStaticData],
     config: &PointerScanConfig,
     progress_callback: F,
     check_cancelled: C,
-) -> Result<Vec<PointerChain>>
+) -> Result<(Vec<PointerChain>, ChainBuildStats)>
 where
     F: Fn(u32, i32, i64) + Sync + Send + 'static,
     C: Fn() -> bool + Sync,
 {
-    if config.is_layer_bfs {
-        build_pointer_chains_layered_bfs(pointer_lib, static_modules, config, progress_callback, check_cancelled)
+    let budget = NodeBudget::new(config.max_visited_nodes);
+    let chains = if config.is_layer_bfs {
+        build_pointer_chains_layered_bfs(pointer_lib, static_modules, config, &budget, progress_callback, check_cancelled)?
     } else {
-        build_pointer_chains_dfs(pointer_lib, static_modules, config, progress_callback, check_cancelled)
+        build_pointer_chains_dfs(pointer_lib, static_modules, config, &budget, progress_callback, check_cancelled)?
+    };
+
+    let stats = budget.stats();
+    if stats.node_limit_hit {
+        warn!("指针链构造访问节点数达到上限 {}，已停止扩展，结果可能不完整", stats.visited_nodes);
+    }
+
+    let chains = match config.max_results {
+        Some(max_results) => prune_chains(chains, max_results, &config.score_weights),
+        None => chains,
+    };
+    Ok((chains, stats))
+}
+
+/// 链数超过 `max_results` 时只保留得分最高的部分，按得分从高到低排列；未超过时原样返回。
+///
+/// 同分的链按格式化后的字符串排序，保证结果确定。
+pub fn prune_chains(chains: Vec<PointerChain>, max_results: usize, weights: &ChainScoreWeights) -> Vec<PointerChain> {
+    if chains.len() <= max_results {
+        return chains;
+    }
+
+    let total = chains.len();
+    let mut scored: Vec<(f64, String, PointerChain)> = chains
+        .into_par_iter()
+        .map(|chain| (chain.score(weights), chain.format(), chain))
+        .collect();
+    let by_score = |a: &(f64, String, PointerChain), b: &(f64, String, PointerChain)| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1));
+
+    if max_results > 0 {
+        scored.select_nth_unstable_by(max_results - 1, by_score);
+    }
+    scored.truncate(max_results);
+    scored.sort_unstable_by(by_score);
+
+    info!("指针链数量 {} 超过上限 {}，保留得分最高的部分", total, max_results);
+    scored.into_iter().map(|(_, _, chain)| chain).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(root_offset: i64, offsets: &[i64]) -> PointerChain {
+        let mut chain = PointerChain::new(0x7A_0000_1000);
+        chain.push(PointerChainStep::static_root("libgame.so".to_string(), 0, root_offset));
+        for &offset in offsets {
+            chain.push(PointerChainStep::dynamic_offset(offset));
+        }
+        chain
+    }
+
+    #[test]
+    fn test_prune_keeps_top_scored_chains() {
+        let mut chains = Vec::new();
+        for depth in 1..=4usize {
+            for offset in [0x0i64, 0x8, 0x10, -0x20, 0x180, 0xFF8] {
+                for root_offset in [0x10i64, 0x2000, 0x40000] {
+                    chains.push(chain(root_offset, &vec![offset; depth]));
+                }
+            }
+        }
+        let weights = ChainScoreWeights::default();
+
+        let mut expected: Vec<(f64, String)> = chains.iter().map(|c| (c.score(&weights), c.format())).collect();
+        expected.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        expected.truncate(10);
+
+        let kept = prune_chains(chains.clone(), 10, &weights);
+        let kept: Vec<(f64, String)> = kept.iter().map(|c| (c.score(&weights), c.format())).collect();
+        assert_eq!(kept, expected);
+
+        // 最短、偏移最小且离模块基址最近的链排在最前
+        assert_eq!(kept[0].1, "libgame.so[0]+0x10->+0x0");
+        assert!(kept.iter().all(|(_, format)| format.matches("->").count() == 1));
+
+        // 未超过上限时原样返回
+        assert_eq!(prune_chains(chains.clone(), chains.len(), &weights).len(), chains.len());
+        assert!(prune_chains(chains, 0, &weights).is_empty());
+
+        // 深度权重为 0 时，偏移小的长链优先于偏移大的短链
+        let flat = ChainScoreWeights { depth: 0.0, ..weights };
+        let pruned = prune_chains(vec![chain(0x10, &[0xFF8]), chain(0x10, &[0x8, 0x8, 0x8])], 1, &flat);
+        assert_eq!(pruned[0].depth(), 4);
+    }
+
+    const TARGET: u64 = 0x7000_0000_1000;
+
+    /// libgame.so+0x100 -> +0x10 -> +0x8 -> +0x20 到达 TARGET，另有两个静态指针直接指向 TARGET-0x14 和 TARGET-0x18
+    fn pointer_lib(name: &str) -> (MmapQueue<PointerData>, Vec<VmStaticData>) {
+        let (obj_a, obj_b) = (0x7000_0000_2000u64, 0x7000_0000_3000u64);
+        let mut pointers = vec![
+            PointerData::new(0x5000_0100, obj_a - 0x10),
+            PointerData::new(0x5000_0200, TARGET - 0x14),
+            PointerData::new(0x5000_0300, TARGET - 0x18),
+            PointerData::new(obj_a, obj_b - 0x8),
+            PointerData::new(obj_b, TARGET - 0x20),
+        ];
+        pointers.sort_by_key(|pointer| pointer.value);
+
+        let mut queue = MmapQueue::new(&std::env::temp_dir().join("mamu_chain_builder_tests"), name).unwrap();
+        queue.push_batch(&pointers).unwrap();
+        (queue, vec![VmStaticData::new("libgame.so".to_string(), 0x5000_0000, 0x5001_0000, true)])
+    }
+
+    fn build(lib: &MmapQueue<PointerData>, modules: &[VmStaticData], config: &PointerScanConfig) -> (Vec<String>, ChainBuildStats) {
+        let (chains, stats) = build_pointer_chains(lib, modules, config, |_, _, _| {}, || false).unwrap();
+        let mut formatted: Vec<String> = chains.iter().map(PointerChain::format).collect();
+        formatted.sort();
+        (formatted, stats)
+    }
+
+    #[test]
+    fn test_offset_step_and_traversal_caps() {
+        let (lib, modules) = pointer_lib("traversal_caps");
+        let deep_chain = "libgame.so[0]+0x100->+0x10->+0x8->+0x20".to_string();
+
+        for is_layer_bfs in [false, true] {
+            let config = PointerScanConfig { is_layer_bfs, ..PointerScanConfig::new(TARGET).with_offset(0x100) };
+
+            let (all, stats) = build(&lib, &modules, &config);
+            assert_eq!(all, vec![deep_chain.clone(), "libgame.so[0]+0x200->+0x14".to_string(), "libgame.so[0]+0x300->+0x18".to_string()]);
+            assert!(!stats.node_limit_hit);
+
+            // 只沿 8 字节对齐的偏移扩展
+            let (aligned, _) = build(&lib, &modules, &config.clone().with_offset_step(8));
+            assert_eq!(aligned, vec![deep_chain.clone(), "libgame.so[0]+0x300->+0x18".to_string()]);
+
+            // 深度上限：需要 3 次解引用的链在深度 2 时找不到
+            let (shallow, _) = build(&lib, &modules, &config.clone().with_depth(2));
+            assert!(!shallow.contains(&deep_chain));
+            assert_eq!(shallow.len(), 2);
+
+            // 节点上限：TARGET、obj_b 之后不再扩展 obj_a
+            let (capped, stats) = build(&lib, &modules, &config.clone().with_max_visited_nodes(Some(2)));
+            assert!(!capped.contains(&deep_chain));
+            assert_eq!(stats, ChainBuildStats { visited_nodes: 2, node_limit_hit: true });
+
+            let (exact, stats) = build(&lib, &modules, &config.clone().with_max_visited_nodes(Some(3)));
+            assert_eq!(exact, all);
+            assert_eq!(stats, ChainBuildStats { visited_nodes: 3, node_limit_hit: false });
+        }
     }
 }
@@ -89,10 +89,12 @@ const MAX_FANOUT_PER_NODE: usize = 10 * 10000;
 /// - 路径内循环检测：使用 PathNode.visited_addresses 防止 A→B→C→B 类型的循环
 /// - 扇出限制：每个节点最多产生 MAX_FANOUT_PER_NODE 个子节点
 /// - 层级限制：每层最多 MAX_CANDIDATES_PER_LAYER 个节点
+/// - 节点预算：累计查找的节点数达到 `max_visited_nodes` 后停止扩展
 pub fn build_pointer_chains_layered_bfs<F, C>(
     pointer_lib: &MmapQueue<PointerData>,
     static_modules: &[VmStaticData],
     config: &PointerScanConfig,
+    budget: &NodeBudget,
     progress_callback: F,
     check_cancelled: C,
 ) -> Result<Vec<PointerChain>>
@@ -124,6 +126,10 @@ where
             break;
         }
 
+        if budget.is_exhausted() {
+            break;
+        }
+
         info!("处理深度 {}, 当前层 {} 个节点", depth, current_layer.len());
 
         // 并行扫描：每个线程处理current_layer的一个分块
@@ -132,11 +138,11 @@ where
             .par_iter()
             .enumerate()
             .flat_map(|(parent_idx, node)| {
-                if cancelled.load(AtomicOrdering::Relaxed) {
+                if cancelled.load(AtomicOrdering::Relaxed) || !budget.try_visit() {
                     return Vec::new();
                 }
 
-                let pointers = find_pointers_to_range(pointer_lib, node.current_target, config.max_offset);
+                let pointers = find_pointers_to_range(pointer_lib, node.current_target, config.max_offset, config.offset_step);
 
                 // 过滤掉循环引用的候选，并限制扇出数量
                 pointers
@@ -9,6 +9,7 @@ struct DfsContext<'a> {
     pointer_lib: &'a MmapQueue<PointerData>,
     static_modules: &'a [VmStaticData],
     config: &'a PointerScanConfig,
+    budget: &'a NodeBudget,
     cancelled: &'a AtomicBool,
 }
 
@@ -16,6 +17,7 @@ pub fn build_pointer_chains_dfs<F, C>(
     pointer_lib: &MmapQueue<PointerData>,
     static_modules: &[VmStaticData],
     config: &PointerScanConfig,
+    budget: &NodeBudget,
     progress_callback: F,
     check_cancelled: C,
 ) -> Result<Vec<PointerChain>>
@@ -58,11 +60,16 @@ where
         pointer_lib,
         static_modules,
         config,
+        budget,
         cancelled: &cancelled,
     };
 
     // 获取第一层入口点 (反向搜索第一步)
-    let roots = find_pointers_to_range(pointer_lib, config.target_address, config.max_offset);
+    let roots = if budget.try_visit() {
+        find_pointers_to_range(pointer_lib, config.target_address, config.max_offset, config.offset_step)
+    } else {
+        Vec::new()
+    };
     if log_enabled!(Level::Debug) {
         info!("第一层入口点数量: {}", roots.len());
     }
@@ -133,9 +140,14 @@ fn dfs_recursive(ctx: &DfsContext, tx: &Sender<PointerChain>, current_address: u
         return;
     }
 
+    // 节点预算耗尽后不再扩展
+    if !ctx.budget.try_visit() {
+        return;
+    }
+
     // 查找父节点
     // 这里是性能关键点：大量的随机 IO 读取
-    let parents = find_pointers_to_range(ctx.pointer_lib, current_address, ctx.config.max_offset);
+    let parents = find_pointers_to_range(ctx.pointer_lib, current_address, ctx.config.max_offset, ctx.config.offset_step);
 
     for (parent_addr, offset) in parents {
         // 环路检测
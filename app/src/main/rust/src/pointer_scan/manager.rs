@@ -9,7 +9,7 @@ use crate::pointer_scan::chain_builder;
 use crate::pointer_scan::scanner::{self, ScanRegion};
 use crate::pointer_scan::shared_buffer::PointerScanSharedBuffer;
 use crate::pointer_scan::storage::MmapQueue;
-use crate::pointer_scan::types::{PointerChain, PointerData, PointerScanConfig, ScanErrorCode, ScanPhase, VmStaticData};
+use crate::pointer_scan::types::{ChainBuildStats, ChainScoreWeights, Endianness, PointerChain, PointerData, PointerScanConfig, PointerWidth, ScanErrorCode, ScanPhase, VmStaticData};
 use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
 use log::{error, info, log_enabled, warn, Level};
@@ -28,6 +28,8 @@ pub struct PointerScanManager {
     pointer_library: Option<MmapQueue<PointerData>>,
     /// Pointer chain results from Phase 2
     chain_results: Vec<PointerChain>,
+    /// Traversal statistics from Phase 2
+    build_stats: ChainBuildStats,
     /// Current scan configuration
     config: PointerScanConfig,
     /// Shared buffer for progress communication
@@ -50,6 +52,7 @@ impl PointerScanManager {
         Self {
             pointer_library: None,
             chain_results: Vec::new(),
+            build_stats: ChainBuildStats::default(),
             config: PointerScanConfig::default(),
             shared_buffer: PointerScanSharedBuffer::new(),
             cancel_token: None,
@@ -130,10 +133,40 @@ impl PointerScanManager {
         rrt
     }
 
+    /// Set the pointer width and byte order of the target process, used by subsequent scans.
+    pub fn set_pointer_layout(&mut self, pointer_width: PointerWidth, endianness: Endianness) {
+        self.config.pointer_width = pointer_width;
+        self.config.endianness = endianness;
+    }
+
+    /// Only record pointer words that land inside a scanned region, used by subsequent scans.
+    pub fn set_require_mapped(&mut self, require_mapped: bool) {
+        self.config.require_mapped = require_mapped;
+    }
+
+    /// Cap the number of chains kept by subsequent scans, dropping the lowest scored ones.
+    pub fn set_max_results(&mut self, max_results: Option<usize>, score_weights: ChainScoreWeights) {
+        self.config.max_results = max_results;
+        self.config.score_weights = score_weights;
+    }
+
+    /// Limit chain building to offsets that are multiples of `offset_step` and to at most
+    /// `max_visited_nodes` expanded nodes, used by subsequent scans.
+    pub fn set_traversal_limits(&mut self, offset_step: u32, max_visited_nodes: Option<usize>) {
+        self.config.offset_step = offset_step;
+        self.config.max_visited_nodes = max_visited_nodes;
+    }
+
+    /// Traversal statistics of the last completed scan, including whether the node cap was hit.
+    pub fn get_build_stats(&self) -> ChainBuildStats {
+        self.build_stats
+    }
+
     /// Clear all results and reset state.
     pub fn clear(&mut self) {
         self.pointer_library = None;
         self.chain_results.clear();
+        self.build_stats = ChainBuildStats::default();
         self.current_phase = ScanPhase::Idle;
         self.last_error = ScanErrorCode::None;
         self.shared_buffer.reset();
@@ -171,6 +204,13 @@ impl PointerScanManager {
             is_layer_bfs,
             data_start: true,
             bss_start: false,
+            pointer_width: self.config.pointer_width,
+            endianness: self.config.endianness,
+            require_mapped: self.config.require_mapped,
+            max_results: self.config.max_results,
+            score_weights: self.config.score_weights,
+            offset_step: self.config.offset_step,
+            max_visited_nodes: self.config.max_visited_nodes,
         };
 
         // Reset state
@@ -321,13 +361,14 @@ impl PointerScanManager {
 
         // Store results
         match chains_result {
-            Ok(chains) => {
+            Ok((chains, stats)) => {
                 if log_enabled!(Level::Debug) {
-                    info!("Phase 2 complete. Found {} chains", chains.len());
+                    info!("Phase 2 complete. Found {} chains, visited {} nodes", chains.len(), stats.visited_nodes);
                 }
                 if let Ok(mut manager) = POINTER_SCAN_MANAGER.write() {
                     manager.pointer_library = Some(pointer_lib);
                     manager.chain_results = chains;
+                    manager.build_stats = stats;
                     manager.current_phase = ScanPhase::Completed;
                     manager.shared_buffer.write_phase(ScanPhase::Completed);
                     manager.shared_buffer.write_progress(100);
@@ -1,12 +1,13 @@
 //! Phase 1: Pointer Scanner
 //!
 //! This module scans all readable memory regions for valid pointers.
-//! A valid pointer is a 64-bit value whose lower 48 bits fall within
+//! A valid pointer is a pointer word (32 or 64-bit, in the target's byte order,
+//! see [`PointerScanConfig::decode_pointer`]) whose lower 48 bits fall within
 //! a known memory region.
 
 use std::cmp::min;
 use std::path::PathBuf;
-use crate::core::DRIVER_MANAGER;
+use crate::core::globals::driver_manager_read;
 use crate::pointer_scan::storage::MmapQueue;
 use crate::pointer_scan::types::{PointerData, PointerScanConfig};
 use anyhow::{anyhow, Result};
@@ -42,14 +43,18 @@ impl ScanRegion {
 /// Validates if a 64-bit value could be a valid pointer.
 ///
 /// On ARM64, only the lower 48 bits are used for addressing.
-/// The value must fall within a known memory region to be considered valid.
+/// With `require_mapped` the value must fall within a known memory region; otherwise it only
+/// has to lie between the lowest and highest region bounds, which is cheaper but lets through
+/// words pointing into the gaps between regions.
+///
+/// Null is a legitimate pointer value but is never reported: no chain can pass through address 0.
 #[inline]
-fn is_valid_pointer(value: u64, valid_ranges: &[(u64, u64)]) -> bool {
+pub(crate) fn is_valid_pointer(value: u64, valid_ranges: &[(u64, u64)], require_mapped: bool) -> bool {
     // Mask to 48-bit addressable space (ARM64)
     let masked = value & 0x0000_FFFF_FFFF_FFFF;
 
     // Quick range check
-    if valid_ranges.is_empty() {
+    if masked == 0 || valid_ranges.is_empty() {
         return false;
     }
 
@@ -60,6 +65,10 @@ fn is_valid_pointer(value: u64, valid_ranges: &[(u64, u64)]) -> bool {
         return false;
     }
 
+    if !require_mapped {
+        return true;
+    }
+
     // Binary search for containing region
     valid_ranges
         .binary_search_by(|(start, end)| {
@@ -74,6 +83,29 @@ fn is_valid_pointer(value: u64, valid_ranges: &[(u64, u64)]) -> bool {
         .is_ok()
 }
 
+/// Follow a pointer chain starting at `base`.
+///
+/// For each offset the current address is dereferenced (decoded with the configured
+/// pointer width and byte order) and the offset is added, i.e. `*(*base + o0) + o1 ...`.
+/// Returns `None` if any read fails or the address arithmetic overflows.
+pub fn resolve_pointer_chain<F>(base: u64, offsets: &[i64], config: &PointerScanConfig, mut read: F) -> Option<u64>
+where
+    F: FnMut(u64, &mut [u8]) -> bool,
+{
+    let mut word = [0u8; 8];
+    let word = &mut word[..config.pointer_width.size()];
+    let mut addr = base;
+
+    for &offset in offsets {
+        if !read(addr, word) {
+            return None;
+        }
+        addr = config.decode_pointer(word).checked_add_signed(offset)?;
+    }
+
+    Some(addr)
+}
+
 /// Scan a single memory chunk for valid pointers.
 /// Only scans pages that were successfully read (indicated by page_bitmap).
 /// Returns a vector of found pointers with their addresses and values.
@@ -81,17 +113,18 @@ fn is_valid_pointer(value: u64, valid_ranges: &[(u64, u64)]) -> bool {
 fn scan_chunk_for_pointers(
     buffer: &[u8],
     base_addr: u64,
-    align: u32,
+    config: &PointerScanConfig,
     valid_ranges: &[(u64, u64)],
     page_bitmap: &PageStatusBitmap,
 ) -> Vec<PointerData> {
     let mut results = Vec::with_capacity(1024);
+    let word_size = config.pointer_width.size();
 
-    if buffer.len() < 8 {
+    if buffer.len() < word_size {
         return results;
     }
 
-    let step = align as usize;
+    let step = config.align as usize;
     let num_pages = page_bitmap.num_pages();
 
     // 直接迭代所有页面，避免 collect() 分配内存
@@ -114,28 +147,28 @@ fn scan_chunk_for_pointers(
         // 实际可用的切片
         let page_slice = &buffer[page_start_idx..page_end_idx];
 
-        // 只有当剩余数据足够放一个 u64 (8字节) 时才扫描
-        if page_slice.len() < 8 {
+        // 只有当剩余数据足够放一个指针字时才扫描
+        if page_slice.len() < word_size {
             continue;
         }
 
         // 限制扫描的终点，防止读取越界
-        // 例子：Slice 长度 4096。最大 offset 应该是 4088。4088..4096 是最后8字节。
-        let scan_limit = page_slice.len() - 8;
+        // 例子：Slice 长度 4096，64 位指针时最大 offset 应该是 4088。4088..4096 是最后8字节。
+        let scan_limit = page_slice.len() - word_size;
 
         for offset in (0..=scan_limit).step_by(step) {
-            // Safety: 我们已经通过 scan_limit 保证了 offset+8 不会越界
-            // 使用 try_into 会被编译器优化掉，这里是零开销
+            // Safety: 我们已经通过 scan_limit 保证了 offset+word_size 不会越界
             let bytes = unsafe {
                 // 使用 unsafe get_unchecked 可以进一步减少边界检查，提升 extreme performance
                 // 但在标准安全代码中， slice索引就够了。这里演示最安全写法。
-                page_slice.get_unchecked(offset..offset + 8)
+                page_slice.get_unchecked(offset..offset + word_size)
             };
 
-            let value = u64::from_le_bytes(bytes.try_into().unwrap());
+            // 按目标进程的指针宽度和字节序解码
+            let value = config.decode_pointer(bytes);
 
             // is_valid_pointer 最好是 #[inline] 的
-            if is_valid_pointer(value, valid_ranges) {
+            if is_valid_pointer(value, valid_ranges, config.require_mapped) {
                 // 计算实际内存地址：基址 + 页偏移 + 页内偏移
                 let ptr_address = base_addr + (page_start_idx + offset) as u64;
                 results.push(PointerData::new(ptr_address, value));
@@ -159,7 +192,7 @@ fn scan_region_for_pointers(
     assert_eq!(region.end & (*PAGE_SIZE as u64 - 1), 0);
     assert_eq!(chunk_size & (*PAGE_SIZE - 1), 0);
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let driver_manager = driver_manager_read();
 
     let mut buffer = vec![0u8; chunk_size];
     let mut current_addr = region.start;
@@ -180,7 +213,7 @@ fn scan_region_for_pointers(
                 // todo：Chunk 边界的指针遗漏，在 scan_region_for_pointers 中，你按 chunk_size (512KB) 逐块读取内存
                 // 在 scan_chunk_for_pointers 中，扫描循环限制为 scan_limit = page_slice.len() - 8
                 // 这意味着如果一个指针横跨了两个 Chunk（例如：指针起始地址在 Chunk A 的最后 4 个字节，结束地址在 Chunk B 的前 4 个字节），这个指针会被彻底漏掉。它在 Chunk A 中因为长度不足 8 被截断，在 Chunk B 中因为起始偏移是 0 而被跳过。
-                let chunk_results = scan_chunk_for_pointers(&buffer[..read_size], current_addr, config.align, valid_ranges, &page_bitmap);
+                let chunk_results = scan_chunk_for_pointers(&buffer[..read_size], current_addr, config, valid_ranges, &page_bitmap);
 
                 if !chunk_results.is_empty() {
                     if log_enabled!(Level::Debug) {
@@ -446,4 +479,117 @@ fn merge_temp_files_kway(files: Vec<PathBuf>, out_dir: &PathBuf, out_name: &str)
     }
 
     Ok(queue)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer_scan::types::{Endianness, PointerWidth};
+    use crate::search::tests::mock_memory::MockMemory;
+
+    /// base -> obj_a(+0x10) -> obj_b(+0x8) -> target(+0x20)，指针字按 `config` 编码写入
+    fn build_chain(mem: &mut MockMemory, config: &PointerScanConfig, base: u64, obj_a: u64, obj_b: u64) {
+        let encode = |value: u64| -> Vec<u8> {
+            match (config.pointer_width, config.endianness) {
+                (PointerWidth::Bits32, Endianness::Little) => (value as u32).to_le_bytes().to_vec(),
+                (PointerWidth::Bits32, Endianness::Big) => (value as u32).to_be_bytes().to_vec(),
+                (PointerWidth::Bits64, Endianness::Little) => value.to_le_bytes().to_vec(),
+                (PointerWidth::Bits64, Endianness::Big) => value.to_be_bytes().to_vec(),
+            }
+        };
+        mem.mem_write(base, &encode(obj_a)).unwrap();
+        mem.mem_write(obj_a + 0x10, &encode(obj_b)).unwrap();
+        mem.mem_write(obj_b + 0x8, &encode(obj_b + 0x100)).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_chain_32_and_64_bit() {
+        let layouts = [
+            (PointerWidth::Bits32, Endianness::Little, 0x1000_0000u64),
+            (PointerWidth::Bits32, Endianness::Big, 0x1000_0000u64),
+            (PointerWidth::Bits64, Endianness::Little, 0x7A_0000_0000u64),
+            (PointerWidth::Bits64, Endianness::Big, 0x7A_0000_0000u64),
+        ];
+
+        for (width, endianness, space) in layouts {
+            let config = PointerScanConfig::default().with_pointer_width(width).with_endianness(endianness);
+
+            let mut mem = MockMemory::new();
+            let base = mem.malloc(space, 0x1000).unwrap();
+            let obj_a = mem.malloc(space + 0x10_0000, 0x1000).unwrap();
+            let obj_b = mem.malloc(space + 0x20_0000, 0x1000).unwrap();
+            build_chain(&mut mem, &config, base, obj_a, obj_b);
+
+            let read = |addr: u64, buf: &mut [u8]| match mem.mem_read(addr, buf.len()) {
+                Ok(data) => {
+                    buf.copy_from_slice(&data);
+                    true
+                },
+                Err(_) => false,
+            };
+
+            let resolved = resolve_pointer_chain(base, &[0x10, 0x8, 0x20], &config, read);
+            assert_eq!(resolved, Some(obj_b + 0x120), "{:?} {:?}", width, endianness);
+
+            // 32 位指针在块扫描时按 4 字节读取，64 位按 8 字节读取
+            let mut buffer = vec![0u8; 0x1000];
+            let mut page_status = PageStatusBitmap::new(buffer.len(), obj_a as usize);
+            mem.mem_read_with_status(obj_a, &mut buffer, &mut page_status).unwrap();
+            let valid_ranges = [(obj_b, obj_b + 0x1000)];
+            let found = scan_chunk_for_pointers(&buffer, obj_a, &config, &valid_ranges, &page_status);
+            assert_eq!(found, vec![PointerData::new(obj_a + 0x10, obj_b)], "{:?} {:?}", width, endianness);
+        }
+    }
+
+    #[test]
+    fn test_resolve_chain_stops_on_failed_read() {
+        let config = PointerScanConfig::default().with_pointer_width(PointerWidth::Bits32);
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x1000_0000, 0x1000).unwrap();
+        // 指向未映射的地址
+        mem.mem_write_u32(base, 0x2000_0000).unwrap();
+
+        let read = |addr: u64, buf: &mut [u8]| match mem.mem_read(addr, buf.len()) {
+            Ok(data) => {
+                buf.copy_from_slice(&data);
+                true
+            },
+            Err(_) => false,
+        };
+
+        assert_eq!(resolve_pointer_chain(base, &[0x0], &config, read), Some(0x2000_0000));
+        assert_eq!(resolve_pointer_chain(base, &[0x0, 0x4], &config, read), None);
+    }
+    #[test]
+    fn test_require_mapped_rejects_words_between_regions() {
+        let mut mem = MockMemory::new();
+        let scanned = mem.malloc(0x7A_0000_0000, 0x1000).unwrap();
+        let heap = mem.malloc(0x7A_0010_0000, 0x1000).unwrap();
+        let stack = mem.malloc(0x7A_0040_0000, 0x1000).unwrap();
+
+        // 两个指向已映射区域的指针、一个空指针、一个落在区域间空隙的值、一个超出所有区域的值
+        mem.mem_write_u64(scanned, heap + 0x80).unwrap();
+        mem.mem_write_u64(scanned + 0x8, 0).unwrap();
+        mem.mem_write_u64(scanned + 0x10, 0x7A_0020_0000).unwrap();
+        mem.mem_write_u64(scanned + 0x18, 0x1234_5678_9ABC).unwrap();
+        mem.mem_write_u64(scanned + 0x20, stack + 0xFF8).unwrap();
+
+        let mut buffer = vec![0u8; 0x1000];
+        let mut page_status = PageStatusBitmap::new(buffer.len(), scanned as usize);
+        mem.mem_read_with_status(scanned, &mut buffer, &mut page_status).unwrap();
+        let valid_ranges = [(scanned, scanned + 0x1000), (heap, heap + 0x1000), (stack, stack + 0x1000)];
+        let scan = |config: &PointerScanConfig| -> Vec<u64> {
+            scan_chunk_for_pointers(&buffer, scanned, config, &valid_ranges, &page_status)
+                .iter()
+                .map(|p| p.address())
+                .collect()
+        };
+
+        let config = PointerScanConfig::default().with_align(8);
+        assert!(config.require_mapped);
+        assert_eq!(scan(&config), vec![scanned, scanned + 0x20]);
+
+        // 只做上下界检查时，空隙中的值也会被当作指针
+        let loose = config.with_require_mapped(false);
+        assert_eq!(scan(&loose), vec![scanned, scanned + 0x10, scanned + 0x20]);
+    }
+}
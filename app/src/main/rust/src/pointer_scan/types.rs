@@ -132,6 +132,24 @@ impl PointerChain {
         self.steps.len()
     }
 
+    /// Score used to rank chains when results are capped, higher is better.
+    ///
+    /// Each level, each byte of dynamic offset and each byte of the root's distance from its
+    /// module base costs the corresponding weight.
+    pub fn score(&self, weights: &ChainScoreWeights) -> f64 {
+        let mut root_offset = 0u64;
+        let mut dynamic_offset = 0u64;
+        for step in &self.steps {
+            if step.is_static {
+                root_offset += step.offset.unsigned_abs();
+            } else {
+                dynamic_offset += step.offset.unsigned_abs();
+            }
+        }
+
+        -(weights.depth * self.depth() as f64 + weights.offset * dynamic_offset as f64 + weights.root_offset * root_offset as f64)
+    }
+
     /// Format the chain as a string like "libil2cpp.so[0]+0x1A2B3C0->+0x18->-0x20"
     pub fn format(&self) -> String {
         if self.steps.is_empty() {
@@ -164,6 +182,55 @@ impl PointerChain {
     }
 }
 
+/// Weights of [`PointerChain::score`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainScoreWeights {
+    /// Cost per chain level (default: 0x1000, one level weighs as much as a page of offsets)
+    pub depth: f64,
+    /// Cost per byte of dynamic offsets (default: 1)
+    pub offset: f64,
+    /// Cost per byte between the static root and its module base (default: 1/256)
+    pub root_offset: f64,
+}
+
+impl Default for ChainScoreWeights {
+    fn default() -> Self {
+        Self {
+            depth: 4096.0,
+            offset: 1.0,
+            root_offset: 1.0 / 256.0,
+        }
+    }
+}
+
+/// Width of a pointer word in the target process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointerWidth {
+    /// 32-bit process (armeabi-v7a)
+    Bits32,
+    /// 64-bit process (arm64-v8a)
+    #[default]
+    Bits64,
+}
+
+impl PointerWidth {
+    /// Size of a pointer word in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            PointerWidth::Bits32 => 4,
+            PointerWidth::Bits64 => 8,
+        }
+    }
+}
+
+/// Byte order of pointer words in the target process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
 /// Configuration for pointer scanning.
 #[derive(Debug, Clone)]
 pub struct PointerScanConfig {
@@ -181,6 +248,21 @@ pub struct PointerScanConfig {
     pub data_start: bool,
     /// lookup Base Addr from start of .bss
     pub bss_start: bool,
+    /// Pointer width of the target process (default: 64-bit)
+    pub pointer_width: PointerWidth,
+    /// Byte order of pointer words (default: little-endian)
+    pub endianness: Endianness,
+    /// Only accept words that point into one of the scanned regions (default: true).
+    /// When false, any word between the lowest and highest region bounds is accepted.
+    pub require_mapped: bool,
+    /// Keep at most this many chains, the best by [`PointerChain::score`] (default: unlimited)
+    pub max_results: Option<usize>,
+    /// Weights used to rank chains when `max_results` is hit
+    pub score_weights: ChainScoreWeights,
+    /// Only follow offsets that are a multiple of this step, e.g. 8 for pointer-aligned fields (default: 1, every offset)
+    pub offset_step: u32,
+    /// Stop expanding chains after this many nodes have been looked up (default: unlimited)
+    pub max_visited_nodes: Option<usize>,
 }
 
 impl Default for PointerScanConfig {
@@ -193,6 +275,13 @@ impl Default for PointerScanConfig {
             is_layer_bfs: false,
             data_start: true,
             bss_start: false,
+            pointer_width: PointerWidth::default(),
+            endianness: Endianness::default(),
+            require_mapped: true,
+            max_results: None,
+            score_weights: ChainScoreWeights::default(),
+            offset_step: 1,
+            max_visited_nodes: None,
         }
     }
 }
@@ -219,6 +308,63 @@ impl PointerScanConfig {
         self.align = align;
         self
     }
+
+    pub fn with_pointer_width(mut self, pointer_width: PointerWidth) -> Self {
+        self.pointer_width = pointer_width;
+        self
+    }
+
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    pub fn with_require_mapped(mut self, require_mapped: bool) -> Self {
+        self.require_mapped = require_mapped;
+        self
+    }
+
+    pub fn with_max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    pub fn with_score_weights(mut self, score_weights: ChainScoreWeights) -> Self {
+        self.score_weights = score_weights;
+        self
+    }
+
+    pub fn with_offset_step(mut self, offset_step: u32) -> Self {
+        self.offset_step = offset_step;
+        self
+    }
+
+    pub fn with_max_visited_nodes(mut self, max_visited_nodes: Option<usize>) -> Self {
+        self.max_visited_nodes = max_visited_nodes;
+        self
+    }
+
+    /// Decode a pointer word using the configured width and byte order.
+    ///
+    /// `bytes` must hold at least `pointer_width.size()` bytes; 32-bit pointers are zero-extended.
+    #[inline]
+    pub fn decode_pointer(&self, bytes: &[u8]) -> u64 {
+        match (self.pointer_width, self.endianness) {
+            (PointerWidth::Bits32, Endianness::Little) => u32::from_le_bytes(bytes[..4].try_into().unwrap()) as u64,
+            (PointerWidth::Bits32, Endianness::Big) => u32::from_be_bytes(bytes[..4].try_into().unwrap()) as u64,
+            (PointerWidth::Bits64, Endianness::Little) => u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            (PointerWidth::Bits64, Endianness::Big) => u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+        }
+    }
+}
+
+/// Summary of a chain building pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChainBuildStats {
+    /// Nodes whose referrers were looked up in the pointer library
+    pub visited_nodes: usize,
+    /// True if `max_visited_nodes` was reached and the chains are incomplete
+    pub node_limit_hit: bool,
 }
 
 /// Scan phase enumeration for progress tracking.
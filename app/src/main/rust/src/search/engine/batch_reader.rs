@@ -1,6 +1,6 @@
-use crate::core::DRIVER_MANAGER;
+use crate::core::globals::driver_manager_read;
 use crate::search::result_manager::FuzzySearchResultItem;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use log::{debug, log_enabled, Level};
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -158,7 +158,7 @@ where
         .try_fold(
             || Vec::new(), // 线程本地累加器
             |mut acc, (batch_idx, batch)| -> Result<Vec<(FuzzySearchResultItem, Vec<u8>)>> {
-                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+                let driver_manager = driver_manager_read();
 
                 // 分配批次缓冲区
                 let mut buffer = vec![0u8; batch.total_size];
@@ -0,0 +1,123 @@
+//! 命名检查点：记录区域内每页内容的哈希，之后只重新扫描内容变化过的页
+//!
+//! 每页只保存一个 u64 哈希（4KB 页约占 1/512 的空间）。读取失败的页记为 [`UNREADABLE`]，
+//! 之后恢复可读（或变为不可读）都视为变化。
+
+use super::manager::page_aligned_chunk_size;
+use crate::search::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+use anyhow::Result;
+use std::hash::{DefaultHasher, Hasher};
+
+/// 不可读页的哈希值，正常页的哈希最低位恒为 1，不会与之冲突
+pub(crate) const UNREADABLE: u64 = 0;
+
+/// 一个区域的页哈希，`hashes[i]` 对应从 `start` 所在页起的第 i 页
+#[derive(Debug, Clone)]
+struct CheckpointRegion {
+    start: u64,
+    end: u64,
+    hashes: Vec<u64>,
+}
+
+/// 一组区域在某一时刻的内容摘要
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoint {
+    regions: Vec<CheckpointRegion>,
+}
+
+impl Checkpoint {
+    /// 读取 `regions` 并记录每页哈希，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn capture<R>(regions: &[(u64, u64)], chunk_size: usize, mut read: R) -> Self
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let regions = regions
+            .iter()
+            .map(|&(start, end)| CheckpointRegion {
+                start,
+                end,
+                hashes: hash_region_pages(start, end, chunk_size, &mut read),
+            })
+            .collect();
+        Self { regions }
+    }
+
+    /// 检查点覆盖的页数
+    pub fn page_count(&self) -> usize {
+        self.regions.iter().map(|region| region.hashes.len()).sum()
+    }
+
+    /// 重新读取并与检查点比较，返回内容变化的页合并后的地址范围
+    ///
+    /// 每段范围向两侧各扩展 `margin` 字节（跨页的元素、组搜索窗口），并裁剪到原区域内
+    pub(crate) fn changed_ranges<R>(&self, chunk_size: usize, margin: u64, mut read: R) -> Vec<(u64, u64)>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let page_size = *PAGE_SIZE as u64;
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+
+        for region in &self.regions {
+            let current = hash_region_pages(region.start, region.end, chunk_size, &mut read);
+            let page_base = region.start & !(page_size - 1);
+            let region_first = ranges.len();
+
+            for (idx, (old, new)) in region.hashes.iter().zip(&current).enumerate() {
+                if old == new {
+                    continue;
+                }
+
+                let page_start = page_base + idx as u64 * page_size;
+                let range_start = page_start.saturating_sub(margin).max(region.start);
+                let range_end = page_start.saturating_add(page_size + margin).min(region.end);
+
+                // 同一区域内重叠或相邻的范围合并
+                match ranges[region_first..].last_mut() {
+                    Some(last) if last.1 >= range_start => last.1 = last.1.max(range_end),
+                    _ => ranges.push((range_start, range_end)),
+                }
+            }
+        }
+
+        ranges
+    }
+}
+
+/// 计算 `[start, end)` 所覆盖每一页的哈希，首尾不完整的页按页内实际读取的部分计算
+fn hash_region_pages<R>(start: u64, end: u64, chunk_size: usize, read: &mut R) -> Vec<u64>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    let page_size = *PAGE_SIZE;
+    let chunk_size = page_aligned_chunk_size(chunk_size);
+    let mut hashes = Vec::new();
+    let mut chunk_buffer = vec![0u8; chunk_size];
+    let mut current = start & !(page_size as u64 - 1);
+
+    while current < end {
+        let chunk_end = current.saturating_add(chunk_size as u64).min(end);
+        let chunk_len = (chunk_end - current) as usize;
+        let page_count = chunk_len.div_ceil(page_size);
+
+        let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+        if read(current, &mut chunk_buffer[..chunk_len], &mut page_status).is_ok() {
+            for page_idx in 0..page_count {
+                if !page_status.is_page_success(page_idx) {
+                    hashes.push(UNREADABLE);
+                    continue;
+                }
+                let page_end = ((page_idx + 1) * page_size).min(chunk_len);
+                let mut hasher = DefaultHasher::new();
+                hasher.write(&chunk_buffer[page_idx * page_size..page_end]);
+                hashes.push(hasher.finish() | 1);
+            }
+        } else {
+            hashes.extend(std::iter::repeat_n(UNREADABLE, page_count));
+        }
+
+        current = chunk_end;
+    }
+
+    hashes
+}
@@ -0,0 +1,132 @@
+//! 结果导出：把结果集写成 CSV 或 JSON，供外部工具或下次启动时使用
+//!
+//! 每条结果除绝对地址外还带一列 `module+offset`，模块基址随启动变化时仍能定位；
+//! 地址不属于任何已知模块时该列退回到十六进制绝对地址。两种写法都能被
+//! [`parse_address`](crate::search::parser::parse_address) 解析回来。
+
+use crate::pointer_scan::VmStaticData;
+use crate::search::result_manager::SearchResultItem;
+use crate::search::ValueType;
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ExportFormat::Csv),
+            1 => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// 把地址格式化为 `libil2cpp.so+0x1A2B3C`，不属于 `modules` 中任何模块时为 `0x7F001000`
+pub fn format_address(address: u64, modules: &[VmStaticData]) -> String {
+    ModuleIndex::new(modules).format(address)
+}
+
+/// 按基址排序的模块表，二分查找地址所属模块
+struct ModuleIndex<'a> {
+    modules: Vec<&'a VmStaticData>,
+}
+
+impl<'a> ModuleIndex<'a> {
+    fn new(modules: &'a [VmStaticData]) -> Self {
+        let mut modules: Vec<&VmStaticData> = modules.iter().collect();
+        modules.sort_by_key(|module| module.base_address);
+        Self { modules }
+    }
+
+    fn format(&self, address: u64) -> String {
+        let idx = self.modules.partition_point(|module| module.base_address <= address);
+        match self.modules[..idx].last().filter(|module| module.contains(address)) {
+            Some(module) => format!("{}+0x{:X}", module.name, module.offset_from_base(address)),
+            None => format!("0x{:X}", address),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ExportRow {
+    pub address: String,
+    pub module_offset: String,
+    pub value_type: String,
+    /// 模糊搜索记录的值，精确结果没有
+    pub value: Option<String>,
+}
+
+/// 把结果转换为导出行，`modules` 为当前进程的模块表
+pub(crate) fn build_rows(items: &[SearchResultItem], modules: &[VmStaticData]) -> Vec<ExportRow> {
+    let index = ModuleIndex::new(modules);
+    items
+        .iter()
+        .map(|item| {
+            let (address, value_type, value) = match item {
+                SearchResultItem::Exact(exact) => (exact.address, exact.typ, None),
+                SearchResultItem::Fuzzy(fuzzy) => {
+                    let (address, value, value_type) = (fuzzy.address, fuzzy.value, fuzzy.value_type);
+                    (address, value_type, Some(format_recorded_value(&value, value_type)))
+                },
+            };
+            ExportRow {
+                address: format!("0x{:X}", address),
+                module_offset: index.format(address),
+                value_type: value_type.to_string(),
+                value,
+            }
+        })
+        .collect()
+}
+
+/// 模糊结果只保存了前 8 字节，Oword、特征码和字符串以十六进制输出这 8 字节
+fn format_recorded_value(value: &[u8; 8], value_type: ValueType) -> String {
+    match value_type {
+        ValueType::Byte => value[0].to_string(),
+        ValueType::Word => u16::from_le_bytes([value[0], value[1]]).to_string(),
+        ValueType::Dword | ValueType::Auto | ValueType::Xor => u32::from_le_bytes([value[0], value[1], value[2], value[3]]).to_string(),
+        ValueType::Qword => u64::from_le_bytes(*value).to_string(),
+        ValueType::Float => f32::from_le_bytes([value[0], value[1], value[2], value[3]]).to_string(),
+        ValueType::Double => f64::from_le_bytes(*value).to_string(),
+        ValueType::Oword | ValueType::Bytes(_) | ValueType::Text(..) => value.iter().map(|b| format!("{:02X}", b)).collect(),
+    }
+}
+
+pub(crate) fn write_rows<W: Write>(mut writer: W, rows: &[ExportRow], format: ExportFormat) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(writer, "address,module_offset,type,value")?;
+            for row in rows {
+                writeln!(
+                    writer,
+                    "{},{},{},{}",
+                    row.address,
+                    csv_field(&row.module_offset),
+                    row.value_type,
+                    row.value.as_deref().unwrap_or_default()
+                )?;
+            }
+        },
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, rows)?;
+            writeln!(writer)?;
+        },
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 模块名可能含逗号或引号，按 RFC 4180 加引号
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -28,6 +28,18 @@ impl SearchFilter {
         self.enable_address_filter || self.enable_type_filter || !self.type_ids.is_empty()
     }
 
+    /// 结果是否通过当前过滤条件
+    #[inline]
+    pub fn matches(&self, address: u64, typ: ValueType) -> bool {
+        if self.enable_address_filter && (address < self.address_start || address > self.address_end) {
+            return false;
+        }
+        if self.enable_type_filter && !self.type_ids.is_empty() && !self.type_ids.contains(&typ) {
+            return false;
+        }
+        true
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         *self = Self::default();
@@ -1,9 +1,9 @@
 use super::super::result_manager::FuzzySearchResultItem;
 use super::super::types::{FuzzyCondition, ValueType};
-use super::manager::{BPLUS_TREE_ORDER};
-use crate::core::DRIVER_MANAGER;
+use super::manager::{page_aligned_chunk_size, BPLUS_TREE_ORDER};
+use crate::core::globals::driver_manager_read;
 use crate::wuwa::PageStatusBitmap;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use bplustree::BPlusTreeSet;
 use log::{debug, log_enabled, warn, Level};
 use rayon::prelude::*;
@@ -39,10 +39,16 @@ pub(crate) fn fuzzy_initial_scan<F>(
 where
     F: Fn() -> bool,
 {
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    // 零长度或反向区域直接跳过
+    if start >= end {
+        return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
+    }
+
+    let driver_manager = driver_manager_read();
 
     let element_size = value_type.size();
     let page_size = *PAGE_SIZE;
+    let chunk_size = page_aligned_chunk_size(chunk_size);
 
     let mut results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
 
@@ -63,7 +69,7 @@ where
             }
         }
 
-        let chunk_end = (current + chunk_size as u64).min(end);
+        let chunk_end = current.saturating_add(chunk_size as u64).min(end);
         let chunk_len = (chunk_end - current) as usize;
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
@@ -181,7 +187,7 @@ fn scan_single_page(
     page_idx: usize,
 ) -> Vec<FuzzySearchResultItem> {
     let page_start_addr = buffer_addr + (page_idx * page_size) as u64;
-    let page_end_addr = page_start_addr + page_size as u64;
+    let page_end_addr = page_start_addr.saturating_add(page_size as u64);
 
     // 与搜索范围取交集
     let effective_start = page_start_addr.max(search_start);
@@ -192,11 +198,12 @@ fn scan_single_page(
     }
 
     // 对齐到元素边界
-    let rem = effective_start % element_size as u64;
+    let alignment = value_type.alignment();
+    let rem = effective_start % alignment as u64;
     let first_addr = if rem == 0 {
         effective_start
     } else {
-        effective_start + element_size as u64 - rem
+        effective_start.saturating_add(alignment as u64 - rem)
     };
 
     if first_addr >= effective_end {
@@ -204,7 +211,7 @@ fn scan_single_page(
     }
 
     // 预计算元素数量，一次性分配
-    let elements_count = ((effective_end - first_addr) as usize) / element_size;
+    let elements_count = ((effective_end - first_addr) as usize) / alignment;
     let mut results = Vec::with_capacity(elements_count);
 
     // 批量处理：直接遍历字节切片，无需逐元素检查页状态
@@ -222,8 +229,8 @@ fn scan_single_page(
         let item = FuzzySearchResultItem::from_bytes(addr, &buffer[offset..offset + element_size], value_type);
         results.push(item);
 
-        offset += element_size;
-        addr += element_size as u64;
+        offset += alignment;
+        addr = addr.saturating_add(alignment as u64);
     }
 
     results
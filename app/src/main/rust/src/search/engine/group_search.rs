@@ -1,27 +1,136 @@
-use super::super::types::{SearchMode, SearchQuery, SearchValue, ValueType};
-use super::manager::{ValuePair, BPLUS_TREE_ORDER};
-use crate::core::DRIVER_MANAGER;
+use super::super::types::{SearchMode, SearchQuery, SearchValue, ValueType, MAX_BYTE_PATTERN_LEN};
+use super::manager::{page_aligned_chunk_size, SearchEngineManager, ValuePair, BPLUS_TREE_ORDER};
+use crate::core::globals::driver_manager_read;
 use crate::search::{PAGE_MASK, PAGE_SIZE};
 use crate::wuwa::PageStatusBitmap;
-use anyhow::anyhow;
 use anyhow::Result;
 use bplustree::BPlusTreeSet;
+use lazy_static::lazy_static;
 use log::{debug, log_enabled, warn, Level};
 use memchr::memmem;
 use std::collections::HashSet;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// 组匹配检查取消的频率：窗口内每扫描这么多字节调用一次 `check_cancelled`
+///
+/// `query.range` 最大 64KB，单个窗口的匹配本身就可能很慢；4KB 在响应速度和调用开销之间折中
+pub(crate) const CANCEL_CHECK_BYTES: usize = 4 * 1024;
+
+/// 按字节数节流的取消检查，每累计 [`CANCEL_CHECK_BYTES`] 字节才真正调用一次 `check_cancelled`
+struct CancelThrottle<'a, F: Fn() -> bool> {
+    check_cancelled: &'a F,
+    remaining: usize,
+}
+
+impl<'a, F: Fn() -> bool> CancelThrottle<'a, F> {
+    fn new(check_cancelled: &'a F) -> Self {
+        Self {
+            check_cancelled,
+            remaining: CANCEL_CHECK_BYTES,
+        }
+    }
+
+    /// 记录又扫描了 `bytes` 字节，返回是否已取消
+    #[inline]
+    fn advance(&mut self, bytes: usize) -> bool {
+        if bytes < self.remaining {
+            self.remaining -= bytes;
+            return false;
+        }
+        self.remaining = CANCEL_CHECK_BYTES;
+        (self.check_cancelled)()
+    }
+}
+
+/// 组搜索的读取块大小：页对齐，且不小于 `search_range`
+///
+/// 滑动窗口只保留上一个块，跨块的组需要上一块尾部的 `search_range` 字节作为上下文；
+/// 范围超过块大小时放大块（缓冲区随之变大），而不是丢失上下文
+pub(crate) fn group_chunk_size(per_chunk_size: usize, search_range: usize) -> usize {
+    page_aligned_chunk_size(per_chunk_size.max(search_range.next_multiple_of(*PAGE_SIZE)))
+}
 
 pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per_chunk_size: usize) -> Result<Vec<ValuePair>> {
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    search_region_group_with_cancel(query, start, end, per_chunk_size, &|| false)
+}
+
+/// 支持取消的普通组搜索，取消后返回已找到的部分结果
+pub(crate) fn search_region_group_with_cancel<F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    check_cancelled: &F,
+) -> Result<Vec<ValuePair>>
+where
+    F: Fn() -> bool,
+{
+    // 零长度或反向区域直接跳过
+    if start >= end {
+        return Ok(Vec::new());
+    }
+
+    let driver_manager = driver_manager_read();
+
+    search_region_group_with(
+        query,
+        start,
+        end,
+        per_chunk_size,
+        usize::MAX,
+        |addr, buf, page_status| driver_manager.read_memory_unified(addr, buf, Some(page_status)),
+        check_cancelled,
+    )
+}
+
+/// 按区域、按区域顺序做组搜索，累计 `n` 个值地址后立即返回，剩余区域不再读取
+///
+/// 每个区域内的结果按地址排序，保证在相同内存内容下结果确定。`read` 负责读取页对齐地址处的数据。
+pub(crate) fn find_first_n_group<R>(query: &SearchQuery, regions: &[(u64, u64)], chunk_size: usize, n: usize, mut read: R) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    let mut pairs = Vec::with_capacity(n.min(1024));
+    for &(start, end) in regions {
+        if pairs.len() >= n {
+            break;
+        }
+        let mut region_results = search_region_group_with(query, start, end, chunk_size, n - pairs.len(), &mut read, &|| false)?;
+        region_results.sort();
+        pairs.extend(region_results);
+    }
+    pairs.truncate(n);
+    Ok(pairs)
+}
+
+/// 组搜索的区域扫描实现：`read` 负责读取数据，结果数达到 `limit` 后在当前块结束时停止
+pub(crate) fn search_region_group_with<R, F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    limit: usize,
+    mut read: R,
+    check_cancelled: &F,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool,
+{
+    if start >= end {
+        return Ok(Vec::new());
+    }
 
     let mut results = Vec::new();
     let mut read_success = 0usize;
     let mut read_failed = 0usize;
     let mut matches_checked = 0usize;
 
-    let min_element_size = query.values.iter().map(|v| v.value_type().size()).min().unwrap_or(1);
+    let min_element_size = query.values.iter().map(|v| v.value_type().alignment()).min().unwrap_or(1);
     let search_range = query.range as usize;
+    let per_chunk_size = group_chunk_size(per_chunk_size, search_range);
 
     let mut current = start & *PAGE_MASK as u64;
     let mut sliding_buffer = vec![0u8; per_chunk_size * 2]; // 双倍大小的滑动窗口缓冲区
@@ -29,13 +138,17 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
     let mut prev_chunk_valid = false; // 前半部分是否有效（读取成功）
 
     while current < end {
-        let chunk_end = (current + per_chunk_size as u64).min(end);
+        if check_cancelled() {
+            break;
+        }
+
+        let chunk_end = current.saturating_add(per_chunk_size as u64).min(end);
         let chunk_len = (chunk_end - current) as usize;
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
         // 读取数据到滑动窗口的后半部分
-        let read_result = driver_manager.read_memory_unified(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], Some(&mut page_status));
+        let read_result = read(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], &mut page_status);
 
         match read_result {
             Ok(_) => {
@@ -45,7 +158,7 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
 
                     if is_first_chunk {
                         // 第一个chunk：只搜索前半部分（刚读取的数据）
-                        search_in_buffer_group(
+                        search_in_buffer_group_with_cancel(
                             &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
                             current,
                             start,
@@ -55,6 +168,7 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
                             &page_status,
                             &mut results,
                             &mut matches_checked,
+                            check_cancelled,
                         );
                         is_first_chunk = false;
                     } else if prev_chunk_valid {
@@ -91,7 +205,7 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
                             }
                         }
 
-                        search_in_buffer_group(
+                        search_in_buffer_group_with_cancel(
                             &sliding_buffer[overlap_start_offset..per_chunk_size + chunk_len],
                             overlap_start_addr,
                             start,
@@ -101,10 +215,11 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
                             &combined_status,
                             &mut results,
                             &mut matches_checked,
+                            check_cancelled,
                         );
                     } else {
                         // 前一个chunk无效：只搜索当前chunk（后半部分）
-                        search_in_buffer_group(
+                        search_in_buffer_group_with_cancel(
                             &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
                             current,
                             start,
@@ -114,6 +229,7 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
                             &page_status,
                             &mut results,
                             &mut matches_checked,
+                            check_cancelled,
                         );
                     }
 
@@ -138,6 +254,10 @@ pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per
         }
 
         current = chunk_end;
+
+        if results.len() >= limit {
+            break;
+        }
     }
 
     if log_enabled!(Level::Debug) {
@@ -174,60 +294,216 @@ pub(crate) fn search_region_group_deep_with_cancel<F>(
 where
     F: Fn() -> bool,
 {
+    // 零长度或反向区域直接跳过
+    if start >= end {
+        return Ok(Vec::new());
+    }
+
     // Check cancellation before starting.
     if check_cancelled() {
         return Ok(Vec::new());
     }
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let driver_manager = driver_manager_read();
+
+    search_region_group_deep_with(
+        query,
+        start,
+        end,
+        per_chunk_size,
+        |addr, buf, page_status| driver_manager.read_memory_unified(addr, buf, Some(page_status)),
+        check_cancelled,
+    )
+}
+
+/// 深度组搜索的区域扫描实现，`read` 负责读取页对齐地址处的数据
+pub(crate) fn search_region_group_deep_with<R, F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    read: R,
+    check_cancelled: &F,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool,
+{
+    search_region_group_deep_queued(query, start, end, per_chunk_size, read, check_cancelled, &DEEP_ITEM_BUDGET).map(|(results, _)| results)
+}
+
+/// 深度搜索中每个线程最多排队的工作项数，限制同时占用的 chunk 缓冲区
+const DEEP_QUEUE_ITEMS_PER_THREAD: usize = 4;
+
+/// 深度搜索排队工作项的额度，每个工作项持有一个 chunk 缓冲区
+///
+/// 多个区域并行做深度搜索时共用同一份额度，缓冲区总数不随并行区域数增长
+pub(crate) struct DeepItemBudget {
+    capacity: usize,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl DeepItemBudget {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            in_flight: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// 额度未用完时占用一项，返回是否成功
+    fn try_acquire(&self) -> bool {
+        let acquired = self
+            .in_flight
+            .fetch_update(AtomicOrdering::AcqRel, AtomicOrdering::Acquire, |n| (n < self.capacity).then_some(n + 1));
+        if let Ok(previous) = acquired {
+            self.peak.fetch_max(previous + 1, AtomicOrdering::Relaxed);
+        }
+        acquired.is_ok()
+    }
+
+    fn release(&self) {
+        self.in_flight.fetch_sub(1, AtomicOrdering::Release);
+    }
+
+    /// 当前占用的项数
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(AtomicOrdering::Acquire)
+    }
+
+    /// 同时占用过的最大项数
+    pub(crate) fn peak(&self) -> usize {
+        self.peak.load(AtomicOrdering::Relaxed)
+    }
+}
+
+lazy_static! {
+    /// 所有深度搜索共享的额度：全局线程池线程数的 [`DEEP_QUEUE_ITEMS_PER_THREAD`] 倍
+    pub(crate) static ref DEEP_ITEM_BUDGET: DeepItemBudget = DeepItemBudget::new(rayon::current_num_threads() * DEEP_QUEUE_ITEMS_PER_THREAD);
+}
+
+/// 深度搜索的一个工作项：一个 chunk，前一个 chunk 可读时带上与它重叠的 `range` 字节
+struct DeepWorkItem {
+    buffer: Vec<u8>,
+    buffer_addr: u64,
+    chunk_end: u64,
+    page_status: PageStatusBitmap,
+}
+
+/// [`search_region_group_deep_queued`] 的运行统计
+#[derive(Debug, Default)]
+pub(crate) struct DeepQueueStats {
+    /// 各线程执行的工作项数，下标为 rayon 线程序号，最后一项是线程池之外的线程
+    pub items_per_thread: Vec<usize>,
+    pub matches_checked: usize,
+}
+
+/// 工作项结束（包括 panic）时减少排队计数并归还额度，避免分发线程一直等待
+struct InFlightGuard<'a> {
+    in_flight: &'a AtomicUsize,
+    budget: &'a DeepItemBudget,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, AtomicOrdering::Release);
+        self.budget.release();
+    }
+}
+
+/// 深度组搜索的区域扫描：当前线程按顺序读取 chunk，每个 chunk 作为工作项交给 rayon 的工作窃取队列
+///
+/// 深度搜索各窗口的代价相差悬殊，少数密集窗口的组合数会占据大部分耗时；按工作项分发后，
+/// 空闲线程会接手其余 chunk，而不是由一个线程扫完整个区域。每个工作项先从 `budget` 占用一项额度，
+/// 所有并行区域排队的工作项总数不超过额度，等待期间当前线程也会执行队列中的工作。
+///
+/// `check_cancelled` 只在当前线程调用，取消后通过原子标志让正在执行的工作项尽快返回。结果按地址排序并去重
+pub(crate) fn search_region_group_deep_queued<R, F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    mut read: R,
+    check_cancelled: &F,
+    budget: &DeepItemBudget,
+) -> Result<(Vec<ValuePair>, DeepQueueStats)>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool,
+{
+    if start >= end || check_cancelled() {
+        return Ok((Vec::new(), DeepQueueStats::default()));
+    }
 
-    let mut results = Vec::new();
     let mut read_success = 0usize;
     let mut read_failed = 0usize;
-    let mut matches_checked = 0usize;
 
-    let min_element_size = query.values.iter().map(|v| v.value_type().size()).min().unwrap_or(1);
+    let min_element_size = query.values.iter().map(|v| v.value_type().alignment()).min().unwrap_or(1);
     let search_range = query.range as usize;
+    let per_chunk_size = group_chunk_size(per_chunk_size, search_range);
 
-    let mut current = start & *PAGE_MASK as u64;
-    let mut sliding_buffer = vec![0u8; per_chunk_size * 2];
-    let mut is_first_chunk = true;
-    let mut prev_chunk_valid = false;
+    let threads = rayon::current_num_threads();
+    let items_per_thread: Vec<AtomicUsize> = (0..=threads).map(|_| AtomicUsize::new(0)).collect();
+    let results = Mutex::new(Vec::new());
+    let matches_checked = AtomicUsize::new(0);
+    let in_flight = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
 
-    while current < end {
-        // Check cancellation at each chunk.
-        if check_cancelled() {
-            return Ok(results);
+    // 没有可执行的工作时短暂休眠，避免空转
+    let help_or_sleep = || {
+        if !matches!(rayon::yield_now(), Some(rayon::Yield::Executed)) {
+            std::thread::sleep(Duration::from_micros(50));
         }
+    };
+    // 等待本区域排队的工作项降到 `limit` 及以下，期间轮询取消并帮忙执行队列中的工作
+    let wait_until_at_most = |limit: usize| {
+        while in_flight.load(AtomicOrdering::Acquire) > limit {
+            if check_cancelled() {
+                cancelled.store(true, AtomicOrdering::Relaxed);
+            }
+            help_or_sleep();
+        }
+    };
+    // 等待共享额度空出一项，取消时返回 false
+    let acquire_budget = || {
+        while !budget.try_acquire() {
+            if check_cancelled() {
+                cancelled.store(true, AtomicOrdering::Relaxed);
+                return false;
+            }
+            help_or_sleep();
+        }
+        true
+    };
 
-        let chunk_end = (current + per_chunk_size as u64).min(end);
-        let chunk_len = (chunk_end - current) as usize;
+    rayon::in_place_scope(|scope| {
+        let mut current = start & *PAGE_MASK as u64;
+        let mut sliding_buffer = vec![0u8; per_chunk_size * 2];
+        let mut prev_chunk_valid = false;
 
-        let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+        while current < end {
+            if cancelled.load(AtomicOrdering::Relaxed) || check_cancelled() {
+                cancelled.store(true, AtomicOrdering::Relaxed);
+                break;
+            }
 
-        let read_result = driver_manager.read_memory_unified(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], Some(&mut page_status));
+            let chunk_end = current.saturating_add(per_chunk_size as u64).min(end);
+            let chunk_len = (chunk_end - current) as usize;
 
-        match read_result {
-            Ok(_) => {
-                let success_pages = page_status.success_count();
-                if success_pages > 0 {
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+
+            let read_result = read(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], &mut page_status);
+
+            match read_result {
+                Ok(_) if page_status.success_count() > 0 => {
                     read_success += 1;
+                    if !acquire_budget() {
+                        break;
+                    }
 
-                    if is_first_chunk {
-                        search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
-                        );
-                        is_first_chunk = false;
-                    } else if prev_chunk_valid {
+                    let item = if prev_chunk_valid {
                         let overlap_start_offset = per_chunk_size.saturating_sub(search_range);
                         let overlap_start_addr = current - search_range as u64;
                         let overlap_len = search_range + chunk_len;
@@ -256,68 +532,235 @@ where
                             }
                         }
 
-                        search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[overlap_start_offset..per_chunk_size + chunk_len],
-                            overlap_start_addr,
-                            start,
+                        DeepWorkItem {
+                            buffer: sliding_buffer[overlap_start_offset..per_chunk_size + chunk_len].to_vec(),
+                            buffer_addr: overlap_start_addr,
                             chunk_end,
-                            min_element_size,
-                            query,
-                            &combined_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
-                        );
+                            page_status: combined_status,
+                        }
                     } else {
+                        DeepWorkItem {
+                            buffer: sliding_buffer[per_chunk_size..per_chunk_size + chunk_len].to_vec(),
+                            buffer_addr: current,
+                            chunk_end,
+                            page_status,
+                        }
+                    };
+                    prev_chunk_valid = true;
+
+                    in_flight.fetch_add(1, AtomicOrdering::AcqRel);
+                    let (results, matches_checked, in_flight, cancelled, items_per_thread) =
+                        (&results, &matches_checked, &in_flight, &cancelled, &items_per_thread);
+                    scope.spawn(move |_| {
+                        let _guard = InFlightGuard { in_flight, budget };
+                        let slot = rayon::current_thread_index().unwrap_or(threads).min(threads);
+                        items_per_thread[slot].fetch_add(1, AtomicOrdering::Relaxed);
+
+                        let mut found = Vec::new();
+                        let mut checked = 0usize;
+                        let is_cancelled = || cancelled.load(AtomicOrdering::Relaxed);
                         search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
+                            &item.buffer,
+                            item.buffer_addr,
                             start,
-                            chunk_end,
+                            item.chunk_end,
                             min_element_size,
                             query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
+                            &item.page_status,
+                            &mut found,
+                            &mut checked,
+                            &is_cancelled,
                         );
-                    }
 
-                    prev_chunk_valid = true;
-                } else {
+                        matches_checked.fetch_add(checked, AtomicOrdering::Relaxed);
+                        if !found.is_empty()
+                            && let Ok(mut results) = results.lock()
+                        {
+                            results.append(&mut found);
+                        }
+                    });
+                },
+                Ok(_) => {
                     read_failed += 1;
                     prev_chunk_valid = false;
-                }
-            },
-            Err(error) => {
-                if log_enabled!(Level::Debug) {
-                    warn!("Failed to read memory at 0x{:X} - 0x{:X}, err: {:?}", current, chunk_end, error);
-                }
-                read_failed += 1;
-                prev_chunk_valid = false;
-            },
-        }
+                },
+                Err(error) => {
+                    if log_enabled!(Level::Debug) {
+                        warn!("Failed to read memory at 0x{:X} - 0x{:X}, err: {:?}", current, chunk_end, error);
+                    }
+                    read_failed += 1;
+                    prev_chunk_valid = false;
+                },
+            }
 
-        if chunk_end < end {
-            sliding_buffer.copy_within(per_chunk_size..per_chunk_size + chunk_len, 0);
+            if chunk_end < end {
+                sliding_buffer.copy_within(per_chunk_size..per_chunk_size + chunk_len, 0);
+            }
+
+            current = chunk_end;
         }
 
-        current = chunk_end;
-    }
+        wait_until_at_most(0);
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    results.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
+    results.dedup();
+
+    let stats = DeepQueueStats {
+        items_per_thread: items_per_thread.into_iter().map(AtomicUsize::into_inner).collect(),
+        matches_checked: matches_checked.into_inner(),
+    };
 
     if log_enabled!(Level::Debug) {
         let region_size = end - start;
         debug!(
-            "Deep group search stats: size={}MB, reads={} success + {} failed, matches_checked={}, found={}",
+            "Deep group search stats: size={}MB, reads={} success + {} failed, matches_checked={}, found={}, threads={}",
             region_size / 1024 / 1024,
             read_success,
             read_failed,
-            matches_checked,
-            results.len()
+            stats.matches_checked,
+            results.len(),
+            stats.items_per_thread.iter().filter(|&&items| items > 0).count()
         );
     }
 
-    Ok(results)
+    Ok((results, stats))
+}
+
+/// 熵低于该值（比特）的 anchor 视为弱 anchor，例如 `100` 或 `1.0f` 这类大部分字节为 0 的编码
+pub(crate) const WEAK_ANCHOR_ENTROPY_BITS: f64 = 8.0;
+
+/// 组搜索中用于 memmem 扫描的 Fixed 值
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Anchor {
+    /// 在 `query.values` 中的下标
+    pub(crate) index: usize,
+    storage: [u8; MAX_BYTE_PATTERN_LEN], // 最长为整个特征码或字符串
+    len: usize,
+    pub(crate) alignment: usize,
+    /// anchor 字节在值内的偏移，只有特征码不为 0
+    pub(crate) offset: usize,
+}
+
+impl Anchor {
+    fn from_value(index: usize, value: &SearchValue) -> Option<Self> {
+        let mut storage = [0u8; MAX_BYTE_PATTERN_LEN];
+        let mut offset = 0;
+        let (len, value_type) = match value {
+            // 整数值已按自身字节序编码
+            SearchValue::FixedInt { value, value_type, .. } => {
+                let size = value_type.size();
+                storage[..size].copy_from_slice(&value[..size]);
+                (size, *value_type)
+            },
+            // 只有精确比较的浮点值才能按字节定位
+            SearchValue::FixedFloat {
+                value,
+                value_type,
+                tolerance,
+                byte_order,
+            } if tolerance.is_exact() => {
+                let size = match value_type {
+                    ValueType::Float => {
+                        storage[..4].copy_from_slice(&(*value as f32).to_le_bytes());
+                        4
+                    },
+                    ValueType::Double => {
+                        storage[..8].copy_from_slice(&value.to_le_bytes());
+                        8
+                    },
+                    _ => return None,
+                };
+                byte_order.apply(&mut storage[..size]);
+                (size, *value_type)
+            },
+            // 特征码取最长的一段非通配字节
+            SearchValue::Bytes { pattern, mask } => {
+                let (run_start, run_len) = longest_fixed_run(mask)?;
+                storage[..run_len].copy_from_slice(&pattern[run_start..run_start + run_len]);
+                offset = run_start;
+                (run_len, value.value_type())
+            },
+            SearchValue::Text { bytes, .. } => {
+                storage[..bytes.len()].copy_from_slice(bytes);
+                (bytes.len(), value.value_type())
+            },
+            _ => return None,
+        };
+
+        Some(Self {
+            index,
+            storage,
+            len,
+            alignment: value_type.alignment(),
+            offset,
+        })
+    }
+
+    #[inline]
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.storage[..self.len]
+    }
+}
+
+/// 最长一段连续非通配字节的 `(起点, 长度)`，全是通配符时为 None
+fn longest_fixed_run(mask: &[bool]) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut run_start = 0;
+    for (i, &fixed) in mask.iter().chain(std::iter::once(&false)).enumerate() {
+        if fixed {
+            continue;
+        }
+        let run_len = i - run_start;
+        if run_len > best.map_or(0, |(_, len)| len) {
+            best = Some((run_start, run_len));
+        }
+        run_start = i + 1;
+    }
+    best
+}
+
+/// 估计 anchor 编码的信息量（比特）：按字节分布计算香农熵，0x00/0xFF 在内存中随处可见，不计入
+pub(crate) fn anchor_entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+
+    let total = bytes.len() as f64;
+    counts
+        .iter()
+        .enumerate()
+        .filter(|&(b, &count)| count > 0 && b != 0x00 && b != 0xFF)
+        .map(|(_, &count)| {
+            let count = count as f64;
+            -count * (count / total).log2()
+        })
+        .sum()
+}
+
+/// 选择 anchor：默认取第一个 Fixed 值；若它的编码熵过低，改用熵最高的 Fixed 值以减少候选数
+///
+/// Ordered 模式的值之间可以有间隔，只有第一个值的位置能直接推出序列起点，因此不换 anchor
+pub(crate) fn select_anchor(query: &SearchQuery) -> Option<Anchor> {
+    let mut anchors = query.values.iter().enumerate().filter_map(|(idx, value)| Anchor::from_value(idx, value));
+    let first = anchors.next()?;
+
+    if query.mode == SearchMode::Ordered || anchor_entropy(first.bytes()) >= WEAK_ANCHOR_ENTROPY_BITS {
+        return Some(first);
+    }
+
+    let mut best = first;
+    let mut best_entropy = anchor_entropy(first.bytes());
+    for anchor in anchors {
+        let entropy = anchor_entropy(anchor.bytes());
+        if entropy > best_entropy {
+            best = anchor;
+            best_entropy = entropy;
+        }
+    }
+    Some(best)
 }
 
 #[inline]
@@ -332,45 +775,42 @@ pub(crate) fn search_in_buffer_group(
     results: &mut Vec<ValuePair>,
     matches_checked: &mut usize,
 ) {
-    // anchor-first 优化：尝试使用第一个 Fixed 值作为 anchor 进行 SIMD 扫描
-    let mut anchor_index = None;
-    let mut anchor_bytes_storage = [0u8; 8]; // 最大 8 字节（Qword/Double）
-    let mut anchor_bytes_len = 0;
-
-    for (idx, value) in query.values.iter().enumerate() {
-        match value {
-            SearchValue::FixedInt { value, value_type } => {
-                let size = value_type.size();
-                anchor_bytes_storage[..size].copy_from_slice(&value[..size]);
-                anchor_bytes_len = size;
-                anchor_index = Some(idx);
-                break;
-            },
-            SearchValue::FixedFloat { value, value_type } => {
-                match value_type {
-                    ValueType::Float => {
-                        let f32_val = *value as f32;
-                        let bytes = f32_val.to_le_bytes();
-                        anchor_bytes_storage[..4].copy_from_slice(&bytes);
-                        anchor_bytes_len = 4;
-                    },
-                    ValueType::Double => {
-                        let bytes = value.to_le_bytes();
-                        anchor_bytes_storage[..8].copy_from_slice(&bytes);
-                        anchor_bytes_len = 8;
-                    },
-                    _ => continue,
-                }
-                anchor_index = Some(idx);
-                break;
-            },
-            _ => continue,
-        }
-    }
+    search_in_buffer_group_with_cancel(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        min_element_size,
+        query,
+        page_status,
+        results,
+        matches_checked,
+        &|| false,
+    );
+}
+
+/// [`search_in_buffer_group`] 的可取消版本，候选校验与窗口匹配中按 [`CANCEL_CHECK_BYTES`] 检查取消
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_in_buffer_group_with_cancel<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    min_element_size: usize,
+    query: &SearchQuery,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    matches_checked: &mut usize,
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool,
+{
+    // anchor-first 优化：选一个 Fixed 值作为 anchor 进行 SIMD 扫描
+    let anchor = select_anchor(query);
 
     // 如果没有找到 Fixed 值作为 anchor，回退到传统逐地址扫描
-    if anchor_index.is_none() {
-        search_in_buffer_group_fallback(
+    let Some(anchor) = anchor else {
+        search_in_buffer_group_fallback_with_cancel(
             buffer,
             buffer_addr,
             region_start,
@@ -380,13 +820,13 @@ pub(crate) fn search_in_buffer_group(
             page_status,
             results,
             matches_checked,
+            check_cancelled,
         );
         return;
-    }
+    };
 
     // 使用 anchor-first SIMD 优化
-    let anchor_bytes = &anchor_bytes_storage[..anchor_bytes_len];
-    let finder = memmem::Finder::new(anchor_bytes);
+    let finder = memmem::Finder::new(anchor.bytes());
     let mut candidates = Vec::new();
     let mut pos = 0;
 
@@ -395,12 +835,7 @@ pub(crate) fn search_in_buffer_group(
     let search_end = buffer_end.min(region_end);
 
     // 计算第一个对齐地址
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    let first_addr = align_up_addr(search_start, min_element_size);
 
     // 预先构建成功页的地址范围
     let page_ranges = page_status.get_success_page_ranges();
@@ -409,33 +844,71 @@ pub(crate) fn search_in_buffer_group(
     }
 
     let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
-    let anchor_alignment = anchor_bytes_len;
+
+    // anchor 过于常见（如 0 或 1~2 字节值）时候选会爆炸，此时逐地址扫描反而更快
+    let candidate_limit = query.anchor_candidate_limit(buffer.len());
 
     // SIMD 快速扫描找到所有 anchor 候选位置
     while pos < buffer.len() {
         if let Some(offset) = finder.find(&buffer[pos..]) {
             let absolute_offset = pos + offset;
-            let addr = buffer_addr + absolute_offset as u64;
+            pos = absolute_offset + 1;
 
-            // 过滤1: 检查对齐（使用 anchor 的大小）
-            if addr % anchor_alignment as u64 == 0 && addr >= first_addr && addr < search_end {
-                candidates.push(absolute_offset);
-            }
+            // anchor 不在值开头时（特征码），换算回值的起点
+            let Some(value_offset) = absolute_offset.checked_sub(anchor.offset) else {
+                continue;
+            };
+            let addr = buffer_addr + value_offset as u64;
 
-            pos = absolute_offset + 1;
+            // 过滤1: 检查对齐（使用 anchor 类型的对齐）
+            if addr % anchor.alignment as u64 == 0 && addr >= first_addr && addr < search_end {
+                candidates.push(value_offset);
+            }
         } else {
             break;
         }
+
+        if candidates.len() > candidate_limit {
+            debug!(
+                "Anchor too common in window 0x{:X} (>{} candidates in {} bytes), falling back to linear scan",
+                buffer_addr,
+                candidate_limit,
+                buffer.len()
+            );
+            search_in_buffer_group_fallback_with_cancel(
+                buffer,
+                buffer_addr,
+                region_start,
+                region_end,
+                min_element_size,
+                query,
+                page_status,
+                results,
+                matches_checked,
+                check_cancelled,
+            );
+            return;
+        }
     }
 
     // 对候选位置做页面过滤和完整校验
-    let anchor_idx = anchor_index.unwrap();
+    let anchor_idx = anchor.index;
+    let mut throttle = CancelThrottle::new(check_cancelled);
+    let mut last_offset = 0usize;
+    // 有序模式下已被校验覆盖的最远起点：候选按地址递增，起点相同的候选结果必然相同；
+    // Ordered 为贪心匹配，起点不超过上一次匹配首值地址的候选也会找到同一组值
+    let mut ordered_covered: Option<u64> = None;
 
     for &offset in &candidates {
+        if throttle.advance(offset - last_offset) {
+            return;
+        }
+        last_offset = offset;
+
         let anchor_addr = buffer_addr + offset as u64;
 
         // 根据搜索模式计算需要验证的区域
-        let (start_addr, _start_offset) = if query.mode == SearchMode::Ordered {
+        let (start_addr, _start_offset) = if query.mode.is_ordered() {
             // Ordered 模式：根据 anchor 在 query 中的位置，反推序列起始位置
             let anchor_offset_in_sequence = query.values[..anchor_idx].iter().map(|v| v.value_type().size()).sum::<usize>();
 
@@ -451,17 +924,17 @@ pub(crate) fn search_in_buffer_group(
         };
 
         // 检查地址是否在有效范围内
-        let check_range_addr = if query.mode == SearchMode::Ordered { start_addr } else { anchor_addr };
+        let check_range_addr = if query.mode.is_ordered() { start_addr } else { anchor_addr };
         if check_range_addr < region_start || check_range_addr >= region_end {
             continue;
         }
 
         // 检查地址是否在有效页范围内
-        let check_addr = if query.mode == SearchMode::Ordered { start_addr } else { anchor_addr };
+        let check_addr = if query.mode.is_ordered() { start_addr } else { anchor_addr };
         let mut in_valid_page = false;
         for (start_page, end_page) in &page_ranges {
             let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-            let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+            let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
             if check_addr >= page_range_start && check_addr < page_range_end {
                 in_valid_page = true;
@@ -477,26 +950,47 @@ pub(crate) fn search_in_buffer_group(
         let total_values_size: usize = query.values.iter().map(|v| v.value_type().size()).sum();
         let min_buffer_size = (total_values_size as u64).max(query.range as u64);
 
-        let (check_start, check_end) = if query.mode == SearchMode::Ordered {
+        let (check_start, check_end) = if query.mode.is_ordered() {
             // Ordered 模式：序列必须完整在 buffer 内才能验证
             if start_addr < buffer_addr {
                 continue;
             }
-            (start_addr, (start_addr + min_buffer_size).min(buffer_end).min(region_end))
+            (start_addr, start_addr.saturating_add(min_buffer_size).min(buffer_end).min(region_end))
         } else {
             // Unordered 模式：需要覆盖 anchor 前后的范围
             let unordered_start = anchor_addr.saturating_sub(query.range as u64).max(buffer_addr);
-            let unordered_end = (anchor_addr + query.range as u64).min(buffer_end).min(region_end);
+            let unordered_end = anchor_addr.saturating_add(query.range as u64).min(buffer_end).min(region_end);
             (unordered_start, unordered_end)
         };
 
+        if query.mode.is_ordered() && ordered_covered.is_some_and(|covered| start_addr <= covered) {
+            continue;
+        }
+        // 紧密排列时第一个值就在起点，起点不在对象对齐网格上可直接跳过
+        if query.mode == SearchMode::OrderedContiguous && !query.on_object_grid(start_addr) {
+            continue;
+        }
+
         let check_start_offset = (check_start - buffer_addr) as usize;
         let range_size = (check_end - check_start) as usize;
 
         if check_start_offset + range_size <= buffer.len() {
             *matches_checked += 1;
+            if query.mode.is_ordered() {
+                ordered_covered = Some(start_addr);
+            }
 
-            if let Some(offsets) = try_match_group_at_address(&buffer[check_start_offset..check_start_offset + range_size], check_start, query) {
+            if let Some(offsets) =
+                try_match_group_at_address_with_cancel(&buffer[check_start_offset..check_start_offset + range_size], check_start, query, check_cancelled)
+            {
+                if query.mode.is_ordered()
+                    && let Some(&first) = offsets.first()
+                {
+                    ordered_covered = Some(check_start + first as u64);
+                }
+                if !query.on_object_grid(check_start + offsets[0] as u64) {
+                    continue;
+                }
                 for (idx, value_offset) in offsets.iter().enumerate() {
                     let value_addr = check_start + *value_offset as u64;
                     let value_type = query.values[idx].value_type();
@@ -509,6 +1003,7 @@ pub(crate) fn search_in_buffer_group(
 
 /// 传统逐地址扫描方法（用于没有 Fixed 值作为 anchor 时的降级）
 #[inline]
+#[cfg(test)]
 pub(crate) fn search_in_buffer_group_fallback(
     buffer: &[u8],
     buffer_addr: u64,
@@ -520,31 +1015,115 @@ pub(crate) fn search_in_buffer_group_fallback(
     results: &mut Vec<ValuePair>,
     matches_checked: &mut usize,
 ) {
+    search_in_buffer_group_fallback_with_cancel(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        min_element_size,
+        query,
+        page_status,
+        results,
+        matches_checked,
+        &|| false,
+    );
+}
+
+/// 成功页占比不低于该值时逐地址线性扫描，否则按成功页区间扫描
+pub(crate) const LINEAR_SCAN_MIN_COVERAGE: f64 = 0.9;
+
+/// 逐地址扫描时遍历可读页的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PageScanStrategy {
+    /// 预先求出连续成功页区间，只在区间内扫描；可读内存稀疏时跳过大段失败页
+    SuccessRanges,
+    /// 整个 buffer 线性扫描，逐页检查位图；几乎全部可读时省去构建区间
+    Linear,
+}
+
+impl PageScanStrategy {
+    pub(crate) fn for_status(page_status: &PageStatusBitmap) -> Self {
+        if page_status.coverage_ratio() >= LINEAR_SCAN_MIN_COVERAGE {
+            PageScanStrategy::Linear
+        } else {
+            PageScanStrategy::SuccessRanges
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_in_buffer_group_fallback_with_cancel<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    min_element_size: usize,
+    query: &SearchQuery,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    matches_checked: &mut usize,
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool,
+{
+    search_in_buffer_group_fallback_with_strategy(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        min_element_size,
+        query,
+        page_status,
+        results,
+        matches_checked,
+        check_cancelled,
+        PageScanStrategy::for_status(page_status),
+    );
+}
+
+/// 按指定的 [`PageScanStrategy`] 逐地址扫描，两种策略结果相同
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_in_buffer_group_fallback_with_strategy<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    min_element_size: usize,
+    query: &SearchQuery,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    matches_checked: &mut usize,
+    check_cancelled: &F,
+    strategy: PageScanStrategy,
+) where
+    F: Fn() -> bool,
+{
     let buffer_end = buffer_addr + buffer.len() as u64;
     let search_start = buffer_addr.max(region_start);
     let search_end = buffer_end.min(region_end);
     let search_range = query.range as u64;
 
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
+    let first_addr = align_up_addr(search_start, min_element_size);
+
+    // 优化：可读内存稀疏时预先构建成功页的地址范围；线性扫描时整个 buffer 作为一个范围，逐页检查
+    let linear = strategy == PageScanStrategy::Linear;
+    let page_ranges = if linear {
+        vec![(0, page_status.num_pages())]
     } else {
-        search_start + min_element_size as u64 - rem
+        page_status.get_success_page_ranges()
     };
-
-    // 优化：预先构建成功页的地址范围
-    let page_ranges = page_status.get_success_page_ranges();
     if page_ranges.is_empty() {
         return;
     }
 
     // buffer_addr 所在页的起始地址（页对齐）
     let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
+    let mut throttle = CancelThrottle::new(check_cancelled);
 
     for (start_page, end_page) in page_ranges {
         // 将相对页索引转换为绝对地址范围
         let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+        let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
         // 限制在 buffer 和搜索范围内
         let range_start = page_range_start.max(buffer_addr);
@@ -559,21 +1138,37 @@ pub(crate) fn search_in_buffer_group_fallback(
             first_addr // first_addr 已经对齐
         } else {
             // range_start > first_addr，需要对齐
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            align_up_addr(range_start, min_element_size)
         };
 
         // 在这个有效页范围内搜索
         while addr < range_end {
+            if throttle.advance(min_element_size) {
+                return;
+            }
+
+            if linear && !page_status.is_page_success(((addr - buffer_page_start) / *PAGE_SIZE as u64) as usize) {
+                addr = addr.saturating_add(min_element_size as u64);
+                continue;
+            }
+
+            // 有序模式下网格上的第一个值总能从它自己的地址开始匹配到，网格外的起点无需校验
+            if query.mode.is_ordered() && !query.on_object_grid(addr) {
+                addr = addr.saturating_add(min_element_size as u64);
+                continue;
+            }
+
             let offset = (addr - buffer_addr) as usize;
             if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
+                let range_end_check = addr.saturating_add(search_range).min(buffer_end).min(search_end);
                 let range_size = (range_end_check - addr) as usize;
 
                 if range_size >= query.range as usize && offset + range_size <= buffer.len() {
                     *matches_checked += 1;
 
-                    if let Some(offsets) = try_match_group_at_address(&buffer[offset..offset + range_size], addr, query) {
+                    if let Some(offsets) = try_match_group_at_address_with_cancel(&buffer[offset..offset + range_size], addr, query, check_cancelled)
+                        && query.on_object_grid(addr + offsets[0] as u64)
+                    {
                         // 保存所有匹配值的地址
                         for (idx, value_offset) in offsets.iter().enumerate() {
                             let value_addr = addr + *value_offset as u64;
@@ -583,26 +1178,120 @@ pub(crate) fn search_in_buffer_group_fallback(
                     }
                 }
             }
-            addr += min_element_size as u64;
+            addr = addr.saturating_add(min_element_size as u64);
         }
     }
 }
 
+/// 把 `addr` 向上对齐到 `align`，越过地址空间末尾时饱和为 `u64::MAX`，不会落在任何区域之内
+#[inline]
+fn align_up_addr(addr: u64, align: usize) -> u64 {
+    let rem = addr % align as u64;
+    if rem == 0 { addr } else { addr.saturating_add(align as u64 - rem) }
+}
+
+/// 返回 >= offset 的第一个使 `base_addr + offset` 按 `align` 对齐的 offset
+///
+/// 组内值的对齐必须按绝对地址计算，窗口起点只按最小对齐步进，相对偏移对齐会让大类型落在错位地址上
+#[inline]
+fn align_offset(base_addr: u64, offset: usize, align: usize) -> usize {
+    let rem = ((base_addr + offset as u64) % align as u64) as usize;
+    if rem == 0 { offset } else { offset + (align - rem) }
+}
+
+/// 缓冲区内 DFS 选定全部值后检查关系约束，`chosen` 按查询值顺序记录各值地址
+#[inline]
+fn constraints_hold_for_chosen(buffer: &[u8], base_addr: u64, query: &SearchQuery, chosen: &[(u64, ValueType)]) -> bool {
+    if query.constraints.is_empty() {
+        return true;
+    }
+    let offsets: Vec<usize> = chosen.iter().map(|&(addr, _)| (addr - base_addr) as usize).collect();
+    query.constraints_hold(buffer, &offsets)
+}
+
+/// 各值分别读取时检查关系约束：按查询值顺序拼接成连续缓冲区后交给 [`SearchQuery::constraints_hold`]
+fn constraints_hold_for_values(query: &SearchQuery, values: &[&[u8]]) -> bool {
+    if query.constraints.is_empty() {
+        return true;
+    }
+    let mut buffer = Vec::with_capacity(values.iter().map(|value| value.len()).sum());
+    let mut offsets = Vec::with_capacity(values.len());
+    for value in values {
+        offsets.push(buffer.len());
+        buffer.extend_from_slice(value);
+    }
+    query.constraints_hold(&buffer, &offsets)
+}
+
+#[cfg(test)]
 pub(crate) fn try_match_group_at_address(buffer: &[u8], start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
-    match query.mode {
-        SearchMode::Ordered => try_match_ordered(buffer, start_addr, query),
-        SearchMode::Unordered => try_match_unordered(buffer, start_addr, query),
+    try_match_group_at_address_with_cancel(buffer, start_addr, query, &|| false)
+}
+
+/// 可取消的窗口匹配：窗口内每扫描 [`CANCEL_CHECK_BYTES`] 字节检查一次，取消时返回 None
+pub(crate) fn try_match_group_at_address_with_cancel<F>(buffer: &[u8], start_addr: u64, query: &SearchQuery, check_cancelled: &F) -> Option<Vec<usize>>
+where
+    F: Fn() -> bool,
+{
+    let offsets = match query.mode {
+        SearchMode::Ordered => try_match_ordered(buffer, start_addr, query, check_cancelled),
+        // 紧密排列只检查固定位置，不需要中途取消
+        SearchMode::OrderedContiguous => try_match_ordered_contiguous(buffer, start_addr, query),
+        SearchMode::Unordered => try_match_unordered(buffer, start_addr, query, check_cancelled),
+    }?;
+
+    // 关系约束在各值位置确定后检查
+    query.constraints_hold(buffer, &offsets).then_some(offsets)
+}
+
+/// `addr` 是否紧跟在最后一个已选值之后
+#[inline]
+fn follows_last(chosen: &[(u64, ValueType)], addr: u64) -> bool {
+    chosen
+        .last()
+        .is_some_and(|&(last_addr, last_type)| last_addr.checked_add(last_type.size() as u64) == Some(addr))
+}
+
+/// 紧密排列匹配：第一个值位于窗口起点，之后每个值紧跟上一个值，不在窗口内跳跃查找
+pub(crate) fn try_match_ordered_contiguous(buffer: &[u8], start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
+    let mut offsets = Vec::with_capacity(query.values.len());
+    let mut current_offset = 0usize;
+
+    for target_value in &query.values {
+        let value_size = target_value.value_type().size();
+        let alignment = target_value.value_type().alignment() as u64;
+
+        if !(start_addr + current_offset as u64).is_multiple_of(alignment) {
+            return None;
+        }
+
+        let element_bytes = buffer.get(current_offset..current_offset + value_size)?;
+        if !matches!(target_value.matched(element_bytes), Ok(true)) {
+            return None;
+        }
+
+        offsets.push(current_offset);
+        current_offset += value_size;
     }
+
+    Some(offsets)
 }
 
-pub(crate) fn try_match_ordered(buffer: &[u8], _start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
+pub(crate) fn try_match_ordered<F>(buffer: &[u8], start_addr: u64, query: &SearchQuery, check_cancelled: &F) -> Option<Vec<usize>>
+where
+    F: Fn() -> bool,
+{
     let mut offsets = Vec::with_capacity(query.values.len());
     let mut current_offset = 0usize;
+    let mut throttle = CancelThrottle::new(check_cancelled);
 
     for target_value in &query.values {
         let value_size = target_value.value_type().size();
+        let alignment = target_value.value_type().alignment();
         let mut found = false;
 
+        current_offset = align_offset(start_addr, current_offset, alignment);
+
         while current_offset + value_size <= buffer.len() {
             let element_bytes = &buffer[current_offset..current_offset + value_size];
 
@@ -613,8 +1302,10 @@ pub(crate) fn try_match_ordered(buffer: &[u8], _start_addr: u64, query: &SearchQ
                 break;
             }
 
-            let alignment = target_value.value_type().size();
             current_offset += alignment;
+            if throttle.advance(alignment) {
+                return None;
+            }
         }
 
         if !found {
@@ -625,9 +1316,13 @@ pub(crate) fn try_match_ordered(buffer: &[u8], _start_addr: u64, query: &SearchQ
     Some(offsets)
 }
 
-pub(crate) fn try_match_unordered(buffer: &[u8], _start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
+pub(crate) fn try_match_unordered<F>(buffer: &[u8], start_addr: u64, query: &SearchQuery, check_cancelled: &F) -> Option<Vec<usize>>
+where
+    F: Fn() -> bool,
+{
     let mut offsets = vec![None; query.values.len()];
     let mut found_count = 0;
+    let mut throttle = CancelThrottle::new(check_cancelled);
 
     for (value_idx, target_value) in query.values.iter().enumerate() {
         if offsets[value_idx].is_some() {
@@ -635,8 +1330,8 @@ pub(crate) fn try_match_unordered(buffer: &[u8], _start_addr: u64, query: &Searc
         }
 
         let value_size = target_value.value_type().size();
-        let alignment = value_size;
-        let mut offset = 0usize;
+        let alignment = target_value.value_type().alignment();
+        let mut offset = align_offset(start_addr, 0, alignment);
 
         while offset + value_size <= buffer.len() {
             let element_bytes = &buffer[offset..offset + value_size];
@@ -648,6 +1343,9 @@ pub(crate) fn try_match_unordered(buffer: &[u8], _start_addr: u64, query: &Searc
             }
 
             offset += alignment;
+            if throttle.advance(alignment) {
+                return None;
+            }
         }
     }
 
@@ -686,6 +1384,24 @@ pub(crate) fn search_in_buffer_group_deep(
     matches_checked: &mut usize,
 ) {
     match query.mode {
+        // 紧密排列时每个起点只有一种组合，深度搜索等价于普通搜索
+        SearchMode::OrderedContiguous => {
+            let mut found = Vec::new();
+            search_in_buffer_group(
+                buffer,
+                buffer_addr,
+                region_start,
+                region_end,
+                min_element_size,
+                query,
+                page_status,
+                &mut found,
+                matches_checked,
+            );
+            for pair in found {
+                results.insert(pair);
+            }
+        },
         SearchMode::Ordered => search_ordered_deep(
             buffer,
             buffer_addr,
@@ -727,6 +1443,17 @@ pub(crate) fn search_in_buffer_group_deep_with_cancel<F>(
     F: Fn() -> bool,
 {
     match query.mode {
+        SearchMode::OrderedContiguous => search_in_buffer_group(
+            buffer,
+            buffer_addr,
+            region_start,
+            region_end,
+            min_element_size,
+            query,
+            page_status,
+            results,
+            matches_checked,
+        ),
         SearchMode::Ordered => search_ordered_deep_with_cancel(
             buffer,
             buffer_addr,
@@ -773,12 +1500,7 @@ fn search_ordered_deep(
     let search_end = buffer_end.min(region_end);
 
     // Calculate first aligned address
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    let first_addr = align_up_addr(search_start, min_element_size);
 
     // Get successful page ranges
     let page_ranges = page_status.get_success_page_ranges();
@@ -792,7 +1514,7 @@ fn search_ordered_deep(
     // Iterate through each aligned address as potential starting point
     for (start_page, end_page) in page_ranges {
         let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+        let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
         let range_start = page_range_start.max(buffer_addr);
         let range_end = page_range_end.min(search_end).min(buffer_end);
@@ -804,14 +1526,13 @@ fn search_ordered_deep(
         let mut addr = if range_start <= first_addr {
             first_addr
         } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            align_up_addr(range_start, min_element_size)
         };
 
         while addr < range_end {
             let offset = (addr - buffer_addr) as usize;
             if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
+                let range_end_check = addr.saturating_add(search_range).min(buffer_end).min(search_end);
                 let range_size = (range_end_check - addr) as usize;
 
                 if range_size >= query.range as usize && offset + range_size <= buffer.len() {
@@ -833,7 +1554,7 @@ fn search_ordered_deep(
                     );
                 }
             }
-            addr += min_element_size as u64;
+            addr = addr.saturating_add(min_element_size as u64);
         }
     }
 }
@@ -851,6 +1572,9 @@ fn dfs_ordered(
 ) {
     // Found complete match
     if query_idx == query.values.len() {
+        if !constraints_hold_for_chosen(buffer, base_addr, query, chosen) {
+            return;
+        }
         for (addr, vt) in chosen.iter() {
             results.insert(ValuePair::new(*addr, *vt));
         }
@@ -859,14 +1583,14 @@ fn dfs_ordered(
 
     let target_value = &query.values[query_idx];
     let value_size = target_value.value_type().size();
-    let alignment = value_size;
+    let alignment = target_value.value_type().alignment();
 
-    let mut offset = search_offset;
+    let mut offset = align_offset(base_addr, search_offset, alignment);
     while offset + value_size <= buffer.len() {
         let addr = base_addr + offset as u64;
 
-        // Check if address is already used
-        if used.contains(&addr) {
+        // Check if address is already used; the first value must also sit on the object grid
+        if used.contains(&addr) || (query_idx == 0 && !query.on_object_grid(addr)) {
             offset += alignment;
             continue;
         }
@@ -917,12 +1641,7 @@ fn search_unordered_deep(
     let search_start = buffer_addr.max(region_start);
     let search_end = buffer_end.min(region_end);
 
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    let first_addr = align_up_addr(search_start, min_element_size);
 
     let page_ranges = page_status.get_success_page_ranges();
     if page_ranges.is_empty() {
@@ -934,7 +1653,7 @@ fn search_unordered_deep(
 
     for (start_page, end_page) in page_ranges {
         let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+        let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
         let range_start = page_range_start.max(buffer_addr);
         let range_end = page_range_end.min(search_end).min(buffer_end);
@@ -946,15 +1665,14 @@ fn search_unordered_deep(
         let mut addr = if range_start <= first_addr {
             first_addr
         } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            align_up_addr(range_start, min_element_size)
         };
 
         while addr < range_end {
             let offset = (addr - buffer_addr) as usize;
             if offset < buffer.len() {
                 let unordered_start = addr.saturating_sub(search_range).max(buffer_addr);
-                let unordered_end = (addr + search_range).min(buffer_end).min(search_end);
+                let unordered_end = addr.saturating_add(search_range).min(buffer_end).min(search_end);
                 let start_offset = (unordered_start - buffer_addr) as usize;
                 let range_size = (unordered_end - unordered_start) as usize;
 
@@ -976,7 +1694,7 @@ fn search_unordered_deep(
                     );
                 }
             }
-            addr += min_element_size as u64;
+            addr = addr.saturating_add(min_element_size as u64);
         }
     }
 }
@@ -994,6 +1712,9 @@ fn dfs_unordered(
 ) {
     // Found complete match
     if query_idx == query.values.len() {
+        if !constraints_hold_for_chosen(buffer, base_addr, query, chosen) {
+            return;
+        }
         for (addr, vt) in chosen.iter() {
             results.insert(ValuePair::new(*addr, *vt));
         }
@@ -1002,13 +1723,13 @@ fn dfs_unordered(
 
     let target_value = &query.values[query_idx];
     let value_size = target_value.value_type().size();
-    let alignment = value_size;
+    let alignment = target_value.value_type().alignment();
 
-    let mut offset = search_offset;
+    let mut offset = align_offset(base_addr, search_offset, alignment);
     while offset + value_size <= buffer.len() {
         let addr = base_addr + offset as u64;
 
-        if used.contains(&addr) {
+        if used.contains(&addr) || (query_idx == 0 && !query.on_object_grid(addr)) {
             offset += alignment;
             continue;
         }
@@ -1060,12 +1781,7 @@ fn search_ordered_deep_with_cancel<F>(
     let search_start = buffer_addr.max(region_start);
     let search_end = buffer_end.min(region_end);
 
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    let first_addr = align_up_addr(search_start, min_element_size);
 
     let page_ranges = page_status.get_success_page_ranges();
     if page_ranges.is_empty() {
@@ -1085,7 +1801,7 @@ fn search_ordered_deep_with_cancel<F>(
         }
 
         let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+        let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
         let range_start = page_range_start.max(buffer_addr);
         let range_end = page_range_end.min(search_end).min(buffer_end);
@@ -1097,8 +1813,7 @@ fn search_ordered_deep_with_cancel<F>(
         let mut addr = if range_start <= first_addr {
             first_addr
         } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            align_up_addr(range_start, min_element_size)
         };
 
         let mut iteration_count = 0u64;
@@ -1112,7 +1827,7 @@ fn search_ordered_deep_with_cancel<F>(
 
             let offset = (addr - buffer_addr) as usize;
             if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
+                let range_end_check = addr.saturating_add(search_range).min(buffer_end).min(search_end);
                 let range_size = (range_end_check - addr) as usize;
 
                 if range_size >= query.range as usize && offset + range_size <= buffer.len() {
@@ -1139,7 +1854,7 @@ fn search_ordered_deep_with_cancel<F>(
                     }
                 }
             }
-            addr += min_element_size as u64;
+            addr = addr.saturating_add(min_element_size as u64);
         }
     }
 }
@@ -1168,6 +1883,9 @@ fn dfs_ordered_with_cancel<F>(
 
     // Found complete match.
     if query_idx == query.values.len() {
+        if !constraints_hold_for_chosen(buffer, base_addr, query, chosen) {
+            return;
+        }
         for (addr, vt) in chosen.iter() {
             results.push(ValuePair::new(*addr, *vt));
         }
@@ -1176,9 +1894,9 @@ fn dfs_ordered_with_cancel<F>(
 
     let target_value = &query.values[query_idx];
     let value_size = target_value.value_type().size();
-    let alignment = value_size;
+    let alignment = target_value.value_type().alignment();
 
-    let mut offset = search_offset;
+    let mut offset = align_offset(base_addr, search_offset, alignment);
     let mut iteration_count = 0u64;
     while offset + value_size <= buffer.len() {
         // Check cancellation periodically (every 500 iterations in DFS).
@@ -1192,7 +1910,7 @@ fn dfs_ordered_with_cancel<F>(
 
         let addr = base_addr + offset as u64;
 
-        if used.contains(&addr) {
+        if used.contains(&addr) || (query_idx == 0 && !query.on_object_grid(addr)) {
             offset += alignment;
             continue;
         }
@@ -1250,12 +1968,7 @@ fn search_unordered_deep_with_cancel<F>(
     let search_start = buffer_addr.max(region_start);
     let search_end = buffer_end.min(region_end);
 
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    let first_addr = align_up_addr(search_start, min_element_size);
 
     let page_ranges = page_status.get_success_page_ranges();
     if page_ranges.is_empty() {
@@ -1274,7 +1987,7 @@ fn search_unordered_deep_with_cancel<F>(
         }
 
         let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+        let page_range_end = buffer_page_start.saturating_add((end_page * *PAGE_SIZE) as u64);
 
         let range_start = page_range_start.max(buffer_addr);
         let range_end = page_range_end.min(search_end).min(buffer_end);
@@ -1286,8 +1999,7 @@ fn search_unordered_deep_with_cancel<F>(
         let mut addr = if range_start <= first_addr {
             first_addr
         } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            align_up_addr(range_start, min_element_size)
         };
 
         let mut iteration_count = 0u64;
@@ -1302,7 +2014,7 @@ fn search_unordered_deep_with_cancel<F>(
             let offset = (addr - buffer_addr) as usize;
             if offset < buffer.len() {
                 let unordered_start = addr.saturating_sub(search_range).max(buffer_addr);
-                let unordered_end = (addr + search_range).min(buffer_end).min(search_end);
+                let unordered_end = addr.saturating_add(search_range).min(buffer_end).min(search_end);
                 let start_offset = (unordered_start - buffer_addr) as usize;
                 let range_size = (unordered_end - unordered_start) as usize;
 
@@ -1330,7 +2042,7 @@ fn search_unordered_deep_with_cancel<F>(
                     }
                 }
             }
-            addr += min_element_size as u64;
+            addr = addr.saturating_add(min_element_size as u64);
         }
     }
 }
@@ -1357,6 +2069,9 @@ fn dfs_unordered_with_cancel<F>(
     }
 
     if query_idx == query.values.len() {
+        if !constraints_hold_for_chosen(buffer, base_addr, query, chosen) {
+            return;
+        }
         for (addr, vt) in chosen.iter() {
             results.push(ValuePair::new(*addr, *vt));
         }
@@ -1365,9 +2080,9 @@ fn dfs_unordered_with_cancel<F>(
 
     let target_value = &query.values[query_idx];
     let value_size = target_value.value_type().size();
-    let alignment = value_size;
+    let alignment = target_value.value_type().alignment();
 
-    let mut offset = search_offset;
+    let mut offset = align_offset(base_addr, search_offset, alignment);
     let mut iteration_count = 0u64;
     while offset + value_size <= buffer.len() {
         iteration_count += 1;
@@ -1380,7 +2095,7 @@ fn dfs_unordered_with_cancel<F>(
 
         let addr = base_addr + offset as u64;
 
-        if used.contains(&addr) {
+        if used.contains(&addr) || (query_idx == 0 && !query.on_object_grid(addr)) {
             offset += alignment;
             continue;
         }
@@ -1426,16 +2141,20 @@ fn dfs_unordered_with_cancel<F>(
 /// - `existing_results`: 已有的搜索结果集合 (B+树，已按地址排序)
 /// - `query`: 组搜索查询条件
 /// - `processed_counter`: 可选的已处理地址计数器（用于进度追踪）
+/// - `read`: 读取单个结果地址处的值
 ///
 /// # 返回值
 /// 返回满足条件的所有地址的集合
-#[deprecated]
-pub(crate) fn refine_search_group_with_dfs(
+pub(crate) fn refine_search_group_with_dfs<R>(
     existing_results: &Vec<ValuePair>,
     query: &SearchQuery,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
-) -> Result<BPlusTreeSet<ValuePair>> {
+    mut read: R,
+) -> Result<BPlusTreeSet<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
     use rayon::prelude::*;
     use std::collections::HashSet;
     use std::sync::atomic::Ordering;
@@ -1444,8 +2163,6 @@ pub(crate) fn refine_search_group_with_dfs(
         debug!("当前结果数量: {}", existing_results.len())
     }
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
     let mut refined_results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
 
     if query.values.is_empty() {
@@ -1457,9 +2174,8 @@ pub(crate) fn refine_search_group_with_dfs(
     for pair in existing_results.iter() {
         let addr = pair.addr;
         let value_size = pair.value_type.size();
-        let mut buffer = vec![0u8; value_size];
 
-        if driver_manager.read_memory_unified(addr, &mut buffer, None).is_ok() {
+        if let Some(buffer) = SearchEngineManager::read_value_with(addr, value_size, &mut read) {
             addr_values.push((addr, buffer));
         } else {
             // 读取失败也要更新计数器
@@ -1485,11 +2201,13 @@ pub(crate) fn refine_search_group_with_dfs(
 
     // 找所有锚点
     let first_query_target = &query.values[0];
-    let anchors: Vec<u64> = addr_values
+    let anchors: Vec<(u64, &[u8])> = addr_values
         .par_iter()
         .filter_map(|(addr, bytes)| {
-            if let Ok(true) = first_query_target.matched(&bytes) {
-                Some(*addr) // 是锚点，不更新计数器
+            if query.on_object_grid(*addr)
+                && let Ok(true) = first_query_target.matched(&bytes)
+            {
+                Some((*addr, bytes.as_slice())) // 是锚点，不更新计数器
             } else {
                 // 更新已处理计数器 (非锚点更新)
                 if let Some(counter) = &processed_counter {
@@ -1512,17 +2230,17 @@ pub(crate) fn refine_search_group_with_dfs(
     if query.values.len() == 1 {
         // 单值改善, 直接返回锚点结果
         let value_type = query.values[0].value_type();
-        for anchor_addr in anchors {
+        for (anchor_addr, _) in anchors {
             refined_results.insert(ValuePair::new(anchor_addr, value_type));
         }
         return Ok(refined_results);
     }
 
     // 主循环：每个锚点执行 DFS
-    for anchor_addr in anchors {
+    for (anchor_addr, anchor_bytes) in anchors {
         let (min_addr, max_addr) = match query.mode {
-            SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr + query.range as u64),
-            SearchMode::Ordered => (anchor_addr, anchor_addr + query.range as u64),
+            SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr.saturating_add(query.range as u64)),
+            SearchMode::Ordered | SearchMode::OrderedContiguous => (anchor_addr, anchor_addr.saturating_add(query.range as u64)),
         };
 
         // 候选（不含锚点本身，避免重复使用）
@@ -1548,13 +2266,17 @@ pub(crate) fn refine_search_group_with_dfs(
         // 当前选择的地址（含锚点）
         let mut chosen: Vec<(u64, ValueType)> = Vec::with_capacity(query.values.len());
         chosen.push((anchor_addr, query.values[0].value_type()));
+        // 与 chosen 一一对应的当前值，用于检查关系约束
+        let mut chosen_bytes: Vec<&[u8]> = Vec::with_capacity(query.values.len());
+        chosen_bytes.push(anchor_bytes);
 
         // 回溯函数
-        fn dfs(
+        fn dfs<'a>(
             cand_idx: usize,
-            candidates: &[(u64, &Vec<u8>)],
+            candidates: &[(u64, &'a Vec<u8>)],
             query: &SearchQuery,
             chosen: &mut Vec<(u64, ValueType)>,
+            chosen_bytes: &mut Vec<&'a [u8]>,
             used: &mut HashSet<u64>,
             refined_results: &mut BPlusTreeSet<ValuePair>,
         ) -> Result<()> {
@@ -1563,6 +2285,9 @@ pub(crate) fn refine_search_group_with_dfs(
 
             // 成功匹配全部查询值
             if have == need_total {
+                if !constraints_hold_for_values(query, chosen_bytes) {
+                    return Ok(());
+                }
                 for (addr, vt) in chosen.iter() {
                     refined_results.insert(ValuePair::new(*addr, *vt));
                 }
@@ -1595,6 +2320,11 @@ pub(crate) fn refine_search_group_with_dfs(
                     continue;
                 }
 
+                // 紧密排列：必须紧跟上一个已选值
+                if query.mode == SearchMode::OrderedContiguous && !follows_last(chosen, addr) {
+                    continue;
+                }
+
                 // 地址唯一约束
                 if used.contains(&addr) {
                     continue;
@@ -1603,19 +2333,21 @@ pub(crate) fn refine_search_group_with_dfs(
                 // 选择
                 used.insert(addr);
                 chosen.push((addr, sv.value_type()));
+                chosen_bytes.push(bytes);
 
                 // 下一层从 i+1 开始（保证组合不重复）
-                dfs(i + 1, candidates, query, chosen, used, refined_results)?;
+                dfs(i + 1, candidates, query, chosen, chosen_bytes, used, refined_results)?;
 
                 // 回溯
                 chosen.pop();
+                chosen_bytes.pop();
                 used.remove(&addr);
             }
 
             Ok(())
         }
 
-        dfs(0, &candidates, query, &mut chosen, &mut used, &mut refined_results)?;
+        dfs(0, &candidates, query, &mut chosen, &mut chosen_bytes, &mut used, &mut refined_results)?;
 
         // 更新已处理计数器
         if let Some(counter) = &processed_counter {
@@ -1660,7 +2392,7 @@ where
         return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
     }
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let driver_manager = driver_manager_read();
 
     let mut refined_results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
 
@@ -1710,11 +2442,13 @@ where
 
     // Find all anchor points.
     let first_query_target = &query.values[0];
-    let anchors: Vec<u64> = addr_values
+    let anchors: Vec<(u64, &[u8])> = addr_values
         .par_iter()
         .filter_map(|(addr, bytes)| {
-            if let Ok(true) = first_query_target.matched(&bytes) {
-                Some(*addr)
+            if query.on_object_grid(*addr)
+                && let Ok(true) = first_query_target.matched(&bytes)
+            {
+                Some((*addr, bytes.as_slice()))
             } else {
                 if let Some(counter) = &processed_counter {
                     counter.fetch_add(1, Ordering::Relaxed);
@@ -1735,7 +2469,7 @@ where
     if query.values.len() == 1 {
         // Single value refine, return anchor results directly.
         let value_type = query.values[0].value_type();
-        for anchor_addr in anchors {
+        for (anchor_addr, _) in anchors {
             refined_results.insert(ValuePair::new(anchor_addr, value_type));
         }
         return Ok(refined_results);
@@ -1747,11 +2481,12 @@ where
     let cancelled = AtomicBool::new(false);
 
     // Inner DFS function with cancellation support.
-    fn dfs_with_cancel<FC>(
+    fn dfs_with_cancel<'a, FC>(
         cand_idx: usize,
-        candidates: &[(u64, &Vec<u8>)],
+        candidates: &[(u64, &'a Vec<u8>)],
         query: &SearchQuery,
         chosen: &mut Vec<(u64, ValueType)>,
+        chosen_bytes: &mut Vec<&'a [u8]>,
         used: &mut HashSet<u64>,
         local_results: &mut Vec<(u64, ValueType)>,
         check_cancelled: &FC,
@@ -1769,6 +2504,9 @@ where
         let have = chosen.len();
 
         if have == need_total {
+            if !constraints_hold_for_values(query, chosen_bytes) {
+                return;
+            }
             for (addr, vt) in chosen.iter() {
                 local_results.push((*addr, *vt));
             }
@@ -1803,18 +2541,24 @@ where
                 continue;
             }
 
+            if query.mode == SearchMode::OrderedContiguous && !follows_last(chosen, addr) {
+                continue;
+            }
+
             if used.contains(&addr) {
                 continue;
             }
 
             used.insert(addr);
             chosen.push((addr, sv.value_type()));
+            chosen_bytes.push(bytes);
 
             dfs_with_cancel(
                 i + 1,
                 candidates,
                 query,
                 chosen,
+                chosen_bytes,
                 used,
                 local_results,
                 check_cancelled,
@@ -1828,6 +2572,7 @@ where
             }
 
             chosen.pop();
+            chosen_bytes.pop();
             used.remove(&addr);
         }
     }
@@ -1835,7 +2580,7 @@ where
     // Parallel processing of anchors using rayon.
     let all_results: Vec<Vec<(u64, ValueType)>> = anchors
         .par_iter()
-        .filter_map(|anchor_addr| {
+        .filter_map(|&(anchor_addr, anchor_bytes)| {
             // Check cancellation.
             if check_cancelled() || cancelled.load(Ordering::Relaxed) {
                 cancelled.store(true, Ordering::Relaxed);
@@ -1843,14 +2588,14 @@ where
             }
 
             let (min_addr, max_addr) = match query.mode {
-                SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr + query.range as u64),
-                SearchMode::Ordered => (*anchor_addr, anchor_addr + query.range as u64),
+                SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr.saturating_add(query.range as u64)),
+                SearchMode::Ordered | SearchMode::OrderedContiguous => (anchor_addr, anchor_addr.saturating_add(query.range as u64)),
             };
 
             // Candidates (excluding anchor itself to avoid duplicate usage).
             let mut candidates: Vec<(u64, &Vec<u8>)> = Vec::new();
             for (addr, bytes) in &addr_values {
-                if *addr >= min_addr && *addr <= max_addr && *addr != *anchor_addr {
+                if *addr >= min_addr && *addr <= max_addr && *addr != anchor_addr {
                     candidates.push((*addr, bytes));
                 }
             }
@@ -1865,10 +2610,12 @@ where
 
             // DFS: find all valid combinations.
             let mut used: HashSet<u64> = HashSet::new();
-            used.insert(*anchor_addr);
+            used.insert(anchor_addr);
 
             let mut chosen: Vec<(u64, ValueType)> = Vec::with_capacity(query.values.len());
-            chosen.push((*anchor_addr, query.values[0].value_type()));
+            chosen.push((anchor_addr, query.values[0].value_type()));
+            let mut chosen_bytes: Vec<&[u8]> = Vec::with_capacity(query.values.len());
+            chosen_bytes.push(anchor_bytes);
 
             let mut local_results: Vec<(u64, ValueType)> = Vec::new();
             let mut iteration_count = 0u64;
@@ -1878,6 +2625,7 @@ where
                 &candidates,
                 query,
                 &mut chosen,
+                &mut chosen_bytes,
                 &mut used,
                 &mut local_results,
                 check_cancelled,
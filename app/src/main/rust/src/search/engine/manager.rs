@@ -1,23 +1,44 @@
-use super::super::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
-use super::super::types::{FuzzyCondition, SearchQuery, ValueType};
+use super::super::result_manager::packed::{decode_packed, PackedResultWriter};
+use super::super::result_manager::{FuzzySearchResultItem, MemoryUsage, ResultStoreKind, SearchResultManager, SearchResultMode, DEFAULT_INSERT_BATCH_SIZE};
+use super::super::types::{DeltaOp, FuzzyCondition, SearchMode, SearchQuery, SearchValue, ValueType};
 use super::super::SearchResultItem;
+use super::checkpoint::Checkpoint;
+use super::export::{self, ExportFormat};
 use super::filter::SearchFilter;
 use super::fuzzy_search;
+use super::region::RegionTags;
 use super::group_search;
+use super::mode_stats::{ModeStatsBook, ScanSample, DEFAULT_MAX_FAULT_RATE, MODE_STATS_FILE};
+use super::outcome::{ScanStats, SearchOutcome};
+use super::query_cache::{QueryCache, QueryCacheKey, DEFAULT_QUERY_CACHE_CAPACITY};
+use super::sampling::{self, SampleScanReport};
+use super::session::{self, FilterManifest, FrozenManifest, SessionManifest, SESSION_VERSION};
 use super::shared_buffer::{SearchErrorCode, SearchStatus, SharedBuffer};
 use super::single_search;
-use crate::core::globals::TOKIO_RUNTIME;
-use crate::core::DRIVER_MANAGER;
+use super::streaming::{self, ResultBatch};
+use crate::core::freeze_manager::FreezeManager;
+use crate::core::globals::{driver_manager_read, TOKIO_RUNTIME};
+use crate::core::{DriverManager, MemoryAccessMode, MemorySource};
+use crate::pointer_scan::scanner::is_valid_pointer;
+use crate::pointer_scan::VmStaticData;
+use crate::search::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
 use anyhow::{anyhow, Result};
 use bplustree::BPlusTreeSet;
 use lazy_static::lazy_static;
 use log::{debug, error, info, log_enabled, warn, Level};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use rayon::prelude::*;
+use std::borrow::BorrowMut;
 use std::cmp::Ordering as CmpOrdering;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering as AtomicOrdering};
-use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
@@ -58,6 +79,529 @@ pub const BPLUS_TREE_ORDER: u16 = 256;
 /// Legacy callback interface for search progress. Kept for backward compatibility.
 pub trait SearchProgressCallback: Send + Sync {
     fn on_search_complete(&self, total_found: usize, total_regions: usize, elapsed_millis: u64);
+
+    /// 扫描进度，`bytes_done` 为已扫描的区域字节数，`bytes_total` 为所有区域的总字节数
+    ///
+    /// 在 rayon 工作线程上调用，每扫描约 [`PROGRESS_REPORT_INTERVAL`] 字节以及扫描完所有区域时各一次
+    fn on_progress(&self, _bytes_done: u64, _bytes_total: u64) {}
+}
+
+/// 两次 [`SearchProgressCallback::on_progress`] 之间扫描的字节数
+pub const PROGRESS_REPORT_INTERVAL: u64 = 16 * 1024 * 1024;
+
+/// 按字节累计一次扫描的进度，各区域的工作线程共享
+///
+/// 每个块只做一次原子加法，跨过报告间隔的那个线程负责回调，不需要加锁
+pub(crate) struct ScanProgress {
+    callback: Option<Arc<dyn SearchProgressCallback>>,
+    bytes_total: u64,
+    bytes_done: AtomicU64,
+}
+
+impl ScanProgress {
+    /// 总字节数按 `regions` 预先计算，无效区域按 0 字节计
+    pub(crate) fn new(regions: &[(u64, u64)], callback: Option<Arc<dyn SearchProgressCallback>>) -> Self {
+        let bytes_total = regions.iter().map(|&(start, end)| end.saturating_sub(start)).sum();
+        Self {
+            callback,
+            bytes_total,
+            bytes_done: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录又扫描了 `bytes` 字节，超过总数的部分（例如扫描后的复核）不再报告
+    pub(crate) fn add(&self, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        let before = self.bytes_done.fetch_add(bytes, AtomicOrdering::Relaxed);
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        if before >= self.bytes_total {
+            return;
+        }
+        let after = before.saturating_add(bytes).min(self.bytes_total);
+        if before / PROGRESS_REPORT_INTERVAL != after / PROGRESS_REPORT_INTERVAL || after == self.bytes_total {
+            callback.on_progress(after, self.bytes_total);
+        }
+    }
+
+    /// 实际读取的字节数，包括扫描后复核的读取；取消或超时的区域只计已读取的块
+    pub(crate) fn bytes_done(&self) -> u64 {
+        self.bytes_done.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// 过滤掉零长度（`start == end`）和反向（`end < start`）的区域，并记录日志
+pub(crate) fn sanitize_regions(regions: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    let total = regions.len();
+    let valid: Vec<(u64, u64)> = regions
+        .into_iter()
+        .filter(|&(start, end)| {
+            if start >= end {
+                warn!("Skipping invalid region 0x{:X} - 0x{:X}", start, end);
+                false
+            } else {
+                true
+            }
+        })
+        .collect();
+
+    if valid.len() != total && log_enabled!(Level::Debug) {
+        debug!("Skipped {} invalid regions out of {}", total - valid.len(), total);
+    }
+
+    valid
+}
+
+/// 校验查询并清理区域，供不写入结果集的扫描接口共用
+fn checked_regions(query: &SearchQuery, regions: &[(u64, u64)]) -> Result<Vec<(u64, u64)>> {
+    query.validate().map_err(|e| anyhow!("Invalid query: {}", e))?;
+    Ok(sanitize_regions(regions.to_vec()))
+}
+
+/// 计数器搜索第二步使用的模糊条件：`delta` 为 None 时任意增大，正数时恰好增加 `delta`
+pub(crate) fn counter_condition(delta: Option<i128>) -> Result<FuzzyCondition> {
+    match delta {
+        None => Ok(FuzzyCondition::Increased),
+        Some(delta) if delta > 0 => Ok(FuzzyCondition::IncreasedBy(
+            i64::try_from(delta).map_err(|_| anyhow!("Counter delta {} out of range", delta))?,
+        )),
+        Some(delta) => Err(anyhow!("Counter delta must be positive, got {}", delta)),
+    }
+}
+
+/// 把读取块大小规整为页大小的整数倍（向下取整，至少一页）
+///
+/// 每个块都从页首开始读取，块之间不会重复读取同一页，页状态位图的下标也与块内页一一对应
+pub(crate) fn page_aligned_chunk_size(chunk_size: usize) -> usize {
+    let page_size = *PAGE_SIZE;
+    (chunk_size / page_size).max(1) * page_size
+}
+
+/// 持有驱动读锁，按调用方指定的内存访问模式读取
+struct ModeReader {
+    driver_manager: RwLockReadGuard<'static, DriverManager>,
+    mode: MemoryAccessMode,
+}
+
+impl ModeReader {
+    /// 解析 `memory_mode` 并确认驱动当前能以该模式读取
+    fn lock(memory_mode: i32) -> Result<Self> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+        Ok(Self { driver_manager, mode })
+    }
+
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()> {
+        self.driver_manager.read_memory_with_mode(self.mode, addr, buf, Some(page_status))
+    }
+}
+
+/// 单个区域的软超时计时器，在区域的取消检查里调用 [`Self::expired`]
+struct RegionTimer {
+    start: Instant,
+    timeout: Option<Duration>,
+    timed_out: Cell<bool>,
+}
+
+impl RegionTimer {
+    fn start(timeout: Option<Duration>) -> Self {
+        Self {
+            start: Instant::now(),
+            timeout,
+            timed_out: Cell::new(false),
+        }
+    }
+
+    /// 是否已超时，超时后记录下来供 [`Self::timed_out`] 查询
+    fn expired(&self) -> bool {
+        if self.timeout.is_some_and(|timeout| self.start.elapsed() >= timeout) {
+            self.timed_out.set(true);
+        }
+        self.timed_out.get()
+    }
+
+    fn timed_out(&self) -> bool {
+        self.timed_out.get()
+    }
+}
+
+/// 扫描预演结果：只校验和统计，不读取内存
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    /// 过滤掉无效区域并按起始地址排序后的区域
+    pub validated_regions: Vec<(u64, u64)>,
+    /// 所有有效区域的字节数之和
+    pub total_bytes: u64,
+    /// 实际扫描时会读取的块数（块从页对齐处开始）
+    pub estimated_chunks: usize,
+    pub query_ok: bool,
+    /// 查询校验失败时的错误信息
+    pub query_error: Option<String>,
+}
+
+/// [`SearchEngineManager::scan_with_alignment_fallback`] 的结果
+#[derive(Clone)]
+pub struct AlignmentFallbackScan {
+    /// 按地址排序
+    pub results: Vec<SearchResultItem>,
+    /// 自然对齐下没有结果，`results` 来自逐字节重新扫描
+    pub used_fallback: bool,
+}
+
+/// [`SearchEngineManager::scan_via_pointers`] 保留下来的一个指针
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerFieldMatch {
+    /// 指针本身所在的地址
+    pub pointer_addr: u64,
+    /// 指针指向的地址（对象基址），已去掉高位标签
+    pub target: u64,
+    /// `target + field_offset`，即匹配的字段地址
+    pub field_addr: u64,
+}
+
+/// 一段连续地址的读取结果，见 [`SearchEngineManager::read_around`]
+pub struct SpanRead {
+    /// 读取的起始地址
+    pub start: u64,
+    /// 长度总是等于请求的长度，不可读的页填 0
+    pub bytes: Vec<u8>,
+    /// 覆盖整段请求，第 0 页是 `start` 所在的页
+    pub page_status: PageStatusBitmap,
+}
+
+impl SpanRead {
+    /// 请求涉及的页数
+    pub fn page_count(&self) -> usize {
+        ((self.start as usize % *PAGE_SIZE) + self.bytes.len()).div_ceil(*PAGE_SIZE)
+    }
+
+    /// 可读的地址范围 `[start, end)`，相邻的可读页合并为一段
+    pub fn readable_ranges(&self) -> Vec<(u64, u64)> {
+        let end = self.start + self.bytes.len() as u64;
+        let page_base = self.start & !(*PAGE_SIZE as u64 - 1);
+
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for page in (0..self.page_count()).filter(|&i| self.page_status.is_page_success(i)) {
+            let page_start = (page_base + (page * *PAGE_SIZE) as u64).max(self.start);
+            let page_end = (page_base + ((page + 1) * *PAGE_SIZE) as u64).min(end);
+            match ranges.last_mut() {
+                Some(last) if last.1 == page_start => last.1 = page_end,
+                _ => ranges.push((page_start, page_end)),
+            }
+        }
+        ranges
+    }
+}
+
+/// 同步搜索准备阶段从管理器取出的快照，扫描阶段只使用它，不需要持有管理器
+struct PreparedSearch {
+    regions: Vec<(u64, u64)>,
+    cache_key: QueryCacheKey,
+    cached: Option<Arc<Vec<ValuePair>>>,
+    region_timeout: Option<Duration>,
+    /// 开启 [`SearchQuery::verify_after_scan`] 时合并结果所用的间距，见 [`verify_results`]
+    verify_gap: Option<u64>,
+    /// 见 [`SearchQuery::stop_after_first_hit_region`]
+    stop_after_first_hit_region: bool,
+    shared_buffer: SharedBuffer,
+    cancel_token: CancellationToken,
+    /// 调用方持有的取消标志，见 [`SearchEngineManager::search_memory_cancellable`]
+    cancel_flag: Option<Arc<AtomicBool>>,
+    start_time: Instant,
+    /// 扫描中途出错、panic 或没有走到收尾阶段时，随 PreparedSearch 一起释放
+    active: SearchActiveGuard,
+}
+
+/// 同步搜索进行中的标记，释放时复位 [`SearchEngineManager::is_searching`]
+struct SearchActiveGuard(Arc<AtomicBool>);
+
+impl SearchActiveGuard {
+    fn activate(flag: &Arc<AtomicBool>) -> Self {
+        flag.store(true, AtomicOrdering::Release);
+        Self(Arc::clone(flag))
+    }
+}
+
+impl Drop for SearchActiveGuard {
+    fn drop(&mut self) {
+        self.0.store(false, AtomicOrdering::Release);
+    }
+}
+
+/// 扫描阶段的产出，由 [`SearchEngineManager::finish_search`] 写回管理器
+struct ScannedResults {
+    /// 按地址排序并去重
+    results: Vec<ValuePair>,
+    cancelled: bool,
+    completed_regions: usize,
+    failed_regions: usize,
+    timed_out_regions: usize,
+}
+
+impl PreparedSearch {
+    /// 并行扫描所有区域；取消来自 [`SearchEngineManager::request_cancel`]、共享缓冲区或调用方的取消标志
+    fn scan<S>(&self, search_region: S) -> ScannedResults
+    where
+        S: Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync,
+    {
+        let total_regions = self.regions.len();
+
+        // 没有可搜索的区域（为空或全部无效）：直接得到空结果，回调仍会以 0 个区域触发
+        if total_regions == 0 {
+            debug!("No regions to search");
+            return ScannedResults {
+                results: Vec::new(),
+                cancelled: false,
+                completed_regions: 0,
+                failed_regions: 0,
+                timed_out_regions: 0,
+            };
+        }
+
+        if let Some(ref cached) = self.cached {
+            debug!("Query cache hit: {} results", cached.len());
+            return ScannedResults {
+                results: cached.as_ref().clone(),
+                cancelled: false,
+                completed_regions: total_regions,
+                failed_regions: 0,
+                timed_out_regions: 0,
+            };
+        }
+
+        let completed_regions = AtomicUsize::new(0);
+        let failed_regions = AtomicUsize::new(0);
+        let timed_out_regions = AtomicUsize::new(0);
+        let total_found_count = AtomicI64::new(0);
+        let cancelled = AtomicBool::new(false);
+
+        let check_cancelled = || {
+            if cancelled.load(AtomicOrdering::Relaxed) {
+                return true;
+            }
+            let flag_set = self.cancel_flag.as_ref().is_some_and(|flag| flag.load(AtomicOrdering::Relaxed));
+            if flag_set || self.cancel_token.is_cancelled() || self.shared_buffer.is_cancel_requested() {
+                cancelled.store(true, AtomicOrdering::Relaxed);
+                return true;
+            }
+            false
+        };
+
+        let scan_region = |idx: usize, (start, end): &(u64, u64)| {
+            if check_cancelled() {
+                return None;
+            }
+
+            let region_timer = RegionTimer::start(self.region_timeout);
+            let check_region = || check_cancelled() || region_timer.expired();
+
+            let region_results = match search_region(*start, *end, &check_region) {
+                Ok(results) => results,
+                Err(e) => {
+                    error!("Failed to search region {}: {:?}", idx, e);
+                    failed_regions.fetch_add(1, AtomicOrdering::Relaxed);
+                    Vec::new()
+                },
+            };
+
+            if region_timer.timed_out() {
+                warn!("Region {} (0x{:X} - 0x{:X}) exceeded {:?}, abandoned", idx, start, end, self.region_timeout);
+                timed_out_regions.fetch_add(1, AtomicOrdering::Relaxed);
+            }
+
+            let completed = completed_regions.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+            let found_in_region = region_results.len() as i64;
+            total_found_count.fetch_add(found_in_region, AtomicOrdering::Relaxed);
+
+            // Update shared buffer progress if set.
+            if self.shared_buffer.is_set() {
+                let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                let total_found = total_found_count.load(AtomicOrdering::Relaxed);
+                self.shared_buffer.update_progress(progress, completed as i32, total_found);
+            }
+
+            Some(region_results)
+        };
+
+        let mut results = if self.stop_after_first_hit_region {
+            scan_until_first_hit(&self.regions, scan_region)
+        } else {
+            // 取消后 while_some 停止分派剩余区域，已在扫描的区域在下一个块边界返回
+            self.regions
+                .par_iter()
+                .enumerate()
+                .map(|(idx, region)| scan_region(idx, region))
+                .while_some()
+                .reduce(Vec::new, |mut a, mut b| {
+                    a.append(&mut b);
+                    a
+                })
+        };
+
+        results.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
+        results.dedup();
+
+        let cancelled = cancelled.into_inner();
+        if let Some(gap) = self.verify_gap
+            && !cancelled
+        {
+            results = verify_results(results, gap, &search_region);
+        }
+
+        ScannedResults {
+            results,
+            cancelled,
+            completed_regions: completed_regions.into_inner(),
+            failed_regions: failed_regions.into_inner(),
+            timed_out_regions: timed_out_regions.into_inner(),
+        }
+    }
+}
+
+/// 按区域顺序逐个扫描，返回第一个有结果的区域的结果，之后的区域不再扫描
+///
+/// `scan_region` 返回 None 表示搜索已取消
+fn scan_until_first_hit<F>(regions: &[(u64, u64)], scan_region: F) -> Vec<ValuePair>
+where
+    F: Fn(usize, &(u64, u64)) -> Option<Vec<ValuePair>>,
+{
+    for (idx, region) in regions.iter().enumerate() {
+        match scan_region(idx, region) {
+            Some(results) if !results.is_empty() => {
+                debug!("Region {} has {} results, skipping the remaining regions", idx, results.len());
+                return results;
+            },
+            Some(_) => {},
+            None => break,
+        }
+    }
+    Vec::new()
+}
+
+/// 合并结果时使用的间距：组搜索的结果按组出现，间距取组的范围，保证同一组落在同一段内
+fn verify_gap(query: &SearchQuery) -> u64 {
+    if query.values.len() > 1 { query.range as u64 } else { 0 }
+}
+
+/// 扫描后的校验：把相距不超过 `gap` 的结果合并成一段，用 `search_region` 重新搜索这些段，
+/// 只保留再次被找到的结果。`results` 须按地址排序
+///
+/// 某一段重新搜索失败时无法判断，保留该段原有的结果
+fn verify_results<S>(results: Vec<ValuePair>, gap: u64, search_region: &S) -> Vec<ValuePair>
+where
+    S: Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync,
+{
+    let mut spans: Vec<(u64, u64)> = Vec::new();
+    for pair in &results {
+        let end = pair.addr.saturating_add(pair.value_type.size() as u64);
+        match spans.last_mut() {
+            Some(last) if pair.addr <= last.1.saturating_add(gap) => last.1 = last.1.max(end),
+            _ => spans.push((pair.addr, end)),
+        }
+    }
+
+    let not_cancelled = || false;
+    let mut confirmed: Vec<ValuePair> = spans
+        .par_iter()
+        .flat_map_iter(|&(start, end)| match search_region(start, end, &not_cancelled) {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("Failed to verify 0x{:X} - 0x{:X}, keeping its results: {:?}", start, end, e);
+                let from = results.partition_point(|pair| pair.addr < start);
+                let to = results.partition_point(|pair| pair.addr < end);
+                results[from..to].to_vec()
+            },
+        })
+        .collect();
+    confirmed.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
+
+    let before = results.len();
+    let verified: Vec<ValuePair> = results
+        .into_iter()
+        .filter(|pair| confirmed.binary_search_by(|c| c.addr.cmp(&pair.addr)).is_ok())
+        .collect();
+    debug!("Post-scan verification: {} -> {} results", before, verified.len());
+    verified
+}
+
+/// 在实时进程中搜索单个区域，每个区域单独获取一次驱动读锁，见 [`search_region_in`]
+fn region_searcher<'a>(
+    query: &'a SearchQuery,
+    use_deep_search: bool,
+    chunk_size: usize,
+    progress: Option<&'a ScanProgress>,
+) -> impl Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync + 'a {
+    move |start, end, check_cancelled| {
+        let driver_manager = driver_manager_read();
+        search_region_in(&*driver_manager, query, use_deep_search, chunk_size, start, end, check_cancelled, progress)
+    }
+}
+
+/// 在 `source` 上搜索单个区域，按查询类型分派到单值、组或深度组搜索
+/// 设置了对象对齐时单值搜索只保留网格上的结果
+///
+/// 每读取一个块，把块内新覆盖到的区域字节数计入 `progress`，块之间的重叠部分不重复计算
+#[allow(clippy::too_many_arguments)]
+fn search_region_in<M>(
+    source: &M,
+    query: &SearchQuery,
+    use_deep_search: bool,
+    chunk_size: usize,
+    start: u64,
+    end: u64,
+    check_cancelled: &dyn Fn() -> bool,
+    progress: Option<&ScanProgress>,
+) -> Result<Vec<ValuePair>>
+where
+    M: MemorySource + ?Sized,
+{
+    let mut covered = start;
+    let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+        if let Some(progress) = progress {
+            let chunk_end = addr.saturating_add(buf.len() as u64).min(end);
+            if chunk_end > covered {
+                progress.add(chunk_end - covered);
+                covered = chunk_end;
+            }
+        }
+        source.read(addr, buf, page_status)
+    };
+
+    if query.values.len() > 1 {
+        if use_deep_search {
+            group_search::search_region_group_deep_with(query, start, end, chunk_size, read, &check_cancelled)
+        } else {
+            group_search::search_region_group_with(query, start, end, chunk_size, usize::MAX, read, &check_cancelled)
+        }
+    } else {
+        let mut results = single_search::search_region_single_with(&query.values[0], start, end, chunk_size, read, &check_cancelled)?;
+        if query.has_object_grid() {
+            results.retain(|pair| query.on_object_grid(pair.addr));
+        }
+        Ok(results)
+    }
+}
+
+/// 按查询类型分派到单值或组的顺序扫描，累计 `n` 个结果后返回
+///
+/// 单值查询在计数前按对象对齐网格过滤，组搜索在匹配时已经检查网格
+pub(crate) fn find_first_n_in<R>(query: &SearchQuery, regions: &[(u64, u64)], chunk_size: usize, n: usize, read: R) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    if query.values.len() == 1 {
+        let target = &query.values[0];
+        let alignment = target.value_type().alignment();
+        single_search::find_first_n_single_where(target, regions, chunk_size, n, alignment, |addr| query.on_object_grid(addr), read)
+    } else {
+        group_search::find_first_n_group(query, regions, chunk_size, n, read)
+    }
 }
 
 /// Search engine manager with async support.
@@ -70,6 +614,34 @@ pub struct SearchEngineManager {
     search_handle: Option<JoinHandle<()>>,
     /// 兼容模式：所有搜索结果都以模糊搜索格式存储，支持精确搜索和模糊搜索互相切换
     compatibility_mode: bool,
+    /// 采样类功能使用的随机种子，None 时每次随机
+    sampling_seed: Option<u64>,
+    /// 是否在搜索时记录区域表，供结果查询所属区域
+    region_tagging: bool,
+    /// 最近一次搜索的区域表，未开启 region_tagging 时为 None
+    region_tags: Option<RegionTags>,
+    /// 同步搜索最多保留的结果数，None 表示不限制
+    max_results: Option<usize>,
+    /// 单个区域的软超时，超时后放弃该区域继续搜索其它区域，None 表示不限制
+    region_timeout: Option<Duration>,
+    /// 上一次异步搜索的区域统计，见 [`Self::last_async_scan_stats`]
+    async_scan_stats: Option<ScanStats>,
+    /// 命名检查点，见 [`Self::checkpoint`]
+    checkpoints: HashMap<String, Checkpoint>,
+    /// 同步搜索的结果缓存
+    query_cache: QueryCache,
+    /// 同步搜索正在扫描，见 [`Self::search_memory_shared`]，由 [`SearchActiveGuard`] 复位
+    sync_search_active: Arc<AtomicBool>,
+    /// 用户备注，随会话存档保存
+    notes: String,
+    /// 结果批量写入的分批大小，见 [`Self::set_insert_batch_size`]
+    insert_batch_size: usize,
+    /// 各内存访问模式的扫描统计，见 [`Self::recommend_memory_mode`]
+    mode_stats: ModeStatsBook,
+    /// 统计文件，初始化后为缓存目录下的 [`MODE_STATS_FILE`]
+    mode_stats_path: Option<PathBuf>,
+    /// 统计归属的目标包名，None 时不记录
+    stats_target: Option<String>,
 }
 
 impl SearchEngineManager {
@@ -82,7 +654,150 @@ impl SearchEngineManager {
             cancel_token: None,
             search_handle: None,
             compatibility_mode: false,
+            sampling_seed: None,
+            region_tagging: false,
+            region_tags: None,
+            max_results: None,
+            region_timeout: None,
+            async_scan_stats: None,
+            checkpoints: HashMap::new(),
+            query_cache: QueryCache::new(DEFAULT_QUERY_CACHE_CAPACITY),
+            sync_search_active: Arc::new(AtomicBool::new(false)),
+            notes: String::new(),
+            insert_batch_size: DEFAULT_INSERT_BATCH_SIZE,
+            mode_stats: ModeStatsBook::default(),
+            mode_stats_path: None,
+            stats_target: None,
+        }
+    }
+
+    /// Fix the RNG seed used by sampling-based features, making them reproducible.
+    pub fn set_sampling_seed(&mut self, seed: u64) {
+        self.sampling_seed = Some(seed);
+    }
+
+    /// Restore random seeding for sampling-based features.
+    pub fn clear_sampling_seed(&mut self) {
+        self.sampling_seed = None;
+    }
+
+    /// 每次调用都返回新的 RNG，固定种子时多次调用得到相同序列
+    fn sampling_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.sampling_seed.unwrap_or_else(rand::random))
+    }
+
+    /// 开启后每次搜索都会记录区域表，结果可通过 [`Self::get_results_tagged`] 查询所属区域
+    pub fn set_region_tagging(&mut self, enabled: bool) {
+        self.region_tagging = enabled;
+        if !enabled {
+            self.region_tags = None;
+        }
+    }
+
+    pub(crate) fn record_region_tags(&mut self, regions: &[(u64, u64)]) {
+        self.region_tags = self.region_tagging.then(|| RegionTags::new(regions));
+    }
+
+    /// 地址在最近一次搜索中所属区域的编号（搜索时传入区域列表的下标）
+    pub fn get_result_region(&self, addr: u64) -> Option<u32> {
+        self.region_tags.as_ref().and_then(|tags| tags.region_of(addr))
+    }
+
+    /// 与 [`Self::get_results`] 相同，并附带每条结果的区域编号；未开启区域标记时均为 None
+    pub fn get_results_tagged(&self, start: usize, size: usize) -> Result<Vec<(SearchResultItem, Option<u32>)>> {
+        let results = self.get_results(start, size)?;
+
+        Ok(results
+            .into_iter()
+            .map(|item| {
+                let region = self.get_result_region(item.address());
+                (item, region)
+            })
+            .collect())
+    }
+
+    /// 设置同步搜索的结果上限，超出部分按地址顺序丢弃，并在 [`SearchOutcome::truncated`] 中标记
+    pub fn set_max_results(&mut self, max_results: Option<usize>) {
+        self.max_results = max_results;
+    }
+
+    /// 设置单个区域的软超时，防止驱动读取卡住的区域拖住整个搜索
+    ///
+    /// 超时在块之间检查，正在进行的读取不会被打断
+    pub fn set_region_timeout(&mut self, timeout: Option<Duration>) {
+        self.region_timeout = timeout;
+    }
+
+    /// 上一次完成的异步搜索中各区域的处理情况，搜索进行中或被取消时为 None
+    pub fn last_async_scan_stats(&self) -> Option<ScanStats> {
+        self.async_scan_stats
+    }
+
+    /// 设置最多缓存几次查询的结果，0 表示关闭缓存（默认）
+    ///
+    /// 缓存只在本应用写入内存后失效，目标进程自己修改的内存不会被察觉，开启后可能返回旧结果
+    pub fn set_query_cache_capacity(&mut self, capacity: usize) {
+        self.query_cache.set_capacity(capacity);
+    }
+
+    /// 设置结果写入存储时的分批大小，0 表示每个区域的结果整批写入
+    ///
+    /// 分批越大，追加越接近一次内存拷贝，但单批占用的临时内存也越多
+    pub fn set_insert_batch_size(&mut self, size: usize) {
+        self.insert_batch_size = size;
+        if let Some(result_mgr) = self.result_manager.as_mut() {
+            result_mgr.set_insert_batch_size(size);
+        }
+    }
+
+    /// 设置扫描统计归属的目标包名，切换目标进程时调用；None 时不再记录
+    pub fn set_stats_target(&mut self, package: Option<String>) {
+        self.stats_target = package;
+    }
+
+    /// 为当前目标记录一次以 `mode` 完成的扫描，初始化后同时写入统计文件
+    pub fn record_scan_stats(&mut self, mode: MemoryAccessMode, sample: ScanSample) {
+        let Some(target) = self.stats_target.as_deref() else {
+            return;
+        };
+        self.mode_stats.record(target, mode, &sample);
+
+        if let Some(path) = self.mode_stats_path.as_deref()
+            && let Err(e) = self.mode_stats.save(path)
+        {
+            warn!("Failed to save mode stats: {:?}", e);
+        }
+    }
+
+    /// 当前目标历史上吞吐最高、出错率不超过 [`DEFAULT_MAX_FAULT_RATE`] 的内存访问模式
+    ///
+    /// 没有目标或统计不足时返回驱动的默认模式 [`MemoryAccessMode::None`]
+    pub fn recommend_memory_mode(&self) -> MemoryAccessMode {
+        self.stats_target
+            .as_deref()
+            .and_then(|target| self.mode_stats.recommend(target, DEFAULT_MAX_FAULT_RATE))
+            .unwrap_or(MemoryAccessMode::None)
+    }
+
+    /// 把一次读取驱动内存的同步搜索记入当前访问模式的统计，命中缓存或被取消的搜索不计
+    fn record_driver_scan(&mut self, progress: &ScanProgress, outcome: &SearchOutcome) {
+        if outcome.cache_hit || outcome.cancelled || self.stats_target.is_none() {
+            return;
         }
+
+        let mode = driver_manager_read().get_access_mode();
+        let sample = ScanSample {
+            bytes: progress.bytes_done(),
+            elapsed: outcome.elapsed,
+            regions: outcome.stats.regions_total,
+            failed_regions: outcome.stats.regions_failed,
+        };
+        self.record_scan_stats(mode, sample);
+    }
+
+    /// 丢弃所有缓存的查询结果
+    pub fn clear_query_cache(&mut self) {
+        self.query_cache.clear();
     }
 
     /// Set compatibility mode
@@ -98,17 +813,32 @@ impl SearchEngineManager {
     }
 
     /// Sets the shared buffer for progress communication.
+    ///
+    /// 搜索期间扫描线程持有旧缓冲区的指针，此时拒绝替换，返回 false
     pub fn set_shared_buffer(&mut self, ptr: *mut u8, len: usize) -> bool {
+        if self.is_searching() {
+            warn!("Cannot replace the shared buffer while a search is running");
+            return false;
+        }
         self.shared_buffer.set(ptr, len)
     }
 
     /// Clears the shared buffer.
-    pub fn clear_shared_buffer(&mut self) {
+    ///
+    /// 搜索期间扫描线程仍在写入缓冲区，此时拒绝清除，避免 Kotlin 侧提前释放
+    pub fn clear_shared_buffer(&mut self) -> Result<()> {
+        if self.is_searching() {
+            return Err(anyhow!("Cannot clear the shared buffer while a search is running"));
+        }
         self.shared_buffer.clear();
+        Ok(())
     }
 
     /// Checks if a search is currently running.
     pub fn is_searching(&self) -> bool {
+        if self.sync_search_active.load(AtomicOrdering::Acquire) {
+            return true;
+        }
         if let Some(ref handle) = self.search_handle {
             !handle.is_finished()
         } else {
@@ -124,13 +854,29 @@ impl SearchEngineManager {
     }
 
     pub fn init(&mut self, memory_buffer_size: usize, cache_dir: String, chunk_size: usize) -> Result<()> {
+        self.init_with_store(memory_buffer_size, cache_dir, chunk_size, ResultStoreKind::default())
+    }
+
+    /// Initializes with an explicit storage backend for exact results.
+    ///
+    /// With [`ResultStoreKind::Persistent`], results last committed to `cache_dir` are recovered.
+    pub fn init_with_store(&mut self, memory_buffer_size: usize, cache_dir: String, chunk_size: usize, store: ResultStoreKind) -> Result<()> {
         if self.result_manager.is_some() {
             warn!("SearchEngineManager already initialized, reinitializing...");
         }
 
         let cache_path = PathBuf::from(cache_dir);
-        self.result_manager = Some(SearchResultManager::new(memory_buffer_size, cache_path));
-        self.chunk_size = if chunk_size == 0 { 512 * 1024 } else { chunk_size };
+        let mode_stats_path = cache_path.join(MODE_STATS_FILE);
+        self.mode_stats = ModeStatsBook::load(&mode_stats_path).unwrap_or_else(|e| {
+            warn!("Discarding unreadable mode stats: {:?}", e);
+            ModeStatsBook::default()
+        });
+        self.mode_stats_path = Some(mode_stats_path);
+
+        let mut result_manager = SearchResultManager::with_store(memory_buffer_size, cache_path, store)?;
+        result_manager.set_insert_batch_size(self.insert_batch_size);
+        self.result_manager = Some(result_manager);
+        self.chunk_size = if chunk_size == 0 { 512 * 1024 } else { page_aligned_chunk_size(chunk_size) };
 
         Ok(())
     }
@@ -139,6 +885,13 @@ impl SearchEngineManager {
         self.result_manager.is_some()
     }
 
+    /// Returns the storage backend used for exact results.
+    pub fn get_store_kind(&self) -> Result<ResultStoreKind> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        Ok(result_mgr.store_kind())
+    }
+
     /// Starts an async memory search. Returns immediately.
     /// Progress and status are communicated via the shared buffer.
     ///
@@ -162,6 +915,7 @@ impl SearchEngineManager {
             .result_manager
             .as_mut()
             .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
+        result_mgr.clear_history();
 
         // Check if we need to convert fuzzy results to exact results
         if keep_results && result_mgr.get_mode() == SearchResultMode::Fuzzy {
@@ -191,17 +945,22 @@ impl SearchEngineManager {
         self.shared_buffer.reset();
         self.shared_buffer.clear_cancel_flag();
         self.shared_buffer.write_status(SearchStatus::Searching);
+        self.async_scan_stats = None;
 
         // Create new cancellation token.
         let cancel_token = CancellationToken::new();
         self.cancel_token = Some(cancel_token.clone());
 
+        self.record_region_tags(&regions);
+
         let chunk_size = self.chunk_size;
         let compatibility_mode = self.compatibility_mode;
+        let region_timeout = self.region_timeout;
+        let regions = sanitize_regions(regions);
 
         // Spawn async search task.
         let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_search_task(query, regions, use_deep_search, chunk_size, compatibility_mode, cancel_token).await;
+            Self::run_search_task(query, regions, use_deep_search, chunk_size, compatibility_mode, region_timeout, cancel_token).await;
         });
 
         self.search_handle = Some(handle);
@@ -215,11 +974,11 @@ impl SearchEngineManager {
         use_deep_search: bool,
         chunk_size: usize,
         compatibility_mode: bool,
+        region_timeout: Option<Duration>,
         cancel_token: CancellationToken,
     ) {
         let start_time = Instant::now();
         let total_regions = regions.len();
-        let is_group_search = query.values.len() > 1;
 
         if log_enabled!(Level::Debug) {
             debug!(
@@ -236,97 +995,106 @@ impl SearchEngineManager {
 
         // Shared state for progress tracking.
         let completed_regions = Arc::new(AtomicUsize::new(0));
+        let failed_regions = Arc::new(AtomicUsize::new(0));
+        let timed_out_regions = Arc::new(AtomicUsize::new(0));
         let total_found_count = Arc::new(AtomicI64::new(0));
         let cancelled = Arc::new(AtomicBool::new(false));
 
         // Clone for the blocking task.
         let completed_regions_clone = Arc::clone(&completed_regions);
+        let failed_regions_clone = Arc::clone(&failed_regions);
+        let timed_out_regions_clone = Arc::clone(&timed_out_regions);
         let total_found_clone = Arc::clone(&total_found_count);
         let cancelled_clone = Arc::clone(&cancelled);
         let cancel_token_clone = cancel_token.clone();
 
         // Run the CPU-intensive search in a blocking task with rayon.
         let search_result = tokio::task::spawn_blocking(move || {
-            let mut all_results: Vec<_> = regions
-                .par_iter()
-                .enumerate()
-                .filter_map(|(idx, (start, end))| {
-                    // Check cancellation from both CancellationToken and shared buffer.
-                    if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
+            let search_region = region_searcher(&query, use_deep_search, chunk_size, None);
+            let scan_region = |idx: usize, (start, end): &(u64, u64)| {
+                // Check cancellation from both CancellationToken and shared buffer.
+                if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
+                    cancelled_clone.store(true, AtomicOrdering::Relaxed);
+                    return None;
+                }
+
+                // Check cancel flag from shared buffer.
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    if manager.shared_buffer.is_cancel_requested() {
                         cancelled_clone.store(true, AtomicOrdering::Relaxed);
                         return None;
                     }
+                }
+
+                // if log_enabled!(Level::Debug) {
+                //     debug!("Searching region {}: 0x{:X} - 0x{:X}", idx, start, end);
+                // }
 
-                    // Check cancel flag from shared buffer.
+                // Create a cancel check closure for group search.
+                // This closure also sets cancelled_clone to propagate cancellation to other parallel tasks.
+                let region_timer = RegionTimer::start(region_timeout);
+                let check_cancelled_for_region = || -> bool {
+                    if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
+                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
+                        return true;
+                    }
                     if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
                         if manager.shared_buffer.is_cancel_requested() {
                             cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return None;
+                            return true;
                         }
                     }
+                    region_timer.expired()
+                };
 
-                    // if log_enabled!(Level::Debug) {
-                    //     debug!("Searching region {}: 0x{:X} - 0x{:X}", idx, start, end);
-                    // }
+                let result = search_region(*start, *end, &check_cancelled_for_region);
 
-                    // Create a cancel check closure for deep search.
-                    // This closure also sets cancelled_clone to propagate cancellation to other parallel tasks.
-                    let check_cancelled_for_region = || -> bool {
-                        if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                            cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return true;
-                        }
-                        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                            if manager.shared_buffer.is_cancel_requested() {
-                                cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                                return true;
-                            }
-                        }
-                        false
-                    };
+                if region_timer.timed_out() {
+                    warn!("Region {} (0x{:X} - 0x{:X}) exceeded {:?}, abandoned", idx, start, end, region_timeout);
+                    timed_out_regions_clone.fetch_add(1, AtomicOrdering::Relaxed);
+                }
 
-                    let result = if is_group_search {
-                        if use_deep_search {
-                            // Use cancellable version for deep search.
-                            group_search::search_region_group_deep_with_cancel(&query, *start, *end, chunk_size, &check_cancelled_for_region)
-                        } else {
-                            group_search::search_region_group(&query, *start, *end, chunk_size)
-                        }
-                    } else {
-                        single_search::search_region_single(&query.values[0], *start, *end, chunk_size)
-                    };
+                let region_results = match result {
+                    Ok(results) => results,
+                    Err(e) => {
+                        error!("Failed to search region {}: {:?}", idx, e);
+                        failed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed);
+                        Vec::new()
+                    },
+                };
 
-                    let region_results = match result {
-                        Ok(results) => results,
-                        Err(e) => {
-                            error!("Failed to search region {}: {:?}", idx, e);
-                            Vec::new()
-                        },
-                    };
+                // Update progress counters.
+                let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+                let found_in_region = region_results.len() as i64;
+                let total_found = total_found_clone.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
 
-                    // Update progress counters.
-                    let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
-                    let found_in_region = region_results.len() as i64;
-                    let total_found = total_found_clone.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
+                // Update shared buffer with progress information.
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                    manager.shared_buffer.update_progress(progress, completed as i32, total_found);
+                    manager.shared_buffer.tick_heartbeat();
+                }
 
-                    // Update shared buffer with progress information.
-                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                        manager.shared_buffer.update_progress(progress, completed as i32, total_found);
-                        manager.shared_buffer.tick_heartbeat();
-                    }
+                if log_enabled!(Level::Debug) && completed % 100 == 0 {
+                    let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                    debug!("Search progress: {}% ({}/{})", progress, completed, total_regions);
+                }
 
-                    if log_enabled!(Level::Debug) && completed % 100 == 0 {
-                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                        debug!("Search progress: {}% ({}/{})", progress, completed, total_regions);
-                    }
+                Some(region_results)
+            };
 
-                    Some(region_results)
-                })
-                .reduce(Vec::new, |mut a, mut b| {
-                    a.append(&mut b);
-                    a
-                });
+            let mut all_results = if query.stop_after_first_hit_region {
+                scan_until_first_hit(&regions, scan_region)
+            } else {
+                regions
+                    .par_iter()
+                    .enumerate()
+                    .filter_map(|(idx, region)| scan_region(idx, region))
+                    .reduce(Vec::new, |mut a, mut b| {
+                        a.append(&mut b);
+                        a
+                    })
+            };
 
             let start = Instant::now();
             all_results.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
@@ -335,6 +1103,10 @@ impl SearchEngineManager {
                 info!("搜索排序去重复耗时: {:?}", start.elapsed())
             }
 
+            if query.verify_after_scan && !cancelled_clone.load(AtomicOrdering::Relaxed) {
+                all_results = verify_results(all_results, verify_gap(&query), &search_region);
+            }
+
             all_results
         })
         .await;
@@ -363,22 +1135,22 @@ impl SearchEngineManager {
                                 if let Err(e) = result_mgr.set_mode(SearchResultMode::Fuzzy) {
                                     error!("Failed to set mode: {:?}", e);
                                 }
-                                if let Ok(driver_manager) = DRIVER_MANAGER.read() {
-                                    let fuzzy_results: Vec<FuzzySearchResultItem> = all_results
-                                        .into_iter() // todo 可以并行吗?
-                                        .filter_map(|pair| {
-                                            let size = pair.value_type.size();
-                                            let mut buffer = vec![0u8; size];
-                                            if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-                                                Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .collect();
-                                    if let Err(e) = result_mgr.add_fuzzy_results_batch(fuzzy_results) {
-                                        error!("Failed to add fuzzy results: {:?}", e);
-                                    }
+                                let driver_manager = driver_manager_read();
+                                let fuzzy_results: Vec<FuzzySearchResultItem> = all_results
+                                    .into_iter() // todo 可以并行吗?
+                                    .filter_map(|pair| {
+                                        let size = pair.value_type.size();
+                                        let mut buffer = vec![0u8; size];
+                                        if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
+                                            Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
+                                        } else {
+                                            None
+                                        }
+                                    })
+                                    .collect();
+                                drop(driver_manager);
+                                if let Err(e) = result_mgr.add_fuzzy_results_batch(fuzzy_results) {
+                                    error!("Failed to add fuzzy results: {:?}", e);
                                 }
                             } else {
                                 // 标准模式：存储为精确搜索格式
@@ -404,6 +1176,14 @@ impl SearchEngineManager {
                             manager.shared_buffer.write_progress(100);
                             manager.shared_buffer.write_regions_done(total_regions as i32);
 
+                            let timed_out_regions = timed_out_regions.load(AtomicOrdering::Relaxed);
+                            manager.async_scan_stats = Some(ScanStats {
+                                regions_total: total_regions,
+                                regions_searched: completed_regions.load(AtomicOrdering::Relaxed) - timed_out_regions,
+                                regions_failed: failed_regions.load(AtomicOrdering::Relaxed),
+                                regions_timed_out: timed_out_regions,
+                            });
+
                             (final_count as i64, elapsed, true)
                         } else {
                             error!("result_manager is None when processing search results");
@@ -586,6 +1366,10 @@ impl SearchEngineManager {
                 match SEARCH_ENGINE_MANAGER.write() {
                     Ok(mut manager) => {
                         if let Some(ref mut result_mgr) = manager.result_manager {
+                            if let Err(e) = result_mgr.record_history() {
+                                warn!("Failed to record refine history: {:?}", e);
+                            }
+
                             // Clear and update results.
                             let _ = result_mgr.clear();
 
@@ -602,26 +1386,27 @@ impl SearchEngineManager {
                                     SearchResultMode::Fuzzy => {
                                         let _ = result_mgr.set_mode(SearchResultMode::Fuzzy);
                                         // Convert to FuzzySearchResultItem by reading current memory values
-                                        if let Ok(driver_manager) = DRIVER_MANAGER.read() {
-                                            let fuzzy_results: Vec<_> = refined_results
-                                                .into_iter() // todo 是否需要优化成并行的？
-                                                .filter_map(|pair| {
-                                                    let size = pair.value_type.size();
-                                                    let mut buffer = vec![0u8; size];
-                                                    if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-                                                        Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
-                                                    } else {
-                                                        None
-                                                    }
-                                                })
-                                                .collect();
-                                            let _ = result_mgr.add_fuzzy_results_batch(fuzzy_results);
-                                        }
+                                        let driver_manager = driver_manager_read();
+                                        let fuzzy_results: Vec<_> = refined_results
+                                            .into_iter() // todo 是否需要优化成并行的？
+                                            .filter_map(|pair| {
+                                                let size = pair.value_type.size();
+                                                let mut buffer = vec![0u8; size];
+                                                if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
+                                                    Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
+                                                } else {
+                                                    None
+                                                }
+                                            })
+                                            .collect();
+                                        drop(driver_manager);
+                                        let _ = result_mgr.add_fuzzy_results_batch(fuzzy_results);
                                     },
                                 }
                             } else {
                                 let _ = result_mgr.set_mode(original_mode);
                             }
+                            let _ = result_mgr.seal_history();
 
                             let elapsed = start_time.elapsed().as_millis() as u64;
                             let final_count = result_mgr.total_count();
@@ -667,12 +1452,41 @@ impl SearchEngineManager {
     /// # Parameters
     /// * `keep_results` - If true and currently in exact mode, convert exact results to fuzzy results
     pub fn start_fuzzy_search_async(&mut self, value_type: ValueType, regions: Vec<(u64, u64)>, keep_results: bool) -> Result<()> {
+        let Some((regions, cancel_token)) = self.begin_fuzzy_initial(value_type, regions, keep_results)? else {
+            return Ok(());
+        };
+
+        let chunk_size = self.chunk_size;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_fuzzy_initial_task(value_type, regions, chunk_size, cancel_token, true).await;
+        });
+
+        self.search_handle = Some(handle);
+        Ok(())
+    }
+
+    /// 模糊首次扫描的准备：校验状态、切换到模糊模式、重置共享缓冲区并创建取消令牌
+    ///
+    /// `keep_results` 时把现有精确结果直接转换为模糊结果，无需扫描，返回 None
+    fn begin_fuzzy_initial(
+        &mut self,
+        value_type: ValueType,
+        regions: Vec<(u64, u64)>,
+        keep_results: bool,
+    ) -> Result<Option<(Vec<(u64, u64)>, CancellationToken)>> {
         if !self.is_initialized() {
             self.shared_buffer.write_status(SearchStatus::Error);
             self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
             return Err(anyhow!("SearchEngineManager not initialized"));
         }
 
+        // 模糊结果只保存 8 字节的值
+        if value_type.size() > 8 {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!("Fuzzy search does not support {}", value_type));
+        }
+
         if self.is_searching() {
             self.shared_buffer.write_status(SearchStatus::Error);
             self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
@@ -684,13 +1498,14 @@ impl SearchEngineManager {
             .result_manager
             .as_mut()
             .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
+        result_mgr.clear_history();
 
         // Check if we need to convert exact results to fuzzy results
         if keep_results && result_mgr.get_mode() == SearchResultMode::Exact {
             let exact_results = result_mgr.get_all_exact_results()?;
             if !exact_results.is_empty() {
                 // Convert exact to fuzzy: need to read current values
-                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+                let driver_manager = driver_manager_read();
 
                 let mut fuzzy_results = Vec::with_capacity(exact_results.len());
                 for exact in exact_results {
@@ -716,7 +1531,7 @@ impl SearchEngineManager {
                 self.shared_buffer.write_status(SearchStatus::Completed);
                 self.shared_buffer.write_found_count(result_mgr.total_count() as i64);
                 self.shared_buffer.write_progress(100);
-                return Ok(());
+                return Ok(None);
             } else {
                 result_mgr.clear()?;
                 result_mgr.set_mode(SearchResultMode::Fuzzy)?;
@@ -734,18 +1549,19 @@ impl SearchEngineManager {
         let cancel_token = CancellationToken::new();
         self.cancel_token = Some(cancel_token.clone());
 
-        let chunk_size = self.chunk_size;
-
-        let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_fuzzy_initial_task(value_type, regions, chunk_size, cancel_token).await;
-        });
-
-        self.search_handle = Some(handle);
-        Ok(())
+        Ok(Some((sanitize_regions(regions), cancel_token)))
     }
 
     /// Internal async fuzzy initial scan task.
-    async fn run_fuzzy_initial_task(value_type: ValueType, regions: Vec<(u64, u64)>, chunk_size: usize, cancel_token: CancellationToken) {
+    ///
+    /// 成功且未取消时返回 true。`completes_search` 为 false 时成功后不写入 Completed，由后续步骤收尾
+    async fn run_fuzzy_initial_task(
+        value_type: ValueType,
+        regions: Vec<(u64, u64)>,
+        chunk_size: usize,
+        cancel_token: CancellationToken,
+        completes_search: bool,
+    ) -> bool {
         let start_time = Instant::now();
         let total_regions = regions.len();
 
@@ -835,7 +1651,7 @@ impl SearchEngineManager {
                 manager.shared_buffer.write_status(SearchStatus::Cancelled);
             }
             info!("Fuzzy initial scan cancelled");
-            return;
+            return false;
         }
 
         // Process results.
@@ -883,13 +1699,15 @@ impl SearchEngineManager {
 
         // Set status after releasing write lock.
         if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-            if success {
-                manager.shared_buffer.write_status(SearchStatus::Completed);
-            } else {
+            if !success {
                 manager.shared_buffer.write_status(SearchStatus::Error);
                 manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            } else if completes_search {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
             }
         }
+
+        success
     }
 
     /// Starts async fuzzy refine search.
@@ -1018,11 +1836,15 @@ impl SearchEngineManager {
                         if let Some(ref mut result_mgr) = manager.result_manager {
                             // Convert tree to vec and replace all results.
                             let refined_vec: Vec<_> = refined_tree.iter().cloned().collect();
+                            if let Err(e) = result_mgr.record_history() {
+                                warn!("Failed to record refine history: {:?}", e);
+                            }
 
                             if let Err(e) = result_mgr.replace_all_fuzzy_results(refined_vec) {
                                 error!("Failed to replace fuzzy results: {:?}", e);
                                 false
                             } else {
+                                let _ = result_mgr.seal_history();
                                 let elapsed = start_time.elapsed().as_millis() as u64;
                                 let final_count = result_mgr.total_count();
 
@@ -1062,6 +1884,8 @@ impl SearchEngineManager {
     }
 
     /// Legacy synchronous search method. Kept for backward compatibility.
+    ///
+    /// 只返回结果数量，需要截断、取消等信息时使用 [`Self::search_memory_outcome`]
     #[deprecated]
     pub fn search_memory(
         &mut self,
@@ -1070,76 +1894,254 @@ impl SearchEngineManager {
         use_deep_search: bool,
         callback: Option<Arc<dyn SearchProgressCallback>>,
     ) -> Result<usize> {
-        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        self.search_memory_outcome(query, regions, use_deep_search, callback)
+            .map(|outcome| outcome.count)
+    }
 
-        result_mgr.clear()?;
-        result_mgr.set_mode(SearchResultMode::Exact)?;
+    /// 同步搜索，返回结果数量以及是否截断、是否取消、耗时和区域统计
+    ///
+    /// 通过共享缓冲区的取消标志取消，取消前找到的结果仍会写入结果集。调用方在整个扫描期间独占管理器，
+    /// 需要在扫描期间查询进度或取消时使用 [`Self::search_memory_shared`]
+    ///
+    /// 没有有效区域时不扫描，结果集被清空，回调以 0 个结果、0 个区域触发
+    pub fn search_memory_outcome(
+        &mut self,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+    ) -> Result<SearchOutcome> {
+        let progress = ScanProgress::new(regions, callback.clone());
+        let search_region = region_searcher(query, use_deep_search, self.chunk_size, Some(&progress));
+        let outcome = self.search_memory_with(query, regions, use_deep_search, callback, search_region)?;
+        self.record_driver_scan(&progress, &outcome);
+        Ok(outcome)
+    }
 
-        let start_time = Instant::now();
+    /// 与 [`Self::search_memory_outcome`] 相同，但从任意 [`MemorySource`] 读取，例如模拟器的 RAM 或 core dump
+    ///
+    /// `regions` 为 None 时搜索 [`MemorySource::regions`] 返回的全部区域
+    pub fn search_memory_from<M>(
+        &mut self,
+        source: &M,
+        query: &SearchQuery,
+        regions: Option<&[(u64, u64)]>,
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+    ) -> Result<SearchOutcome>
+    where
+        M: MemorySource + ?Sized,
+    {
+        let source_regions;
+        let regions = match regions {
+            Some(regions) => regions,
+            None => {
+                source_regions = source.regions();
+                &source_regions
+            },
+        };
+
+        let chunk_size = self.chunk_size;
+        let progress = ScanProgress::new(regions, callback.clone());
+        let search_region = |start, end, check_cancelled: &dyn Fn() -> bool| {
+            search_region_in(source, query, use_deep_search, chunk_size, start, end, check_cancelled, Some(&progress))
+        };
+        self.search_memory_with(query, regions, use_deep_search, callback, search_region)
+    }
+
+    /// [`Self::search_memory_outcome`] 的实现，`search_region` 搜索单个区域 `[start, end)`
+    pub(crate) fn search_memory_with<S>(
+        &mut self,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+        search_region: S,
+    ) -> Result<SearchOutcome>
+    where
+        S: Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync,
+    {
+        self.search_memory_cancellable_with(query, regions, use_deep_search, callback, None, search_region)
+    }
+
+    /// 与 [`Self::search_memory_outcome`] 相同，另外在 `cancel` 被置为 true 后尽快停止
+    ///
+    /// 正在扫描的区域在下一个块边界返回已找到的结果，尚未开始的区域不再扫描。
+    /// 取消前找到的结果仍会写入结果集，返回的 [`SearchOutcome::cancelled`] 为 true
+    pub fn search_memory_cancellable(
+        &mut self,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<SearchOutcome> {
+        let progress = ScanProgress::new(regions, callback.clone());
+        let search_region = region_searcher(query, use_deep_search, self.chunk_size, Some(&progress));
+        let outcome = self.search_memory_cancellable_with(query, regions, use_deep_search, callback, Some(cancel), search_region)?;
+        self.record_driver_scan(&progress, &outcome);
+        Ok(outcome)
+    }
+
+    /// [`Self::search_memory_cancellable`] 的实现，`cancel` 为 None 时只响应管理器自身的取消
+    pub(crate) fn search_memory_cancellable_with<S>(
+        &mut self,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+        cancel: Option<Arc<AtomicBool>>,
+        search_region: S,
+    ) -> Result<SearchOutcome>
+    where
+        S: Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync,
+    {
+        let mut prepared = self.prepare_search(query, regions, use_deep_search)?;
+        prepared.cancel_flag = cancel;
+        let scanned = prepared.scan(search_region);
+        self.finish_search(prepared, scanned, callback)
+    }
+
+    /// 与 [`Self::search_memory_outcome`] 相同，但只在开始和结束时短暂持有 `manager` 的写锁
+    ///
+    /// 扫描期间其它线程可以通过读锁查询进度、调用 [`Self::request_cancel`]，
+    /// 此时 [`Self::is_searching`] 为 true，新的搜索会被拒绝
+    pub fn search_memory_shared<M>(
+        manager: &RwLock<M>,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+    ) -> Result<SearchOutcome>
+    where
+        M: BorrowMut<SearchEngineManager>,
+    {
+        let chunk_size = {
+            let guard = manager.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+            let manager: &SearchEngineManager = (*guard).borrow();
+            manager.chunk_size
+        };
+        let progress = ScanProgress::new(regions, callback.clone());
+        let search_region = region_searcher(query, use_deep_search, chunk_size, Some(&progress));
+
+        let outcome = Self::search_memory_shared_with(manager, query, regions, use_deep_search, callback, search_region)?;
+        if let Ok(mut guard) = manager.write() {
+            let manager: &mut SearchEngineManager = (*guard).borrow_mut();
+            manager.record_driver_scan(&progress, &outcome);
+        }
+        Ok(outcome)
+    }
+
+    /// [`Self::search_memory_shared`] 的实现，`search_region` 搜索单个区域 `[start, end)`
+    pub(crate) fn search_memory_shared_with<M, S>(
+        manager: &RwLock<M>,
+        query: &SearchQuery,
+        regions: &[(u64, u64)],
+        use_deep_search: bool,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+        search_region: S,
+    ) -> Result<SearchOutcome>
+    where
+        M: BorrowMut<SearchEngineManager>,
+        S: Fn(u64, u64, &dyn Fn() -> bool) -> Result<Vec<ValuePair>> + Sync,
+    {
+        let write_lock = || manager.write().map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"));
+
+        let prepared = {
+            let mut guard = write_lock()?;
+            let manager: &mut SearchEngineManager = (*guard).borrow_mut();
+            manager.prepare_search(query, regions, use_deep_search)?
+        };
+
+        // 扫描期间不持有锁
+        let scanned = prepared.scan(search_region);
+
+        let mut guard = write_lock()?;
+        let manager: &mut SearchEngineManager = (*guard).borrow_mut();
+        manager.finish_search(prepared, scanned, callback)
+    }
+
+    /// 同步搜索的准备阶段：校验状态、记录区域表、查询缓存，并取出扫描阶段需要的配置
+    fn prepare_search(&mut self, query: &SearchQuery, regions: &[(u64, u64)], use_deep_search: bool) -> Result<PreparedSearch> {
+        if self.result_manager.is_none() {
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        self.region_tags = self.region_tagging.then(|| RegionTags::new(regions));
+        let regions = sanitize_regions(regions.to_vec());
 
         debug!(
-            "Starting search: {} values, mode={:?}, range={}, regions={}, chunk_size={} KB, deep_search={}",
+            "Starting search: {} values, mode={:?}, range={}, regions={}, chunk_size={} KB, max_results={:?}",
             query.values.len(),
             query.mode,
             query.range,
             regions.len(),
             self.chunk_size / 1024,
-            use_deep_search
+            self.max_results
         );
 
-        let chunk_size = self.chunk_size;
-        let is_group_search = query.values.len() > 1;
-        let total_regions = regions.len();
-
-        let completed_regions = Arc::new(AtomicUsize::new(0));
-        let total_found_count = Arc::new(AtomicI64::new(0));
-
-        let mut all_results = regions
-            .par_iter()
-            .enumerate()
-            .map(|(idx, (start, end))| {
-                // if log_enabled!(Level::Debug) {
-                //     debug!("Searching region {}: 0x{:X} - 0x{:X}", idx, start, end);
-                // }
-
-                let result = if is_group_search {
-                    if use_deep_search {
-                        group_search::search_region_group_deep(query, *start, *end, chunk_size) // 废弃调用点
-                    } else {
-                        group_search::search_region_group(query, *start, *end, chunk_size) // 废弃调用点
-                    }
-                } else {
-                    single_search::search_region_single(&query.values[0], *start, *end, chunk_size) // 废弃调用点
-                };
-
-                let region_results = match result {
-                    Ok(results) => results,
-                    Err(e) => {
-                        error!("Failed to search region {}: {:?}", idx, e);
-                        Vec::new()
-                    },
-                };
+        // 相同查询、相同区域且内存未被写入过，直接复用上一次的结果
+        let cache_key = QueryCacheKey::new(query, &regions, use_deep_search);
+        let cached = self.query_cache.get(&cache_key);
 
-                let completed = completed_regions.fetch_add(1, AtomicOrdering::Relaxed) + 1;
-                let found_in_region = region_results.len() as i64;
-                total_found_count.fetch_add(found_in_region, AtomicOrdering::Relaxed);
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        let active = SearchActiveGuard::activate(&self.sync_search_active);
+
+        Ok(PreparedSearch {
+            regions,
+            cache_key,
+            cached,
+            region_timeout: self.region_timeout,
+            verify_gap: query.verify_after_scan.then(|| verify_gap(query)),
+            stop_after_first_hit_region: query.stop_after_first_hit_region,
+            shared_buffer: self.shared_buffer.clone(),
+            cancel_token,
+            cancel_flag: None,
+            start_time: Instant::now(),
+            active,
+        })
+    }
 
-                // Update shared buffer progress if set.
-                if self.shared_buffer.is_set() {
-                    let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                    let total_found = total_found_count.load(AtomicOrdering::Relaxed);
-                    self.shared_buffer.update_progress(progress, completed as i32, total_found);
-                }
+    /// 同步搜索的收尾阶段：写入缓存和结果集，回调并生成 [`SearchOutcome`]
+    fn finish_search(
+        &mut self,
+        prepared: PreparedSearch,
+        scanned: ScannedResults,
+        callback: Option<Arc<dyn SearchProgressCallback>>,
+    ) -> Result<SearchOutcome> {
+        drop(prepared.active);
+
+        let ScannedResults {
+            results: mut all_results,
+            cancelled,
+            completed_regions,
+            failed_regions,
+            timed_out_regions,
+        } = scanned;
+        let cache_hit = prepared.cached.is_some();
+
+        // 不完整的结果不缓存
+        if !cache_hit && !cancelled && failed_regions == 0 && timed_out_regions == 0 {
+            self.query_cache.insert(prepared.cache_key, all_results.clone());
+        }
 
-                region_results
-            })
-            .reduce(Vec::new, |mut a, mut b| {
-                a.append(&mut b);
-                a
-            });
+        let truncated = match self.max_results {
+            Some(max) if all_results.len() > max => {
+                warn!("Search found {} results, truncated to {}", all_results.len(), max);
+                all_results.truncate(max);
+                true
+            },
+            _ => false,
+        };
 
-        all_results.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
-        all_results.dedup();
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        result_mgr.clear()?;
+        result_mgr.clear_history();
+        result_mgr.set_mode(SearchResultMode::Exact)?;
 
         let converted_results: Vec<_> = all_results
             .into_iter()
@@ -1147,18 +2149,36 @@ impl SearchEngineManager {
             .collect();
         result_mgr.add_results_batch(converted_results)?;
 
-        let elapsed = start_time.elapsed().as_millis() as u64;
+        let elapsed = prepared.start_time.elapsed();
         let final_count = result_mgr.total_count();
 
         if log_enabled!(Level::Debug) {
-            info!("Search completed: {} results in {} ms", final_count, elapsed);
+            info!(
+                "Search completed: {} results in {} ms (cancelled={}, cache_hit={})",
+                final_count,
+                elapsed.as_millis(),
+                cancelled,
+                cache_hit
+            );
         }
 
         if let Some(ref cb) = callback {
-            cb.on_search_complete(final_count, regions.len(), elapsed);
+            cb.on_search_complete(final_count, prepared.regions.len(), elapsed.as_millis() as u64);
         }
 
-        Ok(final_count)
+        Ok(SearchOutcome {
+            count: final_count,
+            truncated,
+            cancelled,
+            cache_hit,
+            elapsed,
+            stats: ScanStats {
+                regions_total: prepared.regions.len(),
+                regions_searched: completed_regions - timed_out_regions,
+                regions_failed: failed_regions,
+                regions_timed_out: timed_out_regions,
+            },
+        })
     }
 
     pub fn get_results(&self, start: usize, size: usize) -> Result<Vec<SearchResultItem>> {
@@ -1173,9 +2193,62 @@ impl SearchEngineManager {
         Ok(result_mgr.total_count())
     }
 
+    /// 把 `[start, start + size)` 的结果打包成一个字节块，布局见 [`crate::search::result_manager::packed`]
+    ///
+    /// 应用当前过滤器；精确结果的值通过驱动实时读取
+    pub fn pack_results(&self, start: usize, size: usize) -> Result<Vec<u8>> {
+        let driver_manager = driver_manager_read();
+
+        self.pack_results_with(start, size, |addr, buf| driver_manager.read_memory_unified(addr, buf, None).is_ok())
+    }
+
+    /// [`Self::pack_results`] 的实现，`read` 读取精确结果的当前值并返回是否成功
+    pub(crate) fn pack_results_with<F>(&self, start: usize, size: usize, read: F) -> Result<Vec<u8>>
+    where
+        F: Fn(u64, &mut [u8]) -> bool,
+    {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        let results = result_mgr.get_results(start, size)?;
+        let mut writer = PackedResultWriter::new(result_mgr.get_mode(), results.len());
+        let mut buffer = [0u8; 8];
+
+        for (offset, item) in results.into_iter().enumerate() {
+            let index = (start + offset) as u64;
+            match item {
+                SearchResultItem::Exact(exact) => {
+                    let (address, typ) = (exact.address, exact.typ);
+                    if !self.filter.matches(address, typ) {
+                        continue;
+                    }
+                    let value = &mut buffer[..typ.size().min(8)];
+                    let ok = read(address, value);
+                    writer.push(index, address, typ, ok.then_some(&*value));
+                },
+                SearchResultItem::Fuzzy(fuzzy) => {
+                    let (address, typ, value) = (fuzzy.address, fuzzy.value_type, fuzzy.value);
+                    if !self.filter.matches(address, typ) {
+                        continue;
+                    }
+                    writer.push(index, address, typ, Some(&value[..typ.size().min(8)]));
+                },
+            }
+        }
+
+        Ok(writer.finish())
+    }
+
+    /// 当前结果集的内存/磁盘占用
+    pub fn get_memory_usage(&self) -> Result<MemoryUsage> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        Ok(result_mgr.memory_usage())
+    }
+
     pub fn clear_results(&mut self) -> Result<()> {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
+        result_mgr.clear_history();
         result_mgr.clear()
     }
 
@@ -1197,65 +2270,937 @@ impl SearchEngineManager {
         result_mgr.keep_only_results(keep_indices)
     }
 
-    pub fn set_result_mode(&mut self, mode: SearchResultMode) -> Result<()> {
+    /// 只保留地址同样出现在 `other` 结果集中的结果（两次独立搜索求交集），返回剩余数量
+    pub fn intersect_results_with(&mut self, other: &SearchEngineManager) -> Result<usize> {
+        let other_mgr = other.result_manager.as_ref().ok_or_else(|| anyhow!("Other SearchEngineManager not initialized"))?;
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        result_mgr.set_mode(mode)
+        result_mgr.record_history()?;
+        let remaining = result_mgr.intersect_with(other_mgr)?;
+        result_mgr.seal_history()?;
+        Ok(remaining)
     }
 
-    pub fn add_results_batch(&mut self, results: Vec<SearchResultItem>) -> Result<()> {
+    /// 按模块偏移把结果迁移到重启后进程的模块布局，返回剩余数量
+    pub fn rebase_results(&mut self, old_modules: &[VmStaticData], new_modules: &[VmStaticData]) -> Result<usize> {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        result_mgr.add_results_batch(results)
+        // 旧布局下的地址在新进程里没有意义
+        result_mgr.clear_history();
+        result_mgr.rebase(old_modules, new_modules)
     }
 
-    pub fn set_filter(
-        &mut self,
-        enable_address_filter: bool,
-        address_start: u64,
-        address_end: u64,
-        enable_type_filter: bool,
-        type_ids: Vec<i32>,
-    ) -> Result<()> {
-        self.filter.enable_address_filter = enable_address_filter;
-        self.filter.address_start = address_start;
-        self.filter.address_end = address_end;
-
-        self.filter.enable_type_filter = enable_type_filter;
-        self.filter.type_ids = type_ids.iter().filter_map(|&id| ValueType::from_id(id)).collect();
+    /// 把所有结果改按 `new_type` 解释，不重新扫描，见 [`SearchResultManager::reinterpret_type`]
+    pub fn reinterpret_result_type(&mut self, new_type: ValueType) -> Result<()> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        Ok(())
+        // 撤销记录中的结果仍是旧类型
+        result_mgr.clear_history();
+        result_mgr.reinterpret_type(new_type)
     }
 
-    pub fn clear_filter(&mut self) -> Result<()> {
-        self.filter.clear();
-        Ok(())
-    }
+    /// 按地址取回结果，用于同步监视表，见 [`SearchResultManager::get_results_for_addrs`]
+    pub fn get_results_for_addrs(&self, addrs: &[u64]) -> Result<Vec<Option<SearchResultItem>>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-    pub fn get_filter(&self) -> &SearchFilter {
-        &self.filter
+        result_mgr.get_results_for_addrs(addrs)
     }
 
-    pub fn get_current_mode(&self) -> Result<SearchResultMode> {
+    /// 按与 `ref_addr` 的距离从近到远返回所有结果，找到一个已知结构后用来发现同一对象的其它字段
+    pub fn sort_by_distance_to(&self, ref_addr: u64) -> Result<Vec<SearchResultItem>> {
         let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        Ok(result_mgr.get_mode())
+        result_mgr.sort_by_distance_to(ref_addr)
     }
 
-    /// Legacy synchronous refine search method.
-    #[deprecated]
-    pub fn refine_search(&mut self, query: &SearchQuery, callback: Option<Arc<dyn SearchProgressCallback>>) -> Result<usize> {
+    /// 把值相近的结果聚成簇，例如找出同一队伍所有成员的血量，见 [`SearchResultManager::cluster_by_value`]
+    pub fn cluster_by_value(&self, tolerance: f64) -> Result<Vec<Vec<usize>>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.cluster_by_value(tolerance)
+    }
+
+    /// 撤销最近一次细化或比较，恢复之前的结果集；没有可撤销的步骤时返回 false
+    pub fn undo_refine(&mut self) -> Result<bool> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.undo_refine()
+    }
+
+    /// 可撤销的细化步数
+    pub fn refine_history_len(&self) -> usize {
+        self.result_manager.as_ref().map_or(0, |result_mgr| result_mgr.history_len())
+    }
+
+    /// 设置最多保留的撤销步数，0 表示不记录历史
+    pub fn set_refine_history_depth(&mut self, depth: usize) -> Result<()> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.set_history_depth(depth);
+        Ok(())
+    }
+
+    /// 切换结果存储模式，各模式的含义见 [`SearchResultMode`]
+    ///
+    /// 模式不变时什么都不做；模式改变时清空当前结果和撤销历史，之后只能写入新模式的结果。搜索进行中时返回错误
+    pub fn set_result_mode(&mut self, mode: SearchResultMode) -> Result<()> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        // 切换模式会清空结果，之前的历史随之失效
+        if mode != result_mgr.get_mode() {
+            result_mgr.clear_history();
+        }
+        result_mgr.set_mode(mode)
+    }
+
+    pub fn add_results_batch(&mut self, results: Vec<SearchResultItem>) -> Result<()> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.add_results_batch(results)
+    }
+
+    pub fn set_filter(
+        &mut self,
+        enable_address_filter: bool,
+        address_start: u64,
+        address_end: u64,
+        enable_type_filter: bool,
+        type_ids: Vec<i32>,
+    ) -> Result<()> {
+        self.filter.enable_address_filter = enable_address_filter;
+        self.filter.address_start = address_start;
+        self.filter.address_end = address_end;
+
+        self.filter.enable_type_filter = enable_type_filter;
+        self.filter.type_ids = type_ids.iter().filter_map(|&id| ValueType::from_id(id)).collect();
+
+        Ok(())
+    }
+
+    pub fn clear_filter(&mut self) -> Result<()> {
+        self.filter.clear();
+        Ok(())
+    }
+
+    pub fn get_filter(&self) -> &SearchFilter {
+        &self.filter
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_string();
+    }
+
+    pub fn get_notes(&self) -> &str {
+        &self.notes
+    }
+
+    /// 把全部结果按 `format` 写入 `writer`，返回导出的结果数
+    ///
+    /// 每条结果附带按 `modules` 计算的 `module+offset`，不在任何模块内的地址写为十六进制绝对地址
+    pub fn export_results<W: std::io::Write>(&self, writer: W, format: ExportFormat, modules: &[VmStaticData]) -> Result<usize> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        let items = result_mgr.get_results(0, result_mgr.total_count())?;
+        let rows = export::build_rows(&items, modules);
+        export::write_rows(writer, &rows, format)?;
+        Ok(rows.len())
+    }
+
+    /// 把结果集、过滤器、备注保存为会话存档；提供 `freeze` 时一并保存冻结列表
+    pub fn save_session(&self, path: &Path, freeze: Option<&FreezeManager>) -> Result<()> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let mode = result_mgr.get_mode();
+
+        let mut writer = PackedResultWriter::new(mode, result_mgr.total_count());
+        match mode {
+            SearchResultMode::Exact => {
+                for (index, item) in result_mgr.get_all_exact_results()?.into_iter().enumerate() {
+                    writer.push(index as u64, item.address, item.typ, None);
+                }
+            },
+            SearchResultMode::Fuzzy => {
+                for (index, item) in result_mgr.get_all_fuzzy_results()?.into_iter().enumerate() {
+                    let (address, value, value_type) = (item.address, item.value, item.value_type);
+                    writer.push(index as u64, address, value_type, Some(&value[..value_type.size().min(8)]));
+                }
+            },
+        }
+
+        let manifest = SessionManifest {
+            version: SESSION_VERSION,
+            result_count: result_mgr.total_count(),
+            filter: FilterManifest::from(&self.filter),
+            notes: self.notes.clone(),
+            frozen: freeze.map(|freeze| {
+                freeze
+                    .frozen_entries()
+                    .iter()
+                    .map(|(address, entry)| FrozenManifest::new(*address, entry))
+                    .collect()
+            }),
+        };
+
+        session::write_archive(path, &manifest, &writer.finish())?;
+        info!("Session saved to {:?}: {} results", path, manifest.result_count);
+        Ok(())
+    }
+
+    /// 从会话存档恢复结果集、过滤器和备注，替换当前内容
+    ///
+    /// 存档包含冻结列表且提供了 `freeze` 时，把其中的条目加入冻结管理器（不清除已有条目）
+    pub fn load_session(&mut self, path: &Path, freeze: Option<&FreezeManager>) -> Result<()> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let (manifest, results) = session::read_archive(path)?;
+        let (mode, records) = decode_packed(&results)?;
+        if records.len() != manifest.result_count {
+            return Err(anyhow!("Session result count mismatch: manifest {}, stored {}", manifest.result_count, records.len()));
+        }
+
+        let items: Vec<SearchResultItem> = records
+            .into_iter()
+            .map(|record| match mode {
+                SearchResultMode::Exact => SearchResultItem::new_exact(record.address, record.value_type),
+                SearchResultMode::Fuzzy => {
+                    SearchResultItem::new_fuzzy_from_bytes(record.address, record.value.as_deref().unwrap_or_default(), record.value_type)
+                },
+            })
+            .collect();
+
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        result_mgr.clear()?;
+        result_mgr.clear_history();
+        result_mgr.set_mode(mode)?;
+        result_mgr.add_results_batch(items)?;
+
+        self.filter = manifest.filter.to_filter();
+        self.notes = manifest.notes;
+        self.region_tags = None;
+
+        if let (Some(freeze), Some(frozen)) = (freeze, &manifest.frozen) {
+            for entry in frozen {
+                freeze.restore_entry(entry.address, entry.to_entry());
+            }
+        }
+
+        info!("Session loaded from {:?}: {} results", path, manifest.result_count);
+        Ok(())
+    }
+
+    /// 当前结果存储模式，决定 [`Self::get_results`] 返回精确结果还是带值的模糊结果
+    pub fn get_result_mode(&self) -> Result<SearchResultMode> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        Ok(result_mgr.get_mode())
+    }
+
+    /// 同 [`Self::get_result_mode`]
+    pub fn get_current_mode(&self) -> Result<SearchResultMode> {
+        self.get_result_mode()
+    }
+
+    /// 检查单个地址当前的值是否仍然匹配，用于监视面板实时校验，无需重新扫描
+    ///
+    /// 读取失败或地址所在页不可读时返回 `Ok(false)`；`memory_mode` 无效或无法使用时返回错误
+    pub fn matches_at(&self, addr: u64, value: &SearchValue, memory_mode: i32) -> Result<bool> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        Self::matches_at_with(addr, value, |addr, buf, page_status| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        })
+    }
+
+    /// 与 [`Self::matches_at`] 相同，但从任意 [`MemorySource`] 读取
+    pub fn matches_at_from<M>(&self, source: &M, addr: u64, value: &SearchValue) -> Result<bool>
+    where
+        M: MemorySource + ?Sized,
+    {
+        Self::matches_at_with(addr, value, |addr, buf, page_status| source.read(addr, buf, page_status))
+    }
+
+    /// [`Self::matches_at`] 的实现，`read` 负责读取 `addr` 处的字节并填写页状态
+    pub(crate) fn matches_at_with<F>(addr: u64, value: &SearchValue, read: F) -> Result<bool>
+    where
+        F: FnOnce(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        match Self::read_value_with(addr, value.value_type().size(), read) {
+            Some(buffer) => value.matched(&buffer),
+            None => Ok(false),
+        }
+    }
+
+    /// 读取 `[addr - before, addr + after)`，用于查看某个地址周围的内存
+    ///
+    /// 范围可能跨越相邻的区域、中间夹着未映射的页：返回的缓冲区长度总是等于请求的长度，
+    /// 不可读的页填 0 并在页状态中标记为失败，可读部分见 [`SpanRead::readable_ranges`]
+    pub fn read_around(&self, addr: u64, before: usize, after: usize, memory_mode: i32) -> Result<SpanRead> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        let start = addr.saturating_sub(before as u64);
+        let len = (addr.saturating_add(after as u64) - start) as usize;
+        Ok(Self::read_span_with(start, len, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        }))
+    }
+
+    /// 读取 `[start, start + len)`，`read` 负责读取一段连续地址并填写页状态
+    ///
+    /// 整段读取失败时（通常是范围内有未映射的页）改为逐页读取，只有读不到的页被标记为失败
+    pub(crate) fn read_span_with<F>(start: u64, len: usize, mut read: F) -> SpanRead
+    where
+        F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let mut span = SpanRead {
+            start,
+            bytes: vec![0u8; len],
+            page_status: PageStatusBitmap::new(len, start as usize),
+        };
+        if len == 0 || read(start, &mut span.bytes, &mut span.page_status).is_ok() {
+            return span;
+        }
+
+        span.page_status = PageStatusBitmap::new(len, start as usize);
+        let end = start + len as u64;
+        let page_base = start & !(*PAGE_SIZE as u64 - 1);
+        for page in 0..span.page_count() {
+            let chunk_start = (page_base + (page * *PAGE_SIZE) as u64).max(start);
+            let chunk_end = (page_base + ((page + 1) * *PAGE_SIZE) as u64).min(end);
+            let chunk = &mut span.bytes[(chunk_start - start) as usize..(chunk_end - start) as usize];
+
+            let mut chunk_status = PageStatusBitmap::new(chunk.len(), chunk_start as usize);
+            if read(chunk_start, chunk, &mut chunk_status).is_ok() && chunk_status.is_page_success(0) {
+                span.page_status.mark_success(page);
+            } else {
+                chunk.fill(0);
+            }
+        }
+
+        if log_enabled!(Level::Debug) {
+            let unreadable = span.page_count() - span.page_status.success_count();
+            debug!("read_span: 0x{:X} + {} read page by page, {} pages unreadable", start, len, unreadable);
+        }
+        span
+    }
+
+    /// 经指针搜索字段：找出 `pointer_regions` 中指向 `data_regions` 的指针，检查 `*ptr + field_offset` 处的值是否匹配 `value`
+    ///
+    /// 用于已知结构体布局（例如 HP 在对象 +0x40 处）时直接定位对象。指针按 8 字节对齐、小端 64 位读取，
+    /// 只保留低 48 位；结果按指针地址排序，同时给出指针地址和字段地址。不修改当前结果
+    pub fn scan_via_pointers(
+        &self,
+        field_offset: i64,
+        value: &SearchValue,
+        pointer_regions: &[(u64, u64)],
+        data_regions: &[(u64, u64)],
+        memory_mode: i32,
+    ) -> Result<Vec<PointerFieldMatch>> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        self.scan_via_pointers_with(field_offset, value, pointer_regions, data_regions, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        })
+    }
+
+    /// [`Self::scan_via_pointers`] 的实现，`read` 负责读取一段连续地址并填写页状态
+    pub(crate) fn scan_via_pointers_with<R>(
+        &self,
+        field_offset: i64,
+        value: &SearchValue,
+        pointer_regions: &[(u64, u64)],
+        data_regions: &[(u64, u64)],
+        mut read: R,
+    ) -> Result<Vec<PointerFieldMatch>>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        const POINTER_SIZE: u64 = 8;
+        const POINTER_MASK: u64 = 0x0000_FFFF_FFFF_FFFF;
+
+        let pointer_regions = sanitize_regions(pointer_regions.to_vec());
+        let data_regions = sanitize_regions(data_regions.to_vec());
+        let page_mask = !(*PAGE_SIZE as u64 - 1);
+
+        let mut matches = Vec::new();
+        let mut candidates = 0usize;
+        for (start, end) in pointer_regions {
+            let mut chunk_start = start.next_multiple_of(POINTER_SIZE);
+            while chunk_start.checked_add(POINTER_SIZE).is_some_and(|next| next <= end) {
+                let chunk_end = chunk_start.saturating_add(self.chunk_size as u64).min(end);
+                let span = Self::read_span_with(chunk_start, (chunk_end - chunk_start) as usize, &mut read);
+
+                for (i, word) in span.bytes.chunks_exact(POINTER_SIZE as usize).enumerate() {
+                    let pointer_addr = chunk_start + i as u64 * POINTER_SIZE;
+                    // 对齐的指针不会跨页
+                    let page = ((pointer_addr & page_mask) - (chunk_start & page_mask)) as usize / *PAGE_SIZE;
+                    if !span.page_status.is_page_success(page) {
+                        continue;
+                    }
+
+                    let target = u64::from_le_bytes(word.try_into()?) & POINTER_MASK;
+                    if !is_valid_pointer(target, &data_regions, true) {
+                        continue;
+                    }
+                    let Some(field_addr) = target.checked_add_signed(field_offset) else {
+                        continue;
+                    };
+
+                    candidates += 1;
+                    if Self::matches_at_with(field_addr, value, &mut read)? {
+                        matches.push(PointerFieldMatch {
+                            pointer_addr,
+                            target,
+                            field_addr,
+                        });
+                    }
+                }
+                chunk_start = chunk_end;
+            }
+        }
+
+        debug!("scan_via_pointers: {} pointers into data regions, {} matched", candidates, matches.len());
+        Ok(matches)
+    }
+
+    /// 读取 `addr` 处 `size` 字节，读取失败或值涉及的任一页不可读时返回 `None`
+    pub(crate) fn read_value_with<F>(addr: u64, size: usize, read: F) -> Option<Vec<u8>>
+    where
+        F: FnOnce(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let mut buffer = vec![0u8; size];
+        let mut page_status = PageStatusBitmap::new(size, addr as usize);
+
+        if let Err(e) = read(addr, &mut buffer, &mut page_status) {
+            if log_enabled!(Level::Debug) {
+                debug!("read_value: failed to read 0x{:X}: {:?}", addr, e);
+            }
+            return None;
+        }
+
+        // 值可能跨页，所有涉及的页都必须读取成功
+        let pages = ((addr as usize % *PAGE_SIZE) + size).div_ceil(*PAGE_SIZE);
+        if (0..pages).any(|i| !page_status.is_page_success(i)) {
+            return None;
+        }
+
+        Some(buffer)
+    }
+
+    /// 变化搜索：只保留上次记录的值匹配 `from`、当前值匹配 `to` 的结果，并把记录的值更新为当前值
+    ///
+    /// 需要记录了上次值的模糊结果；`from` 与 `to` 必须是同一类型，类型不同的结果直接丢弃。
+    /// 当前值读取失败的结果同样丢弃。返回剩余结果数
+    pub fn transition_search(&mut self, from: &SearchValue, to: &SearchValue, memory_mode: i32) -> Result<usize> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        self.transition_search_with(from, to, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        })
+    }
+
+    /// [`Self::transition_search`] 的实现，`read` 负责读取单个结果地址处的值
+    pub(crate) fn transition_search_with<F>(&mut self, from: &SearchValue, to: &SearchValue, mut read: F) -> Result<usize>
+    where
+        F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let value_type = from.value_type();
+        if to.value_type() != value_type {
+            return Err(anyhow!("Transition values differ in type: {:?} -> {:?}", value_type, to.value_type()));
+        }
+        if value_type.size() > 8 {
+            return Err(anyhow!("Transition search does not support {:?}", value_type));
+        }
+
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        if result_mgr.get_mode() != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Transition search requires stored values (fuzzy mode)"));
+        }
+
+        let current_results = result_mgr.get_all_fuzzy_results()?;
+        let total = current_results.len();
+        let size = value_type.size();
+
+        let mut survivors = Vec::new();
+        for item in current_results {
+            let (address, stored, item_type) = (item.address, item.value, item.value_type);
+            if item_type != value_type || !from.matched(&stored[..size])? {
+                continue;
+            }
+            let Some(fresh) = Self::read_value_with(address, size, &mut read) else {
+                continue;
+            };
+            if to.matched(&fresh)? {
+                survivors.push(FuzzySearchResultItem::from_bytes(address, &fresh, value_type));
+            }
+        }
+
+        result_mgr.record_history()?;
+        result_mgr.replace_all_fuzzy_results(survivors)?;
+        result_mgr.seal_history()?;
+        let final_count = result_mgr.total_count();
+        info!("Transition search completed: {} -> {} results", total, final_count);
+        Ok(final_count)
+    }
+
+    /// 未知初始值搜索的细化：按相对上一次记录值的变化筛选模糊结果
+    ///
+    /// 首次扫描即 [`Self::start_fuzzy_search_async`] 记录的快照。`op` 映射为 [`FuzzyCondition`] 后交给
+    /// [`Self::start_fuzzy_refine_async`]，记录值随每次细化更新，因此可以连续比较"增加 -> 不变 -> 减少 5"
+    pub fn refine_by_delta(&mut self, op: DeltaOp) -> Result<()> {
+        let condition = op.to_condition().ok_or_else(|| anyhow!("Delta {:?} out of range", op))?;
+        self.start_fuzzy_refine_async(condition)
+    }
+
+    /// 计数器搜索：对 `regions` 做模糊首次扫描，间隔 `delay_ms` 毫秒后只保留值增大的地址
+    ///
+    /// `delta` 为 None 时任意正增量都保留，否则只保留恰好增加 `delta` 的地址（浮点类型按容差比较）。
+    /// 首次扫描结果与普通模糊搜索一样写入磁盘，等待期间不持有管理器锁和驱动锁，之后按
+    /// [`counter_condition`] 做一次模糊细化。结果为模糊模式，可继续细化
+    pub fn start_counter_search_async(&mut self, value_type: ValueType, delta: Option<i128>, delay_ms: u64, regions: Vec<(u64, u64)>) -> Result<()> {
+        let condition = counter_condition(delta)?;
+        let (regions, cancel_token) = self
+            .begin_fuzzy_initial(value_type, regions, false)?
+            .ok_or_else(|| anyhow!("Counter search requires an initial scan"))?;
+
+        let chunk_size = self.chunk_size;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            if !Self::run_fuzzy_initial_task(value_type, regions, chunk_size, cancel_token.clone(), false).await {
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+
+            let current_results = match SEARCH_ENGINE_MANAGER.read() {
+                Ok(manager) => match manager.result_manager.as_ref().map(|result_mgr| result_mgr.get_all_fuzzy_results()) {
+                    Some(Ok(results)) => results,
+                    Some(Err(e)) => {
+                        error!("Failed to load counter candidates: {:?}", e);
+                        manager.shared_buffer.write_status(SearchStatus::Error);
+                        manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+                        return;
+                    },
+                    None => return,
+                },
+                Err(e) => {
+                    error!("Failed to acquire read lock for counter search: {:?}", e);
+                    return;
+                },
+            };
+
+            Self::run_fuzzy_refine_task(current_results, condition, cancel_token).await;
+        });
+
+        self.search_handle = Some(handle);
+        Ok(())
+    }
+
+    /// 快速确认：顺序扫描并在找到 `n` 个匹配后立即返回，不修改当前结果
+    ///
+    /// 单值搜索按块短路，组搜索在累计 `n` 个值地址的块结束时短路，两者都使用 `memory_mode` 读取
+    pub fn find_first_n(&self, query: &SearchQuery, regions: &[(u64, u64)], memory_mode: i32, n: usize) -> Result<Vec<SearchResultItem>> {
+        let regions = checked_regions(query, regions)?;
+        let reader = ModeReader::lock(memory_mode)?;
+        let read = |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| reader.read(addr, buf, page_status);
+
+        let pairs = find_first_n_in(query, &regions, self.chunk_size, n, read)?;
+        Ok(pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect())
+    }
+
+    /// 与 [`Self::find_first_n`] 相同，但从任意 [`MemorySource`] 读取
+    ///
+    /// `regions` 为 None 时搜索 [`MemorySource::regions`] 返回的全部区域
+    pub fn find_first_n_from<M>(&self, source: &M, query: &SearchQuery, regions: Option<&[(u64, u64)]>, n: usize) -> Result<Vec<SearchResultItem>>
+    where
+        M: MemorySource + ?Sized,
+    {
+        let regions = checked_regions(query, &regions.map_or_else(|| source.regions(), <[_]>::to_vec))?;
+        let read = |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| source.read(addr, buf, page_status);
+
+        let pairs = find_first_n_in(query, &regions, self.chunk_size, n, read)?;
+        Ok(pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect())
+    }
+
+    /// 抽样搜索：每个区域只扫描开头 `bytes_per_region` 字节，返回其中的匹配，用于快速确认值是否存在
+    ///
+    /// 与 [`Self::estimate_result_count`] 不同，返回的是实际找到的结果而不是估算数量；
+    /// 有区域被截断时 [`SampleScanReport::partial`] 为 true。不修改当前结果，组搜索不做深度搜索
+    pub fn sample_scan(&self, query: &SearchQuery, regions: &[(u64, u64)], memory_mode: i32, bytes_per_region: u64) -> Result<SampleScanReport> {
+        let reader = ModeReader::lock(memory_mode)?;
+        self.sample_scan_with(query, regions, bytes_per_region, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            reader.read(addr, buf, page_status)
+        })
+    }
+
+    /// [`Self::sample_scan`] 的实现，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn sample_scan_with<R>(&self, query: &SearchQuery, regions: &[(u64, u64)], bytes_per_region: u64, read: R) -> Result<SampleScanReport>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let regions = checked_regions(query, regions)?;
+        let prefixes = sampling::region_prefixes(&regions, bytes_per_region);
+
+        let total_bytes: u64 = regions.iter().map(|(start, end)| end - start).sum();
+        let sampled_bytes: u64 = prefixes.iter().map(|(start, end)| end - start).sum();
+
+        let mut pairs = find_first_n_in(query, &prefixes, self.chunk_size, usize::MAX, read)?;
+        pairs.sort_unstable_by_key(|pair| pair.addr);
+        pairs.dedup_by_key(|pair| pair.addr);
+
+        debug!("Sample scan: {} of {} bytes, {} results", sampled_bytes, total_bytes, pairs.len());
+        Ok(SampleScanReport {
+            results: pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect(),
+            sampled_bytes,
+            total_bytes,
+            partial: sampled_bytes < total_bytes,
+        })
+    }
+
+    /// 完整扫描并直接返回结果，不写入结果集（不落盘、不去重存储），结果由调用方自行保存
+    ///
+    /// 使用 `memory_mode` 读取，结果按地址排序；组搜索不做深度搜索。不要求先调用 [`Self::init`]
+    pub fn scan_collect(&self, query: &SearchQuery, regions: &[(u64, u64)], memory_mode: i32) -> Result<Vec<SearchResultItem>> {
+        let reader = ModeReader::lock(memory_mode)?;
+        self.scan_collect_with(query, regions, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            reader.read(addr, buf, page_status)
+        })
+    }
+
+    /// [`Self::scan_collect`] 的实现，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn scan_collect_with<R>(&self, query: &SearchQuery, regions: &[(u64, u64)], read: R) -> Result<Vec<SearchResultItem>>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let regions = &checked_regions(query, regions)?[..];
+
+        let mut pairs = find_first_n_in(query, regions, self.chunk_size, usize::MAX, read)?;
+        pairs.sort_unstable_by_key(|pair| pair.addr);
+        pairs.dedup_by_key(|pair| pair.addr);
+
+        debug!("scan_collect: {} results from {} regions", pairs.len(), regions.len());
+        Ok(pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect())
+    }
+
+    /// 按类型的自然对齐扫描，没有结果时逐字节（对齐为 1）重新扫描一次
+    ///
+    /// 值存在但没有按类型对齐时（例如打包结构体中的字段），用 [`AlignmentFallbackScan::used_fallback`] 提示用户。
+    /// 与 [`Self::scan_collect`] 一样不写入结果集；只有单值查询会回退，组搜索只按自然对齐扫描
+    pub fn scan_with_alignment_fallback(&self, query: &SearchQuery, regions: &[(u64, u64)], memory_mode: i32) -> Result<AlignmentFallbackScan> {
+        let reader = ModeReader::lock(memory_mode)?;
+        self.scan_with_alignment_fallback_with(query, regions, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            reader.read(addr, buf, page_status)
+        })
+    }
+
+    /// [`Self::scan_with_alignment_fallback`] 的实现，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn scan_with_alignment_fallback_with<R>(&self, query: &SearchQuery, regions: &[(u64, u64)], mut read: R) -> Result<AlignmentFallbackScan>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let results = self.scan_collect_with(query, regions, &mut read)?;
+        let retry = results.is_empty() && query.values.len() == 1 && query.values[0].value_type().alignment() > 1;
+        if !retry {
+            return Ok(AlignmentFallbackScan {
+                results,
+                used_fallback: false,
+            });
+        }
+
+        let regions = sanitize_regions(regions.to_vec());
+        let on_grid = |addr| query.on_object_grid(addr);
+        let mut pairs = single_search::find_first_n_single_where(&query.values[0], &regions, self.chunk_size, usize::MAX, 1, on_grid, read)?;
+        pairs.sort_unstable_by_key(|pair| pair.addr);
+        pairs.dedup_by_key(|pair| pair.addr);
+
+        info!("No aligned results, unaligned rescan found {}", pairs.len());
+        Ok(AlignmentFallbackScan {
+            results: pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect(),
+            used_fallback: true,
+        })
+    }
+
+    /// 流式搜索：并行扫描，每完成一个区域就把该区域的结果作为一个批次发送到 `tx`，不写入结果集
+    ///
+    /// 使用 `memory_mode` 读取，组搜索不做深度搜索。返回时 `tx` 被释放，接收方据此得知扫描结束；
+    /// 接收方提前关闭通道会停止扫描。返回发送的结果总数
+    pub fn search_streaming(&self, query: &SearchQuery, regions: &[(u64, u64)], memory_mode: i32, tx: SyncSender<ResultBatch>) -> Result<usize> {
+        let reader = ModeReader::lock(memory_mode)?;
+        let read = |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| reader.read(addr, buf, page_status);
+
+        let chunk_size = self.chunk_size;
+        self.search_streaming_with(query, regions, tx, |start, end| {
+            find_first_n_in(query, &[(start, end)], chunk_size, usize::MAX, read)
+        })
+    }
+
+    /// 与 [`Self::search_streaming`] 相同，但从任意 [`MemorySource`] 读取
+    ///
+    /// `regions` 为 None 时搜索 [`MemorySource::regions`] 返回的全部区域
+    pub fn search_streaming_from<M>(&self, source: &M, query: &SearchQuery, regions: Option<&[(u64, u64)]>, tx: SyncSender<ResultBatch>) -> Result<usize>
+    where
+        M: MemorySource + ?Sized,
+    {
+        let regions = regions.map_or_else(|| source.regions(), <[_]>::to_vec);
+        let read = |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| source.read(addr, buf, page_status);
+
+        let chunk_size = self.chunk_size;
+        self.search_streaming_with(query, &regions, tx, |start, end| {
+            find_first_n_in(query, &[(start, end)], chunk_size, usize::MAX, read)
+        })
+    }
 
-        let current_results: Vec<_> = match result_mgr.get_mode() {
+    /// [`Self::search_streaming`] 的实现，`search_region` 搜索单个区域 `[start, end)`
+    pub(crate) fn search_streaming_with<S>(&self, query: &SearchQuery, regions: &[(u64, u64)], tx: SyncSender<ResultBatch>, search_region: S) -> Result<usize>
+    where
+        S: Fn(u64, u64) -> Result<Vec<ValuePair>> + Sync,
+    {
+        let regions = checked_regions(query, regions)?;
+
+        let start_time = Instant::now();
+        let sent = streaming::stream_regions(&regions, &tx, search_region);
+        debug!("Streaming search sent {} results from {} regions in {} ms", sent, regions.len(), start_time.elapsed().as_millis());
+        Ok(sent)
+    }
+
+    /// 记录 `regions` 当前每页内容的哈希，保存为名为 `name` 的检查点（同名覆盖），返回记录的页数
+    pub fn checkpoint(&mut self, name: &str, regions: &[(u64, u64)]) -> Result<usize> {
+        let driver_manager = driver_manager_read();
+
+        Ok(self.checkpoint_with(name, regions, |addr, buf, page_status| driver_manager.read_memory_unified(addr, buf, Some(page_status))))
+    }
+
+    /// [`Self::checkpoint`] 的实现，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn checkpoint_with<R>(&mut self, name: &str, regions: &[(u64, u64)], read: R) -> usize
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let regions = sanitize_regions(regions.to_vec());
+        let checkpoint = Checkpoint::capture(&regions, self.chunk_size, read);
+        let page_count = checkpoint.page_count();
+
+        debug!("Checkpoint '{}' recorded: {} regions, {} pages", name, regions.len(), page_count);
+        self.checkpoints.insert(name.to_string(), checkpoint);
+        page_count
+    }
+
+    /// 删除检查点，返回是否存在
+    pub fn remove_checkpoint(&mut self, name: &str) -> bool {
+        self.checkpoints.remove(name).is_some()
+    }
+
+    /// 只在内容相对检查点 `name` 变化过的页上搜索，返回匹配结果，不修改当前结果和检查点
+    pub fn search_changed_since(&self, name: &str, query: &SearchQuery, memory_mode: i32) -> Result<Vec<SearchResultItem>> {
+        query.validate().map_err(|e| anyhow!("Invalid query: {}", e))?;
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        self.search_changed_since_with(name, query, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        })
+    }
+
+    /// [`Self::search_changed_since`] 的实现，`read` 负责读取页对齐地址处的数据
+    pub(crate) fn search_changed_since_with<R>(&self, name: &str, query: &SearchQuery, mut read: R) -> Result<Vec<SearchResultItem>>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let checkpoint = self.checkpoints.get(name).ok_or_else(|| anyhow!("Checkpoint '{}' not found", name))?;
+
+        // 跨页的元素、组搜索窗口可能从未变化的相邻页开始
+        let max_value_size = query.values.iter().map(|v| v.value_type().size()).max().unwrap_or(0);
+        let margin = max_value_size.max(query.range as usize) as u64;
+        let changed = checkpoint.changed_ranges(self.chunk_size, margin, &mut read);
+
+        debug!("Checkpoint '{}': {} changed ranges to rescan", name, changed.len());
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let pairs = find_first_n_in(query, &changed, self.chunk_size, usize::MAX, read)?;
+
+        Ok(pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect())
+    }
+
+    /// 离线搜索内存转储：`buffer` 是从 `base_addr` 起的内存内容，所有页视为可读
+    pub fn search_buffer(&self, query: &SearchQuery, base_addr: u64, buffer: &[u8]) -> Result<Vec<SearchResultItem>> {
+        let mut page_status = PageStatusBitmap::new(buffer.len(), base_addr as usize);
+        page_status.mark_all_success();
+        self.search_buffer_with_status(query, base_addr, buffer, &page_status)
+    }
+
+    /// 离线搜索带缺页信息的内存转储，`page_status` 中未标记成功的页与实时搜索时读取失败的页一样跳过
+    ///
+    /// `base_addr` 必须页对齐，`page_status` 的第 i 位对应 `buffer` 的第 i 页
+    pub fn search_buffer_with_status(
+        &self,
+        query: &SearchQuery,
+        base_addr: u64,
+        buffer: &[u8],
+        page_status: &PageStatusBitmap,
+    ) -> Result<Vec<SearchResultItem>> {
+        query.validate().map_err(|e| anyhow!("Invalid query: {}", e))?;
+        if base_addr & (*PAGE_SIZE as u64 - 1) != 0 {
+            return Err(anyhow!("Buffer base address 0x{:X} is not page aligned", base_addr));
+        }
+
+        let end = base_addr
+            .checked_add(buffer.len() as u64)
+            .ok_or_else(|| anyhow!("Buffer at 0x{:X} overflows the address space", base_addr))?;
+        let regions = [(base_addr, end)];
+
+        // 按实时读取的方式从转储中取数据，页状态照搬转储的位图
+        let read = |addr: u64, buf: &mut [u8], status: &mut PageStatusBitmap| -> Result<()> {
+            let offset = (addr - base_addr) as usize;
+            let data = buffer
+                .get(offset..offset + buf.len())
+                .ok_or_else(|| anyhow!("0x{:X} is outside the buffer", addr))?;
+            buf.copy_from_slice(data);
+
+            let first_page = offset / *PAGE_SIZE;
+            for page_idx in 0..buf.len().div_ceil(*PAGE_SIZE) {
+                if page_status.is_page_success(first_page + page_idx) {
+                    status.mark_success(page_idx);
+                }
+            }
+            Ok(())
+        };
+
+        let pairs = find_first_n_in(query, &regions, self.chunk_size, usize::MAX, read)?;
+
+        Ok(pairs.into_iter().map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type)).collect())
+    }
+
+    /// 搜索交错数组（struct-of-arrays 中每隔 `stride` 字节重复的字段），返回候选数组起始地址
+    ///
+    /// 只返回 `value` 以 `stride` 为间隔至少连续出现 `min_repeats` 次的序列起点，不修改当前结果
+    pub fn search_periodic(
+        &self,
+        value: &SearchValue,
+        stride: usize,
+        min_repeats: usize,
+        regions: &[(u64, u64)],
+        memory_mode: i32,
+    ) -> Result<Vec<u64>> {
+        let regions = checked_regions(&SearchQuery::new(vec![value.clone()], SearchMode::Unordered, 0), regions)?;
+        let reader = ModeReader::lock(memory_mode)?;
+
+        single_search::find_periodic_single(value, stride, min_repeats, &regions, self.chunk_size, |addr, buf, page_status| {
+            reader.read(addr, buf, page_status)
+        })
+    }
+
+    /// 预演一次扫描：校验查询、规范化区域并计算读取量，不访问驱动
+    pub fn dry_run(&self, regions: &[(u64, u64)], query: &SearchQuery) -> DryRunReport {
+        let query_error = query.validate().err();
+
+        let mut validated_regions = sanitize_regions(regions.to_vec());
+        validated_regions.sort_unstable();
+
+        let page_mask = !(*PAGE_SIZE as u64 - 1);
+        let chunk_size = self.chunk_size as u64;
+        let mut total_bytes = 0u64;
+        let mut estimated_chunks = 0usize;
+        for &(start, end) in &validated_regions {
+            total_bytes += end - start;
+            estimated_chunks += (end - (start & page_mask)).div_ceil(chunk_size) as usize;
+        }
+
+        DryRunReport {
+            validated_regions,
+            total_bytes,
+            estimated_chunks,
+            query_ok: query_error.is_none(),
+            query_error,
+        }
+    }
+
+    /// 随机采样 `sample_pages` 页估算单值搜索的结果数量，不修改当前结果
+    pub fn estimate_result_count(&self, value: &SearchValue, regions: &[(u64, u64)], sample_pages: usize) -> Result<u64> {
+        let driver_manager = driver_manager_read();
+
+        self.estimate_result_count_with(value, regions, sample_pages, |addr, buf, page_status| {
+            driver_manager.read_memory_unified(addr, buf, Some(page_status))
+        })
+    }
+
+    /// [`Self::estimate_result_count`] 的实现，`read` 负责读取页数据
+    pub(crate) fn estimate_result_count_with<F>(
+        &self,
+        value: &SearchValue,
+        regions: &[(u64, u64)],
+        sample_pages: usize,
+        read: F,
+    ) -> Result<u64>
+    where
+        F: Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let mut rng = self.sampling_rng();
+        sampling::estimate_single_count(value, regions, sample_pages, &mut rng, read)
+    }
+
+    /// Legacy synchronous refine search method.
+    ///
+    /// 只重新读取结果集中已有的地址，使用 `memory_mode` 读取；每个地址保留原来的类型，
+    /// 模糊结果记录的值同时更新为当前值
+    pub fn refine_search(&mut self, query: &SearchQuery, memory_mode: i32, callback: Option<Arc<dyn SearchProgressCallback>>) -> Result<usize> {
+        let mode = MemoryAccessMode::from_id(memory_mode).ok_or_else(|| anyhow!("Invalid memory mode: {}", memory_mode))?;
+        let driver_manager = driver_manager_read();
+        if !driver_manager.can_read_with_mode(mode) {
+            return Err(anyhow!("Memory mode {:?} differs from the configured access mode", mode));
+        }
+
+        self.refine_search_with(query, callback, |addr, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            driver_manager.read_memory_with_mode(mode, addr, buf, Some(page_status))
+        })
+    }
+
+    /// [`Self::refine_search`] 的实现，`read` 负责读取单个结果地址处的值
+    pub(crate) fn refine_search_with<R>(&mut self, query: &SearchQuery, callback: Option<Arc<dyn SearchProgressCallback>>, mut read: R) -> Result<usize>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        let result_mode = result_mgr.get_mode();
+        let current_results: Vec<_> = match result_mode {
             SearchResultMode::Exact => result_mgr
                 .get_all_exact_results()?
                 .into_iter()
                 .map(|result| ValuePair::new(result.address, result.typ))
                 .collect(),
-            SearchResultMode::Fuzzy => {
-                return Err(anyhow!("FuzzySearchResultManager not implemented yet"));
-            },
+            SearchResultMode::Fuzzy => result_mgr
+                .get_all_fuzzy_results()?
+                .into_iter()
+                .map(|result| ValuePair::new(result.address, result.value_type))
+                .collect(),
         };
 
         if current_results.is_empty() {
@@ -1276,26 +3221,48 @@ impl SearchEngineManager {
         let processed_counter = Arc::new(AtomicUsize::new(0));
         let total_found_counter = Arc::new(AtomicUsize::new(0));
 
-        result_mgr.clear()?;
-        result_mgr.set_mode(SearchResultMode::Exact)?;
-
-        let refined_results = if query.values.len() == 1 {
-            single_search::refine_single_search(&current_results, &query.values[0], Some(&processed_counter), Some(&total_found_counter))?
+        let refined_results: Vec<ValuePair> = if query.values.len() == 1 {
+            single_search::refine_single_search(
+                &current_results,
+                &query.values[0],
+                Some(&processed_counter),
+                Some(&total_found_counter),
+                &mut read,
+            )?
         } else {
-            let results = group_search::refine_search_group_with_dfs(&current_results, query, Some(&processed_counter), Some(&total_found_counter))?;
+            let results = group_search::refine_search_group_with_dfs(&current_results, query, Some(&processed_counter), Some(&total_found_counter), &mut read)?;
 
             results.into_iter().cloned().collect()
         };
 
         total_found_counter.store(refined_results.len(), AtomicOrdering::Relaxed);
 
-        if !refined_results.is_empty() {
-            let converted_results: Vec<SearchResultItem> = refined_results
-                .into_iter()
-                .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
-                .collect();
-            result_mgr.add_results_batch(converted_results)?;
+        result_mgr.record_history()?;
+        match result_mode {
+            SearchResultMode::Exact => {
+                result_mgr.clear()?;
+                result_mgr.set_mode(SearchResultMode::Exact)?;
+                if !refined_results.is_empty() {
+                    let converted_results: Vec<SearchResultItem> = refined_results
+                        .into_iter()
+                        .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
+                        .collect();
+                    result_mgr.add_results_batch(converted_results)?;
+                }
+            },
+            SearchResultMode::Fuzzy => {
+                // 按原来的类型重新读取，记录的值更新为当前值，读取失败的地址丢弃
+                let fuzzy_results: Vec<FuzzySearchResultItem> = refined_results
+                    .into_iter()
+                    .filter_map(|pair| {
+                        let buffer = Self::read_value_with(pair.addr, pair.value_type.size(), &mut read)?;
+                        Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
+                    })
+                    .collect();
+                result_mgr.replace_all_fuzzy_results(fuzzy_results)?;
+            },
         }
+        result_mgr.seal_history()?;
 
         let elapsed = start_time.elapsed().as_millis() as u64;
         let final_count = result_mgr.total_count();
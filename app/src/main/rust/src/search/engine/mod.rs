@@ -1,15 +1,34 @@
 //! Search engine implementation modules.
 
 mod batch_reader;
+pub mod checkpoint;
+pub mod export;
 pub mod filter;
 pub mod fuzzy_search;
 pub mod group_search;
 pub mod manager;
 mod memchr_ext;
+pub mod mode_stats;
+pub mod outcome;
+mod query_cache;
+pub mod region;
+pub mod sampling;
+mod session;
 pub mod shared_buffer;
 pub mod single_search;
+pub mod streaming;
 
 pub use crate::core::globals::{PAGE_MASK, PAGE_SIZE};
+pub use checkpoint::Checkpoint;
+pub use export::{format_address, ExportFormat};
 pub use filter::SearchFilter;
-pub use manager::{SearchEngineManager, SearchProgressCallback, ValuePair, BPLUS_TREE_ORDER, SEARCH_ENGINE_MANAGER};
+pub use mode_stats::{ModeStats, ScanSample};
+pub use outcome::{ScanStats, SearchOutcome};
+pub use region::{RegionTags, SearchRegion};
+pub use sampling::SampleScanReport;
+pub use manager::{
+    AlignmentFallbackScan, DryRunReport, PointerFieldMatch, SearchEngineManager, SearchProgressCallback, SpanRead, ValuePair, BPLUS_TREE_ORDER,
+    PROGRESS_REPORT_INTERVAL, SEARCH_ENGINE_MANAGER,
+};
 pub use shared_buffer::{SearchErrorCode, SearchStatus, SharedBuffer, SHARED_BUFFER_SIZE};
+pub use streaming::ResultBatch;
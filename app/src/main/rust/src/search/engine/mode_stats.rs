@@ -0,0 +1,105 @@
+//! 按内存访问模式累计扫描表现，为当前目标推荐吞吐最高且出错率可接受的模式
+//!
+//! 不同设备、不同目标进程上各模式的速度和读取失败情况差别很大，只能靠实际扫描积累。
+//! 统计按目标包名分开，以 JSON 保存在缓存目录的 [`MODE_STATS_FILE`] 中，跨会话累积。
+
+use crate::core::MemoryAccessMode;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// 统计文件名，位于搜索引擎的缓存目录
+pub const MODE_STATS_FILE: &str = "mode_stats.json";
+
+/// 推荐时默认允许的出错率（出错区域占比）
+pub const DEFAULT_MAX_FAULT_RATE: f64 = 0.05;
+
+/// 扫描次数少于该值的模式不参与推荐，避免一次偶然的快扫描定下结论
+pub const MIN_RUNS_FOR_RECOMMENDATION: u32 = 2;
+
+/// 一次扫描的表现
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanSample {
+    /// 实际读取的字节数，取消或超时的区域只计已读取的部分
+    pub bytes: u64,
+    pub elapsed: Duration,
+    pub regions: usize,
+    /// 读取或搜索出错的区域数
+    pub failed_regions: usize,
+}
+
+/// 某个模式在某个目标上的累计统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ModeStats {
+    pub runs: u32,
+    pub bytes: u64,
+    pub micros: u64,
+    pub regions: u64,
+    pub failed_regions: u64,
+}
+
+impl ModeStats {
+    fn add(&mut self, sample: &ScanSample) {
+        self.runs = self.runs.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(sample.bytes);
+        self.micros = self.micros.saturating_add(sample.elapsed.as_micros() as u64);
+        self.regions = self.regions.saturating_add(sample.regions as u64);
+        self.failed_regions = self.failed_regions.saturating_add(sample.failed_regions as u64);
+    }
+
+    /// 平均吞吐（字节/秒），耗时为 0 时按 1 微秒计
+    pub fn throughput(&self) -> f64 {
+        self.bytes as f64 * 1_000_000.0 / self.micros.max(1) as f64
+    }
+
+    /// 出错区域占比
+    pub fn fault_rate(&self) -> f64 {
+        if self.regions == 0 {
+            return 0.0;
+        }
+        self.failed_regions as f64 / self.regions as f64
+    }
+}
+
+/// 所有目标的统计：包名 -> 模式 id -> 累计统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModeStatsBook {
+    targets: BTreeMap<String, BTreeMap<i32, ModeStats>>,
+}
+
+impl ModeStatsBook {
+    /// 读取统计文件，文件不存在时返回空统计
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read mode stats {:?}: {}", path, e))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).map_err(|e| anyhow!("Failed to write mode stats {:?}: {}", path, e))
+    }
+
+    pub fn record(&mut self, target: &str, mode: MemoryAccessMode, sample: &ScanSample) {
+        self.targets.entry(target.to_string()).or_default().entry(mode.to_id()).or_default().add(sample);
+    }
+
+    pub fn stats(&self, target: &str, mode: MemoryAccessMode) -> Option<ModeStats> {
+        self.targets.get(target)?.get(&mode.to_id()).copied()
+    }
+
+    /// 出错率不超过 `max_fault_rate` 的模式中吞吐最高的一个，没有足够统计时为 None
+    pub fn recommend(&self, target: &str, max_fault_rate: f64) -> Option<MemoryAccessMode> {
+        self.targets
+            .get(target)?
+            .iter()
+            .filter(|(_, stats)| stats.runs >= MIN_RUNS_FOR_RECOMMENDATION && stats.fault_rate() <= max_fault_rate)
+            .filter_map(|(&id, stats)| Some((MemoryAccessMode::from_id(id)?, stats.throughput())))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(mode, _)| mode)
+    }
+}
@@ -0,0 +1,33 @@
+//! 同步搜索的结果报告
+//!
+//! 只返回结果数量时，结果被截断或搜索中途取消都无从得知；[`SearchOutcome`] 把这些情况一并带回调用方。
+
+use std::time::Duration;
+
+/// 一次扫描中各区域的处理情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScanStats {
+    /// 清理后参与扫描的区域数
+    pub regions_total: usize,
+    /// 完整扫描过的区域数（含读取失败的区域）
+    pub regions_searched: usize,
+    /// 搜索出错的区域数
+    pub regions_failed: usize,
+    /// 超过单区域超时被放弃的区域数，已找到的部分结果仍保留
+    pub regions_timed_out: usize,
+}
+
+/// 同步搜索的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOutcome {
+    /// 写入结果集的数量
+    pub count: usize,
+    /// 结果超过上限被截断
+    pub truncated: bool,
+    /// 搜索被取消，结果集只包含取消前找到的部分
+    pub cancelled: bool,
+    /// 结果来自缓存，没有实际读取内存
+    pub cache_hit: bool,
+    pub elapsed: Duration,
+    pub stats: ScanStats,
+}
@@ -0,0 +1,85 @@
+//! 搜索结果缓存：同一查询在同一组区域上重复扫描、期间内存没有被写入时，直接复用上一次的结果
+//!
+//! 键由完整的查询、区域列表、是否深度搜索和 [`memory_generation`] 组成，逐项比较，不依赖哈希。
+//! 本应用写入内存、绑定或解绑进程都会让代数递增，旧条目自然失效；区域列表变化则得到不同的键。
+//! 目标进程自己改变内存时代数不变，因此默认关闭，只在确定内存不变的场景下开启。
+
+use super::manager::ValuePair;
+use crate::core::globals::memory_generation;
+use crate::search::SearchQuery;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// 默认缓存的查询数，0 表示关闭
+pub const DEFAULT_QUERY_CACHE_CAPACITY: usize = 0;
+
+/// 超过该数量的结果不缓存，避免缓存本身占用过多内存
+pub const MAX_CACHED_RESULTS: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QueryCacheKey {
+    query: SearchQuery,
+    regions: Vec<(u64, u64)>,
+    use_deep_search: bool,
+    generation: u64,
+}
+
+impl QueryCacheKey {
+    /// `regions` 应为清理后的区域列表，代数取当前值
+    pub(crate) fn new(query: &SearchQuery, regions: &[(u64, u64)], use_deep_search: bool) -> Self {
+        Self {
+            query: query.clone(),
+            regions: regions.to_vec(),
+            use_deep_search,
+            generation: memory_generation(),
+        }
+    }
+}
+
+/// 按最近使用顺序淘汰的结果缓存，队尾为最近使用
+pub(crate) struct QueryCache {
+    capacity: usize,
+    entries: VecDeque<(QueryCacheKey, Arc<Vec<ValuePair>>)>,
+}
+
+impl QueryCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// 命中时把条目移到队尾
+    pub(crate) fn get(&mut self, key: &QueryCacheKey) -> Option<Arc<Vec<ValuePair>>> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos)?;
+        let results = Arc::clone(&entry.1);
+        self.entries.push_back(entry);
+        Some(results)
+    }
+
+    /// 写入结果，已被写入内存等操作作废的条目一并清掉
+    pub(crate) fn insert(&mut self, key: QueryCacheKey, results: Vec<ValuePair>) {
+        if self.capacity == 0 || results.len() > MAX_CACHED_RESULTS {
+            return;
+        }
+
+        self.entries.retain(|(k, _)| k.generation == key.generation && *k != key);
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, Arc::new(results)));
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,99 @@
+//! 带权限标签的搜索区域
+//!
+//! 驱动枚举出的区域带有 MEM_* 权限位，这里保留这些标签，让查询按需包含或排除只读区域。
+//! 数值扫描默认跳过只读区域；字符串、特征码经常位于 `.rodata`/`.text`，需要包含它们。
+
+use super::super::types::SearchQuery;
+use crate::wuwa::{WuwaMemRegionEntry, MEM_READABLE, MEM_WRITABLE};
+
+/// 一个待扫描的内存区域及其标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchRegion {
+    pub start: u64,
+    pub end: u64,
+    /// MEM_* 权限位
+    pub flags: u32,
+    /// 是否映射自文件（名称为绝对路径）
+    pub file_backed: bool,
+}
+
+impl SearchRegion {
+    pub fn new(start: u64, end: u64, flags: u32, file_backed: bool) -> Self {
+        Self {
+            start,
+            end,
+            flags,
+            file_backed,
+        }
+    }
+
+    pub fn from_entry(entry: &WuwaMemRegionEntry) -> Self {
+        let name = entry.name;
+        let file_backed = name[0] == b'/';
+        Self::new(entry.start, entry.end, entry.type_, file_backed)
+    }
+
+    #[inline]
+    pub fn is_readable(&self) -> bool {
+        self.flags & MEM_READABLE != 0
+    }
+
+    /// 可读但不可写，例如 `.rodata` 与代码段
+    #[inline]
+    pub fn is_readonly(&self) -> bool {
+        self.is_readable() && self.flags & MEM_WRITABLE == 0
+    }
+}
+
+/// 按查询的 `include_readonly` 选择要扫描的区域，不可读区域始终被排除
+pub fn regions_for_query(query: &SearchQuery, regions: &[SearchRegion]) -> Vec<(u64, u64)> {
+    select_regions(regions, query.include_readonly())
+}
+
+pub fn select_regions(regions: &[SearchRegion], include_readonly: bool) -> Vec<(u64, u64)> {
+    regions
+        .iter()
+        .filter(|r| r.is_readable() && (include_readonly || !r.is_readonly()))
+        .map(|r| (r.start, r.end))
+        .collect()
+}
+
+/// 结果地址 → 区域编号的映射，用于按模块/区域分组显示结果
+///
+/// 编号是区域在搜索时传入列表中的下标。只在搜索时记录一次区域表，结果本身不额外占用空间。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegionTags {
+    /// (start, end, region_id)，按 start 排序
+    ranges: Vec<(u64, u64, u32)>,
+}
+
+impl RegionTags {
+    pub fn new(regions: &[(u64, u64)]) -> Self {
+        let mut ranges: Vec<(u64, u64, u32)> = regions
+            .iter()
+            .enumerate()
+            .filter(|(_, (start, end))| start < end)
+            .map(|(id, &(start, end))| (start, end, id as u32))
+            .collect();
+        ranges.sort_unstable();
+        Self { ranges }
+    }
+
+    /// 地址所属区域的编号，不在任何区域内时返回 None
+    pub fn region_of(&self, addr: u64) -> Option<u32> {
+        let idx = self.ranges.partition_point(|&(start, _, _)| start <= addr);
+        self.ranges[..idx]
+            .iter()
+            .rev()
+            .find(|&&(_, end, _)| addr < end)
+            .map(|&(_, _, id)| id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
@@ -0,0 +1,107 @@
+//! 基于随机采样的搜索结果数量估算，以及只扫描区域开头部分的抽样搜索
+//!
+//! 随机抽取若干页进行扫描，再按总字节数外推结果数量。RNG 由调用方传入，
+//! 固定种子时结果可复现。
+
+use super::super::types::SearchValue;
+use super::super::SearchResultItem;
+use super::manager::ValuePair;
+use super::single_search::search_in_chunks_with_status;
+use crate::search::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// 抽样搜索的结果：只包含扫描到的那部分内存中的匹配，不代表全部
+#[derive(Clone)]
+pub struct SampleScanReport {
+    /// 按地址排序
+    pub results: Vec<SearchResultItem>,
+    /// 实际扫描的字节数
+    pub sampled_bytes: u64,
+    /// 区域总字节数
+    pub total_bytes: u64,
+    /// 有区域没有扫描完整，没找到不代表不存在
+    pub partial: bool,
+}
+
+/// 把每个区域截成开头 `bytes_per_region` 字节
+pub(crate) fn region_prefixes(regions: &[(u64, u64)], bytes_per_region: u64) -> Vec<(u64, u64)> {
+    regions.iter().map(|&(start, end)| (start, end.min(start.saturating_add(bytes_per_region)))).collect()
+}
+
+/// 在 `regions` 中随机采样 `sample_pages` 页，估算 `target` 的匹配数量
+///
+/// `read` 负责读取页对齐地址处的数据并填写页状态，读取失败的页不计入采样字节数
+pub(crate) fn estimate_single_count<F>(
+    target: &SearchValue,
+    regions: &[(u64, u64)],
+    sample_pages: usize,
+    rng: &mut StdRng,
+    read: F,
+) -> Result<u64>
+where
+    F: Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    let regions: Vec<(u64, u64)> = regions.iter().copied().filter(|(start, end)| start < end).collect();
+    let total_bytes: u64 = regions.iter().map(|(start, end)| end - start).sum();
+    if total_bytes == 0 || sample_pages == 0 {
+        return Ok(0);
+    }
+
+    let value_type = target.value_type();
+    let element_size = value_type.size();
+    let page_size = *PAGE_SIZE as u64;
+
+    let mut page_buffer = vec![0u8; *PAGE_SIZE];
+    let mut sampled_bytes = 0u64;
+    let mut matches = 0u64;
+
+    for _ in 0..sample_pages {
+        // 按区域大小加权随机选取一个位置
+        let mut offset = rng.random_range(0..total_bytes);
+        let &(region_start, region_end) = regions
+            .iter()
+            .find(|(start, end)| {
+                let len = end - start;
+                if offset < len {
+                    true
+                } else {
+                    offset -= len;
+                    false
+                }
+            })
+            .expect("offset is always inside total_bytes");
+
+        let page_addr = (region_start + offset) & !(page_size - 1);
+        let mut page_status = PageStatusBitmap::new(*PAGE_SIZE, page_addr as usize);
+        if read(page_addr, &mut page_buffer, &mut page_status).is_err() || !page_status.is_page_success(0) {
+            continue;
+        }
+
+        let sample_start = page_addr.max(region_start);
+        let sample_end = (page_addr + page_size).min(region_end);
+        sampled_bytes += sample_end - sample_start;
+
+        let mut results: Vec<ValuePair> = Vec::new();
+        search_in_chunks_with_status(
+            &page_buffer,
+            page_addr,
+            sample_start,
+            sample_end,
+            element_size,
+            target,
+            value_type,
+            &page_status,
+            &mut results,
+        );
+        matches += results.len() as u64;
+    }
+
+    if sampled_bytes == 0 {
+        return Ok(0);
+    }
+
+    Ok((matches as u128 * total_bytes as u128 / sampled_bytes as u128) as u64)
+}
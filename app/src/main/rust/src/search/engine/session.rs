@@ -0,0 +1,129 @@
+//! 会话存档：把结果集、过滤器、备注和（可选的）冻结列表打包成一个 zip 文件，之后可完整恢复
+//!
+//! 存档包含两个条目：
+//! - `manifest.json`：版本号、结果数、过滤器、备注、冻结列表
+//! - `results.bin`：结果集，使用 [`packed`](crate::search::result_manager::packed) 格式，头部记录结果模式
+
+use super::filter::SearchFilter;
+use crate::core::freeze_manager::FrozenEntry;
+use crate::search::ValueType;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+pub const SESSION_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const RESULTS_ENTRY: &str = "results.bin";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SessionManifest {
+    pub version: u32,
+    pub result_count: usize,
+    pub filter: FilterManifest,
+    pub notes: String,
+    /// 保存时未提供冻结管理器则为 None，加载时不改动冻结列表
+    pub frozen: Option<Vec<FrozenManifest>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FilterManifest {
+    pub enable_address_filter: bool,
+    pub address_start: u64,
+    pub address_end: u64,
+    pub enable_type_filter: bool,
+    /// [`ValueType::to_id`]
+    pub type_ids: Vec<i32>,
+}
+
+impl From<&SearchFilter> for FilterManifest {
+    fn from(filter: &SearchFilter) -> Self {
+        Self {
+            enable_address_filter: filter.enable_address_filter,
+            address_start: filter.address_start,
+            address_end: filter.address_end,
+            enable_type_filter: filter.enable_type_filter,
+            type_ids: filter.type_ids.iter().map(ValueType::to_id).collect(),
+        }
+    }
+}
+
+impl FilterManifest {
+    /// 无法识别的类型 ID 会被丢弃
+    pub fn to_filter(&self) -> SearchFilter {
+        SearchFilter {
+            enable_address_filter: self.enable_address_filter,
+            address_start: self.address_start,
+            address_end: self.address_end,
+            enable_type_filter: self.enable_type_filter,
+            type_ids: self.type_ids.iter().filter_map(|&id| ValueType::from_id(id)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FrozenManifest {
+    pub address: u64,
+    pub value: Vec<u8>,
+    pub value_type: i32,
+    pub group: Option<String>,
+    pub interval_us: Option<u64>,
+}
+
+impl FrozenManifest {
+    pub fn new(address: u64, entry: &FrozenEntry) -> Self {
+        Self {
+            address,
+            value: entry.value.clone(),
+            value_type: entry.value_type,
+            group: entry.group.clone(),
+            interval_us: entry.interval_us,
+        }
+    }
+
+    pub fn to_entry(&self) -> FrozenEntry {
+        FrozenEntry {
+            value: self.value.clone(),
+            value_type: self.value_type,
+            group: self.group.clone(),
+            interval_us: self.interval_us,
+        }
+    }
+}
+
+/// 写入存档，已存在的文件会被覆盖
+pub(crate) fn write_archive(path: &Path, manifest: &SessionManifest, results: &[u8]) -> Result<()> {
+    let file = File::create(path).map_err(|e| anyhow!("Failed to create session file {:?}: {}", path, e))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default();
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(serde_json::to_string_pretty(manifest)?.as_bytes())?;
+
+    zip.start_file(RESULTS_ENTRY, options)?;
+    zip.write_all(results)?;
+
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// 读取存档，返回清单和 `results.bin` 的内容
+pub(crate) fn read_archive(path: &Path) -> Result<(SessionManifest, Vec<u8>)> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open session file {:?}: {}", path, e))?;
+    let mut zip = ZipArchive::new(BufReader::new(file))?;
+
+    let mut manifest_json = String::new();
+    zip.by_name(MANIFEST_ENTRY)?.read_to_string(&mut manifest_json)?;
+    let manifest: SessionManifest = serde_json::from_str(&manifest_json)?;
+    if manifest.version != SESSION_VERSION {
+        return Err(anyhow!("Unsupported session version {}", manifest.version));
+    }
+
+    let mut results = Vec::new();
+    zip.by_name(RESULTS_ENTRY)?.read_to_end(&mut results)?;
+    Ok((manifest, results))
+}
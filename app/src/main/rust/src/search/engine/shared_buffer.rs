@@ -78,6 +78,16 @@ pub struct SharedBuffer {
     len: usize,
 }
 
+/// A clone points at the same memory; later `set`/`clear` calls on the original do not affect it.
+impl Clone for SharedBuffer {
+    fn clone(&self) -> Self {
+        Self {
+            ptr: AtomicPtr::new(self.ptr.load(Ordering::Acquire)),
+            len: self.len,
+        }
+    }
+}
+
 unsafe impl Send for SharedBuffer {}
 unsafe impl Sync for SharedBuffer {}
 
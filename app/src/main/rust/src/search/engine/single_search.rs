@@ -1,6 +1,6 @@
 use super::super::types::{SearchValue, ValueType};
-use super::manager::{ValuePair, BPLUS_TREE_ORDER};
-use crate::core::DRIVER_MANAGER;
+use super::manager::{page_aligned_chunk_size, SearchEngineManager, ValuePair, BPLUS_TREE_ORDER};
+use crate::core::globals::driver_manager_read;
 use crate::search::engine::memchr_ext::MemchrExt;
 use crate::search::{PAGE_MASK, PAGE_SIZE};
 use crate::wuwa::PageStatusBitmap;
@@ -37,6 +37,38 @@ pub(crate) fn search_in_chunks_with_status(
     value_type: ValueType,          // 目标值类型
     page_status: &PageStatusBitmap, // 页面状态位图
     results: &mut Vec<ValuePair>,   // 搜索结果
+) {
+    search_in_chunks_aligned(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        element_size,
+        value_type.alignment(),
+        target,
+        value_type,
+        page_status,
+        results,
+    )
+}
+
+/// 按指定对齐（扫描步长）搜索，`alignment` 可以小于 `element_size`
+///
+/// `[region_start, region_end)` 不必按元素对齐：起点按绝对地址向上对齐，只有完整落在区域内的元素才会匹配，
+/// 跨过 `region_end` 的元素即使在 buffer 内也不匹配
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_in_chunks_aligned(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    element_size: usize,
+    alignment: usize, // 扫描步长，需为 2 的幂
+    target: &SearchValue,
+    value_type: ValueType,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
 ) {
     assert_eq!(buffer_addr as usize % *PAGE_SIZE, 0);
 
@@ -49,7 +81,8 @@ pub(crate) fn search_in_chunks_with_status(
     }
 
     // 只扫描 buffer 内与 [search_start, search_end) 交集对应的 pos 范围
-    let scan_start_pos = (search_start - buffer_addr) as usize;
+    // 起点按元素对齐，保证每个分块边界都落在对齐位置上，不会切断跨边界的元素
+    let scan_start_pos = first_aligned_pos(buffer_addr, (search_start - buffer_addr) as usize, alignment);
     let scan_end_pos = (search_end - buffer_addr) as usize;
 
     // 按大粒度切分 pos 范围，之前的代码按照4k分块抵消了并行优势
@@ -67,7 +100,13 @@ pub(crate) fn search_in_chunks_with_status(
         && fast_int
         && let Ok(bytes) = bytes_opt
     {
-        bytes.len() > 1 && bytes.len() <= 8 && bytes[0] != 0x00 && bytes[0] != 0xFF && bytes[0] != 0xFE
+        // 该路径不处理跨页元素，仅在对齐等于元素大小时使用
+        alignment == element_size
+            && bytes.len() > 1
+            && bytes.len() <= 8
+            && bytes[0] != 0x00
+            && bytes[0] != 0xFF
+            && bytes[0] != 0xFE
     } else {
         false
     };
@@ -75,7 +114,7 @@ pub(crate) fn search_in_chunks_with_status(
     let hits = ranges
         .into_par_iter()
         .map(|(rs, re)| {
-            let estimated_matches = ((re - rs) / element_size) >> 2;
+            let estimated_matches = ((re - rs) / alignment) >> 2;
             let mut local = Vec::with_capacity(estimated_matches);
 
             // 单字节搜索加速
@@ -108,7 +147,7 @@ pub(crate) fn search_in_chunks_with_status(
                 // memchr 多字节加速路径
                 let bytes = target.bytes().unwrap();
                 let first_byte = bytes[0];
-                let align_mask = (element_size - 1) as u64; // 对齐掩码（2^n - 1）
+                let align_mask = (alignment - 1) as u64; // 对齐掩码（2^n - 1）
 
                 // 按页遍历，只在成功页上搜索
                 let start_page_idx = rs / *PAGE_SIZE;
@@ -162,51 +201,29 @@ pub(crate) fn search_in_chunks_with_status(
                 return local; // 早返回，避免执行慢速路径
             }
 
-            // 这里用 while 方便跳过失败页
-            let mut pos = rs;
-
-            // 注意：对齐必须按绝对地址算
-            pos = first_aligned_pos(buffer_addr, pos, element_size);
-            let mut current_page_end = ((pos / *PAGE_SIZE + 1) * *PAGE_SIZE).min(re);
-
-            while pos < re {
-                // 如果越界（比对需要 element_size/needle_len），提前结束
-                if pos + element_size > re {
-                    break;
-                }
-
-                // 当跨越页边界时才重新检查
-                if pos >= current_page_end {
-                    // 跳过失败页：一旦发现当前 pos 所在页不成功，直接跳到下一页开始，并重新对齐
-                    let page_idx = pos / *PAGE_SIZE;
-                    if !page_status.is_page_success(page_idx) {
-                        let next_page = (page_idx + 1) * *PAGE_SIZE;
-                        pos = first_aligned_pos(buffer_addr, next_page, element_size);
-                        current_page_end = ((pos / *PAGE_SIZE + 1) * *PAGE_SIZE).min(re);
-                        continue;
-                    }
-                }
-
-                let other = &buffer[pos..pos + element_size];
-
-                let ok = if fast_int {
-                    // 如果你有 bytes，且 element_size == bytes.len()，可以直接比较，避免 matched() 的类型分发成本
-                    // （这里假设 bytes.len()==element_size，否则你要按真实逻辑调整）
-                    if let Ok(bytes) = target.bytes() { other == bytes } else { false }
-                } else {
-                    target.matched(other).unwrap_or_else(|e| {
-                        error!("target.matched error, {}", e);
-                        false
-                    })
-                };
-
-                if ok {
-                    local.push(buffer_addr + pos as u64);
-                }
-
-                pos += element_size;
+            // 双字节搜索加速：memchr 锚点对 0x00/0xFF/0xFE 等常见首字节无效，这里直接按 u16 批量比较
+            if fast_int
+                && let Ok(bytes) = target.bytes() && bytes.len() == 2 && element_size == 2 && alignment == 2
+            {
+                let needle = u16::from_le_bytes([bytes[0], bytes[1]]);
+                scan_range_word(buffer, buffer_addr, rs, re, needle, page_status, &mut local);
+                return local;
             }
 
+            scan_range_generic(
+                buffer,
+                buffer_addr,
+                rs,
+                re,
+                scan_end_pos,
+                element_size,
+                alignment,
+                target,
+                fast_int,
+                page_status,
+                &mut local,
+            );
+
             local
         })
         .reduce(Vec::new, |mut a, mut b| {
@@ -219,16 +236,227 @@ pub(crate) fn search_in_chunks_with_status(
     }
 }
 
+/// 每批比较的 u16 个数，批内先做无分支的 OR 归约（可被编译器向量化），命中再逐个定位
+const WORD_SCAN_LANES: usize = 32;
+
+/// 双字节精确值扫描，结果与 [`scan_range_generic`] 完全一致
+#[inline]
+fn scan_range_word(
+    buffer: &[u8],
+    buffer_addr: u64,
+    rs: usize,
+    re: usize,
+    needle: u16,
+    page_status: &PageStatusBitmap,
+    local: &mut Vec<u64>,
+) {
+    let start_page_idx = rs / *PAGE_SIZE;
+    let end_page_idx = re.div_ceil(*PAGE_SIZE);
+
+    for page_idx in start_page_idx..end_page_idx {
+        if !page_status.is_page_success(page_idx) {
+            continue; // 跳过失败页
+        }
+
+        let page_start = first_aligned_pos(buffer_addr, (page_idx * *PAGE_SIZE).max(rs), 2);
+        let page_end = ((page_idx + 1) * *PAGE_SIZE).min(re);
+        if page_start + 2 > page_end {
+            continue;
+        }
+
+        let page_slice = &buffer[page_start..page_end];
+        let mut words = page_slice.chunks_exact(2);
+        let mut base = page_start;
+
+        loop {
+            let mut block = [0u16; WORD_SCAN_LANES];
+            let mut n = 0;
+            for (slot, w) in block.iter_mut().zip(words.by_ref()) {
+                *slot = u16::from_le_bytes([w[0], w[1]]);
+                n += 1;
+            }
+            if n == 0 {
+                break;
+            }
+
+            let hit = block[..n].iter().fold(false, |acc, &w| acc | (w == needle));
+            if hit {
+                for (i, &w) in block[..n].iter().enumerate() {
+                    if w == needle {
+                        local.push(buffer_addr + (base + i * 2) as u64);
+                    }
+                }
+            }
+
+            base += n * 2;
+            if n < WORD_SCAN_LANES {
+                break;
+            }
+        }
+    }
+}
+
+/// 通用逐元素扫描路径，`fast_int` 为 true 时直接比较字节，否则走 `matched()`
+#[inline]
+#[allow(clippy::too_many_arguments)]
+fn scan_range_generic(
+    buffer: &[u8],
+    buffer_addr: u64,
+    rs: usize,
+    re: usize,       // 起始位置上限
+    read_end: usize, // 读取上限，步长小于元素大小时元素可以越过 re
+    element_size: usize,
+    alignment: usize,
+    target: &SearchValue,
+    fast_int: bool,
+    page_status: &PageStatusBitmap,
+    local: &mut Vec<u64>,
+) {
+    // 这里用 while 方便跳过失败页
+    let mut pos = rs;
+
+    // 注意：对齐必须按绝对地址算
+    pos = first_aligned_pos(buffer_addr, pos, alignment);
+    // 初始为 pos，保证第一页也会检查页状态
+    let mut current_page_end = pos;
+
+    while pos < re {
+        // 如果越界（比对需要 element_size/needle_len），提前结束
+        if pos + element_size > read_end {
+            break;
+        }
+
+        // 当跨越页边界时才重新检查
+        if pos >= current_page_end {
+            // 跳过失败页：一旦发现当前 pos 所在页不成功，直接跳到下一页开始，并重新对齐
+            let page_idx = pos / *PAGE_SIZE;
+            if !page_status.is_page_success(page_idx) {
+                let next_page = (page_idx + 1) * *PAGE_SIZE;
+                pos = first_aligned_pos(buffer_addr, next_page, alignment);
+                current_page_end = pos;
+                continue;
+            }
+            current_page_end = ((page_idx + 1) * *PAGE_SIZE).min(re);
+        }
+
+        // 步长小于元素大小时元素可能跨页，下一页读取失败则跳过
+        if alignment < element_size
+            && (pos % *PAGE_SIZE) + element_size > *PAGE_SIZE
+            && !page_status.is_page_success(pos / *PAGE_SIZE + 1)
+        {
+            pos += alignment;
+            continue;
+        }
+
+        let other = &buffer[pos..pos + element_size];
+
+        let ok = if fast_int {
+            // 如果你有 bytes，且 element_size == bytes.len()，可以直接比较，避免 matched() 的类型分发成本
+            // （这里假设 bytes.len()==element_size，否则你要按真实逻辑调整）
+            if let Ok(bytes) = target.bytes() { other == bytes } else { false }
+        } else {
+            target.matched(other).unwrap_or_else(|e| {
+                error!("target.matched error, {}", e);
+                false
+            })
+        };
+
+        if ok {
+            local.push(buffer_addr + pos as u64);
+        }
+
+        pos += alignment;
+    }
+}
+
+/// 仅走通用扫描路径的 [`search_in_chunks_with_status`]，用于和加速路径对比结果
+#[cfg(test)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_in_chunks_generic(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    element_size: usize,
+    target: &SearchValue,
+    value_type: ValueType,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+) {
+    let search_start = buffer_addr.max(region_start);
+    let search_end = (buffer_addr + buffer.len() as u64).min(region_end);
+    if search_start >= search_end {
+        return;
+    }
+
+    let mut local = Vec::new();
+    scan_range_generic(
+        buffer,
+        buffer_addr,
+        (search_start - buffer_addr) as usize,
+        (search_end - buffer_addr) as usize,
+        (search_end - buffer_addr) as usize,
+        element_size,
+        value_type.alignment(),
+        target,
+        false,
+        page_status,
+        &mut local,
+    );
+    results.extend(local.into_iter().map(|addr| ValuePair::new(addr, value_type)));
+}
+
 pub(crate) fn search_region_single(
     target: &SearchValue,
     start: u64,        // 区域起始地址
     end: u64,          // 区域结束地址
     chunk_size: usize, // 每次读取的块大小
 ) -> Result<Vec<ValuePair>> {
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    search_region_single_with_cancel(target, start, end, chunk_size, &|| false)
+}
+
+/// 支持取消的单值搜索，每读取一块前检查一次，取消后返回已找到的部分结果
+pub(crate) fn search_region_single_with_cancel<F>(target: &SearchValue, start: u64, end: u64, chunk_size: usize, check_cancelled: &F) -> Result<Vec<ValuePair>>
+where
+    F: Fn() -> bool,
+{
+    // 零长度或反向区域直接跳过
+    if start >= end {
+        return Ok(Vec::new());
+    }
+
+    let driver_manager = driver_manager_read();
+
+    search_region_single_with(
+        target,
+        start,
+        end,
+        chunk_size,
+        |addr, buf, page_status| driver_manager.read_memory_unified(addr, buf, Some(page_status)),
+        check_cancelled,
+    )
+}
+
+/// 单值搜索的区域扫描实现，`read` 负责读取页对齐地址处的数据
+pub(crate) fn search_region_single_with<R, F>(
+    target: &SearchValue,
+    start: u64,
+    end: u64,
+    chunk_size: usize,
+    mut read: R,
+    check_cancelled: &F,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool,
+{
+    if start >= end {
+        return Ok(Vec::new());
+    }
 
     let value_type = target.value_type();
     let element_size = value_type.size();
+    let chunk_size = page_aligned_chunk_size(chunk_size);
 
     let mut results = Vec::new();
     let mut read_success = 0usize;
@@ -238,13 +466,17 @@ pub(crate) fn search_region_single(
     let mut chunk_buffer = vec![0u8; chunk_size]; // 读取缓冲区
 
     while current < end {
-        let chunk_end = (current + chunk_size as u64).min(end); // 当前块的结束地址，如果超过end则取end
+        if check_cancelled() {
+            break;
+        }
+
+        let chunk_end = current.saturating_add(chunk_size as u64).min(end); // 当前块的结束地址，如果超过end则取end
         let chunk_len = (chunk_end - current) as usize; // 当前块的实际长度
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
         // 这里读取内存，这里的current一定页对齐的
-        let read_result = driver_manager.read_memory_unified(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status));
+        let read_result = read(current, &mut chunk_buffer[..chunk_len], &mut page_status);
 
         match read_result {
             Ok(_) => {
@@ -292,16 +524,196 @@ pub(crate) fn search_region_single(
     Ok(results)
 }
 
+/// 按区域、按块顺序扫描，找到 `n` 个匹配后立即返回，剩余块与区域不再读取
+///
+/// 块内结果按地址排序，保证在相同内存内容下结果确定。`read` 负责读取页对齐地址处的数据。
+pub(crate) fn find_first_n_single<F>(target: &SearchValue, regions: &[(u64, u64)], chunk_size: usize, n: usize, read: F) -> Result<Vec<ValuePair>>
+where
+    F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    find_first_n_single_aligned(target, regions, chunk_size, n, target.value_type().alignment(), read)
+}
+
+/// 按指定扫描步长 `alignment` 执行 [`find_first_n_single`]，例如 1 表示逐字节查找未按类型对齐的值
+pub(crate) fn find_first_n_single_aligned<F>(
+    target: &SearchValue,
+    regions: &[(u64, u64)],
+    chunk_size: usize,
+    n: usize,
+    alignment: usize,
+    read: F,
+) -> Result<Vec<ValuePair>>
+where
+    F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    find_first_n_single_where(target, regions, chunk_size, n, alignment, |_| true, read)
+}
+
+/// [`find_first_n_single_aligned`] 的过滤版本：只保留 `keep` 接受的地址（例如对象对齐网格），`n` 按过滤后的结果计数
+pub(crate) fn find_first_n_single_where<K, F>(
+    target: &SearchValue,
+    regions: &[(u64, u64)],
+    chunk_size: usize,
+    n: usize,
+    alignment: usize,
+    keep: K,
+    mut read: F,
+) -> Result<Vec<ValuePair>>
+where
+    K: Fn(u64) -> bool,
+    F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    let value_type = target.value_type();
+    let element_size = value_type.size();
+    let chunk_size = page_aligned_chunk_size(chunk_size);
+
+    let mut results = Vec::with_capacity(n.min(1024));
+    if n == 0 {
+        return Ok(results);
+    }
+
+    // 步长小于元素大小时元素可以跨过块边界，多读 element_size - 1 字节，起点仍只取本块内的
+    let overlap = if alignment < element_size { element_size - 1 } else { 0 };
+    let mut chunk_buffer = vec![0u8; chunk_size + overlap];
+
+    for &(start, end) in regions {
+        let mut current = start & !(*PAGE_SIZE as u64 - 1);
+
+        while current < end {
+            let chunk_end = current.saturating_add(chunk_size as u64).min(end);
+            let read_end = chunk_end.saturating_add(overlap as u64).min(end);
+            let read_len = (read_end - current) as usize;
+
+            let mut page_status = PageStatusBitmap::new(read_len, current as usize);
+            if read(current, &mut chunk_buffer[..read_len], &mut page_status).is_ok() && page_status.success_count() > 0 {
+                let mut chunk_results = Vec::new();
+                search_in_chunks_aligned(
+                    &chunk_buffer[..read_len],
+                    current,
+                    start,
+                    end,
+                    element_size,
+                    alignment,
+                    target,
+                    value_type,
+                    &page_status,
+                    &mut chunk_results,
+                );
+                chunk_results.retain(|pair| pair.addr < chunk_end && keep(pair.addr));
+                chunk_results.sort();
+
+                let remaining = n - results.len();
+                results.extend(chunk_results.into_iter().take(remaining));
+                if results.len() >= n {
+                    return Ok(results);
+                }
+            }
+
+            current = chunk_end;
+        }
+    }
+
+    Ok(results)
+}
+
+/// 交错数组扫描：找出 `target` 以 `stride` 为间隔至少连续出现 `min_repeats` 次的起始地址
+///
+/// 先按块收集整个区域内的匹配，再检查周期性：前一个周期（`addr - stride`）也匹配的地址
+/// 不是数组起点，会被跳过；连续次数不足 `min_repeats` 的孤立匹配被丢弃。结果按地址排序。
+pub(crate) fn find_periodic_single<F>(
+    target: &SearchValue,
+    stride: usize,
+    min_repeats: usize,
+    regions: &[(u64, u64)],
+    chunk_size: usize,
+    mut read: F,
+) -> Result<Vec<u64>>
+where
+    F: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    if stride == 0 {
+        return Err(anyhow!("Stride must be greater than 0"));
+    }
+
+    let value_type = target.value_type();
+    let element_size = value_type.size();
+    let stride = stride as u64;
+    let min_repeats = min_repeats.max(1);
+    let chunk_size = page_aligned_chunk_size(chunk_size);
+
+    let mut starts = Vec::new();
+    let mut chunk_buffer = vec![0u8; chunk_size];
+
+    for &(start, end) in regions {
+        let mut matches = Vec::new();
+        let mut current = start & !(*PAGE_SIZE as u64 - 1);
+
+        while current < end {
+            let chunk_end = current.saturating_add(chunk_size as u64).min(end);
+            let chunk_len = (chunk_end - current) as usize;
+
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            if read(current, &mut chunk_buffer[..chunk_len], &mut page_status).is_ok() && page_status.success_count() > 0 {
+                search_in_chunks_with_status(
+                    &chunk_buffer[..chunk_len],
+                    current,
+                    start,
+                    end,
+                    element_size,
+                    target,
+                    value_type,
+                    &page_status,
+                    &mut matches,
+                );
+            }
+
+            current = chunk_end;
+        }
+
+        let matched: std::collections::HashSet<u64> = matches.iter().map(|pair| pair.addr).collect();
+        let mut addrs: Vec<u64> = matched.iter().copied().collect();
+        addrs.sort_unstable();
+
+        for addr in addrs {
+            // 前一个周期仍匹配说明 addr 位于某个数组中间
+            if addr.checked_sub(stride).is_some_and(|prev| prev >= start && matched.contains(&prev)) {
+                continue;
+            }
+
+            let mut repeats = 1;
+            let mut next = addr;
+            while repeats < min_repeats {
+                match next.checked_add(stride) {
+                    Some(n) if matched.contains(&n) => {
+                        next = n;
+                        repeats += 1;
+                    },
+                    _ => break,
+                }
+            }
+
+            if repeats >= min_repeats {
+                starts.push(addr);
+            }
+        }
+    }
+
+    Ok(starts)
+}
+
 /// 单值细化搜索
 /// 逐个读取地址的值，再用rayon并行判断
 /// 返回仍然匹配的地址列表
-#[deprecated]
-pub(crate) fn refine_single_search(
+pub(crate) fn refine_single_search<R>(
     addresses: &[ValuePair],
     target: &SearchValue,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
-) -> Result<Vec<ValuePair>> {
+    mut read: R,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
     use rayon::prelude::*;
     use std::sync::atomic::Ordering;
 
@@ -309,8 +721,6 @@ pub(crate) fn refine_single_search(
         return Ok(Vec::new());
     }
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
     let target_type = target.value_type();
     let element_size = target_type.size();
 
@@ -325,8 +735,7 @@ pub(crate) fn refine_single_search(
     let mut address_values: Vec<(ValuePair, Vec<u8>)> = Vec::with_capacity(filtered_addresses.len());
 
     for pair in &filtered_addresses {
-        let mut buffer = vec![0u8; element_size];
-        if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
+        if let Some(buffer) = SearchEngineManager::read_value_with(pair.addr, element_size, &mut read) {
             address_values.push((pair.clone(), buffer));
         }
 
@@ -336,8 +745,6 @@ pub(crate) fn refine_single_search(
         }
     }
 
-    drop(driver_manager);
-
     // 用rayon并行判断
     let results: Vec<ValuePair> = address_values
         .into_par_iter()
@@ -386,7 +793,7 @@ where
         return Ok(Vec::new());
     }
 
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let driver_manager = driver_manager_read();
 
     let target_type = target.value_type();
     let element_size = target_type.size();
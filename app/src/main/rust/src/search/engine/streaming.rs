@@ -0,0 +1,69 @@
+//! 流式搜索：每扫描完一个区域就把结果通过通道发给调用方，UI 无需轮询即可实时显示
+//!
+//! 通道通常是有界的。rayon 工作线程从不阻塞在发送上：区域按线程池大小分批并行扫描，
+//! 一批结束后由调用线程依次发送，接收方处理不过来时调用线程阻塞，下一批扫描随之推迟。
+
+use super::manager::ValuePair;
+use crate::search::SearchResultItem;
+use anyhow::Result;
+use log::{debug, error};
+use rayon::prelude::*;
+use std::sync::mpsc::SyncSender;
+
+/// 一个区域的搜索结果
+pub struct ResultBatch {
+    /// 区域在清理后区域列表中的下标
+    pub region_index: usize,
+    pub region: (u64, u64),
+    /// 按地址排序
+    pub results: Vec<SearchResultItem>,
+}
+
+/// 并行扫描 `regions`，每个有结果的区域发送一个批次，返回发送的结果总数
+///
+/// 接收方关闭通道后停止扫描，返回已发送的数量
+pub(crate) fn stream_regions<S>(regions: &[(u64, u64)], tx: &SyncSender<ResultBatch>, search_region: S) -> usize
+where
+    S: Fn(u64, u64) -> Result<Vec<ValuePair>> + Sync,
+{
+    let window = rayon::current_num_threads().max(1);
+    let mut sent = 0usize;
+
+    for (window_idx, window_regions) in regions.chunks(window).enumerate() {
+        let base_idx = window_idx * window;
+        let batches: Vec<ResultBatch> = window_regions
+            .par_iter()
+            .enumerate()
+            .filter_map(|(offset, &(start, end))| {
+                let mut pairs = match search_region(start, end) {
+                    Ok(pairs) => pairs,
+                    Err(e) => {
+                        error!("Failed to search region {}: {:?}", base_idx + offset, e);
+                        return None;
+                    },
+                };
+                if pairs.is_empty() {
+                    return None;
+                }
+                pairs.sort_unstable_by_key(|pair| pair.addr);
+                Some(ResultBatch {
+                    region_index: base_idx + offset,
+                    region: (start, end),
+                    results: pairs.iter().map(SearchResultItem::from).collect(),
+                })
+            })
+            .collect();
+
+        // 在线程池外发送，有界通道的背压只会阻塞调用线程
+        for batch in batches {
+            let count = batch.results.len();
+            if tx.send(batch).is_err() {
+                debug!("Result receiver closed, streaming stopped after {} results", sent);
+                return sent;
+            }
+            sent += count;
+        }
+    }
+
+    sent
+}
@@ -1,5 +1,5 @@
 use std::i128;
-use super::types::ValueType;
+use super::types::{TextEncoding, ValueType, MAX_BYTE_PATTERN_LEN};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Token<'a> {
@@ -10,6 +10,17 @@ pub enum Token<'a> {
     DoubleColon,
     Tilde,
     DoubleTilde,
+    /// `..`，不含上界的范围
+    DotDot,
+    /// `..=`，闭区间范围
+    DotDotEq,
+    /// 范围前的 `!`，匹配范围之外的值
+    Bang,
+    NotEqual,
+    /// 特征码，如 `48 8B ?? ?? 89`，由 [`parse_byte_pattern`] 解析
+    Bytes(&'a str),
+    /// 字符串，`"..."` 为 UTF-8，`u"..."` 为 UTF-16LE；内容保留转义，由 [`parse_string_literal`] 解析
+    Text(&'a str, TextEncoding),
 }
 
 pub struct Lexer<'a> {
@@ -81,6 +92,57 @@ impl<'a> Lexer<'a> {
         false
     }
 
+    /// 从当前位置开始的特征码长度，不是特征码时为 None
+    ///
+    /// 至少两个以空白分隔的字节，每个字节为两位十六进制数或 `??`/`?`，之后只能是结尾、`;` 或 `:`。
+    /// 普通数值之间不允许出现空白，所以不会与已有写法冲突。
+    fn byte_pattern_len(&self) -> Option<usize> {
+        let mut pos = self.pos;
+        let mut items = 0;
+        loop {
+            let start = pos;
+            while pos < self.bytes.len() && (self.bytes[pos].is_ascii_hexdigit() || self.bytes[pos] == b'?') {
+                pos += 1;
+            }
+            let item = &self.bytes[start..pos];
+            let valid = match item {
+                [b'?'] | [b'?', b'?'] => true,
+                [hi, lo] => hi.is_ascii_hexdigit() && lo.is_ascii_hexdigit(),
+                _ => false,
+            };
+            if !valid {
+                return None;
+            }
+            items += 1;
+            let end = pos;
+
+            while pos < self.bytes.len() && self.bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            match self.bytes.get(pos) {
+                None | Some(b';') | Some(b':') => return (items >= 2).then_some(end - self.pos),
+                Some(_) if pos == end => return None,
+                Some(_) => {},
+            }
+        }
+    }
+
+    /// 读取引号内的内容，当前位置为开头的引号
+    fn read_string(&mut self, encoding: TextEncoding) -> Result<Token<'a>, String> {
+        self.advance();
+        let start = self.pos;
+        while let Some(ch) = self.advance() {
+            match ch {
+                b'\\' => {
+                    self.advance();
+                }
+                b'"' => return Ok(Token::Text(&self.input[start..self.pos - 1], encoding)),
+                _ => {}
+            }
+        }
+        Err("Unterminated string literal".to_string())
+    }
+
     fn read_number(&mut self) -> Result<Token<'a>, String> {
         let start = self.pos;
         let mut is_hex = false;
@@ -132,6 +194,12 @@ impl<'a> Lexer<'a> {
     pub fn next_token(&mut self) -> Result<Option<Token<'a>>, String> {
         self.skip_whitespace();
 
+        if let Some(len) = self.byte_pattern_len() {
+            let pattern = &self.input[self.pos..self.pos + len];
+            self.pos += len;
+            return Ok(Some(Token::Bytes(pattern)));
+        }
+
         match self.peek() {
             None => Ok(None),
             Some(ch) => match ch {
@@ -157,6 +225,29 @@ impl<'a> Lexer<'a> {
                         Ok(Some(Token::Tilde))
                     }
                 }
+                b'.' if self.peek_at(1) == Some(b'.') => {
+                    self.pos += 2;
+                    if self.peek() == Some(b'=') {
+                        self.advance();
+                        Ok(Some(Token::DotDotEq))
+                    } else {
+                        Ok(Some(Token::DotDot))
+                    }
+                }
+                b'!' => {
+                    self.advance();
+                    if self.peek() == Some(b'=') {
+                        self.advance();
+                        Ok(Some(Token::NotEqual))
+                    } else {
+                        Ok(Some(Token::Bang))
+                    }
+                }
+                b'"' => self.read_string(TextEncoding::Utf8).map(Some),
+                b'u' | b'U' if self.peek_at(1) == Some(b'"') => {
+                    self.advance();
+                    self.read_string(TextEncoding::Utf16Le).map(Some)
+                }
                 b'0'..=b'9' => self.read_number().map(Some),
                 b'A'..=b'Z' | b'a'..=b'z' => {
                     let start_pos = self.pos;
@@ -189,6 +280,85 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// 地址表达式的词法单元，例如 `libil2cpp.so+0x1A2B3C` 或 `7F001000-10h`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddressToken<'a> {
+    /// 模块名（无法解析为十六进制数的单词）
+    Module(&'a str),
+    /// 十六进制偏移/地址，`0x` 前缀与 `h` 后缀均可省略
+    Offset(u64),
+    Plus,
+    Minus,
+}
+
+/// 将地址表达式拆分为词法单元
+///
+/// 数值一律按十六进制解析；任何不能解析为十六进制数的单词视为模块名。
+/// 单词中间的 `+`/`-` 只有后面跟着十六进制数、位于末尾或前面是十六进制数时才是运算符，否则属于模块名，例如 `libc++_shared.so+0x1234`。
+pub fn tokenize_address(input: &str) -> Result<Vec<AddressToken<'_>>, String> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::with_capacity(4);
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b if b.is_ascii_whitespace() => pos += 1,
+            b'+' => {
+                tokens.push(AddressToken::Plus);
+                pos += 1;
+            }
+            b'-' => {
+                tokens.push(AddressToken::Minus);
+                pos += 1;
+            }
+            _ => {
+                let start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() && !is_address_operator(input, pos) {
+                    // 十六进制数中不会出现 `+`/`-`，数值之后的运算符总是断开单词
+                    if matches!(bytes[pos], b'+' | b'-') && parse_hex_word(&input[start..pos]).is_some() {
+                        break;
+                    }
+                    pos += 1;
+                }
+                let word = &input[start..pos];
+                match parse_hex_word(word) {
+                    Some(value) => tokens.push(AddressToken::Offset(value)),
+                    None => tokens.push(AddressToken::Module(word)),
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// `pos` 处的 `+`/`-` 后面（可隔空白）是否紧跟一个十六进制数；位于末尾时同样视为运算符，由解析器报告缺少操作数
+fn is_address_operator(input: &str, pos: usize) -> bool {
+    let bytes = input.as_bytes();
+    if bytes[pos] != b'+' && bytes[pos] != b'-' {
+        return false;
+    }
+    let rest = input[pos + 1..].trim_start();
+    if rest.is_empty() {
+        return true;
+    }
+    let end = rest.find(|c: char| c.is_ascii_whitespace() || c == '+' || c == '-').unwrap_or(rest.len());
+    parse_hex_word(&rest[..end]).is_some()
+}
+
+fn parse_hex_word(word: &str) -> Option<u64> {
+    let digits = word
+        .strip_prefix("0x")
+        .or_else(|| word.strip_prefix("0X"))
+        .or_else(|| word.strip_suffix('h'))
+        .or_else(|| word.strip_suffix('H'))
+        .unwrap_or(word);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u64::from_str_radix(digits, 16).ok()
+}
+
 pub fn parse_number(s: &str, is_hex: bool) -> Result<i128, String> {
     let cleaned = s.replace(',', "");
 
@@ -199,7 +369,9 @@ pub fn parse_number(s: &str, is_hex: bool) -> Result<i128, String> {
     };
 
     if is_hex {
+        // 最高位为 1 的 128 位十六进制数按位模式解释
         i128::from_str_radix(cleaned, 16)
+            .or_else(|_| u128::from_str_radix(cleaned, 16).map(|value| value as i128))
             .map_err(|_| format!("Invalid hex number: {}", s))
     } else {
         cleaned.parse::<i128>()
@@ -207,6 +379,51 @@ pub fn parse_number(s: &str, is_hex: bool) -> Result<i128, String> {
     }
 }
 
+/// 解析特征码，返回字节与掩码，掩码为 false 的位置是通配符
+pub fn parse_byte_pattern(s: &str) -> Result<(Vec<u8>, Vec<bool>), String> {
+    let mut pattern = Vec::new();
+    let mut mask = Vec::new();
+    for item in s.split_ascii_whitespace() {
+        if item.bytes().all(|b| b == b'?') {
+            pattern.push(0);
+            mask.push(false);
+        } else {
+            let byte = u8::from_str_radix(item, 16).map_err(|_| format!("Invalid byte in pattern: {}", item))?;
+            pattern.push(byte);
+            mask.push(true);
+        }
+    }
+
+    if !mask.contains(&true) {
+        return Err(format!("Byte pattern needs at least one fixed byte: {}", s));
+    }
+    if pattern.len() > MAX_BYTE_PATTERN_LEN {
+        return Err(format!("Byte pattern too long: {} bytes, at most {}", pattern.len(), MAX_BYTE_PATTERN_LEN));
+    }
+    Ok((pattern, mask))
+}
+
+/// 处理字符串中的转义：`\\`、`\"`、`\n`、`\t`
+pub fn parse_string_literal(s: &str) -> Result<String, String> {
+    let mut text = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            text.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => text.push('\\'),
+            Some('"') => text.push('"'),
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some(other) => return Err(format!("Unknown escape in string: \\{}", other)),
+            None => return Err("Dangling escape at end of string".to_string()),
+        }
+    }
+    Ok(text)
+}
+
 pub fn parse_float(s: &str, is_hex: bool) -> Result<f64, String> {
     if is_hex {
         return Err("Hex notation not supported for floating point".to_string());
@@ -259,6 +476,38 @@ mod tests {
         assert!(matches!(tokens[0], Token::Number("1.0", false)));
     }
 
+    #[test]
+    fn test_tokenize_address() {
+        let tokens = tokenize_address("libil2cpp.so + 0x1A2B3C-10h").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                AddressToken::Module("libil2cpp.so"),
+                AddressToken::Plus,
+                AddressToken::Offset(0x1A2B3C),
+                AddressToken::Minus,
+                AddressToken::Offset(0x10),
+            ]
+        );
+        assert_eq!(tokenize_address("7F001000").unwrap(), vec![AddressToken::Offset(0x7F001000)]);
+
+        // 模块名里不跟数字的 +/- 属于名字本身
+        assert_eq!(
+            tokenize_address("libc++_shared.so+0x1234").unwrap(),
+            vec![AddressToken::Module("libc++_shared.so"), AddressToken::Plus, AddressToken::Offset(0x1234)]
+        );
+        assert_eq!(
+            tokenize_address("libfoo-jni.so-10h + 4").unwrap(),
+            vec![
+                AddressToken::Module("libfoo-jni.so"),
+                AddressToken::Minus,
+                AddressToken::Offset(0x10),
+                AddressToken::Plus,
+                AddressToken::Offset(0x4),
+            ]
+        );
+    }
+
     #[test]
     fn test_tokenize_float_with_range() {
         let mut lexer = Lexer::new("1.0~10.5F");
@@ -268,4 +517,36 @@ mod tests {
         assert!(matches!(tokens[1], Token::Tilde));
         assert!(matches!(tokens[2], Token::Number("10.5", false)));
     }
+
+    #[test]
+    fn test_tokenize_dot_ranges() {
+        let tokens = Lexer::new("!1.5..=2.5F;10..20").tokenize().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Bang,
+                Token::Number("1.5", false),
+                Token::DotDotEq,
+                Token::Number("2.5", false),
+                Token::Type(ValueType::Float),
+                Token::Semicolon,
+                Token::Number("10", false),
+                Token::DotDot,
+                Token::Number("20", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_string() {
+        let mut lexer = Lexer::new(r#"u"Player;1":"say \"hi\"";100D"#);
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(tokens[0], Token::Text("Player;1", TextEncoding::Utf16Le));
+        assert_eq!(tokens[2], Token::Text(r#"say \"hi\""#, TextEncoding::Utf8));
+        assert_eq!(parse_string_literal(r#"say \"hi\" \\"#).unwrap(), r#"say "hi" \"#);
+
+        assert!(Lexer::new(r#""unterminated"#).tokenize().is_err());
+        assert!(parse_string_literal(r"bad \x").is_err());
+    }
 }
@@ -7,7 +7,7 @@ pub mod result_manager;
 #[cfg(test)]
 pub mod tests;
 
-pub use types::{FuzzyCondition, SearchMode, SearchQuery, SearchValue, ValueType};
+pub use types::{ByteOrder, DeltaOp, FloatTolerance, FuzzyCondition, RelConstraint, RelOp, SearchMode, SearchQuery, SearchValue, TextEncoding, ValueType};
 pub use parser::parse_search_query;
 pub use engine::{SearchEngineManager, SEARCH_ENGINE_MANAGER, SearchProgressCallback, BPLUS_TREE_ORDER, PAGE_SIZE, PAGE_MASK, ValuePair};
 pub use result_manager::SearchResultItem;
\ No newline at end of file
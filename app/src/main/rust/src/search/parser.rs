@@ -1,20 +1,52 @@
-use super::lexer::{Lexer, Token, parse_number, parse_float};
-use super::types::{SearchMode, SearchQuery, SearchValue, ValueType};
+use super::lexer::{AddressToken, Lexer, Token, parse_byte_pattern, parse_number, parse_float, parse_string_literal, tokenize_address};
+use super::types::{ByteOrder, SearchMode, SearchQuery, SearchValue, ValueType, MAX_BYTE_PATTERN_LEN};
+
+/// 范围运算符：`~` 与 `..=` 为闭区间，`..` 不含上界，`~~` 匹配区间之外
+#[derive(Debug, Clone, Copy)]
+struct RangeOp {
+    exclude: bool,
+    upper_inclusive: bool,
+}
+
+impl RangeOp {
+    fn from_token(token: Option<&Token>) -> Option<Self> {
+        let (exclude, upper_inclusive) = match token? {
+            Token::Tilde | Token::DotDotEq => (false, true),
+            Token::DoubleTilde => (true, true),
+            Token::DotDot => (false, false),
+            _ => return None,
+        };
+        Some(RangeOp { exclude, upper_inclusive })
+    }
+}
 
 pub struct Parser<'a> {
     tokens: Vec<Token<'a>>,
     pos: usize,
     default_type: ValueType,
+    byte_order: ByteOrder,
+}
+
+/// 拆出开头的字节序前缀 `be:` / `le:`（不区分大小写），没有前缀时为小端
+fn split_byte_order(input: &str) -> (ByteOrder, &str) {
+    let trimmed = input.trim_start();
+    match trimmed.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("be:") => (ByteOrder::Big, &trimmed[3..]),
+        Some(prefix) if prefix.eq_ignore_ascii_case("le:") => (ByteOrder::Little, &trimmed[3..]),
+        _ => (ByteOrder::Little, input),
+    }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str, default_type: ValueType) -> Result<Self, String> {
+        let (byte_order, input) = split_byte_order(input);
         let mut lexer = Lexer::new(input);
         let tokens = lexer.tokenize()?;
         Ok(Parser {
             tokens,
             pos: 0,
             default_type,
+            byte_order,
         })
     }
 
@@ -49,29 +81,57 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_value(&mut self) -> Result<SearchValue, String> {
+        if matches!(self.peek(), Some(Token::NotEqual)) {
+            self.advance();
+            return self.parse_not_equal();
+        }
+
+        if matches!(self.peek(), Some(Token::Bang)) {
+            self.advance();
+            let value = self.parse_value()?;
+            if !value.is_range() {
+                return Err("'!' only applies to ranges, use '!=' for a single value".to_string());
+            }
+            return Ok(value.inverted());
+        }
+
+        if let Some(Token::Bytes(s)) = self.peek() {
+            let (pattern, mask) = parse_byte_pattern(s)?;
+            self.advance();
+            return Ok(SearchValue::byte_pattern(pattern, mask));
+        }
+
+        if let Some(&Token::Text(raw, encoding)) = self.peek() {
+            let bytes = encoding.encode(&parse_string_literal(raw)?);
+            if bytes.is_empty() {
+                return Err("Empty string literal".to_string());
+            }
+            if bytes.len() > MAX_BYTE_PATTERN_LEN {
+                return Err(format!("String too long: {} bytes encoded, at most {}", bytes.len(), MAX_BYTE_PATTERN_LEN));
+            }
+            self.advance();
+            return Ok(SearchValue::Text { bytes, encoding });
+        }
+
         let num_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
             Some(token) => return Err(format!("Expected number, got {:?}", token)),
             None => return Err("Expected number, got EOF".to_string()),
         };
 
-        let next_token = self.peek();
-
-        match next_token {
-            Some(Token::Tilde) | Some(Token::DoubleTilde) => {
-                let exclude = matches!(next_token, Some(Token::DoubleTilde));
-                self.advance();
+        if let Some(op) = RangeOp::from_token(self.peek()) {
+            self.advance();
+            return self.parse_range(num_token, op);
+        }
 
-                self.parse_range(num_token, exclude)
-            }
+        match self.peek() {
             Some(Token::Type(value_type)) => {
                 let value_type = *value_type;
                 self.advance();
 
-                if matches!(self.peek(), Some(Token::Tilde) | Some(Token::DoubleTilde)) {
-                    let exclude = matches!(self.peek(), Some(Token::DoubleTilde));
+                if let Some(op) = RangeOp::from_token(self.peek()) {
                     self.advance();
-                    self.parse_range_with_type(num_token, value_type, exclude)
+                    self.parse_range_with_type(num_token, value_type, op)
                 } else {
                     self.create_fixed_value(num_token, value_type)
                 }
@@ -82,7 +142,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_range(&mut self, start_token: (&'a str, bool), exclude: bool) -> Result<SearchValue, String> {
+    fn parse_range(&mut self, start_token: (&'a str, bool), op: RangeOp) -> Result<SearchValue, String> {
         let end_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
             Some(token) => return Err(format!("Expected number after range operator, got {:?}", token)),
@@ -98,14 +158,14 @@ impl<'a> Parser<'a> {
             _ => self.default_type,
         };
 
-        self.create_range_value(start_token, end_token, value_type, exclude)
+        self.create_range_value(start_token, end_token, value_type, op)
     }
 
     fn parse_range_with_type(
         &mut self,
         start_token: (&'a str, bool),
         value_type: ValueType,
-        exclude: bool,
+        op: RangeOp,
     ) -> Result<SearchValue, String> {
         let end_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
@@ -123,7 +183,32 @@ impl<'a> Parser<'a> {
             self.advance();
         }
 
-        self.create_range_value(start_token, end_token, value_type, exclude)
+        self.create_range_value(start_token, end_token, value_type, op)
+    }
+
+    /// `!=` 之后的数值与可选类型
+    fn parse_not_equal(&mut self) -> Result<SearchValue, String> {
+        let (num_str, is_hex) = match self.advance() {
+            Some(Token::Number(s, is_hex)) => (*s, *is_hex),
+            Some(token) => return Err(format!("Expected number after '!=', got {:?}", token)),
+            None => return Err("Expected number after '!=', got EOF".to_string()),
+        };
+
+        let value_type = match self.peek() {
+            Some(Token::Type(vt)) => {
+                let vt = *vt;
+                self.advance();
+                vt
+            }
+            _ => self.default_type,
+        };
+
+        if value_type.is_float_type() {
+            return Err(format!("Not-equal match does not support {}", value_type));
+        }
+
+        let value = parse_number(num_str, is_hex)?;
+        Ok(SearchValue::not_equal(value, value_type))
     }
 
     fn create_fixed_value(&self, num_token: (&'a str, bool), value_type: ValueType) -> Result<SearchValue, String> {
@@ -134,6 +219,9 @@ impl<'a> Parser<'a> {
             Ok(SearchValue::fixed_float(value, value_type))
         } else {
             let value = parse_number(num_str, is_hex)?;
+            if value_type == ValueType::Oword {
+                return Ok(SearchValue::fixed(value, value_type));
+            }
             if value > u64::MAX as i128  {
                 return Err(format!("Value {} exceeds maximum for fixed search", value));
             }
@@ -149,7 +237,7 @@ impl<'a> Parser<'a> {
         start_token: (&'a str, bool),
         end_token: (&'a str, bool),
         value_type: ValueType,
-        exclude: bool,
+        op: RangeOp,
     ) -> Result<SearchValue, String> {
         let (start_str, start_is_hex) = start_token;
         let (end_str, end_is_hex) = end_token;
@@ -161,8 +249,11 @@ impl<'a> Parser<'a> {
             if start > end {
                 return Err(format!("Range start ({}) must be <= end ({})", start, end));
             }
+            if start == end && !op.upper_inclusive {
+                return Err(format!("Range {}..{} is empty", start, end));
+            }
 
-            Ok(SearchValue::range_float(start, end, value_type, exclude))
+            Ok(SearchValue::range_float(start, end, value_type, op.exclude).with_bounds(true, op.upper_inclusive))
         } else {
             let start = parse_number(start_str, start_is_hex)?;
             let end = parse_number(end_str, end_is_hex)?;
@@ -178,8 +269,11 @@ impl<'a> Parser<'a> {
             if start > end {
                 return Err(format!("Range start ({}) must be <= end ({})", start, end));
             }
+            if start == end && !op.upper_inclusive {
+                return Err(format!("Range {}..{} is empty", start, end));
+            }
 
-            Ok(SearchValue::range(start, end, value_type, exclude))
+            Ok(SearchValue::range(start, end, value_type, op.exclude).with_bounds(true, op.upper_inclusive))
         }
     }
 
@@ -237,7 +331,7 @@ impl<'a> Parser<'a> {
             return Err(format!("Unexpected tokens after query: {:?}", &self.tokens[self.pos..]));
         }
 
-        let query = SearchQuery::new(values, mode, range);
+        let query = SearchQuery::new(values, mode, range).with_byte_order(self.byte_order);
         query.validate()?;
 
         Ok(query)
@@ -249,9 +343,73 @@ pub fn parse_search_query(input: &str, default_type: ValueType) -> Result<Search
     parser.parse()
 }
 
+/// 解析后的地址表达式：可选的模块基址加上有符号偏移
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressExpr {
+    /// 模块名，None 表示绝对地址
+    pub module: Option<String>,
+    /// 相对模块基址（或 0）的偏移
+    pub offset: i64,
+}
+
+impl AddressExpr {
+    /// 使用模块表解析为绝对地址，`lookup` 返回模块基址
+    pub fn resolve<F>(&self, lookup: F) -> Result<u64, String>
+    where
+        F: Fn(&str) -> Option<u64>,
+    {
+        let base = match &self.module {
+            Some(name) => lookup(name).ok_or_else(|| format!("Module not found: {}", name))?,
+            None => 0,
+        };
+        base.checked_add_signed(self.offset)
+            .ok_or_else(|| format!("Address out of range: 0x{:X}{:+}", base, self.offset))
+    }
+}
+
+/// 解析地址表达式，支持 `libil2cpp.so+0x1A2B3C`、`libil2cpp.so-10h`、`7F001000+20` 等形式
+///
+/// 模块名只能出现在表达式开头，后续项必须是带 `+`/`-` 的十六进制偏移。
+pub fn parse_address(input: &str) -> Result<AddressExpr, String> {
+    let tokens = tokenize_address(input)?;
+    let mut iter = tokens.into_iter().peekable();
+
+    let (module, mut offset) = match iter.next() {
+        Some(AddressToken::Module(name)) => (Some(name.to_string()), 0i64),
+        Some(AddressToken::Offset(value)) => {
+            let value = i64::try_from(value).map_err(|_| format!("Address too large: 0x{:X}", value))?;
+            (None, value)
+        }
+        Some(token) => return Err(format!("Expected module or address, got {:?}", token)),
+        None => return Err("Empty address".to_string()),
+    };
+
+    while let Some(token) = iter.next() {
+        let negative = match token {
+            AddressToken::Plus => false,
+            AddressToken::Minus => true,
+            other => return Err(format!("Expected + or -, got {:?}", other)),
+        };
+        let value = match iter.next() {
+            Some(AddressToken::Offset(value)) => i64::try_from(value).map_err(|_| format!("Offset too large: 0x{:X}", value))?,
+            Some(other) => return Err(format!("Expected offset, got {:?}", other)),
+            None => return Err("Expected offset, got EOF".to_string()),
+        };
+        offset = if negative { offset.checked_sub(value) } else { offset.checked_add(value) }
+            .ok_or_else(|| "Offset overflow".to_string())?;
+    }
+
+    if module.is_none() && offset < 0 {
+        return Err("Absolute address cannot be negative".to_string());
+    }
+
+    Ok(AddressExpr { module, offset })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::TextEncoding;
 
     #[test]
     fn test_parse_simple() {
@@ -307,6 +465,17 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_not_equal() {
+        let query = parse_search_query("100D;!=0D;!=FFh::16", ValueType::Dword).unwrap();
+        assert_eq!(query.values.len(), 3);
+        assert_eq!(query.values[1], SearchValue::not_equal(0, ValueType::Dword));
+        assert_eq!(query.values[2], SearchValue::not_equal(0xFF, ValueType::Dword));
+
+        assert!(parse_search_query("!=1.0F", ValueType::Dword).is_err());
+        assert!(parse_search_query("!0D", ValueType::Dword).is_err());
+    }
+
     #[test]
     fn test_parse_float_values() {
         let query = parse_search_query("1.0F", ValueType::Float).unwrap();
@@ -337,4 +506,103 @@ mod tests {
         assert_eq!(query.values.len(), 1);
         assert!(matches!(query.values[0], SearchValue::FixedFloat { .. }));
     }
+
+    #[test]
+    fn test_parse_byte_pattern() {
+        let query = parse_search_query("48 8B 05 ?? ?? ?? ?? 89", ValueType::Dword).unwrap();
+        assert_eq!(query.values.len(), 1);
+        assert_eq!(
+            query.values[0],
+            SearchValue::byte_pattern(
+                vec![0x48, 0x8B, 0x05, 0, 0, 0, 0, 0x89],
+                vec![true, true, true, false, false, false, false, true]
+            )
+        );
+        assert_eq!(query.values[0].value_type(), ValueType::Bytes(8));
+
+        let query = parse_search_query("1F 20 03 D5;100D:64", ValueType::Dword).unwrap();
+        assert_eq!(query.values[0].value_type(), ValueType::Bytes(4));
+        assert_eq!(query.values[1], SearchValue::fixed(100, ValueType::Dword));
+
+        assert!(parse_search_query("?? ??", ValueType::Dword).is_err());
+        assert!(parse_search_query("10 20D", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_string() {
+        let query = parse_search_query(r#""Hero";u"勇者";100D::64"#, ValueType::Dword).unwrap();
+        assert_eq!(query.values.len(), 3);
+        assert_eq!(query.values[0], SearchValue::text("Hero", TextEncoding::Utf8));
+        assert_eq!(query.values[0].value_type(), ValueType::Text(4, TextEncoding::Utf8));
+        assert_eq!(query.values[1].value_type(), ValueType::Text(4, TextEncoding::Utf16Le));
+        assert_eq!(query.mode, SearchMode::Ordered);
+
+        assert!(parse_search_query(r#""""#, ValueType::Dword).is_err());
+        assert!(parse_search_query(&format!("u\"{}\"", "a".repeat(64)), ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_range_bounds() {
+        let query = parse_search_query("100..200;100..=200;100~200", ValueType::Dword).unwrap();
+        let [half_open, closed, tilde] = &query.values[..] else {
+            panic!("expected three values");
+        };
+        assert_eq!(closed, tilde);
+        assert!(half_open.matched(&199u32.to_le_bytes()).unwrap());
+        assert!(!half_open.matched(&200u32.to_le_bytes()).unwrap());
+        assert!(closed.matched(&200u32.to_le_bytes()).unwrap());
+        assert!(half_open.matched(&100u32.to_le_bytes()).unwrap());
+
+        let query = parse_search_query("!100..200D;!1.0..=2.0F:32", ValueType::Dword).unwrap();
+        assert!(query.values[0].matched(&200u32.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&99u32.to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&150u32.to_le_bytes()).unwrap());
+        assert!(!query.values[1].matched(&2.0f32.to_le_bytes()).unwrap());
+        assert!(query.values[1].matched(&2.5f32.to_le_bytes()).unwrap());
+        assert_eq!(
+            parse_search_query("!100~200", ValueType::Dword).unwrap(),
+            parse_search_query("100~~200", ValueType::Dword).unwrap()
+        );
+
+        assert!(parse_search_query("100..100", ValueType::Dword).is_err());
+        assert!(parse_search_query("100..=100", ValueType::Dword).is_ok());
+        assert!(parse_search_query("!100", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_byte_order_prefix() {
+        let query = parse_search_query("be:12345", ValueType::Dword).unwrap();
+        assert_eq!(query.values[0].byte_order(), ByteOrder::Big);
+        assert_eq!(query.values[0].bytes().unwrap(), &12345u32.to_be_bytes());
+
+        let query = parse_search_query(" BE:1.5F;10~20D::16", ValueType::Dword).unwrap();
+        assert!(query.values.iter().all(|value| value.byte_order() == ByteOrder::Big));
+        assert!(query.values[0].matched(&1.5f32.to_be_bytes()).unwrap());
+        assert!(query.values[1].matched(&15u32.to_be_bytes()).unwrap());
+        assert!(!query.values[1].matched(&15u32.to_le_bytes()).unwrap());
+
+        let query = parse_search_query("le:12345", ValueType::Dword).unwrap();
+        assert_eq!(query, parse_search_query("12345", ValueType::Dword).unwrap());
+    }
+
+    #[test]
+    fn test_resolve_module_relative_address() {
+        let modules: std::collections::HashMap<&str, u64> =
+            [("libil2cpp.so", 0x7A_0000_0000u64), ("libunity.so", 0x7B_0000_0000u64)].into_iter().collect();
+        let lookup = |name: &str| modules.get(name).copied();
+
+        let expr = parse_address("libil2cpp.so+0x1A2B3C").unwrap();
+        assert_eq!(expr.module.as_deref(), Some("libil2cpp.so"));
+        assert_eq!(expr.resolve(lookup).unwrap(), 0x7A_0000_0000 + 0x1A2B3C);
+
+        assert_eq!(parse_address("libunity.so-10h+4").unwrap().resolve(lookup).unwrap(), 0x7B_0000_0000 - 0xC);
+        assert_eq!(parse_address("libunity.so").unwrap().resolve(lookup).unwrap(), 0x7B_0000_0000);
+        assert_eq!(parse_address("7F001000+0x20").unwrap().resolve(lookup).unwrap(), 0x7F001020);
+
+        assert!(parse_address("libmissing.so+0x10").unwrap().resolve(lookup).is_err());
+        assert!(parse_address("libil2cpp.so+").is_err());
+        assert!(parse_address("0x10+libil2cpp.so").is_err());
+        assert!(parse_address("10-20").is_err());
+        assert!(parse_address("").is_err());
+    }
 }
@@ -1,21 +1,86 @@
 mod exact;
 mod fuzzy;
+mod history;
+pub mod packed;
+pub mod persistent;
+mod store;
 
 use super::types::ValueType;
 pub use crate::search::result_manager::exact::ExactSearchResultItem;
-use crate::search::result_manager::exact::ExactSearchResultManager;
+pub use crate::search::result_manager::store::ResultStoreKind;
+use crate::search::result_manager::store::ResultStore;
+use crate::search::result_manager::history::{RefineHistory, PAGE_ITEMS};
+pub use crate::search::result_manager::history::DEFAULT_REFINE_HISTORY_DEPTH;
 pub use crate::search::result_manager::fuzzy::{FuzzySearchResultItem, FuzzySearchResultManager};
 use anyhow::{Result, anyhow};
 use log::{debug, error, info};
 use std::path::PathBuf;
 use crate::search::engine::ValuePair;
+use crate::pointer_scan::VmStaticData;
+use std::collections::HashMap;
 
+/// 批量写入结果时每次交给存储后端的默认条数
+pub const DEFAULT_INSERT_BATCH_SIZE: usize = 64 * 1024;
+
+/// 结果集的存储模式，决定每条结果保存什么以及 [`SearchResultManager::get_results`] 返回哪种结果
+///
+/// 两种模式各用一个存储后端，同一时间只有当前模式的结果有效；切换模式会清空旧模式的结果和磁盘文件。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchResultMode {
+    /// 只保存地址和值类型（[`ExactSearchResultItem`]），读取结果时不带值。精确搜索和普通细化使用
     Exact,
+    /// 额外保存 8 字节的值（[`FuzzySearchResultItem`]），作为下一次比较的旧值。
+    /// 模糊搜索、变化搜索和计数器搜索使用，只接受模糊结果
     Fuzzy,
 }
 
+impl SearchResultMode {
+    /// 用于 JNI：0 为精确，1 为模糊
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(SearchResultMode::Exact),
+            1 => Some(SearchResultMode::Fuzzy),
+            _ => None,
+        }
+    }
+
+    pub fn to_id(self) -> i32 {
+        match self {
+            SearchResultMode::Exact => 0,
+            SearchResultMode::Fuzzy => 1,
+        }
+    }
+}
+
+/// 结果集占用的内存/磁盘空间，用于界面在结果过多时提前提示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    /// 内存中已分配的字节数（含预留容量）
+    pub in_memory_bytes: u64,
+    /// 磁盘溢出文件的大小
+    pub on_disk_bytes: u64,
+    pub result_count: usize,
+    /// 平均每条结果占用的字节数，没有结果时为 0
+    pub bytes_per_result: u64,
+}
+
+impl MemoryUsage {
+    pub fn new(in_memory_bytes: u64, on_disk_bytes: u64, result_count: usize) -> Self {
+        let bytes_per_result = if result_count == 0 {
+            0
+        } else {
+            (in_memory_bytes + on_disk_bytes) / result_count as u64
+        };
+        Self {
+            in_memory_bytes,
+            on_disk_bytes,
+            result_count,
+            bytes_per_result,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub enum SearchResultItem {
     Exact(ExactSearchResultItem),
     Fuzzy(FuzzySearchResultItem),
@@ -33,6 +98,13 @@ impl SearchResultItem {
     pub fn new_fuzzy_from_bytes(address: u64, bytes: &[u8], value_type: ValueType) -> Self {
         SearchResultItem::Fuzzy(FuzzySearchResultItem::from_bytes(address, bytes, value_type))
     }
+
+    pub fn address(&self) -> u64 {
+        match self {
+            SearchResultItem::Exact(exact) => exact.address,
+            SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+        }
+    }
 }
 
 impl From<(u64, ValueType)> for SearchResultItem {
@@ -49,17 +121,44 @@ impl From<&ValuePair> for SearchResultItem {
 
 pub(crate) struct SearchResultManager {
     current_mode: SearchResultMode,
-    exact: ExactSearchResultManager,
-    fuzzy: FuzzySearchResultManager,
+    exact: Box<dyn ResultStore<ExactSearchResultItem>>,
+    fuzzy: Box<dyn ResultStore<FuzzySearchResultItem>>,
+    /// 批量写入时的分批大小，0 表示整批一次写入
+    insert_batch_size: usize,
+    /// 细化前的结果集，用于撤销
+    history: RefineHistory,
 }
 
 impl SearchResultManager {
-    pub fn new(memory_buffer_size: usize, cache_dir: PathBuf) -> Self {
-        Self {
-            current_mode: SearchResultMode::Exact,
-            exact: ExactSearchResultManager::new(memory_buffer_size, cache_dir.clone()),
-            fuzzy: FuzzySearchResultManager::new(memory_buffer_size, cache_dir),
-        }
+    /// 使用指定的结果存储后端创建，精确和模糊结果共用同一种后端
+    ///
+    /// 持久化后端恢复出的结果只在一种模式下（切换模式会删除旧模式的文件），恢复出模糊结果时以模糊模式开始
+    pub fn with_store(memory_buffer_size: usize, cache_dir: PathBuf, store: ResultStoreKind) -> Result<Self> {
+        let exact = store.create_exact(memory_buffer_size, cache_dir.clone())?;
+        let fuzzy = store.create_fuzzy(memory_buffer_size, cache_dir.clone())?;
+        let current_mode = if exact.total_count() == 0 && fuzzy.total_count() > 0 {
+            SearchResultMode::Fuzzy
+        } else {
+            SearchResultMode::Exact
+        };
+
+        Ok(Self {
+            current_mode,
+            exact,
+            fuzzy,
+            insert_batch_size: DEFAULT_INSERT_BATCH_SIZE,
+            history: RefineHistory::new(DEFAULT_REFINE_HISTORY_DEPTH, cache_dir),
+        })
+    }
+
+    /// 设置批量写入的分批大小，0 表示整批一次写入
+    pub fn set_insert_batch_size(&mut self, size: usize) {
+        self.insert_batch_size = size;
+    }
+
+    /// 当前结果存储后端类型
+    pub fn store_kind(&self) -> ResultStoreKind {
+        self.exact.kind()
     }
 
     pub fn clear(&mut self) -> Result<()> {
@@ -103,11 +202,43 @@ impl SearchResultManager {
         }
     }
 
+    /// 按 `insert_batch_size` 分批交给存储后端，每批一次性追加；结果类型与当前模式不符时该批整体失败
     pub fn add_results_batch(&mut self, results: Vec<SearchResultItem>) -> Result<()> {
-        for result in results {
-            self.add_result(result)?;
+        let batch_size = self.batch_size_for(results.len());
+        let mut results = results.into_iter();
+        loop {
+            let batch: Vec<SearchResultItem> = results.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+
+            match self.current_mode {
+                SearchResultMode::Exact => {
+                    let items = batch
+                        .into_iter()
+                        .map(|item| match item {
+                            SearchResultItem::Exact(exact_item) => Ok(exact_item),
+                            SearchResultItem::Fuzzy(_) => Err(anyhow!("Mismatched SearchResultMode and SearchResultItem type")),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    self.exact.add_results(items)?;
+                },
+                SearchResultMode::Fuzzy => {
+                    let items = batch
+                        .into_iter()
+                        .map(|item| match item {
+                            SearchResultItem::Fuzzy(fuzzy_item) => Ok(fuzzy_item),
+                            SearchResultItem::Exact(_) => Err(anyhow!("Mismatched SearchResultMode and SearchResultItem type")),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    self.fuzzy.add_results(items)?;
+                },
+            }
         }
-        Ok(())
+    }
+
+    fn batch_size_for(&self, total: usize) -> usize {
+        if self.insert_batch_size == 0 { total.max(1) } else { self.insert_batch_size }
     }
 
     /// 添加模糊搜索结果（直接使用 FuzzySearchResultItem）
@@ -123,10 +254,15 @@ impl SearchResultManager {
         if self.current_mode != SearchResultMode::Fuzzy {
             return Err(anyhow!("Not in fuzzy mode"));
         }
-        for item in results {
-            self.fuzzy.add_result(item)?;
+        let batch_size = self.batch_size_for(results.len());
+        let mut results = results.into_iter();
+        loop {
+            let batch: Vec<FuzzySearchResultItem> = results.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+            self.fuzzy.add_results(batch)?;
         }
-        Ok(())
     }
 
     pub fn get_results(&self, start: usize, size: usize) -> Result<Vec<SearchResultItem>> {
@@ -174,6 +310,14 @@ impl SearchResultManager {
         self.current_mode
     }
 
+    /// 当前模式下结果集的空间占用
+    pub fn memory_usage(&self) -> MemoryUsage {
+        match self.current_mode {
+            SearchResultMode::Exact => self.exact.memory_usage(),
+            SearchResultMode::Fuzzy => self.fuzzy.memory_usage(),
+        }
+    }
+
     pub fn get_all_exact_results(&self) -> Result<Vec<ExactSearchResultItem>> {
         match self.current_mode {
             SearchResultMode::Exact => self.exact.get_all_results(),
@@ -189,6 +333,286 @@ impl SearchResultManager {
         }
     }
 
+    /// 只保留地址同时出现在 `other` 中的结果，返回剩余数量
+    ///
+    /// 两边都按地址递增逐页读取、归并一次，不加载整个结果集；只比较地址不比较值类型，保留下来的结果维持原有顺序。
+    /// 与 [`Self::get_results_for_addrs`] 一样要求结果集按地址有序，乱序写入的结果中可能漏掉公共地址
+    pub fn intersect_with(&mut self, other: &SearchResultManager) -> Result<usize> {
+        let (total, other_total) = (self.total_count(), other.total_count());
+        let mut other_page: Vec<u64> = Vec::new();
+        let (mut other_next, mut j) = (0usize, 0usize);
+
+        let mut keep_indices = Vec::new();
+        let mut start = 0usize;
+        'walk: while start < total {
+            let page = self.get_results(start, PAGE_ITEMS)?;
+            for (offset, item) in page.iter().enumerate() {
+                let addr = item.address();
+                loop {
+                    if j == other_page.len() {
+                        if other_next >= other_total {
+                            break 'walk;
+                        }
+                        other_page = other.get_results(other_next, PAGE_ITEMS)?.iter().map(SearchResultItem::address).collect();
+                        other_next += other_page.len().max(1);
+                        j = 0;
+                    } else if other_page[j] < addr {
+                        j += 1;
+                    } else {
+                        break;
+                    }
+                }
+                if other_page[j] == addr {
+                    keep_indices.push(start + offset);
+                }
+            }
+            start += page.len().max(1);
+        }
+
+        let before = self.total_count();
+        self.keep_only_results(keep_indices)?;
+        debug!("Intersected results: {} -> {}", before, self.total_count());
+
+        Ok(self.total_count())
+    }
+
+    /// 按模块偏移把结果迁移到新进程的模块布局上（游戏重启后绝对地址失效），返回剩余数量
+    ///
+    /// 地址先归属到 `old_modules` 中包含它的模块，再按同名同序号模块在 `new_modules` 中的基址换算；
+    /// 不属于任何模块、模块已消失或偏移超出新模块范围的结果会被丢弃
+    pub fn rebase(&mut self, old_modules: &[VmStaticData], new_modules: &[VmStaticData]) -> Result<usize> {
+        let rebase = ModuleRebase::new(old_modules, new_modules);
+        let before = self.total_count();
+
+        match self.current_mode {
+            SearchResultMode::Exact => {
+                let rebased = self
+                    .exact
+                    .get_all_results()?
+                    .into_iter()
+                    .filter_map(|item| {
+                        let (address, typ) = (item.address, item.typ);
+                        rebase.translate(address).map(|new_address| ExactSearchResultItem::new(new_address, typ))
+                    })
+                    .collect();
+                self.exact.replace_all(rebased)?;
+            },
+            SearchResultMode::Fuzzy => {
+                let rebased = self
+                    .fuzzy
+                    .get_all_results()?
+                    .into_iter()
+                    .filter_map(|item| {
+                        let (address, value, value_type) = (item.address, item.value, item.value_type);
+                        rebase.translate(address).map(|new_address| FuzzySearchResultItem::new(new_address, value, value_type))
+                    })
+                    .collect();
+                self.fuzzy.replace_all(rebased)?;
+            },
+        }
+
+        info!("Rebased results onto new module layout: {} -> {}", before, self.total_count());
+        Ok(self.total_count())
+    }
+
+    /// 不重新扫描，把所有结果改按 `new_type` 解释，例如按 Dword 搜到的值实际是 Float
+    ///
+    /// 新类型只能使用原类型已经覆盖的字节（小端下取低位字节），模糊模式还受限于保存的 8 字节；
+    /// 任一结果放不下新类型时返回错误，结果保持不变
+    pub fn reinterpret_type(&mut self, new_type: ValueType) -> Result<()> {
+        let new_size = new_type.size();
+
+        match self.current_mode {
+            SearchResultMode::Exact => {
+                let results = self.exact.get_all_results()?;
+                if let Some(item) = results.iter().find(|item| item.typ.size() < new_size) {
+                    let (address, typ) = (item.address, item.typ);
+                    return Err(anyhow!("Cannot reinterpret {} at 0x{:X} as {}", typ, address, new_type));
+                }
+                let reinterpreted = results.into_iter().map(|item| ExactSearchResultItem::new(item.address, new_type)).collect();
+                self.exact.replace_all(reinterpreted)?;
+            },
+            SearchResultMode::Fuzzy => {
+                let results = self.fuzzy.get_all_results()?;
+                if let Some(item) = results.iter().find(|item| item.value_type.size().min(item.value.len()) < new_size) {
+                    let (address, value_type) = (item.address, item.value_type);
+                    return Err(anyhow!("Cannot reinterpret {} at 0x{:X} as {}", value_type, address, new_type));
+                }
+                // 超出新类型的字节清零，与按新类型记录时保存的内容一致
+                let reinterpreted = results
+                    .into_iter()
+                    .map(|item| {
+                        let (address, value) = (item.address, item.value);
+                        FuzzySearchResultItem::from_bytes(address, &value[..new_size], new_type)
+                    })
+                    .collect();
+                self.fuzzy.replace_all(reinterpreted)?;
+            },
+        }
+
+        debug!("Reinterpreted {} results as {}", self.total_count(), new_type);
+        Ok(())
+    }
+
+    /// 按地址查找结果，返回值与 `addrs` 一一对应，结果集中没有的地址为 None
+    ///
+    /// 扫描结果按地址递增写入，每个地址在存储上逐条二分查找，只读取 O(log n) 条结果，不加载整个结果集。
+    /// 手动添加等乱序写入的结果集中可能查不到实际存在的地址
+    pub fn get_results_for_addrs(&self, addrs: &[u64]) -> Result<Vec<Option<SearchResultItem>>> {
+        addrs.iter().map(|&addr| self.find_by_addr(addr)).collect()
+    }
+
+    /// 在按地址有序的结果集中二分查找 `addr`，每次比较只读取一条结果
+    fn find_by_addr(&self, addr: u64) -> Result<Option<SearchResultItem>> {
+        let index = self.partition_point_addr(addr)?;
+        Ok(self.get_results(index, 1)?.pop().filter(|item| item.address() == addr))
+    }
+
+    /// 按地址有序的结果集中第一个地址不小于 `addr` 的位置，每次比较只读取一条结果
+    fn partition_point_addr(&self, addr: u64) -> Result<usize> {
+        let (mut low, mut high) = (0, self.total_count());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.get_results(mid, 1)?.pop() {
+                Some(item) if item.address() < addr => low = mid + 1,
+                _ => high = mid,
+            }
+        }
+        Ok(low)
+    }
+
+    /// 按与 `ref_addr` 的距离 `|addr - ref_addr|` 从近到远返回当前模式下的所有结果
+    ///
+    /// 在按地址有序的结果集中二分出参考地址的位置，再从该处向低地址和高地址两侧逐页读取、交替归并，
+    /// 不排序也不复制整个结果集；距离相同时低地址在前
+    pub fn sort_by_distance_to(&self, ref_addr: u64) -> Result<Vec<SearchResultItem>> {
+        let total = self.total_count();
+        let split = self.partition_point_addr(ref_addr)?;
+        // 两侧的当前页都以最靠近参考地址的结果为栈顶
+        let (mut below_next, mut above_next) = (split, split);
+        let (mut below, mut above) = (Vec::new(), Vec::new());
+
+        let mut ordered = Vec::with_capacity(total);
+        loop {
+            if below.is_empty() && below_next > 0 {
+                let start = below_next.saturating_sub(PAGE_ITEMS);
+                below = self.get_results(start, below_next - start)?;
+                below_next = start;
+            }
+            if above.is_empty() && above_next < total {
+                let size = PAGE_ITEMS.min(total - above_next);
+                above = self.get_results(above_next, size)?;
+                above.reverse();
+                above_next += size;
+            }
+
+            let take_below = match (below.last(), above.last()) {
+                (Some(low), Some(high)) => ref_addr.abs_diff(low.address()) <= high.address().abs_diff(ref_addr),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            ordered.extend(if take_below { below.pop() } else { above.pop() });
+        }
+        Ok(ordered)
+    }
+
+    /// 按上次记录的值聚类，返回各簇内结果的下标（升序），簇按值从小到大排列
+    ///
+    /// 值排序后相邻两个相差不超过 `tolerance` 即归为同一簇（单链接），只有一个成员的簇不返回。
+    /// 只有模糊模式保存了值，精确模式返回错误
+    pub fn cluster_by_value(&self, tolerance: f64) -> Result<Vec<Vec<usize>>> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Clustering requires stored values (fuzzy mode)"));
+        }
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(anyhow!("Invalid cluster tolerance: {}", tolerance));
+        }
+
+        let mut values: Vec<(f64, usize)> = self
+            .fuzzy
+            .get_all_results()?
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (item.as_f64(), idx))
+            .filter(|(value, _)| !value.is_nan())
+            .collect();
+        values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut clusters = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+        let mut last_value = f64::NAN;
+        for (value, idx) in values {
+            if !current.is_empty() && value - last_value > tolerance {
+                clusters.push(std::mem::take(&mut current));
+            }
+            current.push(idx);
+            last_value = value;
+        }
+        clusters.push(current);
+
+        clusters.retain(|cluster| cluster.len() > 1);
+        for cluster in &mut clusters {
+            cluster.sort_unstable();
+        }
+        Ok(clusters)
+    }
+
+    /// 细化/比较改写结果集之前调用，把当前结果集按页写入历史文件；深度为 0 时什么也不做
+    pub fn record_history(&mut self) -> Result<()> {
+        if !self.history.is_enabled() {
+            return Ok(());
+        }
+        match self.current_mode {
+            SearchResultMode::Exact => self.history.push(SearchResultMode::Exact, &*self.exact),
+            SearchResultMode::Fuzzy => self.history.push(SearchResultMode::Fuzzy, &*self.fuzzy),
+        }
+    }
+
+    /// 细化/比较写完结果后调用，较大的结果集改为只保存差量
+    pub fn seal_history(&mut self) -> Result<()> {
+        match self.current_mode {
+            SearchResultMode::Exact => self.history.seal(SearchResultMode::Exact, &*self.exact),
+            SearchResultMode::Fuzzy => self.history.seal(SearchResultMode::Fuzzy, &*self.fuzzy),
+        }
+        Ok(())
+    }
+
+    /// 撤销最近一次细化，恢复细化前的结果集；没有可撤销的细化时返回 false
+    ///
+    /// 以差量保存的步骤在当前结果集上还原，细化之后手动删除的结果不会被找回
+    pub fn undo_refine(&mut self) -> Result<bool> {
+        let Some(entry) = self.history.pop() else {
+            return Ok(false);
+        };
+        if entry.is_delta() && entry.mode() != self.current_mode {
+            return Err(anyhow!("Refine history does not match the current result mode"));
+        }
+
+        self.set_mode(entry.mode())?;
+        match entry.mode() {
+            SearchResultMode::Exact => entry.restore(&mut *self.exact)?,
+            SearchResultMode::Fuzzy => entry.restore(&mut *self.fuzzy)?,
+        }
+        debug!("Undid refine: {} results restored, {} steps left", self.total_count(), self.history.len());
+        Ok(true)
+    }
+
+    /// 可撤销的步数
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 设置最多保留的撤销步数，0 表示不记录
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// 新的搜索开始时调用，旧结果集的历史不再有意义
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
     /// 批量替换所有模糊搜索结果（用于细化搜索后）
     pub fn replace_all_fuzzy_results(&mut self, results: Vec<FuzzySearchResultItem>) -> Result<()> {
         if self.current_mode != SearchResultMode::Fuzzy {
@@ -197,3 +621,28 @@ impl SearchResultManager {
         self.fuzzy.replace_all(results)
     }
 }
+
+/// 旧模块布局到新模块布局的地址换算，模块以 (名称, 序号) 对应
+struct ModuleRebase<'a> {
+    /// 按基址排序，二分查找地址所属模块
+    old: Vec<&'a VmStaticData>,
+    new: HashMap<(&'a str, u32), &'a VmStaticData>,
+}
+
+impl<'a> ModuleRebase<'a> {
+    fn new(old_modules: &'a [VmStaticData], new_modules: &'a [VmStaticData]) -> Self {
+        let mut old: Vec<&VmStaticData> = old_modules.iter().collect();
+        old.sort_by_key(|module| module.base_address);
+        let new = new_modules.iter().map(|module| ((module.name.as_str(), module.index), module)).collect();
+        Self { old, new }
+    }
+
+    fn translate(&self, address: u64) -> Option<u64> {
+        let idx = self.old.partition_point(|module| module.base_address <= address);
+        let old_module = self.old[..idx].last().filter(|module| module.contains(address))?;
+        let new_module = self.new.get(&(old_module.name.as_str(), old_module.index))?;
+
+        let new_address = new_module.base_address.checked_add(old_module.offset_from_base(address))?;
+        new_module.contains(new_address).then_some(new_address)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::search::{SearchResultItem, ValueType};
 use crate::search::result_manager::SearchResultManager;
+use crate::search::result_manager::MemoryUsage;
 use log::{debug, info};
 use memmap2::MmapMut;
 use std::fs::{File, OpenOptions};
@@ -110,6 +111,22 @@ impl ExactSearchResultManager {
         Ok(())
     }
 
+    /// 批量追加：先填满内存缓冲，剩余部分依次写入磁盘
+    pub fn add_results(&mut self, items: Vec<ExactSearchResultItem>) -> anyhow::Result<()> {
+        let room = self.memory_buffer_capacity.saturating_sub(self.memory_buffer.len());
+        let mut items = items.into_iter();
+
+        let before = self.memory_buffer.len();
+        self.memory_buffer.extend(items.by_ref().take(room));
+        self.total_count += self.memory_buffer.len() - before;
+
+        for item in items {
+            self.write_to_disk(&item)?;
+            self.total_count += 1;
+        }
+        Ok(())
+    }
+
     fn write_to_disk(&mut self, item: &ExactSearchResultItem) -> anyhow::Result<()> {
         if self.disk_file.is_none() {
             self.init_disk_file()?;
@@ -213,6 +230,13 @@ impl ExactSearchResultManager {
         self.total_count
     }
 
+    /// 内存缓冲按已分配容量计算，磁盘部分按溢出文件的实际大小计算
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let in_memory = size_of::<Self>() + self.memory_buffer.capacity() * size_of::<ExactSearchResultItem>();
+        let on_disk = self.disk_file.as_ref().and_then(|f| f.metadata().ok()).map_or(0, |m| m.len());
+        MemoryUsage::new(in_memory as u64, on_disk, self.total_count)
+    }
+
     pub fn memory_count(&self) -> usize {
         self.memory_buffer.len()
     }
@@ -1,4 +1,5 @@
 use crate::search::FuzzyCondition;
+use crate::search::result_manager::MemoryUsage;
 use crate::search::types::ValueType;
 use anyhow::{Result, anyhow};
 use log::{debug, info};
@@ -79,6 +80,8 @@ impl FuzzySearchResultItem {
             ValueType::Qword => i64::from_le_bytes(self.value),
             ValueType::Float => f32::from_le_bytes(self.value[..4].try_into().unwrap()) as i64,
             ValueType::Double => f64::from_le_bytes(self.value) as i64,
+            // 模糊搜索不支持 Oword、特征码和字符串，这里只可能是低 8 字节
+            ValueType::Oword | ValueType::Bytes(_) | ValueType::Text(..) => i64::from_le_bytes(self.value),
         }
     }
 
@@ -92,6 +95,7 @@ impl FuzzySearchResultItem {
             ValueType::Qword => i64::from_le_bytes(self.value) as f64,
             ValueType::Float => f32::from_le_bytes(self.value[..4].try_into().unwrap()) as f64,
             ValueType::Double => f64::from_le_bytes(self.value),
+            ValueType::Oword | ValueType::Bytes(_) | ValueType::Text(..) => i64::from_le_bytes(self.value) as f64,
         }
     }
 
@@ -289,6 +293,22 @@ impl FuzzySearchResultManager {
         Ok(())
     }
 
+    /// 批量追加：先填满内存缓冲，剩余部分依次写入磁盘
+    pub fn add_results(&mut self, items: Vec<FuzzySearchResultItem>) -> Result<()> {
+        let room = self.memory_buffer_capacity.saturating_sub(self.memory_buffer.len());
+        let mut items = items.into_iter();
+
+        let before = self.memory_buffer.len();
+        self.memory_buffer.extend(items.by_ref().take(room));
+        self.total_count += self.memory_buffer.len() - before;
+
+        for item in items {
+            self.write_to_disk(&item)?;
+            self.total_count += 1;
+        }
+        Ok(())
+    }
+
     fn write_to_disk(&mut self, item: &FuzzySearchResultItem) -> Result<()> {
         if self.disk_file.is_none() {
             self.init_disk_file()?;
@@ -369,6 +389,13 @@ impl FuzzySearchResultManager {
         self.get_results(0, self.total_count)
     }
 
+    /// 内存缓冲按已分配容量计算，磁盘部分按溢出文件的实际大小计算
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let in_memory = size_of::<Self>() + self.memory_buffer.capacity() * Self::ITEM_SIZE;
+        let on_disk = self.disk_file.as_ref().and_then(|f| f.metadata().ok()).map_or(0, |m| m.len());
+        MemoryUsage::new(in_memory as u64, on_disk, self.total_count)
+    }
+
     pub fn total_count(&self) -> usize {
         self.total_count
     }
@@ -0,0 +1,426 @@
+//! 细化历史：每次细化/比较前把结果集写入缓存目录，支持逐步撤销
+//!
+//! 结果集按页从存储后端读出后写入历史文件，不整份载入内存。结果多、且细化只删除或改动了其中一部分时，
+//! 细化完成后按地址归并细化前后的结果集，改为只保存差量（被删除的结果、被改动结果的旧值、新增结果的地址），
+//! 撤销时同样按页归并当前结果集和差量还原。归并要求两边都按地址递增，扫描和细化得到的结果集都满足，
+//! 不满足时保留整份。
+//!
+//! 默认不记录（深度为 0），此时细化不会额外读取结果集。
+
+use super::persistent::IndexRecord;
+use super::store::ResultStore;
+use super::{ExactSearchResultItem, FuzzySearchResultItem, SearchResultMode};
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认保留的撤销步数，0 表示不记录
+pub const DEFAULT_REFINE_HISTORY_DEPTH: usize = 0;
+
+/// 结果数不超过该值时整份保存，不计算差量
+pub(crate) const FULL_COPY_LIMIT: usize = 4096;
+
+/// 每次从存储后端读取、写回的结果条数
+pub(crate) const PAGE_ITEMS: usize = 64 * 1024;
+
+/// 历史文件编号，同一进程内的多个管理器共用缓存目录时也不会重名
+static NEXT_FILE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 历史文件中每条记录的类型，整份保存的记录为 `KEPT`
+const KEPT: u8 = 0;
+/// 细化时被删除的结果
+const REMOVED: u8 = 1;
+/// 被改动结果的旧值
+const CHANGED: u8 = 2;
+/// 细化后才出现的地址
+const ADDED: u8 = 3;
+
+pub(crate) trait HistoryItem: IndexRecord {
+    fn address(&self) -> u64;
+    /// 值和类型都相同
+    fn same(&self, other: &Self) -> bool;
+}
+
+impl HistoryItem for ExactSearchResultItem {
+    fn address(&self) -> u64 {
+        self.address
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        let (typ, other_typ) = (self.typ, other.typ);
+        typ == other_typ
+    }
+}
+
+impl HistoryItem for FuzzySearchResultItem {
+    fn address(&self) -> u64 {
+        self.address
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        let (value, value_type) = (self.value, self.value_type);
+        let (other_value, other_type) = (other.value, other.value_type);
+        value == other_value && value_type == other_type
+    }
+}
+
+/// 按页顺序读出存储后端中的全部结果
+struct StoreCursor<'a, T> {
+    store: &'a dyn ResultStore<T>,
+    next: usize,
+    page: std::vec::IntoIter<T>,
+}
+
+impl<'a, T> StoreCursor<'a, T> {
+    fn new(store: &'a dyn ResultStore<T>) -> Self {
+        Self {
+            store,
+            next: 0,
+            page: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<T> Iterator for StoreCursor<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if let Some(item) = self.page.next() {
+            return Some(Ok(item));
+        }
+        if self.next >= self.store.total_count() {
+            return None;
+        }
+        match self.store.get_results(self.next, PAGE_ITEMS) {
+            Ok(page) => {
+                self.next += page.len().max(1);
+                self.page = page.into_iter();
+                self.page.next().map(Ok)
+            },
+            Err(e) => {
+                self.next = usize::MAX;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// 历史文件写入端，每条记录为 1 字节类型加 [`IndexRecord`] 编码的结果
+struct RecordWriter<T> {
+    path: PathBuf,
+    out: BufWriter<File>,
+    buf: Vec<u8>,
+    count: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: HistoryItem> RecordWriter<T> {
+    fn create(path: PathBuf) -> Result<Self> {
+        let file = File::create(&path).map_err(|e| anyhow!("Failed to create refine history {:?}: {}", path, e))?;
+        Ok(Self {
+            path,
+            out: BufWriter::new(file),
+            buf: vec![0; 1 + T::SIZE],
+            count: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    fn write(&mut self, tag: u8, item: &T) -> Result<()> {
+        self.buf[0] = tag;
+        item.encode(&mut self.buf[1..]);
+        self.out.write_all(&self.buf)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 写完后返回文件路径和记录数
+    fn finish(mut self) -> Result<(PathBuf, usize)> {
+        self.out.flush()?;
+        Ok((std::mem::take(&mut self.path), self.count))
+    }
+}
+
+impl<T> Drop for RecordWriter<T> {
+    /// 没有调用 [`Self::finish`] 时（出错或放弃）删除写了一半的文件
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 顺序读出历史文件中的 (类型, 结果)
+struct RecordReader<T> {
+    input: BufReader<File>,
+    buf: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: HistoryItem> RecordReader<T> {
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| anyhow!("Failed to open refine history {:?}: {}", path, e))?;
+        Ok(Self {
+            input: BufReader::new(file),
+            buf: vec![0; 1 + T::SIZE],
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: HistoryItem> Iterator for RecordReader<T> {
+    type Item = Result<(u8, T)>;
+
+    fn next(&mut self) -> Option<Result<(u8, T)>> {
+        match self.input.read_exact(&mut self.buf) {
+            Ok(()) => Some(T::decode(&self.buf[1..]).map(|item| (self.buf[0], item))),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+/// 取下一条结果并检查地址严格递增
+fn next_in_order<T: HistoryItem>(iter: &mut impl Iterator<Item = Result<T>>, last: &mut Option<u64>) -> Result<Option<T>> {
+    let Some(item) = iter.next().transpose()? else {
+        return Ok(None);
+    };
+    if last.is_some_and(|last| item.address() <= last) {
+        return Err(anyhow!("Results are not in address order"));
+    }
+    *last = Some(item.address());
+    Ok(Some(item))
+}
+
+/// 把历史文件中的结果按页追加到存储后端
+fn load_into<T: HistoryItem>(path: &Path, store: &mut dyn ResultStore<T>) -> Result<()> {
+    let mut page = Vec::with_capacity(PAGE_ITEMS);
+    for record in RecordReader::<T>::open(path)? {
+        page.push(record?.1);
+        if page.len() == PAGE_ITEMS {
+            store.add_results(std::mem::replace(&mut page, Vec::with_capacity(PAGE_ITEMS)))?;
+        }
+    }
+    store.add_results(page)
+}
+
+/// 撤销栈中的一步，释放时删除对应的历史文件
+pub(crate) struct HistoryEntry {
+    mode: SearchResultMode,
+    delta: bool,
+    path: PathBuf,
+    count: usize,
+}
+
+impl HistoryEntry {
+    pub fn mode(&self) -> SearchResultMode {
+        self.mode
+    }
+
+    /// 以差量保存，只能在同一模式的结果集上还原
+    pub fn is_delta(&self) -> bool {
+        self.delta
+    }
+
+    /// 用这一步保存的结果集替换 `store` 中的结果
+    pub fn restore<T: HistoryItem>(&self, store: &mut dyn ResultStore<T>) -> Result<()> {
+        if !self.delta {
+            store.clear()?;
+            return load_into(&self.path, store);
+        }
+
+        // 先把还原结果写入临时文件，再整体替换，避免边读边改
+        let mut restored = RecordWriter::<T>::create(history_path(self.path.parent().unwrap_or(Path::new("."))))?;
+        {
+            let mut current = StoreCursor::new(&*store);
+            let mut delta = RecordReader::<T>::open(&self.path)?;
+            let mut last = None;
+            let mut cur = next_in_order(&mut current, &mut last)?;
+            let mut rec = delta.next().transpose()?;
+            loop {
+                match (cur, rec) {
+                    (None, None) => break,
+                    (Some(item), None) => {
+                        restored.write(KEPT, &item)?;
+                        cur = next_in_order(&mut current, &mut last)?;
+                    },
+                    (Some(item), Some((_, old))) if item.address() < old.address() => {
+                        restored.write(KEPT, &item)?;
+                        cur = next_in_order(&mut current, &mut last)?;
+                    },
+                    (Some(item), Some((tag, old))) if item.address() == old.address() => {
+                        match tag {
+                            CHANGED => restored.write(KEPT, &old)?,
+                            ADDED => {},
+                            _ => restored.write(KEPT, &item)?,
+                        }
+                        cur = next_in_order(&mut current, &mut last)?;
+                        rec = delta.next().transpose()?;
+                    },
+                    // 当前结果集中没有的地址：只找回被删除的结果，细化之后手动删除的结果不会被找回
+                    (_, Some((tag, old))) => {
+                        if tag == REMOVED {
+                            restored.write(KEPT, &old)?;
+                        }
+                        rec = delta.next().transpose()?;
+                    },
+                }
+            }
+        }
+
+        let (path, _) = restored.finish()?;
+        store.clear()?;
+        let loaded = load_into(&path, store);
+        let _ = fs::remove_file(&path);
+        loaded
+    }
+}
+
+impl Drop for HistoryEntry {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            debug!("Failed to remove refine history {:?}: {}", self.path, e);
+        }
+    }
+}
+
+fn history_path(dir: &Path) -> PathBuf {
+    let id = NEXT_FILE_ID.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!("mamu_refine_history_{}_{}.bin", std::process::id(), id))
+}
+
+/// 有界的撤销栈，队尾为最近一步
+pub(crate) struct RefineHistory {
+    depth: usize,
+    /// 历史文件所在目录，即搜索引擎的缓存目录
+    dir: PathBuf,
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl RefineHistory {
+    pub fn new(depth: usize, dir: PathBuf) -> Self {
+        Self {
+            depth,
+            dir,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.entries.len() > depth {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 记录细化前的结果集，超出深度时丢弃最旧的一步
+    pub fn push<T: HistoryItem>(&mut self, mode: SearchResultMode, store: &dyn ResultStore<T>) -> Result<()> {
+        if self.depth == 0 {
+            return Ok(());
+        }
+
+        let mut writer = RecordWriter::<T>::create(history_path(&self.dir))?;
+        for item in StoreCursor::new(store) {
+            writer.write(KEPT, &item?)?;
+        }
+        let (path, count) = writer.finish()?;
+
+        while self.entries.len() >= self.depth {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry {
+            mode,
+            delta: false,
+            path,
+            count,
+        });
+        Ok(())
+    }
+
+    /// 细化完成后调用：最近一步的结果集较大且差量更小时，改为只保存相对 `store` 当前结果的差量
+    pub fn seal<T: HistoryItem>(&mut self, mode: SearchResultMode, store: &dyn ResultStore<T>) {
+        let Some(entry) = self.entries.back() else {
+            return;
+        };
+        if entry.delta || entry.mode != mode || entry.count <= FULL_COPY_LIMIT {
+            return;
+        }
+
+        match Self::write_delta(&self.dir, entry, store) {
+            Ok(Some((path, count))) => {
+                let entry = self.entries.back_mut().unwrap();
+                debug!("Refine history stored as delta: {} of {} records", count, entry.count);
+                // 旧的整份文件随被替换的 HistoryEntry 一起删除
+                *entry = HistoryEntry {
+                    mode,
+                    delta: true,
+                    path,
+                    count,
+                };
+            },
+            Ok(None) => {},
+            Err(e) => warn!("Keeping full refine history: {}", e),
+        }
+    }
+
+    /// 归并细化前的整份记录和细化后的结果集，差量不比整份小时返回 None
+    fn write_delta<T: HistoryItem>(dir: &Path, entry: &HistoryEntry, store: &dyn ResultStore<T>) -> Result<Option<(PathBuf, usize)>> {
+        let mut writer = RecordWriter::<T>::create(history_path(dir))?;
+        let mut old = RecordReader::<T>::open(&entry.path)?.map(|record| record.map(|(_, item)| item));
+        let mut new = StoreCursor::new(store);
+        let (mut last_old, mut last_new) = (None, None);
+        let mut x = next_in_order(&mut old, &mut last_old)?;
+        let mut y = next_in_order(&mut new, &mut last_new)?;
+
+        loop {
+            match (x, y) {
+                (None, None) => break,
+                (Some(removed), None) => {
+                    writer.write(REMOVED, &removed)?;
+                    x = next_in_order(&mut old, &mut last_old)?;
+                },
+                (Some(removed), Some(kept)) if removed.address() < kept.address() => {
+                    writer.write(REMOVED, &removed)?;
+                    x = next_in_order(&mut old, &mut last_old)?;
+                },
+                (Some(before), Some(after)) if before.address() == after.address() => {
+                    if !before.same(&after) {
+                        writer.write(CHANGED, &before)?;
+                    }
+                    x = next_in_order(&mut old, &mut last_old)?;
+                    y = next_in_order(&mut new, &mut last_new)?;
+                },
+                (_, Some(added)) => {
+                    writer.write(ADDED, &added)?;
+                    y = next_in_order(&mut new, &mut last_new)?;
+                },
+            }
+            if writer.count >= entry.count {
+                return Ok(None);
+            }
+        }
+        writer.finish().map(Some)
+    }
+
+    /// 弹出最近一步；没有历史时返回 None
+    pub fn pop(&mut self) -> Option<HistoryEntry> {
+        self.entries.pop_back()
+    }
+}
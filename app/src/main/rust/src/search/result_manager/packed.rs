@@ -0,0 +1,126 @@
+//! 结果批量传输格式
+//!
+//! 一页结果序列化成一个字节块，Java 侧一次 JNI 调用拿到后自行解码，避免逐个创建对象。
+//! 所有整数均为小端序。
+//!
+//! ```text
+//! Header (8 bytes)
+//!   0  u8   mode        0 = Exact, 1 = Fuzzy
+//!   1  u8   version     当前为 2
+//!   2  u16  reserved    0
+//!   4  u32  count       记录数量
+//! Record (32 bytes) × count
+//!   0  u64  address
+//!   8  u64  index       在结果集中的位置（nativePosition）
+//!   16 u16  value_type  ValueType::to_id()
+//!   18 u8   flags       bit0 = value 有效；Exact 模式读取失败时为 0
+//!   19 u8   value_len   value 中有效字节数（0..=8）
+//!   20 u8[4] reserved   0
+//!   24 u8[8] value      原始值字节，不足 8 字节时补 0
+//! ```
+//!
+//! Oword 只传输低 8 字节，`value_len` 为 8，完整值需按地址另行读取。
+
+use super::SearchResultMode;
+use crate::search::types::ValueType;
+
+pub const PACKED_VERSION: u8 = 2;
+pub const PACKED_HEADER_SIZE: usize = 8;
+pub const PACKED_RECORD_SIZE: usize = 32;
+pub const PACKED_FLAG_VALUE_VALID: u8 = 0b0000_0001;
+
+/// 按上面的布局逐条写入记录
+pub struct PackedResultWriter {
+    buf: Vec<u8>,
+    count: u32,
+}
+
+impl PackedResultWriter {
+    pub fn new(mode: SearchResultMode, capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(PACKED_HEADER_SIZE + capacity * PACKED_RECORD_SIZE);
+        buf.push(match mode {
+            SearchResultMode::Exact => 0,
+            SearchResultMode::Fuzzy => 1,
+        });
+        buf.push(PACKED_VERSION);
+        buf.extend_from_slice(&[0u8; 6]);
+        Self { buf, count: 0 }
+    }
+
+    /// `value` 为 None 表示值不可用（例如读取失败），超过 8 字节的部分会被截断
+    pub fn push(&mut self, index: u64, address: u64, value_type: ValueType, value: Option<&[u8]>) {
+        self.buf.extend_from_slice(&address.to_le_bytes());
+        self.buf.extend_from_slice(&index.to_le_bytes());
+        self.buf.extend_from_slice(&(value_type.to_id() as u16).to_le_bytes());
+
+        let mut value_bytes = [0u8; 8];
+        let (flags, len) = match value {
+            Some(bytes) => {
+                let len = bytes.len().min(8);
+                value_bytes[..len].copy_from_slice(&bytes[..len]);
+                (PACKED_FLAG_VALUE_VALID, len as u8)
+            },
+            None => (0, 0),
+        };
+        self.buf.push(flags);
+        self.buf.push(len);
+        self.buf.extend_from_slice(&[0u8; 4]);
+        self.buf.extend_from_slice(&value_bytes);
+        self.count += 1;
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.buf[4..8].copy_from_slice(&self.count.to_le_bytes());
+        self.buf
+    }
+}
+
+/// 解码后的单条记录，用于恢复会话与测试校验格式
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedRecord {
+    pub index: u64,
+    pub address: u64,
+    pub value_type: ValueType,
+    pub value: Option<Vec<u8>>,
+}
+
+pub fn decode_packed(data: &[u8]) -> anyhow::Result<(SearchResultMode, Vec<PackedRecord>)> {
+    use anyhow::anyhow;
+
+    if data.len() < PACKED_HEADER_SIZE {
+        return Err(anyhow!("Packed data too short"));
+    }
+    let mode = match data[0] {
+        0 => SearchResultMode::Exact,
+        1 => SearchResultMode::Fuzzy,
+        m => return Err(anyhow!("Unknown mode {}", m)),
+    };
+    if data[1] != PACKED_VERSION {
+        return Err(anyhow!("Unsupported version {}", data[1]));
+    }
+    let count = u32::from_le_bytes(data[4..8].try_into()?) as usize;
+    if data.len() != PACKED_HEADER_SIZE + count * PACKED_RECORD_SIZE {
+        return Err(anyhow!("Packed data length mismatch"));
+    }
+
+    let records = data[PACKED_HEADER_SIZE..]
+        .chunks_exact(PACKED_RECORD_SIZE)
+        .map(|rec| {
+            let type_id = u16::from_le_bytes(rec[16..18].try_into()?);
+            let value_type = ValueType::from_id(type_id as i32).ok_or_else(|| anyhow!("Unknown value type {}", type_id))?;
+            let value_len = rec[19] as usize;
+            if value_len > 8 {
+                return Err(anyhow!("Invalid value length {}", rec[19]));
+            }
+            let value = (rec[18] & PACKED_FLAG_VALUE_VALID != 0).then(|| rec[24..24 + value_len].to_vec());
+            Ok(PackedRecord {
+                address: u64::from_le_bytes(rec[0..8].try_into()?),
+                index: u64::from_le_bytes(rec[8..16].try_into()?),
+                value_type,
+                value,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok((mode, records))
+}
@@ -0,0 +1,520 @@
+//! 持久化结果索引：结果集保存在 `cache_dir` 下的 mmap 索引文件中，进程被杀后重新打开即可恢复
+//!
+//! 所有整数均为小端序。
+//!
+//! ```text
+//! Superblock × 2，分别位于偏移 0 和 64，每个 64 字节
+//!   0  u8[8]  magic        "MAMUIDX1"
+//!   8  u32    version      当前为 2
+//!   12 u32    record_size  每条记录的字节数
+//!   16 u64    generation   每次提交加 1，写入槽位 generation % 2；0 表示槽位从未写入
+//!   24 u64    count        已提交的记录数
+//!   32 u64    staged_at    非 0 时为待搬移的暂存记录的起始序号
+//!   40 u64    staged_to    暂存记录搬移到的序号，从这里到 count 的记录暂存在 staged_at 处
+//!   48 u32    data_crc     已提交记录的 CRC32，有暂存时按 staged_to 之前的记录加暂存记录计算
+//!   52 u8[8]  reserved     0
+//!   60 u32    header_crc   本槽位 0..60 字节的 CRC32
+//! Record × count，从偏移 4096 开始
+//!   Exact 10 字节：u64 address, u16 value_type
+//!   Fuzzy 18 字节：u64 address, u8[8] value, u16 value_type
+//! ```
+//!
+//! 提交顺序保证崩溃一致：
+//! - 追加：先写入并刷盘新记录，再把新的 superblock 写入另一个槽位并刷盘。崩溃时新槽位要么没写、
+//!   要么校验不匹配，打开时回退到旧槽位，即上一次提交的结果集
+//! - 删除、保留：原地压缩。第一条被删记录之前的记录不动，之后保留的记录逐条复制到已提交记录后面的
+//!   暂存区并刷盘，提交一个带 staged_at 的 superblock，再把暂存区搬回原位并提交不带暂存的 superblock。
+//!   暂存提交之前崩溃时旧记录未被改动；之后崩溃时打开会重做搬移
+//! - 替换：新结果集完整写入临时文件并刷盘后 rename 覆盖索引文件
+//! - 清空：只提交一个 count 为 0 的 superblock
+//!
+//! 恢复时选取 header_crc 有效、记录校验匹配且 generation 最大的槽位；两个槽位都无效时视为空结果集。
+
+use crate::search::result_manager::MemoryUsage;
+use crate::search::result_manager::exact::ExactSearchResultItem;
+use crate::search::result_manager::fuzzy::FuzzySearchResultItem;
+use crate::search::result_manager::store::{ResultStore, ResultStoreKind};
+use crate::search::types::ValueType;
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use memmap2::MmapMut;
+use std::fs::{File, OpenOptions};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+pub const INDEX_MAGIC: [u8; 8] = *b"MAMUIDX1";
+pub const INDEX_VERSION: u32 = 2;
+pub const SUPERBLOCK_SIZE: usize = 64;
+/// 记录区起始偏移
+pub const INDEX_DATA_OFFSET: usize = 4096;
+/// 新建索引文件时预留的记录数
+const INITIAL_CAPACITY: usize = 64 * 1024;
+
+/// 在 `crc` 之后接着计算 `bytes` 的 CRC32，空数据的 CRC32 为 0
+fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new_with_initial(crc);
+    hasher.update(bytes);
+    hasher.finalize()
+}
+
+/// 可写入索引文件的定长记录
+pub(crate) trait IndexRecord: Copy + Send + Sync {
+    const SIZE: usize;
+    fn encode(&self, out: &mut [u8]);
+    fn decode(bytes: &[u8]) -> Result<Self>;
+}
+
+fn decode_value_type(bytes: &[u8]) -> Result<ValueType> {
+    let id = u16::from_le_bytes(bytes.try_into()?);
+    ValueType::from_id(id as i32).ok_or_else(|| anyhow!("Unknown value type {} in result index", id))
+}
+
+impl IndexRecord for ExactSearchResultItem {
+    const SIZE: usize = 10;
+
+    fn encode(&self, out: &mut [u8]) {
+        let (address, typ) = (self.address, self.typ);
+        out[0..8].copy_from_slice(&address.to_le_bytes());
+        out[8..10].copy_from_slice(&(typ.to_id() as u16).to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(u64::from_le_bytes(bytes[0..8].try_into()?), decode_value_type(&bytes[8..10])?))
+    }
+}
+
+impl IndexRecord for FuzzySearchResultItem {
+    const SIZE: usize = 18;
+
+    fn encode(&self, out: &mut [u8]) {
+        let (address, value, value_type) = (self.address, self.value, self.value_type);
+        out[0..8].copy_from_slice(&address.to_le_bytes());
+        out[8..16].copy_from_slice(&value);
+        out[16..18].copy_from_slice(&(value_type.to_id() as u16).to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(
+            u64::from_le_bytes(bytes[0..8].try_into()?),
+            bytes[8..16].try_into()?,
+            decode_value_type(&bytes[16..18])?,
+        ))
+    }
+}
+
+/// 一次提交的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Superblock {
+    generation: u64,
+    count: u64,
+    staged_at: u64,
+    staged_to: u64,
+    data_crc: u32,
+}
+
+impl Superblock {
+    const EMPTY: Superblock = Superblock {
+        generation: 0,
+        count: 0,
+        staged_at: 0,
+        staged_to: 0,
+        data_crc: 0,
+    };
+
+    fn slot_offset(generation: u64) -> usize {
+        (generation % 2) as usize * SUPERBLOCK_SIZE
+    }
+
+    fn encode(&self, record_size: usize) -> [u8; SUPERBLOCK_SIZE] {
+        let mut out = [0u8; SUPERBLOCK_SIZE];
+        out[0..8].copy_from_slice(&INDEX_MAGIC);
+        out[8..12].copy_from_slice(&INDEX_VERSION.to_le_bytes());
+        out[12..16].copy_from_slice(&(record_size as u32).to_le_bytes());
+        out[16..24].copy_from_slice(&self.generation.to_le_bytes());
+        out[24..32].copy_from_slice(&self.count.to_le_bytes());
+        out[32..40].copy_from_slice(&self.staged_at.to_le_bytes());
+        out[40..48].copy_from_slice(&self.staged_to.to_le_bytes());
+        out[48..52].copy_from_slice(&self.data_crc.to_le_bytes());
+        let header_crc = crc32(0, &out[..60]);
+        out[60..64].copy_from_slice(&header_crc.to_le_bytes());
+        out
+    }
+
+    /// 魔数、版本、记录大小或 header_crc 不符时返回 None
+    fn decode(bytes: &[u8], record_size: usize) -> Option<Self> {
+        let read_u64 = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+
+        let header_crc = crc32(0, &bytes[..60]);
+        let valid = bytes[0..8] == INDEX_MAGIC && read_u32(8) == INDEX_VERSION && read_u32(12) as usize == record_size && read_u32(60) == header_crc;
+        let superblock = Superblock {
+            generation: read_u64(16),
+            count: read_u64(24),
+            staged_at: read_u64(32),
+            staged_to: read_u64(40),
+            data_crc: read_u32(48),
+        };
+        (valid && superblock.generation != 0).then_some(superblock)
+    }
+}
+
+/// `count` 条记录占用的字节数
+fn records_len<T: IndexRecord>(count: usize) -> usize {
+    count * T::SIZE
+}
+
+/// 打开的索引文件
+struct IndexFile {
+    file: File,
+    mmap: MmapMut,
+}
+
+impl IndexFile {
+    /// 创建（或截断）文件并预留 `capacity` 条记录的空间
+    fn create<T: IndexRecord>(path: &Path, capacity: usize) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        file.set_len((INDEX_DATA_OFFSET + records_len::<T>(capacity.max(INITIAL_CAPACITY))) as u64)?;
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        Ok(Self { file, mmap })
+    }
+
+    /// 打开已有文件，文件比 superblock 区还短时返回 None
+    fn open(path: &Path) -> Result<Option<Self>> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        if file.metadata()?.len() < INDEX_DATA_OFFSET as u64 {
+            return Ok(None);
+        }
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        Ok(Some(Self { file, mmap }))
+    }
+
+    fn records<T: IndexRecord>(&self, count: usize) -> &[u8] {
+        self.record_range::<T>(0, count)
+    }
+
+    /// 从第 `start` 条开始的 `count` 条记录
+    fn record_range<T: IndexRecord>(&self, start: usize, count: usize) -> &[u8] {
+        let offset = INDEX_DATA_OFFSET + records_len::<T>(start);
+        &self.mmap[offset..offset + records_len::<T>(count)]
+    }
+
+    /// 把从第 `from` 条开始的 `count` 条记录复制到第 `to` 条处
+    fn copy_records<T: IndexRecord>(&mut self, from: usize, to: usize, count: usize) {
+        let src = INDEX_DATA_OFFSET + records_len::<T>(from);
+        let dst = INDEX_DATA_OFFSET + records_len::<T>(to);
+        self.mmap.copy_within(src..src + records_len::<T>(count), dst);
+    }
+
+    fn flush_records<T: IndexRecord>(&self, start: usize, count: usize) -> Result<()> {
+        self.mmap.flush_range(INDEX_DATA_OFFSET + records_len::<T>(start), records_len::<T>(count))?;
+        Ok(())
+    }
+
+    /// 按 superblock 计算已提交记录的 CRC32，暂存区超出文件或位置不合法时返回 None
+    fn committed_crc<T: IndexRecord>(&self, superblock: &Superblock) -> Option<u32> {
+        let count = superblock.count as usize;
+        let capacity = (self.mmap.len() - INDEX_DATA_OFFSET) / T::SIZE;
+        if superblock.staged_at == 0 {
+            return (count <= capacity).then(|| crc32(0, self.records::<T>(count)));
+        }
+
+        let (staged_at, staged_to) = (superblock.staged_at as usize, superblock.staged_to as usize);
+        let staged = count.checked_sub(staged_to)?;
+        if staged_at < count || staged_at.checked_add(staged)? > capacity {
+            return None;
+        }
+        let prefix = crc32(0, self.records::<T>(staged_to));
+        Some(crc32(prefix, self.record_range::<T>(staged_at, staged)))
+    }
+
+    /// 保证能容纳 `count` 条记录，不足时按倍数扩大文件并重新映射
+    fn reserve<T: IndexRecord>(&mut self, count: usize) -> Result<()> {
+        let needed = INDEX_DATA_OFFSET + records_len::<T>(count);
+        if needed <= self.mmap.len() {
+            return Ok(());
+        }
+        let new_len = needed.max(self.mmap.len() * 2);
+        self.file.set_len(new_len as u64)?;
+        self.mmap = unsafe { MmapMut::map_mut(&self.file)? };
+        debug!("Result index grown to {} bytes", new_len);
+        Ok(())
+    }
+
+    fn write_superblock<T: IndexRecord>(&mut self, superblock: &Superblock) -> Result<()> {
+        let offset = Superblock::slot_offset(superblock.generation);
+        self.mmap[offset..offset + SUPERBLOCK_SIZE].copy_from_slice(&superblock.encode(T::SIZE));
+        self.mmap.flush_range(offset, SUPERBLOCK_SIZE)?;
+        Ok(())
+    }
+
+    /// 两个槽位中最新的有效提交
+    fn recover<T: IndexRecord>(&self) -> Option<Superblock> {
+        let mut candidates: Vec<Superblock> = (0..2)
+            .filter_map(|slot| Superblock::decode(&self.mmap[slot * SUPERBLOCK_SIZE..(slot + 1) * SUPERBLOCK_SIZE], T::SIZE))
+            .collect();
+        candidates.sort_by_key(|superblock| std::cmp::Reverse(superblock.generation));
+
+        candidates.into_iter().find(|superblock| {
+            let intact = self.committed_crc::<T>(superblock) == Some(superblock.data_crc);
+            if !intact {
+                warn!("Result index generation {} is torn, falling back", superblock.generation);
+            }
+            intact
+        })
+    }
+}
+
+/// 保存在 mmap 索引文件中的结果存储，按写入顺序保存，每次修改都是一次崩溃一致的提交
+///
+/// 第一次写入结果时才创建文件；[`Self::open`] 同一路径即可恢复上一次提交的结果集
+pub(crate) struct PersistentResultStore<T> {
+    path: PathBuf,
+    index: Option<IndexFile>,
+    committed: Superblock,
+    _marker: PhantomData<T>,
+}
+
+impl<T: IndexRecord> PersistentResultStore<T> {
+    /// 打开 `path` 处的索引，文件不存在时为空结果集，文件损坏时丢弃并视为空结果集
+    pub fn open(path: PathBuf) -> Result<Self> {
+        // 上次重写没有完成，rename 之前的临时文件不含已提交的数据
+        let _ = std::fs::remove_file(Self::rewrite_path(&path));
+
+        let mut store = Self {
+            path,
+            index: None,
+            committed: Superblock::EMPTY,
+            _marker: PhantomData,
+        };
+        if !store.path.exists() {
+            return Ok(store);
+        }
+
+        let recovered = IndexFile::open(&store.path)?.and_then(|index| index.recover::<T>().map(|superblock| (index, superblock)));
+        match recovered {
+            Some((index, superblock)) => {
+                info!(
+                    "Recovered {} results from {:?} (generation {})",
+                    superblock.count, store.path, superblock.generation
+                );
+                store.committed = superblock;
+                store.index = Some(index);
+                if superblock.staged_at != 0 {
+                    store.finish_staged_move()?;
+                }
+            },
+            None => {
+                warn!("No consistent commit in {:?}, starting empty", store.path);
+                std::fs::remove_file(&store.path)?;
+            },
+        }
+        Ok(store)
+    }
+
+    fn rewrite_path(path: &Path) -> PathBuf {
+        path.with_extension("idx.tmp")
+    }
+
+    fn count(&self) -> usize {
+        self.committed.count as usize
+    }
+
+    fn index_mut(&mut self) -> Result<&mut IndexFile> {
+        if self.index.is_none() {
+            self.index = Some(IndexFile::create::<T>(&self.path, 0)?);
+        }
+        Ok(self.index.as_mut().unwrap())
+    }
+
+    /// 写入新的 superblock，成功后才更新内存中的提交状态
+    fn commit(&mut self, count: usize, data_crc: u32) -> Result<()> {
+        self.commit_staged(count, 0, 0, data_crc)
+    }
+
+    fn commit_staged(&mut self, count: usize, staged_at: usize, staged_to: usize, data_crc: u32) -> Result<()> {
+        let superblock = Superblock {
+            generation: self.committed.generation + 1,
+            count: count as u64,
+            staged_at: staged_at as u64,
+            staged_to: staged_to as u64,
+            data_crc,
+        };
+        self.index_mut()?.write_superblock::<T>(&superblock)?;
+        self.committed = superblock;
+        Ok(())
+    }
+
+    /// 原地压缩：`keep` 按序号从小到大对每条已提交记录调用一次，返回 false 的记录被删除
+    ///
+    /// 保留的记录只在 mmap 内逐条复制，不会整份解码到内存
+    fn compact<F: FnMut(usize) -> bool>(&mut self, mut keep: F) -> Result<()> {
+        let count = self.count();
+        let Some(first) = (0..count).find(|&i| !keep(i)) else {
+            return Ok(());
+        };
+        let index = self.index_mut()?;
+        index.reserve::<T>(count + (count - first - 1))?;
+
+        // 第一条被删记录之后保留的记录暂存到已提交记录后面
+        let mut staged = 0;
+        for i in first + 1..count {
+            if keep(i) {
+                index.copy_records::<T>(i, count + staged, 1);
+                staged += 1;
+            }
+        }
+        let prefix = crc32(0, index.records::<T>(first));
+        if staged == 0 {
+            return self.commit(first, prefix);
+        }
+
+        index.flush_records::<T>(count, staged)?;
+        let data_crc = crc32(prefix, index.record_range::<T>(count, staged));
+        self.commit_staged(first + staged, count, first, data_crc)?;
+        self.finish_staged_move()
+    }
+
+    /// 把已提交的暂存记录搬回原位并提交不带暂存的 superblock，中途崩溃后重做结果相同
+    fn finish_staged_move(&mut self) -> Result<()> {
+        let Superblock {
+            count,
+            staged_at,
+            staged_to,
+            data_crc,
+            ..
+        } = self.committed;
+        let (count, staged_at, staged_to) = (count as usize, staged_at as usize, staged_to as usize);
+        let index = self.index_mut()?;
+        index.copy_records::<T>(staged_at, staged_to, count - staged_to);
+        index.flush_records::<T>(staged_to, count - staged_to)?;
+        self.commit(count, data_crc)
+    }
+
+    /// 整份写入临时文件后 rename 覆盖索引文件
+    fn rewrite(&mut self, items: &[T]) -> Result<()> {
+        let tmp_path = Self::rewrite_path(&self.path);
+        let mut index = IndexFile::create::<T>(&tmp_path, items.len())?;
+        let records = &mut index.mmap[INDEX_DATA_OFFSET..INDEX_DATA_OFFSET + records_len::<T>(items.len())];
+        for (item, out) in items.iter().zip(records.chunks_exact_mut(T::SIZE)) {
+            item.encode(out);
+        }
+
+        let superblock = Superblock {
+            generation: self.committed.generation + 1,
+            count: items.len() as u64,
+            staged_at: 0,
+            staged_to: 0,
+            data_crc: crc32(0, index.records::<T>(items.len())),
+        };
+        let offset = Superblock::slot_offset(superblock.generation);
+        index.mmap[offset..offset + SUPERBLOCK_SIZE].copy_from_slice(&superblock.encode(T::SIZE));
+        index.mmap.flush()?;
+        index.file.sync_all()?;
+
+        std::fs::rename(&tmp_path, &self.path)?;
+        if let Some(dir) = self.path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+        self.index = Some(index);
+        self.committed = superblock;
+        Ok(())
+    }
+}
+
+impl<T: IndexRecord> ResultStore<T> for PersistentResultStore<T> {
+    fn kind(&self) -> ResultStoreKind {
+        ResultStoreKind::Persistent
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        if self.index.is_none() {
+            return Ok(());
+        }
+        self.commit(0, 0)
+    }
+
+    fn clear_disk(&mut self) -> Result<()> {
+        self.index = None;
+        self.committed = Superblock::EMPTY;
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    fn add_result(&mut self, item: T) -> Result<()> {
+        self.add_results(vec![item])
+    }
+
+    /// 新记录写在已提交记录之后，刷盘后再提交，中途崩溃不影响已提交的结果
+    fn add_results(&mut self, items: Vec<T>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let count = self.count();
+        let new_count = count + items.len();
+        let prev_crc = self.committed.data_crc;
+        let index = self.index_mut()?;
+        index.reserve::<T>(new_count)?;
+
+        let start = INDEX_DATA_OFFSET + records_len::<T>(count);
+        let len = records_len::<T>(items.len());
+        for (item, out) in items.iter().zip(index.mmap[start..start + len].chunks_exact_mut(T::SIZE)) {
+            item.encode(out);
+        }
+        index.mmap.flush_range(start, len)?;
+
+        let data_crc = crc32(prev_crc, &index.mmap[start..start + len]);
+        self.commit(new_count, data_crc)
+    }
+
+    fn get_results(&self, start: usize, size: usize) -> Result<Vec<T>> {
+        let count = self.count();
+        let Some(index) = self.index.as_ref() else {
+            return Ok(Vec::new());
+        };
+        if start >= count {
+            return Ok(Vec::new());
+        }
+        let end = start.saturating_add(size).min(count);
+        index.records::<T>(end)[records_len::<T>(start)..]
+            .chunks_exact(T::SIZE)
+            .map(T::decode)
+            .collect()
+    }
+
+    fn total_count(&self) -> usize {
+        self.count()
+    }
+
+    fn remove_result(&mut self, index: usize) -> Result<()> {
+        if index >= self.count() {
+            return Err(anyhow!("Index out of bounds: {} >= {}", index, self.count()));
+        }
+        self.compact(|i| i != index)
+    }
+
+    fn remove_results_batch(&mut self, mut indices: Vec<usize>) -> Result<()> {
+        indices.sort_unstable();
+        indices.dedup();
+        let mut remove = indices.into_iter().peekable();
+        self.compact(|i| remove.next_if_eq(&i).is_none())
+    }
+
+    fn keep_only_results(&mut self, mut keep_indices: Vec<usize>) -> Result<()> {
+        keep_indices.sort_unstable();
+        keep_indices.dedup();
+        let mut keep = keep_indices.into_iter().peekable();
+        self.compact(|i| keep.next_if_eq(&i).is_some())
+    }
+
+    fn replace_all(&mut self, results: Vec<T>) -> Result<()> {
+        self.rewrite(&results)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        let on_disk = self.index.as_ref().map_or(0, |index| index.mmap.len());
+        MemoryUsage::new(size_of::<Self>() as u64, on_disk as u64, self.count())
+    }
+}
@@ -0,0 +1,241 @@
+//! 搜索结果的存储后端
+//!
+//! `SearchResultManager` 通过 [`ResultStore`] trait 对象访问精确和模糊结果，存储方式在 `init` 时选择：
+//! - [`ResultStoreKind::Disk`]：内存缓冲 + mmap 磁盘溢出（默认，适合海量结果）
+//! - [`ResultStoreKind::SortedVec`]：纯内存数组，只需要成员判断和计数时更省
+//! - [`ResultStoreKind::Persistent`]：崩溃一致的 mmap 索引文件，进程被杀后用同一个 `cache_dir` 初始化即可恢复结果
+//!
+//! 所有后端都按写入顺序保存结果，基于索引的操作（分页、删除、保留）在各后端之间结果一致
+
+use crate::search::result_manager::exact::{ExactSearchResultItem, ExactSearchResultManager};
+use crate::search::result_manager::fuzzy::{FuzzySearchResultItem, FuzzySearchResultManager};
+use crate::search::result_manager::persistent::PersistentResultStore;
+use crate::search::result_manager::MemoryUsage;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// 结果存储后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultStoreKind {
+    #[default]
+    Disk,
+    SortedVec,
+    Persistent,
+}
+
+impl ResultStoreKind {
+    #[inline]
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ResultStoreKind::Disk),
+            1 => Some(ResultStoreKind::SortedVec),
+            2 => Some(ResultStoreKind::Persistent),
+            _ => None,
+        }
+    }
+
+    /// 持久化后端会打开 `cache_dir` 下已有的索引文件并恢复其中的结果
+    pub(crate) fn create_exact(self, memory_buffer_size: usize, cache_dir: PathBuf) -> Result<Box<dyn ResultStore<ExactSearchResultItem>>> {
+        Ok(match self {
+            ResultStoreKind::Disk => Box::new(ExactSearchResultManager::new(memory_buffer_size, cache_dir)),
+            ResultStoreKind::SortedVec => Box::new(SortedVecResultStore::new()),
+            ResultStoreKind::Persistent => Box::new(PersistentResultStore::<ExactSearchResultItem>::open(cache_dir.join("mamu_exact_results.idx"))?),
+        })
+    }
+
+    pub(crate) fn create_fuzzy(self, memory_buffer_size: usize, cache_dir: PathBuf) -> Result<Box<dyn ResultStore<FuzzySearchResultItem>>> {
+        Ok(match self {
+            ResultStoreKind::Disk => Box::new(FuzzySearchResultManager::new(memory_buffer_size, cache_dir)),
+            ResultStoreKind::SortedVec => Box::new(SortedVecResultStore::new()),
+            ResultStoreKind::Persistent => Box::new(PersistentResultStore::<FuzzySearchResultItem>::open(cache_dir.join("mamu_fuzzy_results.idx"))?),
+        })
+    }
+}
+
+/// 搜索结果存储接口，索引均为结果集中的位置（即写入顺序）
+pub(crate) trait ResultStore<T>: Send + Sync {
+    fn kind(&self) -> ResultStoreKind;
+    fn clear(&mut self) -> Result<()>;
+    /// 释放磁盘等外部资源，没有外部资源的实现什么也不做
+    fn clear_disk(&mut self) -> Result<()>;
+    fn add_result(&mut self, item: T) -> Result<()>;
+    /// 按顺序追加一批结果
+    fn add_results(&mut self, items: Vec<T>) -> Result<()> {
+        for item in items {
+            self.add_result(item)?;
+        }
+        Ok(())
+    }
+    fn get_results(&self, start: usize, size: usize) -> Result<Vec<T>>;
+    fn total_count(&self) -> usize;
+    fn remove_result(&mut self, index: usize) -> Result<()>;
+    fn remove_results_batch(&mut self, indices: Vec<usize>) -> Result<()>;
+    fn keep_only_results(&mut self, keep_indices: Vec<usize>) -> Result<()>;
+    fn memory_usage(&self) -> MemoryUsage;
+
+    fn get_all_results(&self) -> Result<Vec<T>> {
+        self.get_results(0, self.total_count())
+    }
+
+    /// 批量替换所有结果（用于细化搜索后）
+    fn replace_all(&mut self, results: Vec<T>) -> Result<()> {
+        self.clear()?;
+        for item in results {
+            self.add_result(item)?;
+        }
+        Ok(())
+    }
+}
+
+/// 为磁盘后端的结果管理器实现 [`ResultStore`]，方法直接转发到同名的固有方法
+macro_rules! impl_disk_result_store {
+    ($manager:ty, $item:ty) => {
+        impl ResultStore<$item> for $manager {
+            fn kind(&self) -> ResultStoreKind {
+                ResultStoreKind::Disk
+            }
+
+            fn clear(&mut self) -> Result<()> {
+                <$manager>::clear(self)
+            }
+
+            fn clear_disk(&mut self) -> Result<()> {
+                <$manager>::clear_disk(self)
+            }
+
+            fn add_result(&mut self, item: $item) -> Result<()> {
+                <$manager>::add_result(self, item)
+            }
+
+            fn add_results(&mut self, items: Vec<$item>) -> Result<()> {
+                <$manager>::add_results(self, items)
+            }
+
+            fn get_results(&self, start: usize, size: usize) -> Result<Vec<$item>> {
+                <$manager>::get_results(self, start, size)
+            }
+
+            fn total_count(&self) -> usize {
+                <$manager>::total_count(self)
+            }
+
+            fn remove_result(&mut self, index: usize) -> Result<()> {
+                <$manager>::remove_result(self, index)
+            }
+
+            fn remove_results_batch(&mut self, indices: Vec<usize>) -> Result<()> {
+                <$manager>::remove_results_batch(self, indices)
+            }
+
+            fn keep_only_results(&mut self, keep_indices: Vec<usize>) -> Result<()> {
+                <$manager>::keep_only_results(self, keep_indices)
+            }
+
+            fn memory_usage(&self) -> MemoryUsage {
+                <$manager>::memory_usage(self)
+            }
+
+            fn get_all_results(&self) -> Result<Vec<$item>> {
+                <$manager>::get_all_results(self)
+            }
+        }
+    };
+}
+
+impl_disk_result_store!(ExactSearchResultManager, ExactSearchResultItem);
+impl_disk_result_store!(FuzzySearchResultManager, FuzzySearchResultItem);
+
+/// 纯内存结果存储
+///
+/// 扫描结果本身按地址递增写入，追加只需 push，数组自然有序；
+/// 乱序写入时同磁盘后端一样保留写入顺序，不重新排序，保证两种后端的索引含义一致
+pub(crate) struct SortedVecResultStore<T> {
+    items: Vec<T>,
+}
+
+impl<T> SortedVecResultStore<T> {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T: Clone + Send + Sync> ResultStore<T> for SortedVecResultStore<T> {
+    fn kind(&self) -> ResultStoreKind {
+        ResultStoreKind::SortedVec
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.items.clear();
+        Ok(())
+    }
+
+    fn clear_disk(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn add_result(&mut self, item: T) -> Result<()> {
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn add_results(&mut self, items: Vec<T>) -> Result<()> {
+        self.items.extend(items);
+        Ok(())
+    }
+
+    fn get_results(&self, start: usize, size: usize) -> Result<Vec<T>> {
+        if start >= self.items.len() {
+            return Ok(Vec::new());
+        }
+        let end = start.saturating_add(size).min(self.items.len());
+        Ok(self.items[start..end].to_vec())
+    }
+
+    fn total_count(&self) -> usize {
+        self.items.len()
+    }
+
+    fn remove_result(&mut self, index: usize) -> Result<()> {
+        if index >= self.items.len() {
+            return Err(anyhow!("Index out of bounds: {} >= {}", index, self.items.len()));
+        }
+        self.items.remove(index);
+        Ok(())
+    }
+
+    fn remove_results_batch(&mut self, mut indices: Vec<usize>) -> Result<()> {
+        indices.sort_unstable();
+        indices.dedup();
+        let mut remove = indices.into_iter().peekable();
+        let mut index = 0usize;
+        self.items.retain(|_| {
+            let keep = remove.next_if_eq(&index).is_none();
+            index += 1;
+            keep
+        });
+        Ok(())
+    }
+
+    fn keep_only_results(&mut self, mut keep_indices: Vec<usize>) -> Result<()> {
+        keep_indices.sort_unstable();
+        keep_indices.dedup();
+        let mut keep = keep_indices.into_iter().peekable();
+        let mut index = 0usize;
+        self.items.retain(|_| {
+            let kept = keep.next_if_eq(&index).is_some();
+            index += 1;
+            kept
+        });
+        Ok(())
+    }
+
+    fn replace_all(&mut self, results: Vec<T>) -> Result<()> {
+        self.items = results;
+        Ok(())
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        let in_memory = size_of::<Self>() + self.items.capacity() * size_of::<T>();
+        MemoryUsage::new(in_memory as u64, 0, self.items.len())
+    }
+}
@@ -10,7 +10,8 @@ mod tests {
     use crate::search::{SearchMode, SearchQuery, SearchValue, ValueType};
     use crate::search::tests::mock_memory::MockMemory;
     use crate::search::engine::manager::ValuePair;
-    use crate::search::engine::group_search::search_in_buffer_group_deep;
+    use crate::search::engine::group_search::{search_in_buffer_group_deep, search_region_group_deep_queued, DeepItemBudget};
+    use crate::search::PAGE_SIZE;
     use crate::wuwa::PageStatusBitmap;
 
     // ==================== Test Cases ====================
@@ -539,4 +540,87 @@ mod tests {
 
         println!("\n✓ Deep search correctly finds ALL participating addresses!");
     }
+
+    /// 一个组合爆炸的密集窗口夹在大量普通窗口之间：结果与逐窗口扫描一致，且多个线程分担了工作项
+    #[test]
+    fn test_deep_search_queue_balances_dense_window() {
+        println!("\n=== Deep search work queue test ===\n");
+
+        let page = *PAGE_SIZE as u64;
+        let pages = 64u64;
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, (pages * page) as usize).unwrap();
+
+        // 第 32 页每 64 字节一个 1，每个 1 都能与窗口内其余的 1 组合
+        let dense = base_addr + 32 * page;
+        for offset in (0..page).step_by(64) {
+            mem.mem_write_u32(dense + offset, 1).unwrap();
+        }
+        let mut expected: Vec<u64> = (0..page).step_by(64).map(|offset| dense + offset).collect();
+        // 远离密集页的几组普通匹配
+        for index in [4u64, 10, 50] {
+            let addr = base_addr + index * page + 0x100;
+            mem.mem_write_u32(addr, 1).unwrap();
+            mem.mem_write_u32(addr + 8, 1).unwrap();
+            expected.extend([addr, addr + 8]);
+        }
+        expected.sort_unstable();
+
+        let values = vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(1, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 0x800);
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let budget = DeepItemBudget::new(16);
+        let end = base_addr + pages * page;
+        let (results, stats) = pool
+            .install(|| search_region_group_deep_queued(&query, base_addr, end, page as usize, mem.reader(), &|| false, &budget))
+            .unwrap();
+
+        let addresses: Vec<u64> = results.iter().map(|pair| pair.addr).collect();
+        assert_eq!(addresses, expected);
+
+        println!("Work items per thread: {:?}", stats.items_per_thread);
+        assert_eq!(stats.items_per_thread.iter().sum::<usize>(), pages as usize);
+        let participating = stats.items_per_thread.iter().filter(|&&items| items > 0).count();
+        assert!(participating > 1, "only one thread processed work items: {:?}", stats.items_per_thread);
+    }
+
+    /// 多个区域并行做深度搜索时共用一份额度，排队的 chunk 缓冲区总数不超过额度
+    #[test]
+    fn test_deep_search_budget_shared_across_regions() {
+        use rayon::prelude::*;
+
+        let page = *PAGE_SIZE as u64;
+        let pages_per_region = 16u64;
+        let regions = 6u64;
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, (regions * pages_per_region * page) as usize).unwrap();
+
+        // 每页都是密集窗口，工作项执行得慢，排队的工作项容易堆积
+        for offset in (0..regions * pages_per_region * page).step_by(16) {
+            mem.mem_write_u32(base_addr + offset, 1).unwrap();
+        }
+
+        let values = vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(1, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 0x100);
+        let budget = DeepItemBudget::new(3);
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let found: Vec<usize> = pool.install(|| {
+            (0..regions)
+                .into_par_iter()
+                .map(|index| {
+                    let start = base_addr + index * pages_per_region * page;
+                    let end = start + pages_per_region * page;
+                    let (results, _) = search_region_group_deep_queued(&query, start, end, page as usize, mem.reader(), &|| false, &budget).unwrap();
+                    results.len()
+                })
+                .collect()
+        });
+
+        assert!(found.iter().all(|&count| count == (pages_per_region * page / 16) as usize), "{:?}", found);
+        assert!(budget.peak() <= 3, "peak {} exceeds the shared budget", budget.peak());
+        assert!(budget.peak() > 0);
+        assert_eq!(budget.in_flight(), 0);
+    }
 }
@@ -4,15 +4,44 @@
 mod tests {
     use anyhow::Result;
     use log::{log_enabled, warn, Level};
+    use std::cell::Cell;
     use std::time::Instant;
     use bplustree::BPlusTreeSet;
+    use crate::search::engine::group_search::{
+        anchor_entropy, search_in_buffer_group, search_in_buffer_group_deep, search_in_buffer_group_deep_with_cancel,
+        search_in_buffer_group_fallback, search_in_buffer_group_fallback_with_cancel, search_in_buffer_group_fallback_with_strategy,
+        select_anchor, try_match_group_at_address_with_cancel, PageScanStrategy, CANCEL_CHECK_BYTES, WEAK_ANCHOR_ENTROPY_BITS,
+    };
     use crate::search::{
         SearchEngineManager, ValuePair, BPLUS_TREE_ORDER, PAGE_MASK, PAGE_SIZE,
         SearchMode, SearchQuery, SearchValue, ValueType,
+        ByteOrder, FloatTolerance, RelOp, TextEncoding,
     };
     use crate::search::tests::mock_memory::MockMemory;
     use crate::wuwa::PageStatusBitmap;
 
+    const BASE_ADDR: u64 = 0x7000000000;
+
+    fn write(buffer: &mut [u8], offset: usize, bytes: &[u8]) {
+        buffer[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// 在 `BASE_ADDR` 处对整块 buffer 做组搜索，返回去重排序后相对 `BASE_ADDR` 的偏移和校验次数
+    fn run(query: &SearchQuery, buffer: &[u8], fallback: bool) -> (Vec<u64>, usize) {
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE_ADDR as usize);
+        page_status.mark_all_success();
+        let mut results: Vec<ValuePair> = Vec::new();
+        let mut checked = 0usize;
+        let end = BASE_ADDR + buffer.len() as u64;
+        let search = if fallback { search_in_buffer_group_fallback } else { search_in_buffer_group };
+        search(buffer, BASE_ADDR, BASE_ADDR, end, 4, query, &page_status, &mut results, &mut checked);
+
+        let mut addrs: Vec<u64> = results.iter().map(|r| r.addr - BASE_ADDR).collect();
+        addrs.sort_unstable();
+        addrs.dedup();
+        (addrs, checked)
+    }
+
     /// Optimized version of search_in_buffer_group using pre-computed page ranges
     #[inline]
     fn search_in_buffer_group_optimized(
@@ -1241,14 +1270,14 @@ mod tests {
 
         for (idx, value) in query.values.iter().enumerate() {
             match value {
-                SearchValue::FixedInt { value, value_type } => {
+                SearchValue::FixedInt { value, value_type, .. } => {
                     let size = value_type.size();
                     anchor_bytes_storage[..size].copy_from_slice(&value[..size]);
                     anchor_bytes_len = size;
                     anchor_index = Some(idx);
                     break;
                 }
-                SearchValue::FixedFloat { value, value_type } => {
+                SearchValue::FixedFloat { value, value_type, .. } => {
                     let size = value_type.size();
                     match value_type {
                         ValueType::Float => {
@@ -2329,4 +2358,737 @@ mod tests {
 
         println!("\nOrdered group search test passed!");
     }
-}
\ No newline at end of file
+
+    // 混合对齐（DWORD + QWORD）组搜索
+
+    fn mixed_query(mode: SearchMode) -> SearchQuery {
+        SearchQuery::new(
+            vec![SearchValue::fixed(0x11223344, ValueType::Dword), SearchValue::fixed(0x5566778899AABBCC, ValueType::Qword)],
+            mode,
+            32,
+        )
+    }
+
+    #[test]
+    fn test_mixed_dword_qword_ordered_group() {
+        // DWORD @ 0x1000，QWORD @ 0x1008：旧实现从相对偏移 4 开始按 8 步进（0x1004、0x100C...），永远错过 0x1008
+        let base_addr = 0x7000001000u64;
+        let mut buffer = vec![0u8; 32];
+        write(&mut buffer, 0, &0x11223344u32.to_le_bytes());
+        write(&mut buffer, 8, &0x5566778899AABBCCu64.to_le_bytes());
+
+        let query = mixed_query(SearchMode::Ordered);
+        assert!(query.has_mixed_alignment());
+        assert!(query.validate().is_ok());
+
+        let offsets = SearchEngineManager::try_match_group_at_address(&buffer, base_addr, &query);
+        assert_eq!(offsets, Some(vec![0, 8]));
+
+        // 错位的 QWORD（0x1004）不应被匹配
+        let mut misaligned = vec![0u8; 32];
+        write(&mut misaligned, 0, &0x11223344u32.to_le_bytes());
+        write(&mut misaligned, 4, &0x5566778899AABBCCu64.to_le_bytes());
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&misaligned, base_addr, &query), None);
+    }
+
+    #[test]
+    fn test_mixed_dword_qword_unordered_group() {
+        // 窗口起点 0x1004 只满足 DWORD 对齐，QWORD 必须按绝对地址 8 对齐查找
+        let base_addr = 0x7000001004u64;
+        let mut buffer = vec![0u8; 32];
+        write(&mut buffer, 0, &0x11223344u32.to_le_bytes());
+        write(&mut buffer, 12, &0x5566778899AABBCCu64.to_le_bytes());
+
+        let query = mixed_query(SearchMode::Unordered);
+        let offsets = SearchEngineManager::try_match_group_at_address(&buffer, base_addr, &query);
+        assert_eq!(offsets, Some(vec![0, 12]));
+
+        let uniform = SearchQuery::new(
+            vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(2, ValueType::Float)],
+            SearchMode::Unordered,
+            32,
+        );
+        assert!(!uniform.has_mixed_alignment());
+    }
+
+    // anchor 过于常见时回退逐地址扫描
+
+    const ANCHOR_LEN: usize = 4096;
+
+    #[test]
+    fn test_common_anchor_falls_back_to_linear() {
+        // 全零 buffer，anchor 为 0：每个 DWORD 都是候选
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        buffer[0x100..0x104].copy_from_slice(&0x12345678u32.to_le_bytes());
+
+        let values = vec![SearchValue::fixed(0, ValueType::Dword), SearchValue::fixed(0x12345678, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 8);
+        assert!(query.anchor_candidate_limit(ANCHOR_LEN) < ANCHOR_LEN / 4);
+
+        let (linear, linear_checked) = run(&query, &buffer, true);
+        let (skipped, skipped_checked) = run(&query, &buffer, false);
+        // 走了 fallback：校验次数与直接逐地址扫描完全一致
+        assert_eq!(skipped_checked, linear_checked);
+        assert_eq!(skipped, linear);
+        assert!(skipped.contains(&0xFC));
+        assert!(skipped.contains(&0x100));
+
+        // 阈值放开后仍走 anchor 路径，结果必须一致
+        let anchored_query = query.clone().with_anchor_skip_ratio(1.0);
+        let (anchored, _) = run(&anchored_query, &buffer, false);
+        assert_eq!(anchored, linear);
+    }
+
+    #[test]
+    fn test_rare_anchor_keeps_anchor_path() {
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        buffer[0x200..0x204].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
+        buffer[0x204..0x208].copy_from_slice(&7u32.to_le_bytes());
+
+        let values = vec![SearchValue::fixed(0xDEADBEEF, ValueType::Dword), SearchValue::fixed(7, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 8);
+
+        let (linear, linear_checked) = run(&query, &buffer, true);
+        let (anchored, anchored_checked) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x200, 0x204]);
+        // 只有一个候选，远少于逐地址扫描的校验次数
+        assert_eq!(anchored_checked, 1);
+        assert!(linear_checked > anchored_checked);
+    }
+
+    #[test]
+    fn test_weak_anchor_prefers_higher_entropy_value() {
+        // 每 16 字节一个 1.0f，只有一处后面跟着 0x12345678
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        for offset in (0..ANCHOR_LEN).step_by(16) {
+            buffer[offset..offset + 4].copy_from_slice(&1.0f32.to_le_bytes());
+        }
+        buffer[0x404..0x408].copy_from_slice(&0x12345678u32.to_le_bytes());
+
+        assert!(anchor_entropy(&1.0f32.to_le_bytes()) < WEAK_ANCHOR_ENTROPY_BITS);
+        assert!(anchor_entropy(&0x12345678u32.to_le_bytes()) >= WEAK_ANCHOR_ENTROPY_BITS);
+
+        let one = SearchValue::fixed_float(1.0, ValueType::Float);
+        let values = vec![one.clone(), SearchValue::fixed(0x12345678, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Unordered, 16).with_anchor_skip_ratio(1.0);
+        assert_eq!(select_anchor(&query).unwrap().index, 1);
+
+        let (_, linear_checked) = run(&query, &buffer, true);
+        let (anchored, anchored_checked) = run(&query, &buffer, false);
+        assert_eq!(anchored, vec![0x400, 0x404]);
+        assert_eq!(anchored_checked, 1);
+        assert!(linear_checked > 100 * anchored_checked);
+
+        // 所有 Fixed 值都是弱 anchor 时仍用第一个，每个 1.0f 都是候选
+        let weak_values = vec![one, SearchValue::fixed_float(2.0, ValueType::Float)];
+        let weak_query = SearchQuery::new(weak_values, SearchMode::Unordered, 16).with_anchor_skip_ratio(1.0);
+        assert_eq!(select_anchor(&weak_query).unwrap().index, 0);
+        let (_, weak_checked) = run(&weak_query, &buffer, false);
+        assert_eq!(weak_checked, ANCHOR_LEN / 16);
+
+        // Ordered 模式只能用第一个 Fixed 值反推序列起点
+        let ordered = SearchQuery::new(query.values.clone(), SearchMode::Ordered, 16);
+        assert_eq!(select_anchor(&ordered).unwrap().index, 0);
+
+        // 带容差的浮点值无法按字节定位，不能做 anchor
+        let tolerant = SearchValue::fixed_float_with_tolerance(1.0, ValueType::Float, FloatTolerance::RelativeUlps(4));
+        let tolerant_query = SearchQuery::new(vec![tolerant], SearchMode::Unordered, 16);
+        assert!(select_anchor(&tolerant_query).is_none());
+    }
+
+    #[test]
+    fn test_oword_anchor_keeps_all_bytes() {
+        let guid: u128 = 0x6B29FC40_CA47_1067_B31D_00DD010662DA;
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        // 诱饵只有低 8 字节相同
+        buffer[0x100..0x110].copy_from_slice(&(guid ^ (1 << 127)).to_le_bytes());
+        buffer[0x110..0x114].copy_from_slice(&7u32.to_le_bytes());
+        buffer[0x400..0x410].copy_from_slice(&guid.to_le_bytes());
+        buffer[0x410..0x414].copy_from_slice(&7u32.to_le_bytes());
+
+        let values = vec![SearchValue::fixed(guid as i128, ValueType::Oword), SearchValue::fixed(7, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 32).with_anchor_skip_ratio(1.0);
+        assert_eq!(select_anchor(&query).unwrap().bytes(), &guid.to_le_bytes()[..]);
+
+        let (linear, _) = run(&query, &buffer, true);
+        let (anchored, anchored_checked) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x400, 0x410]);
+        assert_eq!(anchored_checked, 1);
+    }
+
+    #[test]
+    fn test_byte_pattern_anchor_uses_longest_fixed_run() {
+        // E8 ?? ?? ?? 48 8B 05 10：最长的非通配段在偏移 4
+        let pattern = vec![0xE8, 0, 0, 0, 0x48, 0x8B, 0x05, 0x10];
+        let mask = vec![true, false, false, false, true, true, true, true];
+        let values = vec![SearchValue::byte_pattern(pattern, mask), SearchValue::fixed(7, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 32);
+        let anchor = select_anchor(&query).unwrap();
+        assert_eq!(anchor.bytes(), &[0x48, 0x8B, 0x05, 0x10]);
+        assert_eq!(anchor.offset, 4);
+
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        let mut place = |at: usize, bytes: [u8; 8]| {
+            buffer[at..at + 8].copy_from_slice(&bytes);
+            buffer[at + 8..at + 12].copy_from_slice(&7u32.to_le_bytes());
+        };
+        place(0x200, [0xE8, 0x11, 0x22, 0x33, 0x48, 0x8B, 0x05, 0x10]);
+        // anchor 相同但首字节不同
+        place(0x300, [0xE9, 0x11, 0x22, 0x33, 0x48, 0x8B, 0x05, 0x10]);
+        place(0x400, [0xE8, 0xAA, 0xBB, 0xCC, 0x48, 0x8B, 0x05, 0x10]);
+        // anchor 位于 buffer 开头，换算出的起点在 buffer 之前
+        buffer[..4].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10]);
+
+        let (linear, _) = run(&query, &buffer, true);
+        let (anchored, _) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x200, 0x208, 0x400, 0x408]);
+    }
+
+    #[test]
+    fn test_text_anchor_uses_whole_string() {
+        // 超过 16 字节的 UTF-16 名字，诱饵只有前 16 字节相同
+        let name = "PlayerNameLong";
+        let encoded = TextEncoding::Utf16Le.encode(name);
+        let decoy = TextEncoding::Utf16Le.encode("PlayerNaXXXXXX");
+        let values = vec![SearchValue::text(name, TextEncoding::Utf16Le), SearchValue::fixed(99, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Unordered, 64);
+        let anchor = select_anchor(&query).unwrap();
+        assert_eq!(anchor.bytes(), &encoded[..]);
+        assert_eq!(anchor.alignment, 2);
+
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        buffer[0x200..0x200 + decoy.len()].copy_from_slice(&decoy);
+        buffer[0x220..0x224].copy_from_slice(&99u32.to_le_bytes());
+        buffer[0x600..0x600 + encoded.len()].copy_from_slice(&encoded);
+        buffer[0x620..0x624].copy_from_slice(&99u32.to_le_bytes());
+
+        let (linear, _) = run(&query, &buffer, true);
+        let (anchored, anchored_checked) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x600, 0x620]);
+        assert_eq!(anchored_checked, 1);
+    }
+
+    #[test]
+    fn test_range_only_query_uses_fallback() {
+        // [1000, 2000) 之后紧跟 [0, 10] 之外的值；范围不能作为 anchor
+        let values = vec![
+            SearchValue::range(1000, 2000, ValueType::Dword, false).with_bounds(true, false),
+            SearchValue::range(0, 10, ValueType::Dword, false).inverted(),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 8);
+        assert!(select_anchor(&query).is_none());
+
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        let mut place = |at: usize, first: u32, second: u32| {
+            buffer[at..at + 4].copy_from_slice(&first.to_le_bytes());
+            buffer[at + 4..at + 8].copy_from_slice(&second.to_le_bytes());
+        };
+        place(0x100, 1500, 50);
+        // 上界不包含在内
+        place(0x200, 2000, 50);
+        // 第二个值落在 [0, 10] 内
+        place(0x300, 1000, 5);
+
+        let (linear, _) = run(&query, &buffer, true);
+        let (anchored, _) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x100, 0x104]);
+    }
+
+    #[test]
+    fn test_big_endian_anchor_bytes() {
+        let values = vec![
+            SearchValue::fixed_float(1234.5, ValueType::Float),
+            SearchValue::fixed(0x0A0B0C0D, ValueType::Dword),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Unordered, 32).with_byte_order(ByteOrder::Big);
+        let anchor = select_anchor(&query).unwrap();
+        assert!(anchor.bytes() == 1234.5f32.to_be_bytes() || anchor.bytes() == 0x0A0B0C0Du32.to_be_bytes());
+
+        let mut buffer = vec![0u8; ANCHOR_LEN];
+        // 小端写入的同样数值不应匹配
+        buffer[0x100..0x104].copy_from_slice(&1234.5f32.to_le_bytes());
+        buffer[0x108..0x10C].copy_from_slice(&0x0A0B0C0Du32.to_le_bytes());
+        buffer[0x300..0x304].copy_from_slice(&1234.5f32.to_be_bytes());
+        buffer[0x308..0x30C].copy_from_slice(&0x0A0B0C0Du32.to_be_bytes());
+
+        let (linear, _) = run(&query, &buffer, true);
+        let (anchored, _) = run(&query, &buffer, false);
+        assert_eq!(anchored, linear);
+        assert_eq!(anchored, vec![0x300, 0x308]);
+    }
+
+    // 紧密排列（OrderedContiguous）组搜索
+
+    fn contiguous_query(mode: SearchMode) -> SearchQuery {
+        let values = vec![
+            SearchValue::fixed(111, ValueType::Dword),
+            SearchValue::fixed(222, ValueType::Dword),
+            SearchValue::fixed(333, ValueType::Qword),
+        ];
+        SearchQuery::new(values, mode, 64)
+    }
+
+    #[test]
+    fn test_contiguous_rejects_gapped_sequence() {
+        let base_addr = 0x7000001000u64;
+
+        let mut packed = vec![0u8; 64];
+        write(&mut packed, 0, &111u32.to_le_bytes());
+        write(&mut packed, 4, &222u32.to_le_bytes());
+        write(&mut packed, 8, &333u64.to_le_bytes());
+
+        let mut gapped = vec![0u8; 64];
+        write(&mut gapped, 0, &111u32.to_le_bytes());
+        write(&mut gapped, 8, &222u32.to_le_bytes());
+        write(&mut gapped, 16, &333u64.to_le_bytes());
+
+        let ordered = contiguous_query(SearchMode::Ordered);
+        let contiguous = contiguous_query(SearchMode::OrderedContiguous);
+        assert!(contiguous.mode.is_ordered());
+
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&packed, base_addr, &contiguous), Some(vec![0, 4, 8]));
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&gapped, base_addr, &ordered), Some(vec![0, 8, 16]));
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&gapped, base_addr, &contiguous), None);
+
+        // 窗口起点不是第一个值时也不会向后查找
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&packed[4..], base_addr + 4, &contiguous), None);
+    }
+
+    #[test]
+    fn test_contiguous_buffer_scan() {
+        let mut buffer = vec![0u8; 4096];
+        // 紧密排列 @ 0x100
+        write(&mut buffer, 0x100, &111u32.to_le_bytes());
+        write(&mut buffer, 0x104, &222u32.to_le_bytes());
+        write(&mut buffer, 0x108, &333u64.to_le_bytes());
+        // 有间隔 @ 0x800
+        write(&mut buffer, 0x800, &111u32.to_le_bytes());
+        write(&mut buffer, 0x808, &222u32.to_le_bytes());
+        write(&mut buffer, 0x810, &333u64.to_le_bytes());
+
+        let (contiguous, _) = run(&contiguous_query(SearchMode::OrderedContiguous), &buffer, false);
+        let (ordered, _) = run(&contiguous_query(SearchMode::Ordered), &buffer, false);
+        assert_eq!(contiguous, vec![0x100, 0x104, 0x108]);
+        assert_eq!(ordered, vec![0x100, 0x104, 0x108, 0x800, 0x808, 0x810]);
+    }
+
+    // 大窗口匹配中途取消
+
+    fn large_query(mode: SearchMode) -> SearchQuery {
+        let values = vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(2, ValueType::Dword)];
+        SearchQuery::new(values, mode, u16::MAX)
+    }
+
+    #[test]
+    fn test_try_match_bails_mid_window() {
+        // 64KB 全零窗口，两个值都不存在：不取消时要完整扫描两遍
+        let buffer = vec![0u8; u16::MAX as usize];
+
+        for mode in [SearchMode::Unordered, SearchMode::Ordered] {
+            let query = large_query(mode);
+            let calls = Cell::new(0usize);
+            let check = || {
+                calls.set(calls.get() + 1);
+                calls.get() >= 3
+            };
+
+            assert_eq!(try_match_group_at_address_with_cancel(&buffer, BASE_ADDR, &query, &check), None);
+            // 第 3 次检查（约 12KB 处）就返回，远早于扫完整个窗口
+            assert_eq!(calls.get(), 3, "{:?}", mode);
+        }
+
+        // 不取消时检查次数与扫描字节数成正比
+        let calls = Cell::new(0usize);
+        let never = || {
+            calls.set(calls.get() + 1);
+            false
+        };
+        let mut buffer = buffer;
+        buffer[0x100..0x104].copy_from_slice(&2u32.to_le_bytes());
+        buffer[0xF000..0xF004].copy_from_slice(&1u32.to_le_bytes());
+        let query = large_query(SearchMode::Unordered);
+        assert_eq!(try_match_group_at_address_with_cancel(&buffer, BASE_ADDR, &query, &never), Some(vec![0xF000, 0x100]));
+        assert_eq!(calls.get(), (0xF000 + 0x100) / CANCEL_CHECK_BYTES);
+    }
+
+    #[test]
+    fn test_buffer_scan_returns_promptly_on_cancel() {
+        let mut buffer = vec![0u8; 256 * 1024];
+        let tail = buffer.len() - 0x100;
+        buffer[tail..tail + 4].copy_from_slice(&1u32.to_le_bytes());
+        buffer[tail + 4..tail + 8].copy_from_slice(&2u32.to_le_bytes());
+
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE_ADDR as usize);
+        page_status.mark_all_success();
+        let end = BASE_ADDR + buffer.len() as u64;
+        let query = SearchQuery::new(
+            vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(2, ValueType::Dword)],
+            SearchMode::Unordered,
+            0x100,
+        );
+
+        let scan = |check: &dyn Fn() -> bool| {
+            let mut results: Vec<ValuePair> = Vec::new();
+            let mut checked = 0usize;
+            search_in_buffer_group_fallback_with_cancel(&buffer, BASE_ADDR, BASE_ADDR, end, 4, &query, &page_status, &mut results, &mut checked, &check);
+            let mut addrs: Vec<u64> = results.iter().map(|r| r.addr).collect();
+            addrs.sort_unstable();
+            addrs.dedup();
+            (addrs, checked)
+        };
+
+        let (found, full_checked) = scan(&|| false);
+        assert_eq!(found, vec![BASE_ADDR + tail as u64, BASE_ADDR + tail as u64 + 4]);
+
+        // 第一次检查（4KB 处）即返回
+        let (found, checked) = scan(&|| true);
+        assert!(found.is_empty());
+        assert!(checked <= CANCEL_CHECK_BYTES / 4, "checked {} of {}", checked, full_checked);
+    }
+
+    // 组内某个字段要求不等于给定值
+
+    #[test]
+    fn test_group_field_must_not_equal() {
+        let values = vec![
+            SearchValue::fixed(111, ValueType::Dword),
+            SearchValue::not_equal(0, ValueType::Dword),
+            SearchValue::fixed(333, ValueType::Dword),
+        ];
+        let query = SearchQuery::new(values, SearchMode::OrderedContiguous, 16);
+
+        // 不等于条件从不作为 anchor；111 的字节熵太低，改用 333
+        assert_eq!(select_anchor(&query).map(|anchor| anchor.index), Some(2));
+
+        let mut buffer = vec![0u8; 4096];
+        // 中间字段非零 @ 0x100
+        write(&mut buffer, 0x100, &111u32.to_le_bytes());
+        write(&mut buffer, 0x104, &5u32.to_le_bytes());
+        write(&mut buffer, 0x108, &333u32.to_le_bytes());
+        // 中间字段为零 @ 0x800
+        write(&mut buffer, 0x800, &111u32.to_le_bytes());
+        write(&mut buffer, 0x808, &333u32.to_le_bytes());
+        // 中间字段为 -1，同样不等于 0 @ 0xC00
+        write(&mut buffer, 0xC00, &111u32.to_le_bytes());
+        write(&mut buffer, 0xC04, &u32::MAX.to_le_bytes());
+        write(&mut buffer, 0xC08, &333u32.to_le_bytes());
+
+        assert_eq!(
+            SearchEngineManager::try_match_group_at_address(&buffer[0x100..], BASE_ADDR + 0x100, &query),
+            Some(vec![0, 4, 8])
+        );
+        assert_eq!(
+            SearchEngineManager::try_match_group_at_address(&buffer[0x800..], BASE_ADDR + 0x800, &query),
+            None
+        );
+
+        assert_eq!(run(&query, &buffer, false).0, vec![0x100, 0x104, 0x108, 0xC00, 0xC04, 0xC08]);
+    }
+
+    #[test]
+    fn test_not_equal_matches_truncated_width() {
+        let value = SearchValue::not_equal(-1, ValueType::Word);
+        assert!(!value.matched(&0xFFFFu16.to_le_bytes()).unwrap());
+        assert!(value.matched(&0xFFFEu16.to_le_bytes()).unwrap());
+        assert!(value.matched(&[0u8; 1]).is_err());
+    }
+
+    // 组内值之间的关系约束
+
+    fn record(marker: u32, field0: u32, field1: u32) -> Vec<u8> {
+        [marker, field0, field1].iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// 标记值之后紧跟两个未知字段，约束 `field0 op field1`
+    fn constraint_query(op: RelOp) -> SearchQuery {
+        SearchQuery::new(
+            vec![
+                SearchValue::fixed(0x1220, ValueType::Dword),
+                SearchValue::any(ValueType::Dword),
+                SearchValue::any(ValueType::Dword),
+            ],
+            SearchMode::OrderedContiguous,
+            16,
+        )
+        .with_constraint(1, op, 2)
+    }
+
+    #[test]
+    fn test_field0_less_than_field1() {
+        let base_addr = 0x7000000000u64;
+        let less = constraint_query(RelOp::Lt);
+        assert!(less.validate().is_ok());
+
+        let ascending = record(0x1220, 3, 9);
+        let descending = record(0x1220, 9, 3);
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&ascending, base_addr, &less), Some(vec![0, 4, 8]));
+        assert_eq!(SearchEngineManager::try_match_group_at_address(&descending, base_addr, &less), None);
+
+        // 有符号比较：-1 < 1
+        let signed = record(0x1220, u32::MAX, 1);
+        assert!(SearchEngineManager::try_match_group_at_address(&signed, base_addr, &less).is_some());
+
+        // 两个未知但相等的字段
+        let equal = constraint_query(RelOp::Eq);
+        assert!(SearchEngineManager::try_match_group_at_address(&record(0x1220, 5, 5), base_addr, &equal).is_some());
+        assert!(SearchEngineManager::try_match_group_at_address(&ascending, base_addr, &equal).is_none());
+    }
+
+    #[test]
+    fn test_deep_search_checks_constraints() {
+        let base_addr = 0x7000000000u64;
+        let mut buffer = vec![0u8; 0x1000];
+        buffer[0x100..0x10C].copy_from_slice(&record(0x1220, 3, 9));
+        buffer[0x200..0x20C].copy_from_slice(&record(0x1220, 9, 3));
+        let mut page_status = PageStatusBitmap::new(buffer.len(), base_addr as usize);
+        page_status.mark_all_success();
+
+        let mut less = constraint_query(RelOp::Lt);
+        less.mode = SearchMode::Ordered;
+        less.range = 12;
+        let region_end = base_addr + buffer.len() as u64;
+        let expected = vec![base_addr + 0x100, base_addr + 0x104, base_addr + 0x108];
+
+        let mut results = BPlusTreeSet::new(32);
+        let mut checked = 0;
+        search_in_buffer_group_deep(&buffer, base_addr, base_addr, region_end, 4, &less, &page_status, &mut results, &mut checked);
+        assert_eq!(results.iter().map(|pair| pair.addr).collect::<Vec<_>>(), expected);
+
+        let mut results = Vec::new();
+        let mut checked = 0;
+        search_in_buffer_group_deep_with_cancel(
+            &buffer,
+            base_addr,
+            base_addr,
+            region_end,
+            4,
+            &less,
+            &page_status,
+            &mut results,
+            &mut checked,
+            &|| false,
+        );
+        let mut addrs: Vec<u64> = results.iter().map(|pair| pair.addr).collect();
+        addrs.sort_unstable();
+        addrs.dedup();
+        assert_eq!(addrs, expected);
+    }
+
+    #[test]
+    fn test_constraint_indices_validated() {
+        let values = vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::any(ValueType::Dword)];
+        let query = SearchQuery::new(values.clone(), SearchMode::Ordered, 16);
+        assert!(query.clone().with_constraint(0, RelOp::Gt, 2).validate().is_err());
+        assert!(query.clone().with_constraint(1, RelOp::Gt, 1).validate().is_err());
+        assert!(query.with_constraint(0, RelOp::Ge, 1).validate().is_ok());
+    }
+
+    // 有序模式下起点被已有匹配覆盖的 anchor 候选不再重复校验
+
+    #[test]
+    fn test_repeating_anchor_validated_once() {
+        let values = vec![
+            SearchValue::range(1, 5, ValueType::Dword, false),
+            SearchValue::fixed(7, ValueType::Dword),
+            SearchValue::fixed(9, ValueType::Dword),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 32);
+        assert_eq!(select_anchor(&query).map(|anchor| anchor.index), Some(1));
+
+        // 重复的 anchor 7 @ 0x100..0x110，真正的序列 3, 7, 9 @ 0x110
+        let mut buffer = vec![0u8; 4096];
+        for (i, value) in [7u32, 7, 7, 7, 3, 7, 9].iter().enumerate() {
+            buffer[0x100 + i * 4..0x104 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let (anchored, anchored_checked) = run(&query, &buffer, false);
+        let (linear, _) = run(&query, &buffer, true);
+        assert_eq!(anchored, vec![0x110, 0x114, 0x118]);
+        assert_eq!(anchored, linear);
+        // 5 个 anchor 候选，第一个校验的起点 0xFC 已匹配到 0x110 的序列，其余起点都不超过 0x110
+        assert_eq!(anchored_checked, 1);
+    }
+
+    // 按成功页占比选择逐地址扫描策略
+
+    fn run_with_strategy(query: &SearchQuery, buffer: &[u8], page_status: &PageStatusBitmap, strategy: PageScanStrategy) -> Vec<u64> {
+        let mut results: Vec<ValuePair> = Vec::new();
+        let mut checked = 0usize;
+        let end = BASE_ADDR + buffer.len() as u64;
+        search_in_buffer_group_fallback_with_strategy(
+            buffer,
+            BASE_ADDR,
+            BASE_ADDR,
+            end,
+            4,
+            query,
+            page_status,
+            &mut results,
+            &mut checked,
+            &|| false,
+            strategy,
+        );
+        let mut addrs: Vec<u64> = results.iter().map(|r| r.addr - BASE_ADDR).collect();
+        addrs.sort_unstable();
+        addrs
+    }
+
+    #[test]
+    fn test_coverage_ratio_and_strategy_switch() {
+        let page = *PAGE_SIZE;
+        let num_pages = 64usize;
+        let mut buffer = vec![0u8; num_pages * page];
+        // 每页中间放一组 11, 22，失败页里的也写入，用于确认失败页被跳过
+        for i in 0..num_pages {
+            let offset = i * page + 0x100;
+            buffer[offset..offset + 4].copy_from_slice(&11u32.to_le_bytes());
+            buffer[offset + 4..offset + 8].copy_from_slice(&22u32.to_le_bytes());
+        }
+        let values = vec![
+            SearchValue::range(10, 12, ValueType::Dword, false),
+            SearchValue::range(21, 23, ValueType::Dword, false),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 8);
+
+        let status = |success: &dyn Fn(usize) -> bool| {
+            let mut page_status = PageStatusBitmap::new(buffer.len(), BASE_ADDR as usize);
+            (0..num_pages).filter(|&i| success(i)).for_each(|i| page_status.mark_success(i));
+            page_status
+        };
+
+        let all = status(&|_| true);
+        let one_fault = status(&|i| i != 10);
+        let sparse = status(&|i| i % 16 == 0);
+        let none = status(&|_| false);
+
+        assert_eq!(all.num_pages(), num_pages);
+        assert_eq!(all.coverage_ratio(), 1.0);
+        assert_eq!(one_fault.coverage_ratio(), 63.0 / 64.0);
+        assert_eq!(sparse.coverage_ratio(), 4.0 / 64.0);
+        assert_eq!(none.coverage_ratio(), 0.0);
+
+        assert_eq!(PageScanStrategy::for_status(&all), PageScanStrategy::Linear);
+        assert_eq!(PageScanStrategy::for_status(&one_fault), PageScanStrategy::Linear);
+        assert_eq!(PageScanStrategy::for_status(&sparse), PageScanStrategy::SuccessRanges);
+        assert_eq!(PageScanStrategy::for_status(&none), PageScanStrategy::SuccessRanges);
+
+        for (page_status, expected_pages) in [(&all, num_pages), (&one_fault, num_pages - 1), (&sparse, 4), (&none, 0)] {
+            let linear = run_with_strategy(&query, &buffer, page_status, PageScanStrategy::Linear);
+            let ranges = run_with_strategy(&query, &buffer, page_status, PageScanStrategy::SuccessRanges);
+            assert_eq!(linear, ranges);
+            assert_eq!(linear.len(), expected_pages * 2);
+        }
+        assert!(!run_with_strategy(&query, &buffer, &one_fault, PageScanStrategy::Linear).contains(&(10 * page as u64 + 0x100)));
+    }
+
+    #[test]
+    fn test_coverage_ratio_counts_real_pages() {
+        // 10 页只占位图一个字的一部分，多出来的填充位不计入页数和覆盖率
+        let page = *PAGE_SIZE;
+        let mut one_fault = PageStatusBitmap::new(10 * page, BASE_ADDR as usize);
+        (0..10).filter(|&i| i != 3).for_each(|i| one_fault.mark_success(i));
+        assert_eq!(one_fault.num_pages(), 10);
+        assert_eq!(one_fault.coverage_ratio(), 0.9);
+        assert_eq!(one_fault.failed_pages(), vec![3]);
+
+        let mut all = PageStatusBitmap::new(10 * page, BASE_ADDR as usize);
+        all.mark_all_success();
+        all.mark_success(10);
+        assert!(!all.is_page_success(10));
+        assert_eq!((all.success_count(), all.failure_count()), (10, 0));
+        assert_eq!(all.coverage_ratio(), 1.0);
+        assert_eq!(all.get_success_page_ranges(), vec![(0, 10)]);
+
+        // 起始地址不对齐时多跨一页
+        let unaligned = PageStatusBitmap::new(10 * page, BASE_ADDR as usize + 0x10);
+        assert_eq!(unaligned.num_pages(), 11);
+        assert_eq!(PageScanStrategy::for_status(&one_fault), PageScanStrategy::Linear);
+    }
+
+    // 对象对齐网格：只检查按对象对齐的第一个值地址
+
+    #[test]
+    fn test_only_object_aligned_bases_checked() {
+        // 16 字节对齐的对象 @ 0x100, 0x200，错位的重复数据 @ 0x308, 0x418
+        let mut buffer = vec![0u8; 4096];
+        for base in [0x100usize, 0x200, 0x308, 0x418] {
+            buffer[base..base + 4].copy_from_slice(&111u32.to_le_bytes());
+            buffer[base + 4..base + 8].copy_from_slice(&222u32.to_le_bytes());
+        }
+
+        for mode in [SearchMode::Ordered, SearchMode::OrderedContiguous] {
+            let values = vec![SearchValue::fixed(111, ValueType::Dword), SearchValue::fixed(222, ValueType::Dword)];
+            let query = SearchQuery::new(values, mode, 8);
+            let aligned = query.clone().with_object_alignment(16, 0);
+            let shifted = query.clone().with_object_alignment(16, 8);
+
+            for fallback in [false, true] {
+                let (all, all_checked) = run(&query, &buffer, fallback);
+                let (grid, grid_checked) = run(&aligned, &buffer, fallback);
+                assert_eq!(all, vec![0x100, 0x104, 0x200, 0x204, 0x308, 0x30C, 0x418, 0x41C]);
+                assert_eq!(grid, vec![0x100, 0x104, 0x200, 0x204], "{:?} fallback={}", mode, fallback);
+                assert_eq!(run(&shifted, &buffer, fallback).0, vec![0x308, 0x30C, 0x418, 0x41C]);
+
+                // 逐地址扫描只校验网格上的 1/4 起点；紧密排列的 anchor 路径跳过错位候选
+                if fallback {
+                    assert!(grid_checked * 3 < all_checked, "{} vs {}", grid_checked, all_checked);
+                } else if mode == SearchMode::OrderedContiguous {
+                    assert_eq!((grid_checked, all_checked), (2, 4));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_search_honours_object_grid() {
+        // 对象 @ 0x100（16 字节对齐），错位的同一组值 @ 0x308
+        let mut buffer = vec![0u8; 4096];
+        for base in [0x100usize, 0x308] {
+            buffer[base..base + 4].copy_from_slice(&111u32.to_le_bytes());
+            buffer[base + 4..base + 8].copy_from_slice(&222u32.to_le_bytes());
+        }
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE_ADDR as usize);
+        page_status.mark_all_success();
+        let end = BASE_ADDR + buffer.len() as u64;
+
+        for mode in [SearchMode::Ordered, SearchMode::Unordered] {
+            let values = vec![SearchValue::fixed(111, ValueType::Dword), SearchValue::fixed(222, ValueType::Dword)];
+            let query = SearchQuery::new(values, mode, 8).with_object_alignment(16, 0);
+
+            let mut checked = 0usize;
+            let mut tree = BPlusTreeSet::new(BPLUS_TREE_ORDER);
+            search_in_buffer_group_deep(&buffer, BASE_ADDR, BASE_ADDR, end, 4, &query, &page_status, &mut tree, &mut checked);
+            let deep: Vec<u64> = tree.iter().map(|r| r.addr - BASE_ADDR).collect();
+            assert_eq!(deep, vec![0x100, 0x104], "{:?}", mode);
+
+            let mut found = Vec::new();
+            search_in_buffer_group_deep_with_cancel(&buffer, BASE_ADDR, BASE_ADDR, end, 4, &query, &page_status, &mut found, &mut checked, &|| false);
+            let mut cancellable: Vec<u64> = found.iter().map(|r| r.addr - BASE_ADDR).collect();
+            cancellable.sort_unstable();
+            cancellable.dedup();
+            assert_eq!(cancellable, vec![0x100, 0x104], "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_on_object_grid() {
+        let query = SearchQuery::new(vec![SearchValue::fixed(1, ValueType::Dword)], SearchMode::Ordered, 0);
+        assert!(query.on_object_grid(0x1004));
+
+        let query = query.with_object_alignment(16, 4);
+        assert!(query.on_object_grid(0x1004));
+        assert!(query.on_object_grid(0x1014));
+        assert!(!query.on_object_grid(0x1000));
+        assert!(!query.on_object_grid(0x100C));
+        // 偏移超过对齐时按模运算处理
+        let query = query.with_object_alignment(12, 20);
+        assert!(query.on_object_grid(8));
+        assert!(query.on_object_grid(0x1004));
+        assert!(!query.on_object_grid(0x1000));
+    }
+}
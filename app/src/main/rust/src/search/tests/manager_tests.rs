@@ -0,0 +1,2226 @@
+//! SearchEngineManager API tests
+
+#[cfg(test)]
+mod tests {
+    use crate::core::freeze_manager::FreezeManager;
+    use crate::core::globals::bump_memory_generation;
+    use crate::core::{MemoryAccessMode, MemorySource, RamSource};
+    use crate::pointer_scan::VmStaticData;
+    use crate::search::engine::group_search::{find_first_n_group, group_chunk_size, search_region_group, search_region_group_deep, search_region_group_with};
+    use crate::search::engine::manager::{counter_condition, find_first_n_in, sanitize_regions, ScanProgress};
+    use crate::search::engine::single_search::{find_first_n_single, find_periodic_single, search_region_single, search_region_single_with};
+    use crate::search::engine::{ExportFormat, ScanSample, ScanStats, SearchProgressCallback, PROGRESS_REPORT_INTERVAL};
+    use crate::search::parser::parse_address;
+    use crate::search::result_manager::packed::{decode_packed, PackedRecord, PACKED_HEADER_SIZE};
+    use crate::search::result_manager::persistent::{IndexRecord, INDEX_DATA_OFFSET, SUPERBLOCK_SIZE};
+    use crate::search::result_manager::{ExactSearchResultItem, FuzzySearchResultItem, ResultStoreKind, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::test_manager::TestManager;
+    use crate::search::{DeltaOp, SearchEngineManager, SearchMode, SearchQuery, SearchResultItem, SearchValue, TextEncoding, ValuePair, ValueType, PAGE_SIZE};
+    use crate::wuwa::PageStatusBitmap;
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, RwLock};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_matches_at_after_write() {
+        println!("\n=== matches_at test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x4000).unwrap();
+        let addr = base_addr + 0x100;
+        mem.mem_write_u32(addr, 12345).unwrap();
+
+        let value = SearchValue::fixed(12345, ValueType::Dword);
+        let read = |mem: &MockMemory| {
+            SearchEngineManager::matches_at_with(addr, &value, mem.reader())
+        };
+
+        assert!(read(&mem).unwrap(), "Value should match before write");
+
+        mem.mem_write_u32(addr, 54321).unwrap();
+        assert!(!read(&mem).unwrap(), "Value should no longer match after write");
+
+        // 不可读的页视为不匹配
+        mem.mem_write_u32(addr, 12345).unwrap();
+        mem.set_faulty_pages(base_addr, &[0]).unwrap();
+        assert!(!read(&mem).unwrap(), "Faulty page should not match");
+
+        // 未映射的地址同样返回 false
+        let unmapped = SearchEngineManager::matches_at_with(0x1000, &value, mem.reader());
+        assert!(!unmapped.unwrap());
+
+        println!("matches_at test passed!");
+    }
+
+    #[test]
+    fn test_estimate_result_count_with_fixed_seed() {
+        println!("\n=== estimate_result_count seed test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 4 * 1024 * 1024).unwrap();
+        for i in 0..4096u64 {
+            mem.mem_write_u32(base_addr + i * 1024, 777).unwrap();
+        }
+
+        let value = SearchValue::fixed(777, ValueType::Dword);
+        let regions = [(base_addr, base_addr + 4 * 1024 * 1024)];
+        let read = mem.reader();
+
+        let mut manager = SearchEngineManager::new();
+        manager.set_sampling_seed(0x1177);
+        let first = manager.estimate_result_count_with(&value, &regions, 64, read).unwrap();
+        let second = manager.estimate_result_count_with(&value, &regions, 64, read).unwrap();
+
+        println!("Estimates: {} / {}", first, second);
+        assert_eq!(first, second, "Same seed should give identical estimates");
+        assert_eq!(first, 4096, "Uniform data should be estimated exactly");
+
+        // 空区域和无效区域不参与估算
+        let empty = manager.estimate_result_count_with(&value, &[(0x1000, 0x1000)], 64, read).unwrap();
+        assert_eq!(empty, 0);
+    }
+
+    #[test]
+    fn test_sample_scan_reads_region_prefix() {
+        let mut mem = MockMemory::new();
+        let first = mem.malloc(0x7000000000, 0x10000).unwrap();
+        let second = mem.malloc(0x7100000000, 0x800).unwrap();
+        mem.mem_write_u32(first + 0x100, 4321).unwrap();
+        mem.mem_write_u32(first + 0x8000, 4321).unwrap();
+        mem.mem_write_u32(second + 0x7FC, 4321).unwrap();
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(4321, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions = [(first, first + 0x10000), (second, second + 0x800)];
+        let manager = SearchEngineManager::new();
+
+        // 只扫描每个区域开头 0x1000 字节：第一个区域后半段的值找不到，较小的区域整个被扫描
+        let report = manager.sample_scan_with(&query, &regions, 0x1000, mem.reader()).unwrap();
+        let found: Vec<u64> = report.results.iter().map(SearchResultItem::address).collect();
+        assert_eq!(found, vec![first + 0x100, second + 0x7FC]);
+        assert!(report.partial);
+        assert_eq!((report.sampled_bytes, report.total_bytes), (0x1800, 0x10800));
+
+        let report = manager.sample_scan_with(&query, &regions, 0x10000, mem.reader()).unwrap();
+        assert_eq!(report.results.len(), 3);
+        assert!(!report.partial);
+    }
+
+    #[test]
+    fn test_find_first_n_stops_early() {
+        println!("\n=== find_first_n test ===\n");
+
+        let mut mem = MockMemory::new();
+        let region_a = mem.malloc(0x7000000000, 1024 * 1024).unwrap();
+        let region_b = mem.malloc(0x7100000000, 1024 * 1024).unwrap();
+        for i in 0..10u64 {
+            mem.mem_write_u32(region_a + 0x1000 + i * 0x100, 4242).unwrap();
+            mem.mem_write_u32(region_b + i * 0x100, 4242).unwrap();
+        }
+
+        let value = SearchValue::fixed(4242, ValueType::Dword);
+        let regions = [(region_a, region_a + 1024 * 1024), (region_b, region_b + 1024 * 1024)];
+        let chunk_size = 64 * 1024;
+
+        let reads = Cell::new(0usize);
+        let inner = mem.reader();
+        let read = |a, buf: &mut [u8], status: &mut _| {
+            reads.set(reads.get() + 1);
+            inner(a, buf, status)
+        };
+
+        let first = find_first_n_single(&value, &regions, chunk_size, 3, read).unwrap();
+        let addrs: Vec<u64> = first.iter().map(|p| p.addr).collect();
+        assert_eq!(addrs, vec![region_a + 0x1000, region_a + 0x1100, region_a + 0x1200]);
+        assert_eq!(reads.get(), 1, "Should stop after the first chunk");
+
+        // 总数不足 n 时返回全部
+        reads.set(0);
+        let all = find_first_n_single(&value, &regions, chunk_size, 100, read).unwrap();
+        assert_eq!(all.len(), 20);
+        assert_eq!(reads.get(), 2 * (1024 * 1024 / chunk_size));
+
+        assert!(find_first_n_single(&value, &regions, chunk_size, 0, read).unwrap().is_empty());
+    }
+
+    /// 对象 @ 0x100、0x200（16 字节对齐），错位的同值 @ 0x108、0x20C
+    fn object_grid_memory() -> (MockMemory, u64, SearchQuery) {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x2000).unwrap();
+        for offset in [0x100u64, 0x108, 0x200, 0x20C] {
+            mem.mem_write_u32(base_addr + offset, 0x1227).unwrap();
+        }
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1227, ValueType::Dword)], SearchMode::Unordered, 0).with_object_alignment(16, 0);
+        (mem, base_addr, query)
+    }
+
+    #[test]
+    fn test_find_first_n_honours_object_grid() {
+        let (mem, base_addr, query) = object_grid_memory();
+        let regions = [(base_addr, base_addr + 0x2000)];
+
+        // n 按网格过滤后的结果计数，错位的 0x108 不占名额
+        let first = find_first_n_in(&query, &regions, 0x1000, 2, mem.reader()).unwrap();
+        let addrs: Vec<u64> = first.iter().map(|p| p.addr - base_addr).collect();
+        assert_eq!(addrs, vec![0x100, 0x200]);
+    }
+
+    #[test]
+    fn test_sample_scan_honours_object_grid() {
+        let (mem, base_addr, query) = object_grid_memory();
+        let manager = SearchEngineManager::new();
+
+        let regions = [(base_addr, base_addr + 0x2000)];
+        let report = manager.sample_scan_with(&query, &regions, 0x1000, mem.reader()).unwrap();
+        let found: Vec<u64> = report.results.iter().map(|item| item.address() - base_addr).collect();
+        assert_eq!(found, vec![0x100, 0x200]);
+    }
+
+    #[test]
+    fn test_scan_collect_honours_object_grid() {
+        let (mem, base_addr, query) = object_grid_memory();
+        let manager = SearchEngineManager::new();
+
+        let regions = [(base_addr, base_addr + 0x2000)];
+        let collected = manager.scan_collect_with(&query, &regions, mem.reader()).unwrap();
+        let found: Vec<u64> = collected.iter().map(|item| item.address() - base_addr).collect();
+        assert_eq!(found, vec![0x100, 0x200]);
+    }
+
+    #[test]
+    fn test_alignment_fallback_honours_object_grid() {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x1000).unwrap();
+        // 字段在对象内偏移 2：只有 0x302 在网格上，0x311 是错位的同值
+        mem.mem_write_u32(base_addr + 0x302, 0x1227).unwrap();
+        mem.mem_write_u32(base_addr + 0x311, 0x1227).unwrap();
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1227, ValueType::Dword)], SearchMode::Unordered, 0).with_object_alignment(16, 2);
+        let manager = SearchEngineManager::new();
+        let regions = [(base_addr, base_addr + 0x1000)];
+        let scan = manager.scan_with_alignment_fallback_with(&query, &regions, mem.reader()).unwrap();
+        assert!(scan.used_fallback);
+        let found: Vec<u64> = scan.results.iter().map(|item| item.address() - base_addr).collect();
+        assert_eq!(found, vec![0x302]);
+    }
+
+    #[test]
+    fn test_streaming_honours_object_grid() {
+        let (mem, base_addr, query) = object_grid_memory();
+        let manager = SearchEngineManager::new();
+        let regions = [(base_addr, base_addr + 0x1000), (base_addr + 0x1000, base_addr + 0x2000)];
+        let search = |start: u64, end: u64| find_first_n_in(&query, &[(start, end)], 0x1000, usize::MAX, mem.reader());
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(4);
+        let sent = manager.search_streaming_with(&query, &regions, tx, search).unwrap();
+        let mut streamed: Vec<u64> = rx.iter().flat_map(|batch| batch.results).map(|item| item.address() - base_addr).collect();
+        streamed.sort_unstable();
+        assert_eq!(sent, 2);
+        assert_eq!(streamed, vec![0x100, 0x200]);
+    }
+
+    #[test]
+    fn test_find_first_n_group_stops_early() {
+        println!("\n=== find_first_n group test ===\n");
+
+        let mut mem = MockMemory::new();
+        let region_a = mem.malloc(0x7000000000, 1024 * 1024).unwrap();
+        let region_b = mem.malloc(0x7100000000, 1024 * 1024).unwrap();
+        for i in 0..10u64 {
+            mem.mem_write_u32(region_a + 0x1000 + i * 0x100, 11).unwrap();
+            mem.mem_write_u32(region_a + 0x1004 + i * 0x100, 22).unwrap();
+            mem.mem_write_u32(region_b + i * 0x100, 11).unwrap();
+            mem.mem_write_u32(region_b + 4 + i * 0x100, 22).unwrap();
+        }
+
+        let query = SearchQuery::new(
+            vec![SearchValue::fixed(11, ValueType::Dword), SearchValue::fixed(22, ValueType::Dword)],
+            SearchMode::Ordered,
+            16,
+        );
+        let regions = [(region_a, region_a + 1024 * 1024), (region_b, region_b + 1024 * 1024)];
+        let chunk_size = 64 * 1024;
+
+        let reads = Cell::new(0usize);
+        let inner = mem.reader();
+        let read = |a, buf: &mut [u8], status: &mut _| {
+            reads.set(reads.get() + 1);
+            inner(a, buf, status)
+        };
+
+        let first = find_first_n_group(&query, &regions, chunk_size, 4, read).unwrap();
+        let addrs: Vec<u64> = first.iter().map(|p| p.addr).collect();
+        assert_eq!(addrs, vec![region_a + 0x1000, region_a + 0x1004, region_a + 0x1100, region_a + 0x1104]);
+        assert_eq!(reads.get(), 1, "Should stop after the first chunk");
+
+        // 总数不足 n 时扫描全部区域
+        reads.set(0);
+        let all = find_first_n_group(&query, &regions, chunk_size, 1000, read).unwrap();
+        assert_eq!(all.len(), 40);
+        assert_eq!(reads.get(), 2 * (1024 * 1024 / chunk_size));
+    }
+
+    #[test]
+    fn test_group_range_larger_than_chunk() {
+        println!("\n=== group range > chunk size test ===\n");
+
+        let page_size = *PAGE_SIZE as u64;
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 8 * page_size as usize).unwrap();
+
+        // 两个值相隔超过一个块，只有保留足够上下文才能配对
+        let first = base_addr + page_size - 0x10;
+        let second = base_addr + 2 * page_size + page_size / 2;
+        mem.mem_write_u32(first, 11).unwrap();
+        mem.mem_write_u32(second, 22).unwrap();
+
+        let range = 2 * page_size as u16;
+        let query = SearchQuery::new(
+            vec![SearchValue::fixed(11, ValueType::Dword), SearchValue::fixed(22, ValueType::Dword)],
+            SearchMode::Ordered,
+            range,
+        );
+        let chunk_size = page_size as usize;
+        assert_eq!(group_chunk_size(chunk_size, range as usize), 2 * chunk_size);
+
+        let regions = [(base_addr, base_addr + 8 * page_size)];
+        let found = find_first_n_group(&query, &regions, chunk_size, usize::MAX, mem.reader()).unwrap();
+        let addrs: Vec<u64> = found.iter().map(|p| p.addr).collect();
+        assert_eq!(addrs, vec![first, second]);
+    }
+
+    #[test]
+    fn test_zero_length_and_inverted_regions_are_skipped() {
+        println!("\n=== Invalid region test ===\n");
+
+        let regions = vec![(0x1000, 0x1000), (0x2000, 0x1000)];
+        assert!(sanitize_regions(regions.clone()).is_empty());
+
+        // 区域函数直接返回空结果，不会 panic
+        let value = SearchValue::fixed(1, ValueType::Dword);
+        let query = SearchQuery::new(
+            vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(2, ValueType::Dword)],
+            SearchMode::Unordered,
+            64,
+        );
+        for &(start, end) in &regions {
+            assert!(search_region_single(&value, start, end, 64 * 1024).unwrap().is_empty());
+            assert!(search_region_group(&query, start, end, 64 * 1024).unwrap().is_empty());
+            assert!(search_region_group_deep(&query, start, end, 64 * 1024).unwrap().is_empty());
+        }
+
+        // 无效区域不影响有效区域的结果
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x10000).unwrap();
+        mem.mem_write_u32(base_addr + 0x40, 1).unwrap();
+        mem.mem_write_u32(base_addr + 0x8000, 1).unwrap();
+
+        let valid = (base_addr, base_addr + 0x10000);
+        let mixed = sanitize_regions(vec![(0x1000, 0x1000), valid, (0x2000, 0x1000)]);
+        assert_eq!(mixed, vec![valid]);
+
+        let read = mem.reader();
+        let expected = find_first_n_single(&value, &[valid], 0x4000, usize::MAX, read).unwrap();
+        let actual = find_first_n_single(&value, &[(base_addr + 0x100, base_addr + 0x100), valid, (base_addr + 0x9000, base_addr)], 0x4000, usize::MAX, read)
+            .unwrap();
+        assert_eq!(expected.len(), 2);
+        assert_eq!(actual, expected);
+    }
+
+    fn result_addresses(manager: &SearchEngineManager) -> Vec<u64> {
+        manager
+            .get_results(0, usize::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Exact(exact) => exact.address,
+                SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_result_stores_produce_identical_results() {
+        println!("\n=== Result store comparison test ===\n");
+
+        let mut mem = MockMemory::new();
+        let region_a = mem.malloc(0x7000000000, 512 * 1024).unwrap();
+        let region_b = mem.malloc(0x7100000000, 512 * 1024).unwrap();
+        for i in 0..250u64 {
+            mem.mem_write_u32(region_a + i * 0x800 + (i % 4) * 4, 31337).unwrap();
+            mem.mem_write_u32(region_b + i * 0x800 + (i % 4) * 4, 31337).unwrap();
+        }
+
+        // 区域按地址倒序传入，扫描结果整体不是按地址有序的
+        let value = SearchValue::fixed(31337, ValueType::Dword);
+        let regions = [(region_b, region_b + 512 * 1024), (region_a, region_a + 512 * 1024)];
+        let pairs = find_first_n_single(&value, &regions, 64 * 1024, usize::MAX, mem.reader()).unwrap();
+        assert_eq!(pairs.len(), 500);
+        assert!(pairs[0].addr > pairs[499].addr);
+
+        let mut outputs = Vec::new();
+        for store in [ResultStoreKind::Disk, ResultStoreKind::SortedVec, ResultStoreKind::Persistent] {
+            // 很小的内存缓冲，让 Disk 存储溢出到 mmap 文件
+            let mut manager = TestManager::with_store("store", 9 * 64, 0, store);
+            assert_eq!(manager.get_store_kind().unwrap(), store);
+
+            manager.add_results_batch(pairs.iter().map(SearchResultItem::from).collect()).unwrap();
+            let after_scan = (manager.get_total_count().unwrap(), result_addresses(&manager));
+
+            manager.remove_results_batch(vec![0, 10, 100, 499]).unwrap();
+            manager.remove_result(3).unwrap();
+            let after_remove = (manager.get_total_count().unwrap(), result_addresses(&manager));
+
+            manager.keep_only_results((0..300).step_by(3).collect()).unwrap();
+            let after_keep = (manager.get_total_count().unwrap(), result_addresses(&manager));
+            let page = manager.get_results(40, 20).unwrap().into_iter().map(|item| item.address()).collect::<Vec<_>>();
+
+            // 模糊结果走同一种后端
+            manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+            let fuzzy = pairs
+                .iter()
+                .map(|p| SearchResultItem::Fuzzy(FuzzySearchResultItem::from_bytes(p.addr, &31337u32.to_le_bytes(), ValueType::Dword)))
+                .collect();
+            manager.add_results_batch(fuzzy).unwrap();
+            manager.remove_results_batch(vec![1, 2, 498]).unwrap();
+            let fuzzy_after_remove = (manager.get_total_count().unwrap(), result_addresses(&manager));
+
+            println!("{:?}: {} -> {} -> {}", store, after_scan.0, after_remove.0, after_keep.0);
+            outputs.push((after_scan, after_remove, after_keep, page, fuzzy_after_remove));
+        }
+
+        // 所有后端都保留写入顺序
+        let pair_addrs: Vec<u64> = pairs.iter().map(|p| p.addr).collect();
+        assert_eq!(outputs[0].0, (500, pair_addrs));
+        assert_eq!(outputs[0].1.0, 495);
+        assert_eq!(outputs[0].2.0, 100);
+        assert_eq!(outputs[0].3.len(), 20);
+        assert_eq!(outputs[0].4.0, 497);
+        assert_eq!(outputs[0], outputs[1]);
+        assert_eq!(outputs[0], outputs[2]);
+    }
+
+    #[test]
+    fn test_intersect_results_keeps_common_addresses() {
+        println!("\n=== intersect_with test ===\n");
+
+        for store in [ResultStoreKind::Disk, ResultStoreKind::SortedVec] {
+            let mut hp_scan = TestManager::with_store("intersect_a", 9 * 16, 0, store);
+            let mut ammo_scan = TestManager::with_store("intersect_b", 9 * 16, 0, store);
+
+            // a: 每 8 字节一个地址，b: 每 12 字节一个地址，公共部分为每 24 字节
+            let a = (0..300u64).map(|i| SearchResultItem::new_exact(0x7000000000 + i * 8, ValueType::Dword)).collect();
+            let b = (0..200u64).map(|i| SearchResultItem::new_exact(0x7000000000 + i * 12, ValueType::Float)).collect();
+            hp_scan.add_results_batch(a).unwrap();
+            ammo_scan.add_results_batch(b).unwrap();
+
+            let remaining = hp_scan.intersect_results_with(&ammo_scan).unwrap();
+            let expected: Vec<u64> = (0..100u64).map(|i| 0x7000000000 + i * 24).collect();
+            assert_eq!(remaining, expected.len(), "{:?}", store);
+            assert_eq!(hp_scan.get_total_count().unwrap(), expected.len());
+            assert_eq!(result_addresses(&hp_scan), expected);
+
+            // 保留下来的是自身的结果，值类型不变；另一边不受影响
+            assert!(hp_scan.get_results(0, 100).unwrap().iter().all(|item| matches!(item, SearchResultItem::Exact(e) if e.typ == ValueType::Dword)));
+            assert_eq!(ammo_scan.get_total_count().unwrap(), 200);
+
+            // 与空结果集求交集清空所有结果
+            let empty = TestManager::with_store("intersect_empty", 0, 0, store);
+            assert_eq!(hp_scan.intersect_results_with(&empty).unwrap(), 0);
+            assert_eq!(hp_scan.get_total_count().unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_rebase_results_onto_new_module_layout() {
+        println!("\n=== rebase test ===\n");
+
+        let module = |name: &str, index: u32, base: u64, size: u64| {
+            let mut module = VmStaticData::new(name.to_string(), base, base + size, true);
+            module.index = index;
+            module
+        };
+        let old_modules = [
+            module("libil2cpp.so", 0, 0x7000000000, 0x10000),
+            module("libunity.so", 0, 0x7100000000, 0x8000),
+            module("libgone.so", 0, 0x7200000000, 0x1000),
+        ];
+        // 重启后模块换了基址、顺序，libunity 变小，libgone 不再加载
+        let new_modules = [
+            module("libunity.so", 0, 0x6E00000000, 0x4000),
+            module("libil2cpp.so", 0, 0x6F00000000, 0x10000),
+        ];
+
+        for store in [ResultStoreKind::Disk, ResultStoreKind::SortedVec] {
+            let mut manager = TestManager::with_store("rebase", 9 * 4, 0, store);
+            let items = vec![
+                SearchResultItem::new_exact(0x7000000010, ValueType::Dword),
+                SearchResultItem::new_exact(0x7200000020, ValueType::Dword), // 模块消失
+                SearchResultItem::new_exact(0x7100000100, ValueType::Float),
+                SearchResultItem::new_exact(0x7100006000, ValueType::Dword), // 超出新模块范围
+                SearchResultItem::new_exact(0x7300000000, ValueType::Dword), // 不属于任何模块
+                SearchResultItem::new_exact(0x700000FFFC, ValueType::Qword),
+            ];
+            manager.add_results_batch(items).unwrap();
+
+            let remaining = manager.rebase_results(&old_modules, &new_modules).unwrap();
+            assert_eq!(remaining, 3, "{:?}", store);
+            assert_eq!(result_addresses(&manager), vec![0x6F00000010, 0x6E00000100, 0x6F0000FFFC]);
+
+            let types: Vec<ValueType> = manager
+                .get_results(0, 3)
+                .unwrap()
+                .into_iter()
+                .map(|item| match item {
+                    SearchResultItem::Exact(exact) => exact.typ,
+                    SearchResultItem::Fuzzy(fuzzy) => fuzzy.value_type,
+                })
+                .collect();
+            assert_eq!(types, vec![ValueType::Dword, ValueType::Float, ValueType::Qword]);
+        }
+    }
+
+    #[test]
+    fn test_search_outcome_reports_truncation() {
+        println!("\n=== search_memory outcome test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x4000).unwrap();
+        for i in 0..10u64 {
+            mem.mem_write_u32(base_addr + i * 0x400, 0x1202).unwrap();
+        }
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1202, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions = [(base_addr, base_addr + 0x2000), (base_addr + 0x2000, base_addr + 0x4000)];
+        let search = |start: u64, end: u64, _: &dyn Fn() -> bool| find_first_n_single(&query.values[0], &[(start, end)], 0x1000, usize::MAX, mem.reader());
+        let all: Vec<u64> = (0..10u64).map(|i| base_addr + i * 0x400).collect();
+
+        let mut manager = TestManager::new("outcome", 0);
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+        assert_eq!(outcome.count, 10);
+        assert!(!outcome.truncated);
+        assert!(!outcome.cancelled);
+        assert_eq!(
+            outcome.stats,
+            ScanStats {
+                regions_total: 2,
+                regions_searched: 2,
+                regions_failed: 0,
+                regions_timed_out: 0,
+            }
+        );
+
+        // 超过上限：按地址保留前 4 个
+        manager.set_max_results(Some(4));
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+        assert_eq!(outcome.count, 4);
+        assert!(outcome.truncated);
+        assert_eq!(result_addresses(&manager), all[..4]);
+
+        // 恰好等于上限不算截断
+        manager.set_max_results(Some(10));
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+        assert_eq!(outcome.count, 10);
+        assert!(!outcome.truncated);
+        assert_eq!(result_addresses(&manager), all);
+    }
+
+    #[test]
+    fn test_region_timeout_abandons_slow_region() {
+        println!("\n=== region timeout test ===\n");
+
+        let mut mem = MockMemory::new();
+        let fast_base = mem.malloc(0x7000000000, 0x4000).unwrap();
+        let slow_base = mem.malloc(0x7100000000, 0x40000).unwrap();
+        for i in 0..4u64 {
+            mem.mem_write_u32(fast_base + i * 0x1000, 0x1204).unwrap();
+        }
+        mem.mem_write_u32(slow_base + 0x3F000, 0x1204).unwrap();
+
+        let target = SearchValue::fixed(0x1204, ValueType::Dword);
+        let query = SearchQuery::new(vec![target.clone()], SearchMode::Unordered, 0);
+        let regions = [(fast_base, fast_base + 0x4000), (slow_base, slow_base + 0x40000)];
+        // 慢区域每页读取耗时 20ms，完整扫描需要 1.28s
+        let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+            let inner = mem.reader();
+            let read = |addr: u64, buf: &mut [u8], status: &mut PageStatusBitmap| {
+                if addr >= slow_base {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                inner(addr, buf, status)
+            };
+            search_region_single_with(&target, start, end, 0x1000, read, &check_cancelled)
+        };
+
+        let mut manager = TestManager::new("region_timeout", 0);
+        manager.set_region_timeout(Some(Duration::from_millis(100)));
+        let started = Instant::now();
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(1000), "slow region was not abandoned");
+        assert!(!outcome.cancelled);
+        assert_eq!(outcome.stats.regions_timed_out, 1);
+        assert_eq!(outcome.stats.regions_searched, 1);
+        // 快区域的结果完整，慢区域末尾的值没有被扫到
+        let expected: Vec<u64> = (0..4u64).map(|i| fast_base + i * 0x1000).collect();
+        assert_eq!(result_addresses(&manager), expected);
+
+        // 不设超时时慢区域也会扫完
+        manager.set_region_timeout(None);
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+        assert_eq!(outcome.stats.regions_timed_out, 0);
+        assert_eq!(outcome.count, 5);
+    }
+
+    #[test]
+    fn test_query_cache_invalidated_by_memory_write() {
+        println!("\n=== query cache test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x2000).unwrap();
+        mem.mem_write_u32(base_addr + 0x100, 0x1207).unwrap();
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1207, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions = [(base_addr, base_addr + 0x2000)];
+        let scans = AtomicUsize::new(0);
+        let mut manager = TestManager::new("query_cache", 0);
+        manager.set_query_cache_capacity(4);
+
+        let mut search = |mem: &MockMemory| {
+            let search = |start: u64, end: u64, _: &dyn Fn() -> bool| {
+                scans.fetch_add(1, Ordering::Relaxed);
+                find_first_n_single(&query.values[0], &[(start, end)], 0x1000, usize::MAX, mem.reader())
+            };
+            manager.search_memory_with(&query, &regions, false, None, search).unwrap()
+        };
+
+        let first = search(&mem);
+        assert!(!first.cache_hit);
+        assert_eq!(first.count, 1);
+        assert_eq!(scans.load(Ordering::Relaxed), 1);
+
+        // 相同查询、内存未写入：命中缓存，不再读取内存
+        let second = search(&mem);
+        assert!(second.cache_hit);
+        assert_eq!(second.count, 1);
+        assert_eq!(second.stats.regions_searched, 1);
+        assert_eq!(scans.load(Ordering::Relaxed), 1);
+
+        // 写入内存后代数递增，缓存失效
+        mem.mem_write_u32(base_addr + 0x1100, 0x1207).unwrap();
+        bump_memory_generation();
+        let third = search(&mem);
+        assert!(!third.cache_hit);
+        assert_eq!(third.count, 2);
+        assert_eq!(scans.load(Ordering::Relaxed), 2);
+        assert_eq!(result_addresses(&manager), vec![base_addr + 0x100, base_addr + 0x1100]);
+
+        // 默认关闭，相同查询也重新扫描
+        let mut uncached = TestManager::new("query_cache_off", 0);
+        for _ in 0..2 {
+            let search = |start: u64, end: u64, _: &dyn Fn() -> bool| find_first_n_single(&query.values[0], &[(start, end)], 0x1000, usize::MAX, mem.reader());
+            assert!(!uncached.search_memory_with(&query, &regions, false, None, search).unwrap().cache_hit);
+        }
+    }
+
+    #[test]
+    fn test_search_buffer_honors_dump_page_status() {
+        println!("\n=== search_buffer_with_status test ===\n");
+
+        let page = *PAGE_SIZE as u64;
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 4 * page as usize).unwrap();
+        for i in 0..4u64 {
+            mem.mem_write_u32(base_addr + i * page + 0x40, 0x1210).unwrap();
+            mem.mem_write_u32(base_addr + i * page + 0x44, 0x2420).unwrap();
+        }
+        mem.set_faulty_pages(base_addr, &[1, 3]).unwrap();
+
+        // 转储保留了故障页的旧内容，位图记录哪些页实际读取成功
+        let dump = mem.mem_read(base_addr, 4 * page as usize).unwrap();
+        let mut dump_status = PageStatusBitmap::new(dump.len(), base_addr as usize);
+        mem.mem_read_with_status(base_addr, &mut vec![0u8; dump.len()], &mut dump_status).unwrap();
+
+        let manager = TestManager::new("search_buffer", 0);
+        let regions = [(base_addr, base_addr + 4 * page)];
+        let addresses = |items: Vec<SearchResultItem>| -> Vec<u64> { items.iter().map(|item| item.address()).collect() };
+
+        let single = SearchQuery::new(vec![SearchValue::fixed(0x1210, ValueType::Dword)], SearchMode::Unordered, 0);
+        let live: Vec<u64> = find_first_n_single(&single.values[0], &regions, 0x4000, usize::MAX, mem.reader())
+            .unwrap()
+            .iter()
+            .map(|pair| pair.addr)
+            .collect();
+        assert_eq!(live, vec![base_addr + 0x40, base_addr + 2 * page + 0x40]);
+        assert_eq!(addresses(manager.search_buffer_with_status(&single, base_addr, &dump, &dump_status).unwrap()), live);
+        // 不带位图时故障页的旧内容也会匹配
+        assert_eq!(manager.search_buffer(&single, base_addr, &dump).unwrap().len(), 4);
+
+        let group = SearchQuery::new(
+            vec![SearchValue::fixed(0x1210, ValueType::Dword), SearchValue::fixed(0x2420, ValueType::Dword)],
+            SearchMode::Ordered,
+            8,
+        );
+        let live: Vec<u64> = find_first_n_group(&group, &regions, 0x4000, usize::MAX, mem.reader())
+            .unwrap()
+            .iter()
+            .map(|pair| pair.addr)
+            .collect();
+        assert_eq!(live.len(), 4);
+        assert_eq!(addresses(manager.search_buffer_with_status(&group, base_addr, &dump, &dump_status).unwrap()), live);
+
+        assert!(manager.search_buffer(&single, base_addr + 1, &dump).is_err());
+    }
+
+    #[test]
+    fn test_cancellable_search_stops_dispatching_regions() {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x100000).unwrap();
+        let regions: Vec<(u64, u64)> = (0..256u64).map(|i| (base_addr + i * 0x1000, base_addr + (i + 1) * 0x1000)).collect();
+        for &(start, _) in &regions {
+            mem.mem_write_u32(start + 0x10, 0x1212).unwrap();
+        }
+
+        let target = SearchValue::fixed(0x1212, ValueType::Dword);
+        let query = SearchQuery::new(vec![target.clone()], SearchMode::Unordered, 0);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let scanned = AtomicUsize::new(0);
+        // 第 4 个区域扫描完成后取消；单线程池保证取消前开始的区域都已完整扫描
+        let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+            let result = search_region_single_with(&target, start, end, 0x1000, mem.reader(), &check_cancelled);
+            if scanned.fetch_add(1, Ordering::Relaxed) == 3 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            result
+        };
+
+        let mut manager = TestManager::new("cancellable_search", 0);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let outcome = pool
+            .install(|| manager.search_memory_cancellable_with(&query, &regions, false, None, Some(cancel.clone()), search))
+            .unwrap();
+        assert!(outcome.cancelled);
+        let scanned = scanned.into_inner();
+        assert!(scanned < regions.len(), "scanned {} regions after cancel", scanned);
+        // 取消前找到的结果仍写入结果集，取消后的区域不再分发
+        assert_eq!(outcome.count, scanned);
+
+        // 标志未设置时完整扫描
+        cancel.store(false, Ordering::Relaxed);
+        let search =
+            |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| search_region_single_with(&target, start, end, 0x1000, mem.reader(), &check_cancelled);
+        let outcome = manager
+            .search_memory_cancellable_with(&query, &regions, false, None, Some(cancel), search)
+            .unwrap();
+        assert!(!outcome.cancelled);
+        assert_eq!(outcome.count, regions.len());
+    }
+
+    #[test]
+    fn test_cancel_while_shared_search_running() {
+        println!("\n=== cancel during shared search test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x40000).unwrap();
+        mem.mem_write_u32(base_addr + 0x100, 0x1212).unwrap();
+        mem.mem_write_u32(base_addr + 0x3F000, 0x1212).unwrap();
+
+        let target = SearchValue::fixed(0x1212, ValueType::Dword);
+        let query = SearchQuery::new(vec![target.clone()], SearchMode::Unordered, 0);
+        let regions = [(base_addr, base_addr + 0x40000)];
+        // 每页读取耗时 20ms，完整扫描需要 1.28s
+        let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+            let inner = mem.reader();
+            let read = |addr: u64, buf: &mut [u8], status: &mut PageStatusBitmap| {
+                std::thread::sleep(Duration::from_millis(20));
+                inner(addr, buf, status)
+            };
+            search_region_single_with(&target, start, end, 0x1000, read, &check_cancelled)
+        };
+
+        let lock = RwLock::new(TestManager::new("shared_search", 0));
+        let started = Instant::now();
+        let outcome = std::thread::scope(|scope| {
+            let handle = scope.spawn(|| SearchEngineManager::search_memory_shared_with(&lock, &query, &regions, false, None, search));
+
+            // 扫描期间可以拿到读锁查询状态并取消
+            while !lock.read().unwrap().is_searching() {
+                assert!(!handle.is_finished(), "search finished before it was observed running");
+                std::thread::yield_now();
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            {
+                // 扫描线程还在写共享缓冲区，不能替换或清除
+                let mut manager = lock.write().unwrap();
+                let mut buffer = [0u8; 32];
+                assert!(!manager.set_shared_buffer(buffer.as_mut_ptr(), buffer.len()));
+                assert!(manager.clear_shared_buffer().is_err());
+                // 扫描结束时按原模式写入结果，期间不能切换模式
+                assert!(manager.set_result_mode(SearchResultMode::Fuzzy).is_err());
+            }
+            {
+                let manager = lock.read().unwrap();
+                assert!(manager.is_searching());
+                manager.request_cancel();
+            }
+
+            handle.join().unwrap().unwrap()
+        });
+
+        assert!(started.elapsed() < Duration::from_millis(1000), "cancel did not take effect");
+        assert!(outcome.cancelled);
+        let manager = lock.read().unwrap();
+        assert!(!manager.is_searching());
+        // 取消前找到的结果仍写入结果集
+        assert_eq!(result_addresses(&manager), vec![base_addr + 0x100]);
+    }
+
+    #[test]
+    fn test_panicking_search_resets_searching_state() {
+        let query = SearchQuery::new(vec![SearchValue::fixed(1, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions = [(0x7000000000u64, 0x7000001000u64)];
+        let search = |_start: u64, _end: u64, _check_cancelled: &dyn Fn() -> bool| -> anyhow::Result<Vec<ValuePair>> { panic!("region search failed") };
+
+        let lock = RwLock::new(TestManager::new("panicking_search", 0));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            SearchEngineManager::search_memory_shared_with(&lock, &query, &regions, false, None, search)
+        }));
+        assert!(result.is_err());
+
+        // 扫描阶段不持有锁，锁没有中毒，搜索状态随 PreparedSearch 一起复位
+        let mut manager = lock.write().unwrap();
+        assert!(!manager.is_searching());
+        assert!(manager.clear_shared_buffer().is_ok());
+        let empty = |_: u64, _: u64, _: &dyn Fn() -> bool| Ok(Vec::new());
+        assert!(manager.search_memory_with(&query, &regions, false, None, empty).is_ok());
+    }
+
+    #[test]
+    fn test_search_changed_since_checkpoint() {
+        println!("\n=== checkpoint test ===\n");
+
+        let page = *PAGE_SIZE as u64;
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 8 * page as usize).unwrap();
+        for i in 0..8u64 {
+            mem.mem_write_u32(base_addr + i * page + 0x100, 0x1205).unwrap();
+        }
+        let regions = [(base_addr, base_addr + 8 * page)];
+
+        let mut manager = TestManager::new("checkpoint", 0);
+        assert_eq!(manager.checkpoint_with("before", &regions, mem.reader()), 8);
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1205, ValueType::Dword)], SearchMode::Unordered, 0);
+        let changed_addresses = |manager: &TestManager, mem: &MockMemory| -> Vec<u64> {
+            let mut addrs: Vec<u64> = manager
+                .search_changed_since_with("before", &query, mem.reader())
+                .unwrap()
+                .iter()
+                .map(|item| item.address())
+                .collect();
+            addrs.sort_unstable();
+            addrs
+        };
+
+        // 内容未变，不扫描任何页
+        assert!(changed_addresses(&manager, &mem).is_empty());
+
+        // 只改第 5 页：其它页里的旧值不会再出现
+        mem.mem_write_u32(base_addr + 5 * page + 0x200, 0x1205).unwrap();
+        assert_eq!(changed_addresses(&manager, &mem), vec![base_addr + 5 * page + 0x100, base_addr + 5 * page + 0x200]);
+
+        // 检查点本身不随搜索更新
+        assert_eq!(changed_addresses(&manager, &mem).len(), 2);
+
+        assert!(manager.search_changed_since_with("missing", &query, mem.reader()).is_err());
+        assert!(manager.remove_checkpoint("before"));
+        assert!(!manager.remove_checkpoint("before"));
+    }
+
+    #[test]
+    fn test_memory_usage_reports_count_and_bytes() {
+        println!("\n=== memory_usage test ===\n");
+
+        for store in [ResultStoreKind::Disk, ResultStoreKind::SortedVec, ResultStoreKind::Persistent] {
+            // 64 条放在内存，其余溢出到磁盘（仅 Disk 存储）
+            let mut manager = TestManager::with_store("usage", 64 * 9, 0, store);
+            assert_eq!(manager.get_memory_usage().unwrap().result_count, 0);
+
+            let items = (0..1000u64).map(|i| SearchResultItem::new_exact(0x7000000000 + i * 4, ValueType::Dword)).collect();
+            manager.add_results_batch(items).unwrap();
+
+            let usage = manager.get_memory_usage().unwrap();
+            println!("{:?}: {:?}", store, usage);
+            assert_eq!(usage.result_count, 1000);
+            assert!(usage.in_memory_bytes > 0);
+            assert!(usage.bytes_per_result > 0);
+            match store {
+                ResultStoreKind::Disk => assert!(usage.on_disk_bytes >= (1000 - 64) * 9),
+                ResultStoreKind::Persistent => assert!(usage.on_disk_bytes >= 1000 * 9),
+                ResultStoreKind::SortedVec => {
+                    assert_eq!(usage.on_disk_bytes, 0);
+                    assert!(usage.in_memory_bytes >= 1000 * 9);
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_dry_run_counts_chunks_and_bytes() {
+        println!("\n=== dry_run test ===\n");
+
+        let manager = TestManager::new("dry_run", 64 * 1024);
+
+        let page = *crate::search::PAGE_SIZE as u64;
+        let regions = [
+            (0x7000200000, 0x7000200000 + 200 * 1024), // 4 块
+            (0x7000000000, 0x7000000000 + 64 * 1024),  // 恰好 1 块
+            (0x7000100000 + 16, 0x7000100000 + page),  // 未对齐起点，从页首开始读，1 块
+            (0x7000300000, 0x7000300000),              // 零长度
+            (0x7000400000, 0x7000300000),              // 反向
+        ];
+        let query = SearchQuery::new(vec![SearchValue::fixed(1, ValueType::Dword)], SearchMode::Unordered, 512);
+
+        let report = manager.dry_run(&regions, &query);
+        println!("{:?}", report);
+        assert!(report.query_ok);
+        assert_eq!(
+            report.validated_regions,
+            vec![
+                (0x7000000000, 0x7000000000 + 64 * 1024),
+                (0x7000100000 + 16, 0x7000100000 + page),
+                (0x7000200000, 0x7000200000 + 200 * 1024),
+            ]
+        );
+        assert_eq!(report.total_bytes, 264 * 1024 + page - 16);
+        assert_eq!(report.estimated_chunks, 6);
+
+        let bad_query = SearchQuery::new(
+            vec![SearchValue::fixed(1, ValueType::Dword), SearchValue::fixed(2, ValueType::Dword)],
+            SearchMode::Unordered,
+            1,
+        );
+        let report = manager.dry_run(&regions, &bad_query);
+        assert!(!report.query_ok);
+        assert!(report.query_error.is_some());
+    }
+
+    #[test]
+    fn test_pack_results_round_trip() {
+        println!("\n=== pack_results round trip test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x2000).unwrap();
+        mem.mem_write_u32(base_addr, 0xDEADBEEF).unwrap();
+        mem.mem_write_u64(base_addr + 0x10, 0x1122334455667788).unwrap();
+        mem.mem_write_f32(base_addr + 0x20, 1.5).unwrap();
+
+        let mut manager = TestManager::new("pack", 0);
+
+        let exact = [
+            (base_addr, ValueType::Dword),
+            (base_addr + 0x10, ValueType::Qword),
+            (base_addr + 0x20, ValueType::Float),
+            (0x1000, ValueType::Byte), // 不可读
+        ];
+        manager
+            .add_results_batch(exact.iter().map(|&(addr, typ)| SearchResultItem::new_exact(addr, typ)).collect())
+            .unwrap();
+
+        let read = |addr: u64, buf: &mut [u8]| {
+            let mut status = crate::wuwa::PageStatusBitmap::new(buf.len(), addr as usize);
+            mem.mem_read_with_status(addr, buf, &mut status).is_ok() && status.success_count() > 0
+        };
+
+        let (mode, records) = decode_packed(&manager.pack_results_with(1, 10, read).unwrap()).unwrap();
+        assert_eq!(mode, SearchResultMode::Exact);
+        assert_eq!(
+            records,
+            vec![
+                PackedRecord {
+                    index: 1,
+                    address: base_addr + 0x10,
+                    value_type: ValueType::Qword,
+                    value: Some(0x1122334455667788u64.to_le_bytes().to_vec()),
+                },
+                PackedRecord {
+                    index: 2,
+                    address: base_addr + 0x20,
+                    value_type: ValueType::Float,
+                    value: Some(1.5f32.to_le_bytes().to_vec()),
+                },
+                PackedRecord {
+                    index: 3,
+                    address: 0x1000,
+                    value_type: ValueType::Byte,
+                    value: None,
+                },
+            ]
+        );
+
+        // 空页
+        let (_, records) = decode_packed(&manager.pack_results_with(100, 10, read).unwrap()).unwrap();
+        assert!(records.is_empty());
+
+        // 模糊模式直接使用存储的值
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let fuzzy = FuzzySearchResultItem::from_bytes(base_addr + 0x40, &7u16.to_le_bytes(), ValueType::Word);
+        manager.add_results_batch(vec![SearchResultItem::Fuzzy(fuzzy)]).unwrap();
+        let (mode, records) = decode_packed(&manager.pack_results_with(0, 10, |_, _| false).unwrap()).unwrap();
+        assert_eq!(mode, SearchResultMode::Fuzzy);
+        assert_eq!(
+            records,
+            vec![PackedRecord {
+                index: 0,
+                address: base_addr + 0x40,
+                value_type: ValueType::Word,
+                value: Some(7u16.to_le_bytes().to_vec()),
+            }]
+        );
+
+        // 损坏的记录：value_len 超过 8 字节时报错而不是越界
+        let mut corrupted = manager.pack_results_with(0, 10, |_, _| false).unwrap();
+        corrupted[PACKED_HEADER_SIZE + 19] = 9;
+        assert!(decode_packed(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_search_periodic_finds_interleaved_array_base() {
+        println!("\n=== Periodic search test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x4000).unwrap();
+
+        // struct { u32 hp; u32 id; u64 pad; } units[6]，hp 字段每 16 字节重复
+        let array_base = base_addr + 0x1000;
+        for i in 0..6u64 {
+            mem.mem_write_u32(array_base + i * 16, 999).unwrap();
+            mem.mem_write_u32(array_base + i * 16 + 4, i as u32).unwrap();
+        }
+        // 非周期匹配：孤立值，以及只重复 2 次的短序列
+        mem.mem_write_u32(base_addr + 0x2004, 999).unwrap();
+        mem.mem_write_u32(base_addr + 0x3000, 999).unwrap();
+        mem.mem_write_u32(base_addr + 0x3010, 999).unwrap();
+
+        let value = SearchValue::fixed(999, ValueType::Dword);
+        let regions = vec![(base_addr, base_addr + 0x4000)];
+        let read = mem.reader();
+
+        let starts = find_periodic_single(&value, 16, 4, &regions, 0x1000, read).unwrap();
+        assert_eq!(starts, vec![array_base], "Only the array base should be reported");
+
+        // 放宽重复次数后短序列也会被接受，但数组中间的元素仍不算起点
+        let starts = find_periodic_single(&value, 16, 2, &regions, 0x1000, read).unwrap();
+        assert_eq!(starts, vec![array_base, base_addr + 0x3000]);
+
+        // 错误的步长找不到周期
+        assert!(find_periodic_single(&value, 12, 4, &regions, 0x1000, read).unwrap().is_empty());
+        assert!(find_periodic_single(&value, 0, 4, &regions, 0x1000, read).is_err());
+    }
+
+    #[test]
+    fn test_refine_search_rereads_existing_results() {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x1000).unwrap();
+        let mut manager = TestManager::new("refine_search", 0x1000);
+        manager.set_refine_history_depth(8).unwrap();
+
+        // 最后一个地址没有映射，读取失败
+        let unmapped = base_addr + 0x10000;
+        let items = vec![
+            SearchResultItem::new_exact(base_addr, ValueType::Dword),
+            SearchResultItem::new_exact(base_addr + 0x10, ValueType::Byte),
+            SearchResultItem::new_exact(base_addr + 0x20, ValueType::Dword),
+            SearchResultItem::new_exact(base_addr + 0x30, ValueType::Dword),
+            SearchResultItem::new_exact(unmapped, ValueType::Dword),
+        ];
+        manager.add_results_batch(items).unwrap();
+        mem.mem_write_u32(base_addr, 100).unwrap();
+        mem.mem_write_u32(base_addr + 0x10, 100).unwrap();
+        mem.mem_write_u32(base_addr + 0x20, 5).unwrap();
+        mem.mem_write_u32(base_addr + 0x30, 100).unwrap();
+
+        // Byte 结果不按 Dword 比较，类型不同的地址被丢弃
+        let query = SearchQuery::new(vec![SearchValue::fixed(100, ValueType::Dword)], SearchMode::Unordered, 0);
+        assert_eq!(manager.refine_search_with(&query, None, mem.reader()).unwrap(), 2);
+        assert_eq!(result_addresses(&manager), vec![base_addr, base_addr + 0x30]);
+        assert!(manager.undo_refine().unwrap());
+        assert_eq!(manager.get_total_count().unwrap(), 5);
+
+        let group = SearchQuery::new(
+            vec![SearchValue::fixed(100, ValueType::Dword), SearchValue::fixed(5, ValueType::Dword)],
+            SearchMode::Ordered,
+            0x40,
+        );
+        assert_eq!(manager.refine_search_with(&group, None, mem.reader()).unwrap(), 2);
+        assert_eq!(result_addresses(&manager), vec![base_addr, base_addr + 0x20]);
+
+        // 模糊结果保持模糊模式，记录的值更新为当前值
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let items = [base_addr, base_addr + 0x20, unmapped]
+            .iter()
+            .map(|&addr| SearchResultItem::new_fuzzy_from_bytes(addr, &0u32.to_le_bytes(), ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+        let range = SearchQuery::new(vec![SearchValue::range(50, 150, ValueType::Dword, false)], SearchMode::Unordered, 0);
+        assert_eq!(manager.refine_search_with(&range, None, mem.reader()).unwrap(), 1);
+        match &manager.get_results(0, 10).unwrap()[..] {
+            [SearchResultItem::Fuzzy(fuzzy)] => assert_eq!((fuzzy.address, fuzzy.value_type, fuzzy.as_i64()), (base_addr, ValueType::Dword, 100)),
+            other => panic!("expected one fuzzy result, got {}", other.len()),
+        }
+
+        assert!(manager.refine_search(&query, 99, None).is_err(), "invalid memory mode");
+    }
+
+    #[test]
+    fn test_transition_search_keeps_dropped_values() {
+        println!("\n=== Transition search test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x2000).unwrap();
+        let mut manager = TestManager::new("transition_search", 0x1000);
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+
+        // 四个满血值，以及一个原本就不是 100 的值
+        let full: Vec<u64> = (0..4).map(|i| base_addr + i * 0x100).collect();
+        let other = base_addr + 0x1800;
+        let mut items: Vec<SearchResultItem> = full
+            .iter()
+            .map(|&addr| SearchResultItem::new_fuzzy_from_bytes(addr, &100u32.to_le_bytes(), ValueType::Dword))
+            .collect();
+        items.push(SearchResultItem::new_fuzzy_from_bytes(other, &150u32.to_le_bytes(), ValueType::Dword));
+        manager.add_results_batch(items).unwrap();
+
+        for &addr in &full {
+            mem.mem_write_u32(addr, 100).unwrap();
+        }
+        // 两个受伤，一个加血超过 100，一个不变；另一个值也降到 100 以下
+        mem.mem_write_u32(full[0], 60).unwrap();
+        mem.mem_write_u32(full[2], 99).unwrap();
+        mem.mem_write_u32(full[3], 120).unwrap();
+        mem.mem_write_u32(other, 50).unwrap();
+
+        let from = SearchValue::fixed(100, ValueType::Dword);
+        let to = SearchValue::range(i32::MIN as i128, 99, ValueType::Dword, false);
+        let count = manager.transition_search_with(&from, &to, mem.reader()).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(result_addresses(&manager), vec![full[0], full[2]]);
+
+        // 记录的值更新为当前值，下一次变化以此为起点
+        let stored: Vec<i64> = manager
+            .get_results(0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Fuzzy(fuzzy) => fuzzy.as_i64(),
+                SearchResultItem::Exact(_) => panic!("expected fuzzy results"),
+            })
+            .collect();
+        assert_eq!(stored, vec![60, 99]);
+
+        // 精确结果没有记录的值
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        assert!(manager.transition_search_with(&from, &to, mem.reader()).is_err());
+    }
+
+    #[test]
+    fn test_scan_collect_leaves_result_set_empty() {
+        println!("\n=== scan_collect test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x4000).unwrap();
+        for i in 0..6u64 {
+            mem.mem_write_u32(base_addr + i * 0x7F4, 0x1216).unwrap();
+        }
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1216, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions = [(base_addr, base_addr + 0x2000), (base_addr + 0x2000, base_addr + 0x4000)];
+
+        let collector = TestManager::new("scan_collect", 0x1000);
+        let collected = collector.scan_collect_with(&query, &regions, mem.reader()).unwrap();
+        assert_eq!(collector.get_total_count().unwrap(), 0);
+
+        let mut manager = TestManager::new("scan_collect_normal", 0x1000);
+        let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+            search_region_single_with(&query.values[0], start, end, 0x1000, mem.reader(), &check_cancelled)
+        };
+        manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+
+        let collected: Vec<u64> = collected.iter().map(SearchResultItem::address).collect();
+        assert_eq!(collected.len(), 6);
+        assert_eq!(collected, result_addresses(&manager));
+    }
+
+    #[test]
+    fn test_alignment_fallback_finds_unaligned_value() {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x2000).unwrap();
+        // 打包结构体中的字段：Dword 位于 2 字节偏移处，另一个跨过页边界
+        mem.mem_write_u32(base_addr + 0x102, 0x12553355).unwrap();
+        mem.mem_write_u32(base_addr + 0xFFE, 0x12553355).unwrap();
+
+        let manager = TestManager::new("alignment_fallback", 0x1000);
+        let regions = [(base_addr, base_addr + 0x2000)];
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x12553355, ValueType::Dword)], SearchMode::Unordered, 0);
+
+        let scan = manager.scan_with_alignment_fallback_with(&query, &regions, mem.reader()).unwrap();
+        assert!(scan.used_fallback);
+        let addresses: Vec<u64> = scan.results.iter().map(SearchResultItem::address).collect();
+        assert_eq!(addresses, vec![base_addr + 0x102, base_addr + 0xFFE]);
+
+        // 自然对齐下能找到时不回退
+        mem.mem_write_u32(base_addr + 0x200, 0x12553355).unwrap();
+        let scan = manager.scan_with_alignment_fallback_with(&query, &regions, mem.reader()).unwrap();
+        assert!(!scan.used_fallback);
+        let addresses: Vec<u64> = scan.results.iter().map(SearchResultItem::address).collect();
+        assert_eq!(addresses, vec![base_addr + 0x200]);
+    }
+
+    #[test]
+    fn test_streaming_batches_sum_to_full_result_set() {
+        println!("\n=== streaming search test ===\n");
+
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x10000).unwrap();
+        let mut expected = Vec::new();
+        for i in 0..24u64 {
+            let addr = base_addr + i * 0xA00;
+            mem.mem_write_u32(addr, 0x1219).unwrap();
+            expected.push(addr);
+        }
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1219, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions: Vec<(u64, u64)> = (0..16u64).map(|i| (base_addr + i * 0x1000, base_addr + (i + 1) * 0x1000)).collect();
+        let manager = TestManager::new("streaming", 0x1000);
+        let search = |start: u64, end: u64| find_first_n_single(&query.values[0], &[(start, end)], 0x1000, usize::MAX, mem.reader());
+
+        // 容量为 1 的通道，接收方慢于扫描时靠背压限速
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let (sent, batches) = std::thread::scope(|scope| {
+            let consumer = scope.spawn(move || {
+                let mut batches = Vec::new();
+                for batch in rx {
+                    std::thread::sleep(Duration::from_millis(2));
+                    batches.push(batch);
+                }
+                batches
+            });
+            let sent = manager.search_streaming_with(&query, &regions, tx, search).unwrap();
+            (sent, consumer.join().unwrap())
+        });
+
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|batch| batch.results.len()).sum::<usize>(), sent);
+        let mut streamed: Vec<u64> = batches.iter().flat_map(|batch| batch.results.iter().map(SearchResultItem::address)).collect();
+        streamed.sort_unstable();
+        assert_eq!(streamed, expected);
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+
+        // 接收方提前关闭时停止扫描
+        let (tx, rx) = std::sync::mpsc::sync_channel(0);
+        drop(rx);
+        assert_eq!(manager.search_streaming_with(&query, &regions, tx, search).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        println!("\n=== session save/load test ===\n");
+
+        let mut manager = TestManager::new("session_save", 0x1000);
+        let items = (0..5u64)
+            .map(|i| SearchResultItem::new_exact(0x7000000000 + i * 0x10, ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+        manager
+            .set_filter(true, 0x7000000000, 0x7000000030, true, vec![ValueType::Dword.to_id()])
+            .unwrap();
+        manager.set_notes("health at +0x10");
+
+        let freeze = FreezeManager::new();
+        freeze.add_frozen_with_interval(0x7000000010, 100u32.to_le_bytes().to_vec(), ValueType::Dword.to_id(), Some(5000));
+        freeze.freeze_group("ammo", &[0x7000000020], &99u32.to_le_bytes(), ValueType::Dword.to_id());
+
+        let path = std::env::temp_dir().join(format!("mamu_session_{}.zip", std::process::id()));
+        manager.save_session(&path, Some(&freeze)).unwrap();
+
+        let mut restored = TestManager::new("session_load", 0x1000);
+        let restored_freeze = FreezeManager::new();
+        restored.load_session(&path, Some(&restored_freeze)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.get_current_mode().unwrap(), SearchResultMode::Exact);
+        assert_eq!(restored.get_total_count().unwrap(), 5);
+        assert_eq!(result_addresses(&restored), result_addresses(&manager));
+        assert_eq!(restored.get_notes(), "health at +0x10");
+
+        let filter = restored.get_filter();
+        assert!(filter.enable_address_filter && filter.enable_type_filter);
+        assert_eq!((filter.address_start, filter.address_end), (0x7000000000, 0x7000000030));
+        assert_eq!(filter.type_ids, vec![ValueType::Dword]);
+
+        let frozen: Vec<_> = restored_freeze
+            .frozen_entries()
+            .into_iter()
+            .map(|(address, entry)| (address, entry.value, entry.group, entry.interval_us))
+            .collect();
+        assert_eq!(
+            frozen,
+            vec![
+                (0x7000000010, 100u32.to_le_bytes().to_vec(), None, Some(5000)),
+                (0x7000000020, 99u32.to_le_bytes().to_vec(), Some("ammo".to_string()), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_batch_size_keeps_results_complete() {
+        println!("\n=== insert batch size test ===\n");
+
+        const COUNT: u64 = 200_000;
+        let expected: Vec<u64> = (0..COUNT).map(|i| 0x7000000000 + i * 4).collect();
+
+        // 内存缓冲只放得下一部分，其余溢出到磁盘
+        let stores = [(ResultStoreKind::Disk, 64 * 1024), (ResultStoreKind::SortedVec, 0)];
+        for (store, memory_buffer_size) in stores {
+            for batch_size in [0, 1, 1000, 4096, 1 << 20] {
+                let mut manager = TestManager::with_store("insert_batch", memory_buffer_size, 0x1000, store);
+                manager.set_insert_batch_size(batch_size);
+
+                let items = expected.iter().map(|&addr| SearchResultItem::new_exact(addr, ValueType::Dword)).collect();
+                let start = Instant::now();
+                manager.add_results_batch(items).unwrap();
+                println!("{:?} batch_size={:>8}: {:?}", store, batch_size, start.elapsed());
+
+                assert_eq!(manager.get_total_count().unwrap(), COUNT as usize);
+                assert_eq!(result_addresses(&manager), expected);
+            }
+        }
+
+        // 模式不符的一批整体拒绝
+        let mut manager = TestManager::new("insert_batch_mismatch", 0x1000);
+        let mixed = vec![
+            SearchResultItem::new_exact(0x7000000000, ValueType::Dword),
+            SearchResultItem::new_fuzzy(0x7000000004, [0; 8], ValueType::Dword),
+        ];
+        assert!(manager.add_results_batch(mixed).is_err());
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_reads_through_shared_handle() {
+        use rayon::prelude::*;
+
+        // 与 DRIVER_MANAGER 相同的用法：每个 rayon 任务各自拿读锁，经共享句柄并发读取
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7000000000, 0x40000).unwrap();
+        for i in 0..0x40u64 {
+            mem.mem_write_u32(base + i * 0x1000 + (i % 8) * 4, 0x5A5A).unwrap();
+        }
+        mem.set_faulty_pages(base, &[3, 17]).unwrap();
+        let shared = RwLock::new(mem);
+
+        let target = SearchValue::fixed(0x5A5A, ValueType::Dword);
+        let regions: Vec<(u64, u64)> = (0..16u64).map(|i| (base + i * 0x4000, base + (i + 1) * 0x4000)).collect();
+        let search = |&(start, end): &(u64, u64)| {
+            let guard = shared.read().unwrap();
+            search_region_single_with(&target, start, end, 0x1000, guard.reader(), &|| false).unwrap()
+        };
+
+        let mut expected: Vec<u64> = regions.iter().flat_map(search).map(|pair| pair.addr).collect();
+        expected.sort_unstable();
+        assert_eq!(expected.len(), 0x40 - 2);
+
+        for _ in 0..8 {
+            let mut actual: Vec<u64> = regions.par_iter().flat_map_iter(search).map(|pair| pair.addr).collect();
+            actual.sort_unstable();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_refine_by_delta_maps_onto_fuzzy_refine() {
+        // 每次比较的是上一次细化记录的值，而不是快照时的值
+        let snapshot = FuzzySearchResultItem::from_bytes(0x7000000010, &10u32.to_le_bytes(), ValueType::Dword);
+        let unchanged = DeltaOp::Unchanged.to_condition().unwrap();
+        assert!(snapshot.matches_condition(&10u32.to_le_bytes(), unchanged));
+        let increased_by_5 = DeltaOp::IncreasedBy(5).to_condition().unwrap();
+        assert!(snapshot.matches_condition(&15u32.to_le_bytes(), increased_by_5));
+        let refined = snapshot.with_new_value(&15u32.to_le_bytes());
+        assert!(refined.matches_condition(&20u32.to_le_bytes(), increased_by_5));
+        assert!(!snapshot.matches_condition(&20u32.to_le_bytes(), increased_by_5));
+        assert!(refined.matches_condition(&14u32.to_le_bytes(), DeltaOp::Decreased.to_condition().unwrap()));
+
+        // 超出 i64 的数量和精确结果都在启动细化前被拒绝
+        let mut manager = TestManager::new("refine_by_delta", 0);
+        assert!(manager.refine_by_delta(DeltaOp::DecreasedBy(i128::MAX)).is_err());
+        assert!(manager.refine_by_delta(DeltaOp::Changed).is_err());
+        assert!(!manager.is_searching());
+    }
+
+    #[test]
+    fn test_counter_search_refines_with_increase_conditions() {
+        let counter = FuzzySearchResultItem::from_bytes(0x7000000010, &1000u32.to_le_bytes(), ValueType::Dword);
+        let any = counter_condition(None).unwrap();
+        assert!(counter.matches_condition(&1001u32.to_le_bytes(), any));
+        assert!(counter.matches_condition(&1003u32.to_le_bytes(), any));
+        assert!(!counter.matches_condition(&1000u32.to_le_bytes(), any));
+        assert!(!counter.matches_condition(&999u32.to_le_bytes(), any));
+
+        let by_one = counter_condition(Some(1)).unwrap();
+        assert!(counter.matches_condition(&1001u32.to_le_bytes(), by_one));
+        assert!(!counter.matches_condition(&1002u32.to_le_bytes(), by_one));
+
+        // 非正增量和超出 i64 的增量在首次扫描前被拒绝
+        assert!(counter_condition(Some(0)).is_err());
+        assert!(counter_condition(Some(-1)).is_err());
+        assert!(counter_condition(Some(i128::MAX)).is_err());
+        let mut manager = TestManager::new("counter_search", 0);
+        let regions = vec![(0x7000000000, 0x7000001000)];
+        assert!(manager.start_counter_search_async(ValueType::Dword, Some(0), 0, regions).is_err());
+        assert!(!manager.is_searching());
+    }
+
+    #[test]
+    fn test_undo_refine_restores_previous_sets() {
+        let addr = |i: u64| 0x7000000000 + i * 8;
+        let exact = |indices: &mut dyn Iterator<Item = u64>| indices.map(|i| SearchResultItem::new_exact(addr(i), ValueType::Dword)).collect::<Vec<_>>();
+
+        let mut manager = TestManager::new("undo_refine", 0);
+        manager.add_results_batch(exact(&mut (0..5000))).unwrap();
+        let initial = result_addresses(&manager);
+
+        // 默认不记录历史
+        let mut all = TestManager::new("undo_refine_all", 0);
+        all.add_results_batch(exact(&mut (0..5000))).unwrap();
+        assert_eq!(manager.intersect_results_with(&all).unwrap(), 5000);
+        assert_eq!(manager.refine_history_len(), 0);
+        assert!(!manager.undo_refine().unwrap());
+        manager.set_refine_history_depth(8).unwrap();
+
+        // 第一次比较删掉 1000 个，结果集较大，按差量保存
+        let mut first = TestManager::new("undo_refine_first", 0);
+        first.add_results_batch(exact(&mut (0..5000).filter(|i| i % 5 != 0))).unwrap();
+        assert_eq!(manager.intersect_results_with(&first).unwrap(), 4000);
+        let intermediate = result_addresses(&manager);
+
+        // 第二次只剩 100 个，细化前的 4000 个整份保存
+        let mut second = TestManager::new("undo_refine_second", 0);
+        second.add_results_batch(exact(&mut (0..5000).filter(|i| i % 50 == 1))).unwrap();
+        assert_eq!(manager.intersect_results_with(&second).unwrap(), 100);
+        assert_eq!(manager.refine_history_len(), 2);
+
+        assert!(manager.undo_refine().unwrap());
+        assert_eq!(result_addresses(&manager), intermediate);
+        assert!(manager.undo_refine().unwrap());
+        assert_eq!(result_addresses(&manager), initial);
+        assert!(!manager.undo_refine().unwrap());
+
+        // 细化改写了记录的值，撤销后恢复旧值
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7100000000, 0x1000).unwrap();
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let items = (0..3u64).map(|i| SearchResultItem::new_fuzzy_from_bytes(base + i * 4, &10u32.to_le_bytes(), ValueType::Dword)).collect();
+        manager.add_results_batch(items).unwrap();
+        mem.mem_write_u32(base, 11).unwrap();
+        mem.mem_write_u32(base + 4, 10).unwrap();
+        mem.mem_write_u32(base + 8, 12).unwrap();
+
+        let (from, to) = (SearchValue::fixed(10, ValueType::Dword), SearchValue::fixed(11, ValueType::Dword));
+        assert_eq!(manager.transition_search_with(&from, &to, mem.reader()).unwrap(), 1);
+        assert!(manager.undo_refine().unwrap());
+        let restored: Vec<(u64, i64)> = manager
+            .get_results(0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Fuzzy(fuzzy) => (fuzzy.address, fuzzy.as_i64()),
+                SearchResultItem::Exact(_) => panic!("expected fuzzy results"),
+            })
+            .collect();
+        assert_eq!(restored, vec![(base, 10), (base + 4, 10), (base + 8, 10)]);
+    }
+
+    #[test]
+    fn test_result_mode_controls_storage_and_retrieval() {
+        let mut manager = TestManager::new("result_mode", 0);
+        assert_eq!(manager.get_result_mode().unwrap(), SearchResultMode::Exact);
+        manager.add_results_batch(vec![SearchResultItem::new_exact(0x7000000000, ValueType::Dword)]).unwrap();
+
+        // 切换到模糊模式会清空精确结果，之后只接受带值的结果
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        assert_eq!(manager.get_result_mode().unwrap(), SearchResultMode::Fuzzy);
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+        assert!(manager.add_results_batch(vec![SearchResultItem::new_exact(0x7000000000, ValueType::Dword)]).is_err());
+
+        let items = vec![
+            SearchResultItem::new_fuzzy_from_bytes(0x7000000010, &42u32.to_le_bytes(), ValueType::Dword),
+            SearchResultItem::new_fuzzy_from_bytes(0x7000000020, &1.5f32.to_le_bytes(), ValueType::Float),
+        ];
+        manager.add_results_batch(items).unwrap();
+        let stored: Vec<(u64, [u8; 8], ValueType)> = manager
+            .get_results(0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Fuzzy(fuzzy) => (fuzzy.address, fuzzy.value, fuzzy.value_type),
+                SearchResultItem::Exact(_) => panic!("fuzzy mode returned an exact result"),
+            })
+            .collect();
+        assert_eq!(stored[0], (0x7000000010, [42, 0, 0, 0, 0, 0, 0, 0], ValueType::Dword));
+        assert_eq!(stored[1].0, 0x7000000020);
+        assert_eq!(&stored[1].1[..4], &1.5f32.to_le_bytes());
+
+        // 设置为相同模式不影响已有结果
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 2);
+
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+        assert_eq!(SearchResultMode::from_id(SearchResultMode::Fuzzy.to_id()), Some(SearchResultMode::Fuzzy));
+        assert_eq!(SearchResultMode::from_id(7), None);
+    }
+
+    /// 记录每次 `on_search_complete` 的 (结果数, 区域数)
+    #[derive(Default)]
+    struct RecordingCallback {
+        completions: Mutex<Vec<(usize, usize)>>,
+    }
+
+    impl SearchProgressCallback for RecordingCallback {
+        fn on_search_complete(&self, total_found: usize, total_regions: usize, _elapsed_millis: u64) {
+            self.completions.lock().unwrap().push((total_found, total_regions));
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_search_memory_with_no_regions() {
+        let mut manager = TestManager::new("no_regions", 0);
+        let previous = vec![SearchResultItem::new_exact(0x7000000000, ValueType::Dword)];
+        manager.add_results_batch(previous).unwrap();
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(1, ValueType::Dword)], SearchMode::Unordered, 0);
+        let callback = Arc::new(RecordingCallback::default());
+
+        // 空列表和全部无效的区域都不读取内存，直接得到 0 个结果，上一次的结果被清空
+        for regions in [&[][..], &[(0x7000001000, 0x7000001000)][..]] {
+            let count = manager.search_memory(&query, regions, false, Some(callback.clone())).unwrap();
+            assert_eq!(count, 0);
+            assert_eq!(manager.get_total_count().unwrap(), 0);
+            assert!(!manager.is_searching());
+        }
+        assert_eq!(*callback.completions.lock().unwrap(), vec![(0, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn test_cluster_by_value_groups_close_values() {
+        let mut manager = TestManager::new("cluster", 0);
+        assert!(manager.cluster_by_value(5.0).is_err(), "exact results carry no values");
+
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let items = [500u32, 100, 502, 101]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| SearchResultItem::new_fuzzy_from_bytes(0x7000000000 + i as u64 * 4, &value.to_le_bytes(), ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+
+        // 下标按写入顺序：0=500, 1=100, 2=502, 3=101
+        assert_eq!(manager.cluster_by_value(5.0).unwrap(), vec![vec![1, 3], vec![0, 2]]);
+        assert_eq!(manager.cluster_by_value(1.0).unwrap(), vec![vec![1, 3]]);
+        assert!(manager.cluster_by_value(-1.0).is_err());
+    }
+
+    #[test]
+    fn test_reinterpret_dword_results_as_float() {
+        let mut manager = TestManager::new("reinterpret", 0);
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let items = [1.5f32, -2.0, 100.25]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| SearchResultItem::new_fuzzy_from_bytes(0x7000000000 + i as u64 * 4, &value.to_bits().to_le_bytes(), ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+
+        let decoded = |manager: &SearchEngineManager| -> Vec<(ValueType, f64)> {
+            manager
+                .get_results(0, usize::MAX)
+                .unwrap()
+                .into_iter()
+                .map(|item| match item {
+                    SearchResultItem::Fuzzy(fuzzy) => (fuzzy.value_type, fuzzy.as_f64()),
+                    SearchResultItem::Exact(_) => panic!("expected fuzzy results"),
+                })
+                .collect()
+        };
+        let as_dword = |value: f32| (ValueType::Dword, value.to_bits() as i32 as f64);
+        assert_eq!(decoded(&manager), vec![as_dword(1.5), as_dword(-2.0), as_dword(100.25)]);
+
+        manager.reinterpret_result_type(ValueType::Float).unwrap();
+        let as_float = |value: f32| (ValueType::Float, value as f64);
+        assert_eq!(decoded(&manager), vec![as_float(1.5), as_float(-2.0), as_float(100.25)]);
+        assert_eq!(result_addresses(&manager), vec![0x7000000000, 0x7000000004, 0x7000000008]);
+
+        // Double 需要 8 字节，原来只记录了 4 字节
+        assert!(manager.reinterpret_result_type(ValueType::Double).is_err());
+        assert!(decoded(&manager).iter().all(|(value_type, _)| *value_type == ValueType::Float));
+
+        // 缩小到低位字节：1.5f32 = 0x3FC00000
+        manager.reinterpret_result_type(ValueType::Word).unwrap();
+        assert_eq!(decoded(&manager)[0], (ValueType::Word, 0.0));
+
+        // 精确模式只改类型
+        let mut manager = TestManager::new("reinterpret_exact", 0);
+        let item = SearchResultItem::new_exact(0x7000000000, ValueType::Qword);
+        manager.add_results_batch(vec![item]).unwrap();
+        manager.reinterpret_result_type(ValueType::Double).unwrap();
+        assert!(matches!(&manager.get_results(0, 1).unwrap()[0], SearchResultItem::Exact(exact) if exact.typ == ValueType::Double));
+        assert!(manager.reinterpret_result_type(ValueType::Oword).is_err());
+    }
+
+    #[test]
+    fn test_read_span_across_unmapped_gap() {
+        let page = *PAGE_SIZE as u64;
+        let mut mem = MockMemory::new();
+        // 两个单页区域，中间隔一页未映射
+        let first = mem.malloc(0x7000000000, page as usize).unwrap();
+        let second = mem.malloc(first + 2 * page, page as usize).unwrap();
+        mem.mem_write_u32(first + page - 4, 0x11111111).unwrap();
+        mem.mem_write_u32(second, 0x22222222).unwrap();
+
+        let start = first + page / 2;
+        let len = 2 * page as usize;
+        let span = SearchEngineManager::read_span_with(start, len, mem.reader());
+
+        assert_eq!(span.bytes.len(), len);
+        assert_eq!(span.page_count(), 3);
+        assert!(span.page_status.is_page_success(0));
+        assert!(!span.page_status.is_page_success(1), "the gap page must be flagged");
+        assert!(span.page_status.is_page_success(2));
+        assert_eq!(span.readable_ranges(), vec![(start, first + page), (second, start + len as u64)]);
+
+        let at = |addr: u64| {
+            let offset = (addr - start) as usize;
+            u32::from_le_bytes(span.bytes[offset..offset + 4].try_into().unwrap())
+        };
+        assert_eq!(at(first + page - 4), 0x11111111);
+        assert_eq!(at(second), 0x22222222);
+        assert!(span.bytes[(first + page - start) as usize..(second - start) as usize].iter().all(|&b| b == 0));
+
+        // 不跨区域时一次读完
+        let span = SearchEngineManager::read_span_with(first, 8, mem.reader());
+        assert_eq!(span.readable_ranges(), vec![(first, first + 8)]);
+    }
+
+    #[test]
+    fn test_sort_by_distance_to_interleaves_both_sides() {
+        let mut manager = TestManager::new("distance", 0);
+        let reference = 0x7000001000u64;
+        // 与扫描结果一样按地址递增写入
+        let offsets: [i64; 6] = [-0x100, -0x20, -0x8, 0x0, 0x10, 0x40];
+        let items = offsets
+            .iter()
+            .map(|&offset| SearchResultItem::new_exact(reference.wrapping_add_signed(offset), ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+
+        let ordered: Vec<i64> = manager
+            .sort_by_distance_to(reference)
+            .unwrap()
+            .iter()
+            .map(|item| item.address() as i64 - reference as i64)
+            .collect();
+        assert_eq!(ordered, vec![0x0, -0x8, 0x10, -0x20, 0x40, -0x100]);
+
+        // 距离相同时低地址在前；参考地址在所有结果之外时按单侧排列
+        let addresses = |ref_addr: u64| -> Vec<u64> { manager.sort_by_distance_to(ref_addr).unwrap().iter().map(SearchResultItem::address).collect() };
+        assert_eq!(addresses(reference + 0x8)[..3], [reference, reference + 0x10, reference - 0x8]);
+        assert!(addresses(0).is_sorted());
+        assert!(addresses(u64::MAX).is_sorted_by(|a, b| a > b));
+    }
+
+    #[test]
+    fn test_get_results_for_addrs() {
+        let mut manager = TestManager::new("results_for_addrs", 0);
+        let base = 0x7000002000u64;
+        let items = [0x10u64, 0x20, 0x38, 0x100]
+            .iter()
+            .map(|&offset| SearchResultItem::new_fuzzy_from_bytes(base + offset, &(offset as u32).to_le_bytes(), ValueType::Dword))
+            .collect();
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        manager.add_results_batch(items).unwrap();
+
+        let requested = [base + 0x38, base, base + 0x10, base + 0x24, base + 0x100, base + 0x38];
+        let found = manager.get_results_for_addrs(&requested).unwrap();
+        assert_eq!(found.len(), requested.len());
+
+        let addresses: Vec<Option<u64>> = found.iter().map(|item| item.as_ref().map(SearchResultItem::address)).collect();
+        let expected = [Some(base + 0x38), None, Some(base + 0x10), None, Some(base + 0x100), Some(base + 0x38)];
+        assert_eq!(addresses, expected);
+
+        // 取回的是保存的条目本身，包括记录的值
+        let Some(SearchResultItem::Fuzzy(item)) = &found[4] else {
+            panic!("expected a fuzzy result");
+        };
+        let value = item.value;
+        assert_eq!(u32::from_le_bytes(value[..4].try_into().unwrap()), 0x100);
+    }
+
+    #[test]
+    fn test_search_memory_from_ram_source() {
+        let page = *PAGE_SIZE as u64;
+        let first = 0x10000000u64;
+        let second = first + 4 * page;
+        let source = RamSource::new();
+        source.add_segment(first, vec![0; 2 * page as usize]).unwrap();
+        source.add_segment(second, vec![0; page as usize]).unwrap();
+        assert!(source.add_segment(first + page, vec![0; 16]).is_err(), "overlapping segment");
+
+        for addr in [first + 0x10, first + 2 * page - 4, second + 0x20] {
+            source.write(addr, &1234u32.to_le_bytes()).unwrap();
+        }
+        source.write(second + 0x24, &5678u32.to_le_bytes()).unwrap();
+        assert!(source.write(first + 2 * page, &[0; 4]).is_err(), "gap is unmapped");
+
+        let mut manager = TestManager::new("ram_source", 0);
+        let query = SearchQuery::new(vec![SearchValue::fixed(1234, ValueType::Dword)], SearchMode::Unordered, 0);
+        let outcome = manager.search_memory_from(&source, &query, None, false, None).unwrap();
+        assert_eq!(outcome.count, 3);
+        let mut addresses = result_addresses(&manager);
+        addresses.sort_unstable();
+        assert_eq!(addresses, vec![first + 0x10, first + 2 * page - 4, second + 0x20]);
+
+        // 组搜索只在给定的区域内进行
+        let values = vec![SearchValue::fixed(1234, ValueType::Dword), SearchValue::fixed(5678, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 8);
+        let regions = [(second, second + page)];
+        let outcome = manager.search_memory_from(&source, &query, Some(&regions), false, None).unwrap();
+        assert_eq!(outcome.count, 2);
+        let mut addresses = result_addresses(&manager);
+        addresses.sort_unstable();
+        assert_eq!(addresses, vec![second + 0x20, second + 0x24]);
+    }
+
+    #[test]
+    fn test_ram_source_quick_scans() {
+        let page = *PAGE_SIZE as u64;
+        let first = 0x10000000u64;
+        let second = first + 4 * page;
+        let source = RamSource::new();
+        source.add_segment(first, vec![0; 2 * page as usize]).unwrap();
+        source.add_segment(second, vec![0; page as usize]).unwrap();
+        for addr in [first + 0x10, first + page + 0x40, second + 0x20] {
+            source.write(addr, &1243u32.to_le_bytes()).unwrap();
+        }
+
+        let manager = TestManager::new("ram_source_quick", 0x1000);
+        let value = SearchValue::fixed(1243, ValueType::Dword);
+        let query = SearchQuery::new(vec![value.clone()], SearchMode::Unordered, 0);
+
+        let first_two = manager.find_first_n_from(&source, &query, None, 2).unwrap();
+        let found: Vec<u64> = first_two.iter().map(SearchResultItem::address).collect();
+        assert_eq!(found, vec![first + 0x10, first + page + 0x40]);
+        let in_second = manager.find_first_n_from(&source, &query, Some(&[(second, second + page)]), 10).unwrap();
+        assert_eq!(in_second.iter().map(SearchResultItem::address).collect::<Vec<_>>(), vec![second + 0x20]);
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(4);
+        let sent = manager.search_streaming_from(&source, &query, None, tx).unwrap();
+        let mut streamed: Vec<u64> = rx.iter().flat_map(|batch| batch.results).map(|item| item.address()).collect();
+        streamed.sort_unstable();
+        assert_eq!(sent, 3);
+        assert_eq!(streamed, vec![first + 0x10, first + page + 0x40, second + 0x20]);
+
+        assert!(manager.matches_at_from(&source, second + 0x20, &value).unwrap());
+        source.write(second + 0x20, &0u32.to_le_bytes()).unwrap();
+        assert!(!manager.matches_at_from(&source, second + 0x20, &value).unwrap());
+        // 段之间的空洞不可读
+        assert!(!manager.matches_at_from(&source, first + 2 * page, &value).unwrap());
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+    }
+
+    /// 记录每次 `on_progress` 的 (已扫描字节数, 总字节数)
+    #[derive(Default)]
+    struct ProgressRecorder {
+        reports: Mutex<Vec<(u64, u64)>>,
+    }
+
+    impl SearchProgressCallback for ProgressRecorder {
+        fn on_search_complete(&self, _total_found: usize, _total_regions: usize, _elapsed_millis: u64) {}
+
+        fn on_progress(&self, bytes_done: u64, bytes_total: u64) {
+            self.reports.lock().unwrap().push((bytes_done, bytes_total));
+        }
+    }
+
+    #[test]
+    fn test_search_reports_byte_progress() {
+        let page = *PAGE_SIZE as u64;
+        let base = 0x10000000u64;
+        let len = 2 * PROGRESS_REPORT_INTERVAL + 3 * page;
+        let source = RamSource::new();
+        source.add_segment(base, vec![0; len as usize]).unwrap();
+
+        let mut manager = TestManager::new("byte_progress", 0);
+        let query = SearchQuery::new(vec![SearchValue::fixed(1234, ValueType::Dword)], SearchMode::Unordered, 0);
+        let recorder = Arc::new(ProgressRecorder::default());
+        manager.search_memory_from(&source, &query, None, false, Some(recorder.clone())).unwrap();
+
+        // 单个区域按块顺序读取：每 16MB 报告一次，扫描完时再报告一次总数
+        let reports = recorder.reports.lock().unwrap().clone();
+        assert_eq!(reports.len(), 3);
+        assert!(reports.iter().all(|&(_, total)| total == len));
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(reports[0].0 >= PROGRESS_REPORT_INTERVAL && reports[1].0 >= 2 * PROGRESS_REPORT_INTERVAL);
+        assert_eq!(reports[2], (len, len));
+    }
+
+    #[test]
+    fn test_persistent_store_recovers_after_crash() {
+        let cache_dir = std::env::temp_dir().join(format!("mamu_persistent_test_{}", std::process::id()));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let index_path = cache_dir.join("mamu_exact_results.idx");
+        let open = || {
+            let mut manager = SearchEngineManager::new();
+            manager
+                .init_with_store(0, cache_dir.to_string_lossy().to_string(), 0, ResultStoreKind::Persistent)
+                .unwrap();
+            manager
+        };
+        let items = |range: std::ops::Range<u64>| -> Vec<SearchResultItem> {
+            range.map(|i| SearchResultItem::new_exact(0x7000000000 + i * 4, ValueType::Dword)).collect()
+        };
+
+        let mut manager = open();
+        manager.add_results_batch(items(0..100)).unwrap();
+        manager.remove_results_batch(vec![0, 1]).unwrap();
+        manager.add_results_batch(items(100..110)).unwrap();
+        let committed = result_addresses(&manager);
+        assert_eq!(committed.len(), 108);
+        // 不做任何收尾直接丢弃，相当于进程被杀
+        drop(manager);
+
+        let mut manager = open();
+        assert_eq!(result_addresses(&manager), committed);
+        let before_append = std::fs::read(&index_path).unwrap();
+        manager.add_results_batch(items(200..210)).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 118);
+        drop(manager);
+        let after_append = std::fs::read(&index_path).unwrap();
+
+        // 最后一次追加写入的 superblock 槽位
+        let slot = (0..2)
+            .find(|slot| {
+                let range = slot * SUPERBLOCK_SIZE..(slot + 1) * SUPERBLOCK_SIZE;
+                before_append[range.clone()] != after_append[range]
+            })
+            .unwrap();
+        let torn_superblock = slot * SUPERBLOCK_SIZE + 24;
+        let torn_record = INDEX_DATA_OFFSET + 108 * ExactSearchResultItem::SIZE;
+
+        // superblock 写坏，或新记录没有完整落盘，都回退到追加前的结果集
+        for offset in [torn_superblock, torn_record] {
+            let mut crashed = after_append.clone();
+            crashed[offset] ^= 0xFF;
+            std::fs::write(&index_path, &crashed).unwrap();
+
+            let manager = open();
+            assert_eq!(result_addresses(&manager), committed, "torn byte at {}", offset);
+        }
+
+        // 模糊模式的结果同样恢复，并以模糊模式打开
+        let mut manager = open();
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let fuzzy = FuzzySearchResultItem::from_bytes(0x7000000100, &1234u32.to_le_bytes(), ValueType::Dword);
+        manager.add_results_batch(vec![SearchResultItem::Fuzzy(fuzzy)]).unwrap();
+        drop(manager);
+        assert!(!index_path.exists(), "switching mode drops the exact index");
+
+        let manager = open();
+        assert_eq!(manager.get_result_mode().unwrap(), SearchResultMode::Fuzzy);
+        let results = manager.get_results(0, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        match &results[0] {
+            SearchResultItem::Fuzzy(item) => assert_eq!(item.as_i64(), 1234),
+            SearchResultItem::Exact(_) => panic!("expected a fuzzy result"),
+        }
+        drop(manager);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_persistent_store_keeps_text_encoding() {
+        let cache_dir = std::env::temp_dir().join(format!("mamu_text_index_test_{}", std::process::id()));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let open = || {
+            let mut manager = SearchEngineManager::new();
+            manager
+                .init_with_store(0, cache_dir.to_string_lossy().to_string(), 0, ResultStoreKind::Persistent)
+                .unwrap();
+            manager
+        };
+
+        let utf16 = ValueType::Text(10, TextEncoding::Utf16Le);
+        let types = [utf16, ValueType::Text(10, TextEncoding::Utf8), ValueType::Bytes(10)];
+        let mut manager = open();
+        let items = types.iter().zip(0u64..).map(|(&typ, i)| SearchResultItem::new_exact(0x70000000 + i, typ));
+        manager.add_results_batch(items.collect()).unwrap();
+        drop(manager);
+
+        // 字符串结果不会被当成同样长度的特征码读回
+        let manager = open();
+        let restored: Vec<ValueType> = manager
+            .get_results(0, 10)
+            .unwrap()
+            .iter()
+            .map(|item| match item {
+                SearchResultItem::Exact(exact) => exact.typ,
+                SearchResultItem::Fuzzy(_) => panic!("expected an exact result"),
+            })
+            .collect();
+        assert_eq!(restored, types);
+        drop(manager);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_persistent_store_redoes_interrupted_compaction() {
+        let cache_dir = std::env::temp_dir().join(format!("mamu_compaction_test_{}", std::process::id()));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let index_path = cache_dir.join("mamu_exact_results.idx");
+        let open = || {
+            let mut manager = SearchEngineManager::new();
+            manager
+                .init_with_store(0, cache_dir.to_string_lossy().to_string(), 0, ResultStoreKind::Persistent)
+                .unwrap();
+            manager
+        };
+
+        let mut manager = open();
+        let items: Vec<SearchResultItem> = (0..100u64)
+            .map(|i| SearchResultItem::new_exact(0x7000000000 + i * 4, ValueType::Dword))
+            .collect();
+        manager.add_results_batch(items).unwrap();
+        manager.remove_results_batch(vec![50, 1, 99]).unwrap();
+        manager.keep_only_results((0..97).filter(|i| i % 3 != 0).collect()).unwrap();
+        let compacted = result_addresses(&manager);
+        let expected: Vec<u64> = (0..100u64)
+            .filter(|i| ![1, 50, 99].contains(i))
+            .enumerate()
+            .filter(|(i, _)| i % 3 != 0)
+            .map(|(_, i)| 0x7000000000 + i * 4)
+            .collect();
+        assert_eq!(compacted, expected);
+        drop(manager);
+        let compacted_file = std::fs::read(&index_path).unwrap();
+
+        // 最后一次提交是搬移完成后的提交，另一个槽位是带暂存的提交
+        let generation = |slot: usize| {
+            let at = slot * SUPERBLOCK_SIZE + 16;
+            u64::from_le_bytes(compacted_file[at..at + 8].try_into().unwrap())
+        };
+        let newest = if generation(0) > generation(1) { 0 } else { 1 };
+
+        // 搬移没有完成就崩溃：最新提交没有写入，原位记录也只搬了一半，打开时重做搬移
+        let mut crashed = compacted_file.clone();
+        crashed[newest * SUPERBLOCK_SIZE + 24] ^= 0xFF;
+        crashed[INDEX_DATA_OFFSET + 10 * ExactSearchResultItem::SIZE] ^= 0xFF;
+        std::fs::write(&index_path, &crashed).unwrap();
+
+        let mut manager = open();
+        assert_eq!(result_addresses(&manager), compacted);
+        let extra = SearchResultItem::new_exact(0x7100000000, ValueType::Dword);
+        manager.add_results_batch(vec![extra]).unwrap();
+        drop(manager);
+
+        let manager = open();
+        let mut appended = compacted.clone();
+        appended.push(0x7100000000);
+        assert_eq!(result_addresses(&manager), appended);
+        drop(manager);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_via_pointers_keeps_objects_with_matching_field() {
+        let mut mem = MockMemory::new();
+        let globals = mem.malloc(0x7000000000, 0x2000).unwrap();
+        let heap = mem.malloc(0x7100000000, 0x4000).unwrap();
+
+        // 三个对象：a、b 的 HP 在 +0x40，c 的 1000 在 +0x48
+        let (a, b, c) = (heap + 0x100, heap + 0x200, heap + 0x300);
+        mem.mem_write_u32(a + 0x40, 1000).unwrap();
+        mem.mem_write_u32(b + 0x40, 500).unwrap();
+        mem.mem_write_u32(c + 0x48, 1000).unwrap();
+        // 指针区自身 +0x40 处也有 1000，但它不在数据区内
+        mem.mem_write_u32(globals + 0x1000 + 0x40, 1000).unwrap();
+
+        let pointers = [
+            (globals + 0x10, a),
+            (globals + 0x18, b),
+            (globals + 0x20, c),
+            // 带高位标签的指针
+            (globals + 0x1008, a | 0xB400_0000_0000_0000),
+            (globals + 0x1010, globals + 0x1000),
+            (globals + 0x1018, heap + 0x4000),
+        ];
+        for (addr, target) in pointers {
+            mem.mem_write_u64(addr, target).unwrap();
+        }
+        // 未对齐的指针不扫描
+        mem.mem_write_u64(globals + 0x804, a).unwrap();
+
+        let manager = TestManager::new("scan_via_pointers", 0x1000);
+        let hp = SearchValue::fixed(1000, ValueType::Dword);
+        let pointer_regions = [(globals, globals + 0x2000)];
+        let data_regions = [(heap, heap + 0x4000)];
+
+        let matches = manager
+            .scan_via_pointers_with(0x40, &hp, &pointer_regions, &data_regions, mem.reader())
+            .unwrap();
+        let found: Vec<(u64, u64, u64)> = matches.iter().map(|m| (m.pointer_addr, m.target, m.field_addr)).collect();
+        assert_eq!(found, vec![(globals + 0x10, a, a + 0x40), (globals + 0x1008, a, a + 0x40)]);
+
+        // 偏移可以为负：从字段后方的地址往回找
+        mem.mem_write_u64(globals + 0x28, c + 0x50).unwrap();
+        let matches = manager
+            .scan_via_pointers_with(-0x8, &hp, &pointer_regions, &data_regions, mem.reader())
+            .unwrap();
+        assert_eq!(matches.iter().map(|m| m.pointer_addr).collect::<Vec<_>>(), vec![globals + 0x28]);
+    }
+
+    #[test]
+    fn test_export_results_with_module_offsets() {
+        println!("\n=== export test ===\n");
+
+        let modules = [
+            VmStaticData::new("libunity.so".to_string(), 0x7100000000, 0x7100008000, true),
+            VmStaticData::new("libil2cpp.so".to_string(), 0x7000000000, 0x7000010000, true),
+        ];
+        let mut manager = TestManager::with_store("export", 9 * 4, 0, ResultStoreKind::SortedVec);
+        let items = vec![
+            SearchResultItem::new_exact(0x7000000010, ValueType::Dword),
+            SearchResultItem::new_exact(0x7100000100, ValueType::Float),
+            SearchResultItem::new_exact(0x7100008000, ValueType::Dword), // 紧接在模块末尾之后
+            SearchResultItem::new_exact(0x7300000000, ValueType::Qword), // 不属于任何模块
+        ];
+        manager.add_results_batch(items).unwrap();
+
+        let mut csv = Vec::new();
+        assert_eq!(manager.export_results(&mut csv, ExportFormat::Csv, &modules).unwrap(), 4);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "address,module_offset,type,value\n\
+             0x7000000010,libil2cpp.so+0x10,Dword,\n\
+             0x7100000100,libunity.so+0x100,Float,\n\
+             0x7100008000,0x7100008000,Dword,\n\
+             0x7300000000,0x7300000000,Qword,\n"
+        );
+
+        let mut json = Vec::new();
+        manager.export_results(&mut json, ExportFormat::Json, &modules).unwrap();
+        let rows: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let locations: Vec<&str> = rows.as_array().unwrap().iter().map(|row| row["module_offset"].as_str().unwrap()).collect();
+        assert_eq!(locations, vec!["libil2cpp.so+0x10", "libunity.so+0x100", "0x7100008000", "0x7300000000"]);
+
+        // 导出的两种写法都能解析回原地址
+        let lookup = |name: &str| modules.iter().find(|module| module.name == name).map(|module| module.base_address);
+        let resolved: Vec<u64> = locations
+            .iter()
+            .map(|location| parse_address(location).unwrap().resolve(lookup).unwrap())
+            .collect();
+        assert_eq!(resolved, result_addresses(&manager));
+
+        // 模糊结果附带记录的值
+        let mut manager = TestManager::with_store("export_fuzzy", 17 * 4, 0, ResultStoreKind::SortedVec);
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        let item = SearchResultItem::new_fuzzy_from_bytes(0x7000000020, &1.5f32.to_le_bytes(), ValueType::Float);
+        manager.add_results_batch(vec![item]).unwrap();
+        let mut csv = Vec::new();
+        manager.export_results(&mut csv, ExportFormat::Csv, &modules).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().nth(1), Some("0x7000000020,libil2cpp.so+0x20,Float,1.5"));
+    }
+
+    #[test]
+    fn test_verify_after_scan_drops_transient_matches() {
+        println!("\n=== verify after scan test ===\n");
+
+        // 扫描时看到的内存与校验时的内存：0x800 处的单值和 0x1300 处的组只在扫描时短暂匹配
+        let mut scanned = MockMemory::new();
+        let mut settled = MockMemory::new();
+        let base_addr = scanned.malloc(0x7000000000, 0x2000).unwrap();
+        settled.malloc(base_addr, 0x2000).unwrap();
+        for mem in [&mut scanned, &mut settled] {
+            for offset in [0x100, 0x800, 0x1800] {
+                mem.mem_write_u32(base_addr + offset, 0x1248).unwrap();
+            }
+            for offset in [0x300, 0x1300] {
+                mem.mem_write_u32(base_addr + offset, 0x2222).unwrap();
+                mem.mem_write_u32(base_addr + offset + 4, 0x3333).unwrap();
+            }
+        }
+        settled.mem_write_u32(base_addr + 0x800, 0x1249).unwrap();
+        settled.mem_write_u32(base_addr + 0x1304, 0).unwrap();
+
+        let regions = [(base_addr, base_addr + 0x2000)];
+        let calls = AtomicUsize::new(0);
+        let run = |manager: &mut SearchEngineManager, query: &SearchQuery| {
+            calls.store(0, Ordering::Relaxed);
+            let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+                // 只有一个区域，第一次调用是扫描，之后都是校验
+                let mem = if calls.fetch_add(1, Ordering::Relaxed) == 0 { &scanned } else { &settled };
+                if query.values.len() == 1 {
+                    search_region_single_with(&query.values[0], start, end, 0x1000, mem.reader(), &check_cancelled)
+                } else {
+                    search_region_group_with(query, start, end, 0x1000, usize::MAX, mem.reader(), &check_cancelled)
+                }
+            };
+            manager.search_memory_with(query, &regions, false, None, search).unwrap();
+            result_addresses(manager)
+        };
+
+        let mut manager = TestManager::new("verify_after_scan", 0x1000);
+        let single = SearchQuery::new(vec![SearchValue::fixed(0x1248, ValueType::Dword)], SearchMode::Unordered, 0);
+        assert_eq!(run(&mut manager, &single), vec![base_addr + 0x100, base_addr + 0x800, base_addr + 0x1800]);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        let verified = run(&mut manager, &single.clone().with_verify_after_scan(true));
+        assert_eq!(verified, vec![base_addr + 0x100, base_addr + 0x1800]);
+        assert!(calls.load(Ordering::Relaxed) > 1);
+
+        let values = vec![SearchValue::fixed(0x2222, ValueType::Dword), SearchValue::fixed(0x3333, ValueType::Dword)];
+        let group = SearchQuery::new(values, SearchMode::Ordered, 8);
+        assert_eq!(run(&mut manager, &group).len(), 4);
+        let verified = run(&mut manager, &group.with_verify_after_scan(true));
+        assert_eq!(verified, vec![base_addr + 0x300, base_addr + 0x304]);
+    }
+
+    #[test]
+    fn test_scan_last_page_of_address_space() {
+        // 区域结束地址 u64::MAX 为开区间，块末尾、窗口末尾的地址运算都不能溢出
+        let base = u64::MAX - *PAGE_SIZE as u64 + 1;
+        let end = u64::MAX;
+        let mut page = vec![0u8; *PAGE_SIZE];
+        let mut put = |addr: u64, value: u32| {
+            let offset = (addr - base) as usize;
+            page[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        put(base + 0x100, 0x1234);
+        put(base + 0x104, 0x5678);
+        put(end - 15, 0x1234);
+        put(end - 11, 0x5678);
+        put(end - 7, 0x1234);
+        // 最后 4 字节中有一字节落在 end 上，不完整，不应匹配
+        put(end - 3, 0x1234);
+
+        let read = |addr: u64, buf: &mut [u8], status: &mut PageStatusBitmap| -> anyhow::Result<()> {
+            let offset = (addr - base) as usize;
+            buf.copy_from_slice(&page[offset..offset + buf.len()]);
+            status.mark_all_success();
+            Ok(())
+        };
+
+        let target = SearchValue::fixed(0x1234, ValueType::Dword);
+        let single = search_region_single_with(&target, base, end, 0x1000, read, &|| false).unwrap();
+        let mut addrs: Vec<u64> = single.iter().map(|pair| pair.addr).collect();
+        addrs.sort_unstable();
+        assert_eq!(addrs, vec![base + 0x100, end - 15, end - 7]);
+
+        let values = vec![SearchValue::fixed(0x1234, ValueType::Dword), SearchValue::fixed(0x5678, ValueType::Dword)];
+        let query = SearchQuery::new(values, SearchMode::Ordered, 0x40);
+        let group = search_region_group_with(&query, base, end, 0x1000, usize::MAX, read, &|| false).unwrap();
+        let mut addrs: Vec<u64> = group.iter().map(|pair| pair.addr).collect();
+        addrs.sort_unstable();
+        addrs.dedup();
+        assert_eq!(addrs, vec![base + 0x100, base + 0x104, end - 15, end - 11]);
+    }
+
+    #[test]
+    fn test_stop_after_first_hit_region() {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, 0x5000).unwrap();
+        // 值只在第三个区域和最后一个区域出现
+        mem.mem_write_u32(base_addr + 0x2010, 0x1253).unwrap();
+        mem.mem_write_u32(base_addr + 0x2800, 0x1253).unwrap();
+        mem.mem_write_u32(base_addr + 0x4020, 0x1253).unwrap();
+
+        let query = SearchQuery::new(vec![SearchValue::fixed(0x1253, ValueType::Dword)], SearchMode::Unordered, 0);
+        let regions: Vec<(u64, u64)> = (0..5u64).map(|i| (base_addr + i * 0x1000, base_addr + (i + 1) * 0x1000)).collect();
+
+        let scanned = Mutex::new(Vec::new());
+        let search = |start: u64, end: u64, check_cancelled: &dyn Fn() -> bool| {
+            scanned.lock().unwrap().push(start);
+            search_region_single_with(&query.values[0], start, end, 0x1000, mem.reader(), &check_cancelled)
+        };
+
+        let mut manager = TestManager::new("stop_after_first_hit", 0x1000);
+        let outcome = manager.search_memory_with(&query, &regions, false, None, search).unwrap();
+        assert_eq!(result_addresses(&manager).len(), 3);
+        assert_eq!(outcome.stats.regions_searched, 5);
+
+        scanned.lock().unwrap().clear();
+        let stop_query = query.clone().with_stop_after_first_hit_region(true);
+        let outcome = manager.search_memory_with(&stop_query, &regions, false, None, search).unwrap();
+        assert_eq!(result_addresses(&manager), vec![base_addr + 0x2010, base_addr + 0x2800]);
+        assert_eq!(outcome.stats.regions_searched, 3);
+        // 前两个区域按顺序扫描过，命中区域之后不再扫描
+        assert_eq!(*scanned.lock().unwrap(), vec![regions[0].0, regions[1].0, regions[2].0]);
+    }
+
+    #[test]
+    fn test_recommend_memory_mode_within_fault_limit() {
+        let cache_dir = std::env::temp_dir().join(format!("mamu_mode_stats_test_{}", std::process::id()));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let cache_path = cache_dir.to_string_lossy().to_string();
+
+        let sample = |mb_per_sec: u64, failed_regions: usize| ScanSample {
+            bytes: mb_per_sec << 20,
+            elapsed: Duration::from_secs(1),
+            regions: 100,
+            failed_regions,
+        };
+
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, cache_path.clone(), 0x1000).unwrap();
+        // 没有目标时不记录
+        manager.record_scan_stats(MemoryAccessMode::Normal, sample(100, 0));
+        assert_eq!(manager.recommend_memory_mode(), MemoryAccessMode::None);
+
+        manager.set_stats_target(Some("com.example.game".to_string()));
+        for _ in 0..2 {
+            manager.record_scan_stats(MemoryAccessMode::Normal, sample(100, 0));
+            // 最快但出错太多
+            manager.record_scan_stats(MemoryAccessMode::NonCacheable, sample(300, 20));
+            manager.record_scan_stats(MemoryAccessMode::PageFault, sample(150, 2));
+        }
+        // 只扫描过一次，不足以下结论
+        manager.record_scan_stats(MemoryAccessMode::WriteThrough, sample(500, 0));
+        assert_eq!(manager.recommend_memory_mode(), MemoryAccessMode::PageFault);
+
+        manager.set_stats_target(Some("com.example.other".to_string()));
+        assert_eq!(manager.recommend_memory_mode(), MemoryAccessMode::None);
+        drop(manager);
+
+        // 统计随缓存目录保留到下次会话
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, cache_path, 0x1000).unwrap();
+        manager.set_stats_target(Some("com.example.game".to_string()));
+        assert_eq!(manager.recommend_memory_mode(), MemoryAccessMode::PageFault);
+        drop(manager);
+
+        let _ = std::fs::remove_dir_all(&cache_dir);
+    }
+
+    #[test]
+    fn test_scan_stats_count_bytes_read() {
+        // 没有回调时也累计，统计按实际读取的块计，而不是区域大小
+        let progress = ScanProgress::new(&[(0x7000000000, 0x7000100000)], None);
+        progress.add(0x1000);
+        progress.add(0x2000);
+        assert_eq!(progress.bytes_done(), 0x3000);
+    }
+}
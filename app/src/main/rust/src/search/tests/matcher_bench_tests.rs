@@ -0,0 +1,115 @@
+//! Group matcher benchmark harness
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_search::{search_in_buffer_group, search_in_buffer_group_fallback};
+    use crate::search::{SearchMode, SearchQuery, SearchValue, ValuePair, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::time::{Duration, Instant};
+
+    const BASE_ADDR: u64 = 0x7000000000;
+    const MEM_SIZE: usize = 1024 * 1024;
+    /// 标准数据集埋入的序列数
+    const SEQUENCE_COUNT: usize = 100;
+    const SEED: u64 = 0x5EED_1240;
+    /// 每个匹配器取多次运行中最快的一次，减少调度抖动
+    const ROUNDS: usize = 3;
+    const SEQUENCE: [u32; 3] = [0x5A3C9E17, 0x1F2E3D4C, 0x6B7A8C9D];
+
+    type Matcher = fn(&[u8], u64, u64, u64, usize, &SearchQuery, &PageStatusBitmap, &mut Vec<ValuePair>, &mut usize);
+
+    /// 参与比较的匹配器，新增的匹配器（如多 anchor 的 aho-corasick）在这里注册
+    const MATCHERS: [(&str, Matcher); 2] = [("linear", search_in_buffer_group_fallback), ("anchor-first", search_in_buffer_group)];
+
+    /// 固定种子的随机字节，按固定间隔埋入 [`SEQUENCE_COUNT`] 组紧密排列的序列
+    fn standard_dataset() -> (Vec<u8>, Vec<u64>) {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let mut buffer: Vec<u8> = (0..MEM_SIZE).map(|_| rng.random()).collect();
+
+        let stride = MEM_SIZE / SEQUENCE_COUNT / 4 * 4;
+        let mut expected = Vec::new();
+        for i in 0..SEQUENCE_COUNT {
+            let offset = i * stride;
+            for (j, value) in SEQUENCE.iter().enumerate() {
+                let at = offset + j * 4;
+                buffer[at..at + 4].copy_from_slice(&value.to_le_bytes());
+                expected.push(BASE_ADDR + at as u64);
+            }
+        }
+        (buffer, expected)
+    }
+
+    struct BenchResult {
+        name: &'static str,
+        best: Duration,
+        results: Vec<u64>,
+    }
+
+    impl BenchResult {
+        fn throughput_mb_s(&self) -> f64 {
+            MEM_SIZE as f64 / (1024.0 * 1024.0) / self.best.as_secs_f64().max(f64::EPSILON)
+        }
+    }
+
+    fn run(name: &'static str, matcher: Matcher, buffer: &[u8], query: &SearchQuery, page_status: &PageStatusBitmap) -> BenchResult {
+        let end = BASE_ADDR + buffer.len() as u64;
+        let mut best = Duration::MAX;
+        let mut results = Vec::new();
+
+        for _ in 0..ROUNDS {
+            let mut pairs = Vec::new();
+            let mut matches_checked = 0usize;
+            let start = Instant::now();
+            matcher(buffer, BASE_ADDR, BASE_ADDR, end, 4, query, page_status, &mut pairs, &mut matches_checked);
+            best = best.min(start.elapsed());
+
+            results = pairs.iter().map(|pair| pair.addr).collect();
+            results.sort_unstable();
+            results.dedup();
+        }
+
+        BenchResult { name, best, results }
+    }
+
+    #[test]
+    fn test_group_matcher_throughput() {
+        let (buffer, expected) = standard_dataset();
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE_ADDR as usize);
+        page_status.mark_all_success();
+
+        let values = SEQUENCE.iter().map(|&v| SearchValue::fixed(v as i128, ValueType::Dword)).collect();
+        let query = SearchQuery::new(values, SearchMode::Ordered, 16);
+
+        let benches: Vec<BenchResult> = MATCHERS
+            .iter()
+            .map(|&(name, matcher)| run(name, matcher, &buffer, &query, &page_status))
+            .collect();
+
+        println!("\n=== group matcher throughput ({} KB, {} sequences) ===", MEM_SIZE / 1024, SEQUENCE_COUNT);
+        for bench in &benches {
+            println!(
+                "{:>14}: {:>10.2?}  {:>10.1} MB/s  {} results",
+                bench.name,
+                bench.best,
+                bench.throughput_mb_s(),
+                bench.results.len()
+            );
+        }
+
+        for bench in &benches {
+            assert_eq!(bench.results, expected, "{} matcher returned different results", bench.name);
+        }
+
+        // 基线：anchor-first 不应比逐地址扫描慢
+        let linear = &benches[0];
+        let anchor = &benches[1];
+        assert!(
+            anchor.best <= linear.best,
+            "anchor-first ({:?}) slower than linear ({:?})",
+            anchor.best,
+            linear.best
+        );
+    }
+}
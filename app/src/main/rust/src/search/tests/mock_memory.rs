@@ -132,6 +132,11 @@ impl MockMemory {
         Ok(region.data[offset..offset + size].to_vec())
     }
 
+    /// `mem_read_with_status` as a plain read callback for the `*_with(read)` search entry points
+    pub fn reader(&self) -> impl Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()> + Copy + '_ {
+        move |addr, buf, page_status| self.mem_read_with_status(addr, buf, page_status)
+    }
+
     /// Read data from memory with page fault simulation
     ///
     /// # Arguments
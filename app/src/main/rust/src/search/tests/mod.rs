@@ -4,4 +4,8 @@ pub mod mock_memory;
 pub mod single_search_tests;
 pub mod group_search_tests;
 pub mod refine_search_tests;
-pub mod deep_search_tests;
\ No newline at end of file
+pub mod deep_search_tests;
+pub mod manager_tests;
+pub mod region_tests;
+pub mod matcher_bench_tests;
+pub mod test_manager;
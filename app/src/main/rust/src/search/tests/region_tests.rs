@@ -0,0 +1,105 @@
+//! Region tagging / read-only selection tests
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::region::{regions_for_query, select_regions, RegionTags, SearchRegion};
+    use crate::search::engine::single_search::find_first_n_single;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::test_manager::TestManager;
+    use crate::search::{SearchMode, SearchQuery, SearchResultItem, SearchValue, ValueType};
+    use crate::wuwa::{MEM_EXECUTABLE, MEM_READABLE, MEM_WRITABLE};
+
+    #[test]
+    fn test_readonly_region_included_only_when_requested() {
+        println!("\n=== Read-only region selection test ===\n");
+
+        let mut mem = MockMemory::new();
+        let rodata = mem.malloc(0x7000000000, 0x4000).unwrap();
+        let heap = mem.malloc(0x7100000000, 0x4000).unwrap();
+        let guard = mem.malloc(0x7200000000, 0x1000).unwrap();
+
+        // 只读段中的 8 字节字符串，按 QWORD 搜索
+        let needle = *b"il2cpp!\0";
+        mem.mem_write(rodata + 0x200, &needle).unwrap();
+        mem.mem_write(guard, &needle).unwrap();
+
+        let regions = [
+            SearchRegion::new(rodata, rodata + 0x4000, MEM_READABLE, true),
+            SearchRegion::new(heap, heap + 0x4000, MEM_READABLE | MEM_WRITABLE, false),
+            SearchRegion::new(guard, guard + 0x1000, 0, false),
+        ];
+        assert!(regions[0].is_readonly());
+        assert!(!regions[1].is_readonly());
+        assert!(SearchRegion::new(0, 1, MEM_READABLE | MEM_EXECUTABLE, true).is_readonly());
+
+        let value = SearchValue::fixed(u64::from_le_bytes(needle) as i128, ValueType::Qword);
+        let query = SearchQuery::new(vec![value.clone()], SearchMode::Unordered, 512);
+        let scan = |selected: &[(u64, u64)]| {
+            let read = mem.reader();
+            find_first_n_single(&value, selected, 0x1000, usize::MAX, read)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.addr)
+                .collect::<Vec<_>>()
+        };
+
+        // 数值类型默认跳过只读区域，不可读区域始终排除
+        assert!(!query.include_readonly());
+        let default_regions = regions_for_query(&query, &regions);
+        assert_eq!(default_regions, vec![(heap, heap + 0x4000)]);
+        assert!(scan(&default_regions).is_empty());
+
+        let query = query.with_include_readonly(true);
+        let with_readonly = regions_for_query(&query, &regions);
+        assert_eq!(with_readonly, select_regions(&regions, true));
+        assert_eq!(with_readonly.len(), 2);
+        assert_eq!(scan(&with_readonly), vec![rodata + 0x200]);
+
+        println!("Read-only region selection test passed!");
+    }
+
+    #[test]
+    fn test_results_carry_region_tag() {
+        println!("\n=== Region tag test ===\n");
+
+        let mut mem = MockMemory::new();
+        let region_a = mem.malloc(0x7000000000, 0x2000).unwrap();
+        let region_b = mem.malloc(0x7100000000, 0x2000).unwrap();
+        mem.mem_write_u32(region_a + 0x10, 4242).unwrap();
+        mem.mem_write_u32(region_a + 0x1800, 4242).unwrap();
+        mem.mem_write_u32(region_b + 0x20, 4242).unwrap();
+
+        // 区域顺序与地址顺序相反，编号仍按传入下标
+        let regions = vec![(region_b, region_b + 0x2000), (region_a, region_a + 0x2000)];
+        let value = SearchValue::fixed(4242, ValueType::Dword);
+        let read = mem.reader();
+        let pairs = find_first_n_single(&value, &regions, 0x1000, 100, read).unwrap();
+        assert_eq!(pairs.len(), 3);
+
+        let tags = RegionTags::new(&regions);
+        assert_eq!(tags.region_of(region_a + 0x10), Some(1));
+        assert_eq!(tags.region_of(region_b + 0x20), Some(0));
+        assert_eq!(tags.region_of(region_a + 0x2000), None);
+
+        let mut manager = TestManager::new("region_tag", 0);
+
+        // 未开启标记时不记录区域
+        manager.record_region_tags(&regions);
+        assert_eq!(manager.get_result_region(region_a + 0x10), None);
+
+        manager.set_region_tagging(true);
+        manager.record_region_tags(&regions);
+        manager
+            .add_results_batch(pairs.iter().map(SearchResultItem::from).collect())
+            .unwrap();
+
+        let tagged: Vec<(u64, Option<u32>)> =
+            manager.get_results_tagged(0, 10).unwrap().into_iter().map(|(item, region)| (item.address(), region)).collect();
+        assert_eq!(
+            tagged,
+            vec![(region_b + 0x20, Some(0)), (region_a + 0x10, Some(1)), (region_a + 0x1800, Some(1))]
+        );
+
+        println!("Region tag test passed!");
+    }
+}
@@ -0,0 +1,68 @@
+//! Shared fixture for tests that need an initialized SearchEngineManager
+
+use crate::search::result_manager::ResultStoreKind;
+use crate::search::SearchEngineManager;
+use std::borrow::{Borrow, BorrowMut};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// 带独立缓存目录的 SearchEngineManager，drop 时先释放管理器再删除目录
+pub struct TestManager {
+    manager: SearchEngineManager,
+    cache_dir: PathBuf,
+}
+
+impl TestManager {
+    /// 使用默认存储后端，等价于 `init(0, dir, chunk_size)`
+    pub fn new(name: &str, chunk_size: usize) -> Self {
+        Self::with_store(name, 0, chunk_size, ResultStoreKind::default())
+    }
+
+    pub fn with_store(name: &str, memory_buffer_size: usize, chunk_size: usize, store: ResultStoreKind) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let cache_dir = std::env::temp_dir().join(format!("mamu_{}_test_{}_{}", name, std::process::id(), id));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+
+        let mut manager = SearchEngineManager::new();
+        manager
+            .init_with_store(memory_buffer_size, cache_dir.to_string_lossy().to_string(), chunk_size, store)
+            .unwrap();
+        Self { manager, cache_dir }
+    }
+}
+
+impl Deref for TestManager {
+    type Target = SearchEngineManager;
+
+    fn deref(&self) -> &Self::Target {
+        &self.manager
+    }
+}
+
+impl DerefMut for TestManager {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.manager
+    }
+}
+
+impl Borrow<SearchEngineManager> for TestManager {
+    fn borrow(&self) -> &SearchEngineManager {
+        &self.manager
+    }
+}
+
+impl BorrowMut<SearchEngineManager> for TestManager {
+    fn borrow_mut(&mut self) -> &mut SearchEngineManager {
+        &mut self.manager
+    }
+}
+
+impl Drop for TestManager {
+    fn drop(&mut self) {
+        drop(std::mem::replace(&mut self.manager, SearchEngineManager::new()));
+        let _ = std::fs::remove_dir_all(&self.cache_dir);
+    }
+}
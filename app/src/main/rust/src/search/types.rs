@@ -1,5 +1,7 @@
 use anyhow::anyhow;
+use log::warn;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ValueType {
@@ -11,8 +13,67 @@ pub enum ValueType {
     Double,
     Auto,
     Xor,
+    /// 16 字节整数（SIMD 向量、GUID 等），按字节精确比较
+    Oword,
+    /// 特征码（AOB），携带字节数，见 [`SearchValue::Bytes`]
+    Bytes(u8),
+    /// 字符串，携带编码后的字节数，见 [`SearchValue::Text`]
+    Text(u8, TextEncoding),
 }
 
+/// 字符串搜索的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextEncoding {
+    Utf8,
+    /// Java/Unity（C#）字符串的内存布局
+    Utf16Le,
+}
+
+impl TextEncoding {
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            TextEncoding::Utf8 => text.as_bytes().to_vec(),
+            TextEncoding::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        }
+    }
+
+    /// 解码内存中的字节，无效序列替换为 U+FFFD
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            TextEncoding::Utf16Le => {
+                let units: Vec<u16> = bytes.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+                String::from_utf16_lossy(&units)
+            },
+        }
+    }
+}
+
+/// 数值在内存中的字节序，默认小端；大端用于嵌在内存中的网络包、序列化数据等
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ByteOrder {
+    #[default]
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    /// 在小端编码与该字节序之间原地转换，两个方向相同
+    #[inline]
+    pub fn apply(self, bytes: &mut [u8]) {
+        if self == ByteOrder::Big {
+            bytes.reverse();
+        }
+    }
+}
+
+/// `Bytes` 的 id 为 `BYTES_ID_BASE | 长度`，长度随 id 一起保存在结果索引等只有类型字段的地方
+pub const BYTES_ID_BASE: i32 = 0x80;
+/// `Text` 的 id 为 `TEXT_ID_BASE | 编码 << 7 | 长度`，UTF-8 为 0、UTF-16LE 为 1，读回后仍按原编码解码显示
+pub const TEXT_ID_BASE: i32 = 0x100;
+/// 特征码与字符串编码后的最长字节数，受 id 编码限制
+pub const MAX_BYTE_PATTERN_LEN: usize = 0x7F;
+
 impl ValueType {
     #[inline]
     pub fn from_id(id: i32) -> Option<Self> {
@@ -25,6 +86,10 @@ impl ValueType {
             5 => Self::Double.into(),
             6 => Self::Auto.into(),
             7 => Self::Xor.into(),
+            8 => Self::Oword.into(),
+            0x81..=0xFF => Self::Bytes((id - BYTES_ID_BASE) as u8).into(),
+            0x101..=0x17F => Self::Text((id - TEXT_ID_BASE) as u8, TextEncoding::Utf8).into(),
+            0x181..=0x1FF => Self::Text((id - TEXT_ID_BASE - 0x80) as u8, TextEncoding::Utf16Le).into(),
             _ => None,
         }
     }
@@ -40,6 +105,10 @@ impl ValueType {
             ValueType::Double => 5,
             ValueType::Auto => 6,
             ValueType::Xor => 7,
+            ValueType::Oword => 8,
+            ValueType::Bytes(len) => BYTES_ID_BASE | *len as i32,
+            ValueType::Text(len, TextEncoding::Utf8) => TEXT_ID_BASE | *len as i32,
+            ValueType::Text(len, TextEncoding::Utf16Le) => TEXT_ID_BASE | 0x80 | *len as i32,
         }
     }
 
@@ -54,6 +123,7 @@ impl ValueType {
             'E' => Some(ValueType::Double),
             'A' => Some(ValueType::Auto),
             'X' => Some(ValueType::Xor),
+            'O' => Some(ValueType::Oword),
             _ => None,
         }
     }
@@ -69,6 +139,48 @@ impl ValueType {
             ValueType::Double => 8,
             ValueType::Auto => 4,
             ValueType::Xor => 4,
+            ValueType::Oword => 16,
+            ValueType::Bytes(len) | ValueType::Text(len, _) => *len as usize,
+        }
+    }
+
+    /// 扫描步长/起始地址使用的对齐，与值大小相互独立
+    ///
+    /// 所有 stride 与 first_addr 计算都应走这里，而不是直接使用 `size()`
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        match self {
+            ValueType::Byte => 1,
+            ValueType::Word => 2,
+            ValueType::Dword => 4,
+            ValueType::Qword => 8,
+            ValueType::Float => 4,
+            ValueType::Double => 8,
+            ValueType::Auto => 4,
+            ValueType::Xor => 4,
+            // GUID 等结构只保证 4 字节对齐
+            ValueType::Oword => 4,
+            // 代码与数据中的特征码不保证任何对齐
+            ValueType::Bytes(_) => 1,
+            ValueType::Text(_, TextEncoding::Utf8) => 1,
+            ValueType::Text(_, TextEncoding::Utf16Le) => 2,
+        }
+    }
+
+    /// 默认是否扫描只读区域；数值类型的值几乎不会出现在只读段，默认跳过，
+    /// GUID 等常量、特征码和字符串字面量则常位于 `.rodata` 等只读段
+    #[inline]
+    pub fn prefers_readonly_regions(&self) -> bool {
+        match self {
+            ValueType::Oword | ValueType::Bytes(_) | ValueType::Text(..) => true,
+            ValueType::Byte
+            | ValueType::Word
+            | ValueType::Dword
+            | ValueType::Qword
+            | ValueType::Float
+            | ValueType::Double
+            | ValueType::Auto
+            | ValueType::Xor => false,
         }
     }
 
@@ -89,33 +201,162 @@ impl fmt::Display for ValueType {
             ValueType::Double => write!(f, "Double"),
             ValueType::Auto => write!(f, "Auto"),
             ValueType::Xor => write!(f, "Xor"),
+            ValueType::Oword => write!(f, "Oword"),
+            ValueType::Bytes(len) => write!(f, "Bytes[{}]", len),
+            ValueType::Text(len, TextEncoding::Utf8) => write!(f, "Utf8[{}]", len),
+            ValueType::Text(len, TextEncoding::Utf16Le) => write!(f, "Utf16Le[{}]", len),
         }
     }
 }
 
+/// 精确浮点搜索的容差
+///
+/// 比较在值类型自身的精度下进行：Float 先把目标值舍入为 f32，再与内存中的 f32 比较。
+/// 相等（包括 0.0 与 -0.0、同号无穷）总是匹配，NaN 从不匹配。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatTolerance {
+    /// `|a - b| <= epsilon`，适合数值范围已知且不大的值
+    Absolute(f64),
+    /// 两个值之间可表示浮点数的个数不超过给定值，与数量级无关
+    RelativeUlps(u32),
+    /// `|a - b| <= fraction * max(|a|, |b|)`
+    RelativeFraction(f64),
+}
+
+/// 默认按值类型精度舍入后精确比较：十进制输入的舍入误差已由舍入吸收，组搜索也能用字节做 anchor
+impl Default for FloatTolerance {
+    fn default() -> Self {
+        FloatTolerance::EXACT
+    }
+}
+
+impl FloatTolerance {
+    /// 只匹配同一个浮点数，组搜索只用这种容差的浮点值做 anchor
+    pub const EXACT: FloatTolerance = FloatTolerance::RelativeUlps(0);
+
+    #[inline]
+    pub fn is_exact(&self) -> bool {
+        *self == Self::EXACT
+    }
+
+    /// `actual` 与目标值 `expected` 是否在容差内，`value_type` 决定比较精度
+    pub fn matches(&self, expected: f64, actual: f64, value_type: ValueType) -> bool {
+        let expected = if value_type == ValueType::Float { expected as f32 as f64 } else { expected };
+        if expected == actual {
+            return true;
+        }
+        if expected.is_nan() || actual.is_nan() {
+            return false;
+        }
+
+        match *self {
+            FloatTolerance::Absolute(epsilon) => (expected - actual).abs() <= epsilon,
+            FloatTolerance::RelativeFraction(fraction) => (expected - actual).abs() <= fraction * expected.abs().max(actual.abs()),
+            FloatTolerance::RelativeUlps(ulps) => {
+                let distance = if value_type == ValueType::Float {
+                    ulp_distance_f32(expected as f32, actual as f32)
+                } else {
+                    ulp_distance_f64(expected, actual)
+                };
+                distance <= ulps as u64
+            },
+        }
+    }
+
+    fn hash_bits(&self) -> (u8, u64) {
+        match *self {
+            FloatTolerance::Absolute(epsilon) => (0, epsilon.to_bits()),
+            FloatTolerance::RelativeUlps(ulps) => (1, ulps as u64),
+            FloatTolerance::RelativeFraction(fraction) => (2, fraction.to_bits()),
+        }
+    }
+}
+
+/// 把位模式映射成随数值单调递增的整数，相邻浮点数相差 1，0.0 与 -0.0 都映射为 0
+#[inline]
+fn ordered_bits_f32(value: f32) -> i64 {
+    let bits = value.to_bits() as i32 as i64;
+    if bits < 0 { i32::MIN as i64 - bits } else { bits }
+}
+
+#[inline]
+fn ordered_bits_f64(value: f64) -> i128 {
+    let bits = value.to_bits() as i64 as i128;
+    if bits < 0 { i64::MIN as i128 - bits } else { bits }
+}
+
+/// 两个 f32 之间相隔的可表示浮点数个数
+pub fn ulp_distance_f32(a: f32, b: f32) -> u64 {
+    ordered_bits_f32(a).abs_diff(ordered_bits_f32(b))
+}
+
+/// 两个 f64 之间相隔的可表示浮点数个数，超过 u64 范围时饱和
+pub fn ulp_distance_f64(a: f64, b: f64) -> u64 {
+    u64::try_from(ordered_bits_f64(a).abs_diff(ordered_bits_f64(b))).unwrap_or(u64::MAX)
+}
+
+/// `value` 是否在 `start` 与 `end` 之间，两端按 `lower_inclusive`/`upper_inclusive` 决定是否包含
+#[inline]
+fn in_bounds<T: PartialOrd>(value: T, start: T, end: T, lower_inclusive: bool, upper_inclusive: bool) -> bool {
+    let above = if lower_inclusive { value >= start } else { value > start };
+    let below = if upper_inclusive { value <= end } else { value < end };
+    above && below
+}
+
 #[derive(Debug, Clone)]
 pub enum SearchValue {
-    /// 精确值搜索，存储实际字节表示
+    /// 精确值搜索，存储实际字节表示（已按 `byte_order` 编码）
     FixedInt {
         value: [u8; 16],
         value_type: ValueType,
+        byte_order: ByteOrder,
     },
     FixedFloat {
         value: f64,
         value_type: ValueType,
+        tolerance: FloatTolerance,
+        byte_order: ByteOrder,
     },
     /// 范围搜索，存储起始和结束的字节表示
+    ///
+    /// `lower_inclusive`/`upper_inclusive` 决定边界是否包含在内，`exclude` 为 true 时匹配范围之外的值
     RangeInt {
         start: i128,
         end: i128,
         value_type: ValueType,
         exclude: bool,
+        lower_inclusive: bool,
+        upper_inclusive: bool,
+        byte_order: ByteOrder,
     },
     RangeFloat {
         start: f64,
         end: f64,
         value_type: ValueType,
         exclude: bool,
+        lower_inclusive: bool,
+        upper_inclusive: bool,
+        byte_order: ByteOrder,
+    },
+    /// 匹配任意值，用于未知初始值的快照扫描：记录该类型每个对齐位置
+    Any {
+        value_type: ValueType,
+    },
+    /// 不等于给定值，几乎匹配所有位置，只能走线性扫描，主要用于组搜索中要求某个字段不为某值
+    NotEqual {
+        value: i128,
+        value_type: ValueType,
+        byte_order: ByteOrder,
+    },
+    /// 特征码，`mask[i] == false` 的字节为通配符，与 `pattern` 等长
+    Bytes {
+        pattern: Vec<u8>,
+        mask: Vec<bool>,
+    },
+    /// 字符串，`bytes` 为按 `encoding` 编码后的内容，不含结尾的 0
+    Text {
+        bytes: Vec<u8>,
+        encoding: TextEncoding,
     },
 }
 
@@ -125,14 +366,27 @@ impl SearchValue {
         SearchValue::FixedInt {
             value: i128::to_le_bytes(value),
             value_type,
+            byte_order: ByteOrder::Little,
         }
     }
 
+    /// 使用默认容差，即舍入到 `value_type` 精度后精确比较
     #[inline]
     pub fn fixed_float(value: f64, value_type: ValueType) -> Self {
-        SearchValue::FixedFloat { value, value_type }
+        Self::fixed_float_with_tolerance(value, value_type, FloatTolerance::default())
+    }
+
+    #[inline]
+    pub fn fixed_float_with_tolerance(value: f64, value_type: ValueType, tolerance: FloatTolerance) -> Self {
+        SearchValue::FixedFloat {
+            value,
+            value_type,
+            tolerance,
+            byte_order: ByteOrder::Little,
+        }
     }
 
+    /// 闭区间 `[start, end]`，`exclude` 为 true 时匹配区间之外的值
     #[inline]
     pub fn range(start: i128, end: i128, value_type: ValueType, exclude: bool) -> Self {
         SearchValue::RangeInt {
@@ -140,6 +394,9 @@ impl SearchValue {
             end,
             value_type,
             exclude,
+            lower_inclusive: true,
+            upper_inclusive: true,
+            byte_order: ByteOrder::Little,
         }
     }
 
@@ -150,7 +407,111 @@ impl SearchValue {
             end,
             value_type,
             exclude,
+            lower_inclusive: true,
+            upper_inclusive: true,
+            byte_order: ByteOrder::Little,
+        }
+    }
+
+    /// 设置范围两端是否包含边界，非范围值不受影响
+    pub fn with_bounds(mut self, lower: bool, upper: bool) -> Self {
+        match &mut self {
+            SearchValue::RangeInt {
+                lower_inclusive,
+                upper_inclusive,
+                ..
+            }
+            | SearchValue::RangeFloat {
+                lower_inclusive,
+                upper_inclusive,
+                ..
+            } => {
+                *lower_inclusive = lower;
+                *upper_inclusive = upper;
+            },
+            _ => {},
         }
+        self
+    }
+
+    /// 翻转范围的匹配结果（区间内 <-> 区间外），非范围值不受影响
+    pub fn inverted(mut self) -> Self {
+        if let SearchValue::RangeInt { exclude, .. } | SearchValue::RangeFloat { exclude, .. } = &mut self {
+            *exclude = !*exclude;
+        }
+        self
+    }
+
+    #[inline]
+    pub fn any(value_type: ValueType) -> Self {
+        SearchValue::Any { value_type }
+    }
+
+    #[inline]
+    pub fn not_equal(value: i128, value_type: ValueType) -> Self {
+        SearchValue::NotEqual {
+            value,
+            value_type,
+            byte_order: ByteOrder::Little,
+        }
+    }
+
+    /// 特征码，长度不能超过 [`MAX_BYTE_PATTERN_LEN`]
+    #[inline]
+    pub fn byte_pattern(pattern: Vec<u8>, mask: Vec<bool>) -> Self {
+        debug_assert_eq!(pattern.len(), mask.len());
+        debug_assert!(pattern.len() <= MAX_BYTE_PATTERN_LEN);
+        SearchValue::Bytes { pattern, mask }
+    }
+
+    /// 字符串，编码后不能超过 [`MAX_BYTE_PATTERN_LEN`] 字节
+    #[inline]
+    pub fn text(text: &str, encoding: TextEncoding) -> Self {
+        let bytes = encoding.encode(text);
+        debug_assert!(!bytes.is_empty() && bytes.len() <= MAX_BYTE_PATTERN_LEN);
+        SearchValue::Text { bytes, encoding }
+    }
+
+    /// 按 `order` 字节序比较，特征码、字符串与 Any 不受影响
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        match &mut self {
+            SearchValue::FixedInt { value, value_type, byte_order } => {
+                if *byte_order != order {
+                    value[..value_type.size()].reverse();
+                    *byte_order = order;
+                }
+            },
+            SearchValue::FixedFloat { byte_order, .. }
+            | SearchValue::RangeInt { byte_order, .. }
+            | SearchValue::RangeFloat { byte_order, .. }
+            | SearchValue::NotEqual { byte_order, .. } => *byte_order = order,
+            SearchValue::Any { .. } | SearchValue::Bytes { .. } | SearchValue::Text { .. } => {},
+        }
+        self
+    }
+
+    #[inline]
+    pub fn byte_order(&self) -> ByteOrder {
+        match self {
+            SearchValue::FixedInt { byte_order, .. }
+            | SearchValue::FixedFloat { byte_order, .. }
+            | SearchValue::RangeInt { byte_order, .. }
+            | SearchValue::RangeFloat { byte_order, .. }
+            | SearchValue::NotEqual { byte_order, .. } => *byte_order,
+            SearchValue::Any { .. } | SearchValue::Bytes { .. } | SearchValue::Text { .. } => ByteOrder::Little,
+        }
+    }
+
+    /// 大端值把 `other` 开头的一个值转成小端放进 `swapped` 再比较；FixedInt 已按字节序编码，原样返回
+    #[inline]
+    fn little_endian<'a>(&self, other: &'a [u8], swapped: &'a mut [u8; 16]) -> &'a [u8] {
+        let size = self.value_type().size();
+        if self.byte_order() == ByteOrder::Little || self.is_fixed_int() || other.len() < size || size > swapped.len() {
+            return other;
+        }
+        swapped[..size].copy_from_slice(&other[..size]);
+        swapped[..size].reverse();
+        &swapped[..size]
     }
 
     #[inline]
@@ -160,6 +521,10 @@ impl SearchValue {
             SearchValue::RangeInt { value_type, .. } => *value_type,
             SearchValue::FixedFloat { value_type, .. } => *value_type,
             SearchValue::RangeFloat { value_type, .. } => *value_type,
+            SearchValue::Any { value_type } => *value_type,
+            SearchValue::NotEqual { value_type, .. } => *value_type,
+            SearchValue::Bytes { pattern, .. } => ValueType::Bytes(pattern.len() as u8),
+            SearchValue::Text { bytes, encoding } => ValueType::Text(bytes.len() as u8, *encoding),
         }
     }
 
@@ -178,28 +543,38 @@ impl SearchValue {
         matches!(self, SearchValue::RangeFloat { .. } | SearchValue::RangeInt { .. })
     }
 
+    #[inline]
+    pub fn is_any(&self) -> bool {
+        matches!(self, SearchValue::Any { .. })
+    }
+
     #[inline]
     pub fn bytes(&self) -> anyhow::Result<&[u8]> {
         match self {
-            SearchValue::FixedInt { value, value_type } => {
+            SearchValue::FixedInt { value, value_type, .. } => {
                 let size = value_type.size();
                 Ok(&value[..size])
             },
+            // 不含通配符的特征码可以整体按字节比较
+            SearchValue::Bytes { pattern, mask } if mask.iter().all(|&fixed| fixed) => Ok(pattern),
+            SearchValue::Text { bytes, .. } => Ok(bytes),
             _ => Err(anyhow!("unsupported value type to get bytes: {:?}", self)),
         }
     }
 
     #[inline]
     pub fn matched(&self, other: &[u8]) -> anyhow::Result<bool> {
+        let mut swapped = [0u8; 16];
+        let other = self.little_endian(other, &mut swapped);
         match self {
-            SearchValue::FixedInt { value, value_type } => {
+            SearchValue::FixedInt { value, value_type, .. } => {
                 let size = value_type.size();
                 if other.len() < size {
                     return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", size, other.len()));
                 }
                 Ok(&value[..size] == &other[..size])
             },
-            SearchValue::FixedFloat { value, value_type } => {
+            SearchValue::FixedFloat { value, value_type, tolerance, .. } => {
                 let size = value_type.size();
                 if other.len() < size {
                     return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", size, other.len()));
@@ -215,13 +590,16 @@ impl SearchValue {
                     },
                     _ => return Err(anyhow!("Invalid float size: {}", size)),
                 };
-                Ok((*value - other_value).abs() < f64::EPSILON)
+                Ok(tolerance.matches(*value, other_value, *value_type))
             },
             SearchValue::RangeInt {
                 start,
                 end,
                 value_type,
                 exclude,
+                lower_inclusive,
+                upper_inclusive,
+                ..
             } => {
                 let size = value_type.size();
                 if other.len() < size {
@@ -247,17 +625,16 @@ impl SearchValue {
                     },
                     _ => return Err(anyhow!("Invalid integer size: {}", size)),
                 };
-                if *exclude {
-                    Ok(other_value < *start || other_value > *end)
-                } else {
-                    Ok(other_value >= *start && other_value <= *end)
-                }
+                Ok(in_bounds(other_value, *start, *end, *lower_inclusive, *upper_inclusive) != *exclude)
             },
             SearchValue::RangeFloat {
                 start,
                 end,
                 value_type,
                 exclude,
+                lower_inclusive,
+                upper_inclusive,
+                ..
             } => {
                 let size = value_type.size();
                 if other.len() < size {
@@ -274,20 +651,142 @@ impl SearchValue {
                     },
                     _ => return Err(anyhow!("Invalid float size: {}", size)),
                 };
-                if *exclude {
-                    Ok(other_value < *start || other_value > *end)
-                } else {
-                    Ok(other_value >= *start && other_value <= *end)
+                // NaN 既不在范围内也不在范围外
+                if other_value.is_nan() {
+                    return Ok(false);
                 }
+                Ok(in_bounds(other_value, *start, *end, *lower_inclusive, *upper_inclusive) != *exclude)
+            },
+            SearchValue::Any { value_type } => {
+                let size = value_type.size();
+                if other.len() < size {
+                    return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", size, other.len()));
+                }
+                Ok(true)
+            },
+            SearchValue::NotEqual { value, value_type, .. } => {
+                let size = value_type.size();
+                if other.len() < size {
+                    return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", size, other.len()));
+                }
+                // 按类型宽度截断后比较，有符号与无符号写法的同一个值等价
+                Ok(value.to_le_bytes()[..size] != other[..size])
+            },
+            SearchValue::Bytes { pattern, mask } => {
+                if other.len() < pattern.len() {
+                    return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", pattern.len(), other.len()));
+                }
+                Ok(pattern.iter().zip(mask).zip(other).all(|((p, &fixed), o)| !fixed || p == o))
+            },
+            SearchValue::Text { bytes, .. } => {
+                if other.len() < bytes.len() {
+                    return Err(anyhow!("Input slice too small: expected at least {} bytes, got {}", bytes.len(), other.len()));
+                }
+                Ok(other[..bytes.len()] == bytes[..])
             },
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// 浮点按位模式比较：NaN 等于自身，0.0 与 -0.0 不相等，保证 Eq 与 Hash 一致，可用作缓存键
+impl PartialEq for SearchValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                SearchValue::FixedInt { value: a, value_type: ta, byte_order: oa },
+                SearchValue::FixedInt { value: b, value_type: tb, byte_order: ob },
+            ) => a == b && ta == tb && oa == ob,
+            (
+                SearchValue::FixedFloat { value: a, value_type: ta, tolerance: xa, byte_order: oa },
+                SearchValue::FixedFloat { value: b, value_type: tb, tolerance: xb, byte_order: ob },
+            ) => a.to_bits() == b.to_bits() && ta == tb && xa.hash_bits() == xb.hash_bits() && oa == ob,
+            (
+                SearchValue::RangeInt { start: sa, end: ea, value_type: ta, exclude: xa, lower_inclusive: la, upper_inclusive: ua, byte_order: oa },
+                SearchValue::RangeInt { start: sb, end: eb, value_type: tb, exclude: xb, lower_inclusive: lb, upper_inclusive: ub, byte_order: ob },
+            ) => sa == sb && ea == eb && ta == tb && xa == xb && la == lb && ua == ub && oa == ob,
+            (
+                SearchValue::RangeFloat { start: sa, end: ea, value_type: ta, exclude: xa, lower_inclusive: la, upper_inclusive: ua, byte_order: oa },
+                SearchValue::RangeFloat { start: sb, end: eb, value_type: tb, exclude: xb, lower_inclusive: lb, upper_inclusive: ub, byte_order: ob },
+            ) => sa.to_bits() == sb.to_bits() && ea.to_bits() == eb.to_bits() && ta == tb && xa == xb && la == lb && ua == ub && oa == ob,
+            (SearchValue::Any { value_type: ta }, SearchValue::Any { value_type: tb }) => ta == tb,
+            (
+                SearchValue::NotEqual { value: a, value_type: ta, byte_order: oa },
+                SearchValue::NotEqual { value: b, value_type: tb, byte_order: ob },
+            ) => a == b && ta == tb && oa == ob,
+            (SearchValue::Bytes { pattern: pa, mask: ma }, SearchValue::Bytes { pattern: pb, mask: mb }) => pa == pb && ma == mb,
+            (SearchValue::Text { bytes: a, encoding: ea }, SearchValue::Text { bytes: b, encoding: eb }) => a == b && ea == eb,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SearchValue {}
+
+impl Hash for SearchValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            SearchValue::FixedInt { value, value_type, byte_order } => {
+                value.hash(state);
+                value_type.hash(state);
+                byte_order.hash(state);
+            },
+            SearchValue::FixedFloat { value, value_type, tolerance, byte_order } => {
+                value.to_bits().hash(state);
+                value_type.hash(state);
+                tolerance.hash_bits().hash(state);
+                byte_order.hash(state);
+            },
+            SearchValue::RangeInt { start, end, value_type, exclude, lower_inclusive, upper_inclusive, byte_order } => {
+                start.hash(state);
+                end.hash(state);
+                value_type.hash(state);
+                exclude.hash(state);
+                lower_inclusive.hash(state);
+                upper_inclusive.hash(state);
+                byte_order.hash(state);
+            },
+            SearchValue::RangeFloat { start, end, value_type, exclude, lower_inclusive, upper_inclusive, byte_order } => {
+                start.to_bits().hash(state);
+                end.to_bits().hash(state);
+                value_type.hash(state);
+                exclude.hash(state);
+                lower_inclusive.hash(state);
+                upper_inclusive.hash(state);
+                byte_order.hash(state);
+            },
+            SearchValue::Any { value_type } => value_type.hash(state),
+            SearchValue::NotEqual { value, value_type, byte_order } => {
+                value.hash(state);
+                value_type.hash(state);
+                byte_order.hash(state);
+            },
+            SearchValue::Bytes { pattern, mask } => {
+                pattern.hash(state);
+                mask.hash(state);
+            },
+            SearchValue::Text { bytes, encoding } => {
+                bytes.hash(state);
+                encoding.hash(state);
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SearchMode {
     Unordered,
     Ordered,
+    /// 按顺序且紧密排列（packed struct），每个值必须紧跟在上一个值之后
+    OrderedContiguous,
+}
+
+impl SearchMode {
+    /// Ordered 与 OrderedContiguous 都以第一个值为序列起点
+    #[inline]
+    pub fn is_ordered(&self) -> bool {
+        matches!(self, SearchMode::Ordered | SearchMode::OrderedContiguous)
+    }
 }
 
 /// 模糊搜索条件 - 用于未知值搜索
@@ -342,17 +841,280 @@ impl FuzzyCondition {
     }
 }
 
+/// 未知初始值快照之后的变化比较，旧值为上一次快照或细化记录的值
+///
+/// 只是 [`FuzzyCondition`] 中变化条件的子集，见 [`Self::to_condition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaOp {
+    Increased,
+    Decreased,
+    Changed,
+    Unchanged,
+    /// 恰好增加指定数量
+    IncreasedBy(i128),
+    /// 恰好减少指定数量
+    DecreasedBy(i128),
+}
+
+impl DeltaOp {
+    /// 对应的模糊搜索条件，数量超出 i64 时为 None（快照只记录 8 字节以内的值）
+    pub fn to_condition(self) -> Option<FuzzyCondition> {
+        Some(match self {
+            DeltaOp::Increased => FuzzyCondition::Increased,
+            DeltaOp::Decreased => FuzzyCondition::Decreased,
+            DeltaOp::Changed => FuzzyCondition::Changed,
+            DeltaOp::Unchanged => FuzzyCondition::Unchanged,
+            DeltaOp::IncreasedBy(amount) => FuzzyCondition::IncreasedBy(i64::try_from(amount).ok()?),
+            DeltaOp::DecreasedBy(amount) => FuzzyCondition::DecreasedBy(i64::try_from(amount).ok()?),
+        })
+    }
+}
+
+/// 组内两个值之间的比较关系
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// 组内值之间的关系约束：`values[left] op values[right]`
+///
+/// 用于"两个未知但相等的字段"或"A < B"这类查询。普通组搜索先按各值自身条件确定位置，
+/// 再按匹配位置解码出的值检查约束，不满足则整组不匹配
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RelConstraint {
+    pub left: usize,
+    pub op: RelOp,
+    pub right: usize,
+}
+
+/// 按类型解码后的数值，整数按有符号解释
+#[derive(Debug, Clone, Copy)]
+enum Numeric {
+    Int(i128),
+    Float(f64),
+}
+
+impl Numeric {
+    fn decode(bytes: &[u8], value_type: ValueType) -> Option<Self> {
+        let bytes = bytes.get(..value_type.size())?;
+        Some(match value_type {
+            ValueType::Float => Numeric::Float(f32::from_le_bytes(bytes.try_into().ok()?) as f64),
+            ValueType::Double => Numeric::Float(f64::from_le_bytes(bytes.try_into().ok()?)),
+            ValueType::Byte => Numeric::Int(bytes[0] as i8 as i128),
+            ValueType::Word => Numeric::Int(i16::from_le_bytes(bytes.try_into().ok()?) as i128),
+            ValueType::Dword | ValueType::Auto | ValueType::Xor => Numeric::Int(i32::from_le_bytes(bytes.try_into().ok()?) as i128),
+            ValueType::Qword => Numeric::Int(i64::from_le_bytes(bytes.try_into().ok()?) as i128),
+            ValueType::Oword => Numeric::Int(i128::from_le_bytes(bytes.try_into().ok()?)),
+            // 特征码与字符串不是数值，不参与关系约束
+            ValueType::Bytes(_) | ValueType::Text(..) => return None,
+        })
+    }
+
+    fn partial_cmp(self, other: Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Numeric::Int(a), Numeric::Int(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Numeric::Int(v) => v as f64,
+            Numeric::Float(v) => v,
+        }
+    }
+}
+
+impl RelConstraint {
+    pub fn new(left: usize, op: RelOp, right: usize) -> Self {
+        RelConstraint { left, op, right }
+    }
+
+    /// 检查两侧字节按各自类型解码后是否满足关系；NaN 与任何值比较只满足 `Ne`
+    pub fn holds(&self, left: &[u8], left_type: ValueType, right: &[u8], right_type: ValueType) -> bool {
+        let (Some(a), Some(b)) = (Numeric::decode(left, left_type), Numeric::decode(right, right_type)) else {
+            return false;
+        };
+        match a.partial_cmp(b) {
+            Some(ordering) => match self.op {
+                RelOp::Eq => ordering.is_eq(),
+                RelOp::Ne => ordering.is_ne(),
+                RelOp::Lt => ordering.is_lt(),
+                RelOp::Le => ordering.is_le(),
+                RelOp::Gt => ordering.is_gt(),
+                RelOp::Ge => ordering.is_ge(),
+            },
+            None => self.op == RelOp::Ne,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchQuery {
     pub values: Vec<SearchValue>,
     pub mode: SearchMode,
     pub range: u16,
+    /// 是否扫描只读区域，None 时按值类型决定（见 [`SearchQuery::include_readonly`]）
+    pub include_readonly: Option<bool>,
+    /// anchor 候选数占窗口字节数的比例上限，超过则该窗口改走逐地址扫描，None 时使用默认值
+    pub anchor_skip_ratio: Option<f64>,
+    /// 值之间的关系约束，见 [`RelConstraint`]
+    pub constraints: Vec<RelConstraint>,
+    /// 对象对齐：只检查 `(addr - field_offset) % object_alignment == 0` 的地址，0 或 1 表示不限制
+    ///
+    /// addr 为第一个值的地址，用于分配器把对象按 16 字节等粒度对齐时跳过错位的候选
+    pub object_alignment: usize,
+    /// 第一个值在对象内的偏移，与 `object_alignment` 配合使用
+    pub field_offset: usize,
+    /// 扫描结束后立即重新读取并校验每个结果，丢弃不再匹配的（例如读到撕裂的值造成的误报）
+    ///
+    /// 相当于扫描后自动做一次精炼，会额外读取一遍结果所在的内存，默认关闭
+    pub verify_after_scan: bool,
+    /// 按区域顺序扫描，第一个有结果的区域扫描完后立即停止，只返回该区域的结果
+    ///
+    /// 用于只想知道值是否已加载的场景，配合区域排序先扫描最可能的区域；区域改为逐个扫描，默认关闭
+    pub stop_after_first_hit_region: bool,
+}
+
+impl PartialEq for SearchQuery {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+            && self.mode == other.mode
+            && self.range == other.range
+            && self.include_readonly == other.include_readonly
+            && self.anchor_skip_ratio.map(f64::to_bits) == other.anchor_skip_ratio.map(f64::to_bits)
+            && self.constraints == other.constraints
+            && self.object_alignment == other.object_alignment
+            && self.field_offset == other.field_offset
+            && self.verify_after_scan == other.verify_after_scan
+            && self.stop_after_first_hit_region == other.stop_after_first_hit_region
+    }
+}
+
+impl Eq for SearchQuery {}
+
+impl Hash for SearchQuery {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.hash(state);
+        self.mode.hash(state);
+        self.range.hash(state);
+        self.include_readonly.hash(state);
+        self.anchor_skip_ratio.map(f64::to_bits).hash(state);
+        self.constraints.hash(state);
+        self.object_alignment.hash(state);
+        self.field_offset.hash(state);
+        self.verify_after_scan.hash(state);
+        self.stop_after_first_hit_region.hash(state);
+    }
 }
 
+/// 默认 anchor 放弃阈值：每 32 字节超过 1 个候选即认为 anchor 过于常见
+pub const DEFAULT_ANCHOR_SKIP_RATIO: f64 = 1.0 / 32.0;
+
 impl SearchQuery {
     #[inline]
     pub fn new(values: Vec<SearchValue>, mode: SearchMode, range: u16) -> Self {
-        SearchQuery { values, mode, range }
+        SearchQuery {
+            values,
+            mode,
+            range,
+            include_readonly: None,
+            anchor_skip_ratio: None,
+            constraints: Vec::new(),
+            object_alignment: 0,
+            field_offset: 0,
+            verify_after_scan: false,
+            stop_after_first_hit_region: false,
+        }
+    }
+
+    /// 添加关系约束 `values[left] op values[right]`
+    pub fn with_constraint(mut self, left: usize, op: RelOp, right: usize) -> Self {
+        self.constraints.push(RelConstraint::new(left, op, right));
+        self
+    }
+
+    /// 按各值的匹配偏移检查所有关系约束，`offsets[i]` 是 `values[i]` 在 `buffer` 中的位置
+    pub fn constraints_hold(&self, buffer: &[u8], offsets: &[usize]) -> bool {
+        self.constraints.iter().all(|c| {
+            let (Some(&left), Some(&right)) = (offsets.get(c.left), offsets.get(c.right)) else {
+                return false;
+            };
+            let (left_value, right_value) = (&self.values[c.left], &self.values[c.right]);
+            let (mut left_swapped, mut right_swapped) = ([0u8; 16], [0u8; 16]);
+            c.holds(
+                left_value.little_endian(&buffer[left..], &mut left_swapped),
+                left_value.value_type(),
+                right_value.little_endian(&buffer[right..], &mut right_swapped),
+                right_value.value_type(),
+            )
+        })
+    }
+
+    /// 所有值按 `order` 字节序比较，见 [`ByteOrder`]
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        self.values = self.values.into_iter().map(|value| value.with_byte_order(order)).collect();
+        self
+    }
+
+    /// 显式指定是否扫描只读区域
+    pub fn with_include_readonly(mut self, include: bool) -> Self {
+        self.include_readonly = Some(include);
+        self
+    }
+
+    /// 指定 anchor 放弃阈值（候选数 / 窗口字节数），>= 1.0 等价于从不放弃
+    pub fn with_anchor_skip_ratio(mut self, ratio: f64) -> Self {
+        self.anchor_skip_ratio = Some(ratio);
+        self
+    }
+
+    /// 只在对象对齐网格上搜索：第一个值位于按 `object_alignment` 对齐的对象内 `field_offset` 处
+    pub fn with_object_alignment(mut self, object_alignment: usize, field_offset: usize) -> Self {
+        self.object_alignment = object_alignment;
+        self.field_offset = field_offset;
+        self
+    }
+
+    /// 扫描结束后重新校验所有结果，见 [`Self::verify_after_scan`]
+    pub fn with_verify_after_scan(mut self, verify: bool) -> Self {
+        self.verify_after_scan = verify;
+        self
+    }
+
+    /// 扫描到第一个有结果的区域后停止，见 [`Self::stop_after_first_hit_region`]
+    pub fn with_stop_after_first_hit_region(mut self, stop: bool) -> Self {
+        self.stop_after_first_hit_region = stop;
+        self
+    }
+
+    #[inline]
+    pub fn has_object_grid(&self) -> bool {
+        self.object_alignment > 1
+    }
+
+    /// `addr` 作为第一个值的地址是否落在对象对齐网格上，未设置对象对齐时总为 true
+    #[inline]
+    pub fn on_object_grid(&self, addr: u64) -> bool {
+        let alignment = self.object_alignment as u64;
+        !self.has_object_grid() || addr % alignment == self.field_offset as u64 % alignment
+    }
+
+    /// 当前窗口允许的最大 anchor 候选数，超过即回退到逐地址扫描
+    pub fn anchor_candidate_limit(&self, window_len: usize) -> usize {
+        let ratio = self.anchor_skip_ratio.unwrap_or(DEFAULT_ANCHOR_SKIP_RATIO).max(0.0);
+        (window_len as f64 * ratio) as usize
+    }
+
+    /// 是否扫描只读区域：显式设置优先，否则任一值偏好只读区域时包含
+    pub fn include_readonly(&self) -> bool {
+        self.include_readonly
+            .unwrap_or_else(|| self.values.iter().any(|v| v.value_type().prefers_readonly_regions()))
     }
 
     pub fn total_size(&self) -> usize {
@@ -378,9 +1140,262 @@ impl SearchQuery {
             return Err("Range must be at least 2 for group search".to_string());
         }
 
+        for c in &self.constraints {
+            if c.left >= self.values.len() || c.right >= self.values.len() || c.left == c.right {
+                return Err(format!("Invalid constraint between values {} and {}", c.left, c.right));
+            }
+        }
+
+        if self.has_mixed_alignment() {
+            warn!(
+                "Group query mixes alignments {:?}: candidates step by the smallest alignment and each value is matched at its own absolute alignment; \
+                 values stored at a smaller alignment than their type (e.g. packed 4-aligned QWORD) need an alignment override",
+                self.values.iter().map(|v| v.value_type().alignment()).collect::<Vec<_>>()
+            );
+        }
+
         Ok(())
     }
+
+    /// 组内值的对齐是否不一致（如 DWORD 与 QWORD 混合）
+    ///
+    /// 混合对齐时候选起点按最小对齐步进，每个值再按自身类型在绝对地址上对齐匹配。
+    /// 若目标结构是打包布局（大类型没有按自身大小对齐），推荐使用对齐覆盖把所有值统一到最小对齐。
+    pub fn has_mixed_alignment(&self) -> bool {
+        let mut alignments = self.values.iter().map(|v| v.value_type().alignment());
+        match alignments.next() {
+            Some(first) => alignments.any(|a| a != first),
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_value_type_alignment_table() {
+        let expected = [
+            (ValueType::Byte, 1),
+            (ValueType::Word, 2),
+            (ValueType::Dword, 4),
+            (ValueType::Qword, 8),
+            (ValueType::Float, 4),
+            (ValueType::Double, 8),
+            (ValueType::Auto, 4),
+            (ValueType::Xor, 4),
+            (ValueType::Oword, 4),
+            (ValueType::Bytes(8), 1),
+            (ValueType::Text(5, TextEncoding::Utf8), 1),
+            (ValueType::Text(10, TextEncoding::Utf16Le), 2),
+        ];
+        for (value_type, alignment) in expected {
+            assert_eq!(value_type.alignment(), alignment, "{}", value_type);
+            assert!(alignment.is_power_of_two());
+            assert!(alignment <= value_type.size());
+        }
+    }
+
+    #[test]
+    fn test_byte_pattern_wildcards() {
+        let value = SearchValue::byte_pattern(vec![0x48, 0x8B, 0, 0, 0x89], vec![true, true, false, false, true]);
+        assert_eq!(value.value_type(), ValueType::Bytes(5));
+        assert!(value.matched(&[0x48, 0x8B, 0x12, 0x34, 0x89]).unwrap());
+        assert!(value.matched(&[0x48, 0x8B, 0xFF, 0xFF, 0x89, 0x00]).unwrap());
+        assert!(!value.matched(&[0x48, 0x8C, 0x12, 0x34, 0x89]).unwrap());
+        assert!(value.matched(&[0x48, 0x8B, 0x12, 0x34]).is_err());
+        // 含通配符时不能按字节整体比较
+        assert!(value.bytes().is_err());
+
+        for len in [1u8, 4, MAX_BYTE_PATTERN_LEN as u8] {
+            let value_type = ValueType::Bytes(len);
+            assert_eq!(ValueType::from_id(value_type.to_id()), Some(value_type));
+            assert_eq!(value_type.size(), len as usize);
+        }
+        assert_eq!(ValueType::from_id(BYTES_ID_BASE), None);
+    }
+
+    #[test]
+    fn test_text_value_encoding() {
+        let utf8 = SearchValue::text("Hero", TextEncoding::Utf8);
+        assert_eq!(utf8.value_type(), ValueType::Text(4, TextEncoding::Utf8));
+        assert!(utf8.matched(b"Hero\0").unwrap());
+        assert!(!utf8.matched(b"hero").unwrap());
+
+        let utf16 = SearchValue::text("Hé", TextEncoding::Utf16Le);
+        assert_eq!(utf16.bytes().unwrap(), &[b'H', 0, 0xE9, 0]);
+        assert_eq!(utf16.value_type().size(), 4);
+        assert_eq!(utf16.value_type().alignment(), 2);
+        assert_ne!(utf16, SearchValue::text("Hé", TextEncoding::Utf8));
+        assert_eq!(TextEncoding::Utf16Le.decode(utf16.bytes().unwrap()), "Hé");
+
+        // 字符串有自己的 id，保存后读回仍带编码，且默认扫描只读区域
+        for encoding in [TextEncoding::Utf8, TextEncoding::Utf16Le] {
+            for len in [1u8, 4, MAX_BYTE_PATTERN_LEN as u8] {
+                let value_type = ValueType::Text(len, encoding);
+                assert_eq!(ValueType::from_id(value_type.to_id()), Some(value_type));
+                assert_ne!(value_type.to_id(), ValueType::Bytes(len).to_id());
+            }
+        }
+        assert_eq!(ValueType::from_id(TEXT_ID_BASE), None);
+        assert!(utf8.value_type().prefers_readonly_regions());
+    }
+
+    #[test]
+    fn test_range_bounds_and_invert() {
+        let open = SearchValue::range(10, 20, ValueType::Byte, false).with_bounds(false, false);
+        assert!(!open.matched(&[10]).unwrap());
+        assert!(open.matched(&[11]).unwrap());
+        assert!(!open.matched(&[20]).unwrap());
+
+        let outside = open.clone().inverted();
+        assert!(outside.matched(&[10]).unwrap());
+        assert!(outside.matched(&[20]).unwrap());
+        assert!(!outside.matched(&[15]).unwrap());
+        assert_eq!(outside.inverted(), open);
+        assert_ne!(open, SearchValue::range(10, 20, ValueType::Byte, false));
+
+        let band = SearchValue::range_float(0.0, 1.0, ValueType::Float, false).with_bounds(true, false).inverted();
+        assert!(band.matched(&1.0f32.to_le_bytes()).unwrap());
+        assert!(!band.matched(&0.0f32.to_le_bytes()).unwrap());
+        assert!(!band.matched(&f32::NAN.to_le_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_big_endian_values() {
+        let fixed = SearchValue::fixed(0x1234, ValueType::Dword).with_byte_order(ByteOrder::Big);
+        assert_eq!(fixed.bytes().unwrap(), &[0, 0, 0x12, 0x34]);
+        assert!(fixed.matched(&0x1234u32.to_be_bytes()).unwrap());
+        assert!(!fixed.matched(&0x1234u32.to_le_bytes()).unwrap());
+        // 重复设置同一字节序不会再次反转
+        assert_eq!(fixed.clone().with_byte_order(ByteOrder::Big), fixed);
+        assert_eq!(fixed.with_byte_order(ByteOrder::Little), SearchValue::fixed(0x1234, ValueType::Dword));
+
+        let float = SearchValue::fixed_float(2.5, ValueType::Double).with_byte_order(ByteOrder::Big);
+        assert!(float.matched(&2.5f64.to_be_bytes()).unwrap());
+        assert!(!float.matched(&2.5f64.to_le_bytes()).unwrap());
+
+        let range = SearchValue::range(-10, 10, ValueType::Word, false).with_byte_order(ByteOrder::Big);
+        assert!(range.matched(&(-3i16).to_be_bytes()).unwrap());
+        assert!(!range.matched(&(-3i16).to_le_bytes()).unwrap());
+        assert!(range.matched(&[0]).is_err());
+
+        let not_equal = SearchValue::not_equal(1, ValueType::Dword).with_byte_order(ByteOrder::Big);
+        assert!(!not_equal.matched(&1u32.to_be_bytes()).unwrap());
+        assert!(not_equal.matched(&1u32.to_le_bytes()).unwrap());
+
+        // 关系约束按各值的字节序解码
+        let values = vec![
+            SearchValue::range(0, 1000, ValueType::Word, false),
+            SearchValue::range(0, 1000, ValueType::Word, false),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Unordered, 8)
+            .with_byte_order(ByteOrder::Big)
+            .with_constraint(0, RelOp::Lt, 1);
+        let buffer = [0x00, 0x02, 0x01, 0x00];
+        assert!(query.constraints_hold(&buffer, &[0, 2]));
+        assert!(!query.constraints_hold(&buffer, &[2, 0]));
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_query_equality_and_hash() {
+        let query = |second: f64| {
+            SearchQuery::new(
+                vec![SearchValue::fixed(100, ValueType::Dword), SearchValue::fixed_float(second, ValueType::Float)],
+                SearchMode::Unordered,
+                64,
+            )
+        };
+
+        let a = query(1.5);
+        let b = query(1.5);
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        // 只差一个浮点值
+        let c = query(1.5000001);
+        assert_ne!(a, c);
+        assert_ne!(hash_of(&a), hash_of(&c));
+
+        // 按位比较：0.0 与 -0.0 不同，NaN 等于自身
+        assert_ne!(query(0.0), query(-0.0));
+        assert_eq!(query(f64::NAN), query(f64::NAN));
+
+        // 其它字段也参与比较
+        assert_ne!(a, a.clone().with_anchor_skip_ratio(0.5));
+        let loose = SearchValue::fixed_float_with_tolerance(1.5, ValueType::Float, FloatTolerance::RelativeUlps(4));
+        assert_ne!(a, SearchQuery::new(vec![a.values[0].clone(), loose], SearchMode::Unordered, 64));
+        assert_ne!(a, SearchQuery::new(a.values.clone(), SearchMode::Ordered, 64));
+
+        // 值列表可以去重
+        let mut values = vec![
+            SearchValue::fixed(1, ValueType::Dword),
+            SearchValue::any(ValueType::Dword),
+            SearchValue::fixed(1, ValueType::Dword),
+            SearchValue::fixed(1, ValueType::Qword),
+        ];
+        let mut seen = std::collections::HashSet::new();
+        values.retain(|value| seen.insert(value.clone()));
+        assert_eq!(values.len(), 3);
+    }
+
+    #[test]
+    fn test_float_tolerance_on_large_values() {
+        // 1e9 附近相邻 f64 相差约 1.2e-7，1e8 附近相邻 f32 相差 8
+        let double = 1.0e9f64;
+        let double_near = f64::from_bits(double.to_bits() + 2);
+        let float = 1.0e8f32;
+        let float_near = f32::from_bits(float.to_bits() + 2);
+
+        let absolute = FloatTolerance::Absolute(1e-9);
+        assert!(!absolute.matches(double, double_near, ValueType::Double));
+        assert!(!absolute.matches(float as f64, float_near as f64, ValueType::Float));
+
+        let ulps = FloatTolerance::RelativeUlps(4);
+        assert!(ulps.matches(double, double_near, ValueType::Double));
+        assert!(ulps.matches(float as f64, float_near as f64, ValueType::Float));
+
+        // 十进制输入舍入到 f32 后与内存中的值一致
+        let target = SearchValue::fixed_float(3.14159, ValueType::Float);
+        assert!(target.matched(&3.14159f32.to_le_bytes()).unwrap());
+        assert!(!target.matched(&2.71828f32.to_le_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_float_tolerance_boundaries() {
+        let step = |value: f64, ulps: u64| f64::from_bits(value.to_bits() + ulps);
+
+        let absolute = FloatTolerance::Absolute(0.5);
+        assert!(absolute.matches(10.0, 10.5, ValueType::Double));
+        assert!(absolute.matches(10.0, 9.5, ValueType::Double));
+        assert!(!absolute.matches(10.0, step(10.5, 1), ValueType::Double));
+
+        let ulps = FloatTolerance::RelativeUlps(4);
+        assert!(ulps.matches(1.0, step(1.0, 4), ValueType::Double));
+        assert!(!ulps.matches(1.0, step(1.0, 5), ValueType::Double));
+        assert_eq!(ulp_distance_f32(f32::from_bits(1), -f32::from_bits(1)), 2);
+        assert_eq!(ulp_distance_f64(f64::MAX, f64::INFINITY), 1);
+        assert!(FloatTolerance::RelativeUlps(2).matches(f32::from_bits(1) as f64, -f32::from_bits(1) as f64, ValueType::Float));
+        assert!(!FloatTolerance::EXACT.matches(1.0, step(1.0, 1), ValueType::Double));
+
+        let fraction = FloatTolerance::RelativeFraction(0.01);
+        assert!(fraction.matches(100.0, 101.0, ValueType::Double));
+        assert!(fraction.matches(-100.0, -99.0, ValueType::Double));
+        assert!(!fraction.matches(100.0, 102.0, ValueType::Double));
+
+        // 所有模式下 0.0 与 -0.0、同号无穷相等，NaN 不匹配
+        for tolerance in [FloatTolerance::Absolute(0.0), FloatTolerance::EXACT, FloatTolerance::RelativeFraction(0.0)] {
+            assert!(tolerance.matches(0.0, -0.0, ValueType::Float));
+            assert!(tolerance.matches(f64::INFINITY, f64::INFINITY, ValueType::Double));
+            assert!(!tolerance.matches(f64::NAN, f64::NAN, ValueType::Double));
+            assert!(!tolerance.matches(1.0, f64::NAN, ValueType::Double));
+        }
+    }
+}
@@ -378,6 +378,8 @@ const WUWA_BP_IOCTL_WRITE_MEMORY: Ioctl = _IOWR::<BpWriteMemoryCmd>(b'B' as u32,
 /// Each bit represents one page: 1 = successfully read, 0 = failed to read.
 pub struct PageStatusBitmap {
     bitmap: Vec<libc::c_ulong>,
+    /// Pages actually covered by the read; the bitmap itself is rounded up to whole `c_ulong`s
+    num_pages: usize,
 }
 
 impl PageStatusBitmap {
@@ -394,13 +396,14 @@ impl PageStatusBitmap {
 
         Self {
             bitmap: vec![0; num_longs],
+            num_pages,
         }
     }
 
     /// Mark all pages as successfully read
     pub fn mark_all_success(&mut self) {
-        for long in self.bitmap.iter_mut() {
-            *long = !0;
+        for page_index in 0..self.num_pages {
+            self.mark_success(page_index);
         }
     }
 
@@ -412,7 +415,7 @@ impl PageStatusBitmap {
         let long_idx = page_index / (std::mem::size_of::<libc::c_ulong>() * 8);
         let bit_idx = page_index % (std::mem::size_of::<libc::c_ulong>() * 8);
 
-        if long_idx < self.bitmap.len() {
+        if page_index < self.num_pages {
             self.bitmap[long_idx] |= 1u64 << bit_idx;
         }
     }
@@ -430,21 +433,32 @@ impl PageStatusBitmap {
         let long_idx = page_index / (std::mem::size_of::<libc::c_ulong>() * 8);
         let bit_idx = page_index % (std::mem::size_of::<libc::c_ulong>() * 8);
 
-        if long_idx >= self.bitmap.len() {
+        if page_index >= self.num_pages {
             return false;
         }
 
         (self.bitmap[long_idx] & (1u64 << bit_idx)) != 0
     }
 
-    /// Get total number of pages
+    /// Get total number of pages covered by the read
     pub fn num_pages(&self) -> usize {
-        self.bitmap.len() * std::mem::size_of::<libc::c_ulong>() * 8
+        self.num_pages
     }
 
     /// Get number of successfully read pages
+    ///
+    /// Padding bits past [`Self::num_pages`] are ignored even if the kernel set them.
     pub fn success_count(&self) -> usize {
-        self.bitmap.iter().map(|&bits| bits.count_ones() as usize).sum()
+        let bits_per_long = std::mem::size_of::<libc::c_ulong>() * 8;
+        self.bitmap
+            .iter()
+            .enumerate()
+            .map(|(long_idx, &bits)| {
+                let valid = self.num_pages.saturating_sub(long_idx * bits_per_long).min(bits_per_long);
+                let mask = if valid == bits_per_long { !0 } else { (1 << valid) - 1 };
+                (bits & mask).count_ones() as usize
+            })
+            .sum()
     }
 
     /// Get number of failed pages
@@ -452,6 +466,17 @@ impl PageStatusBitmap {
         self.num_pages() - self.success_count()
     }
 
+    /// Fraction of pages successfully read, in `0.0..=1.0`
+    ///
+    /// Returns 0.0 for an empty bitmap.
+    pub fn coverage_ratio(&self) -> f64 {
+        let num_pages = self.num_pages();
+        if num_pages == 0 {
+            return 0.0;
+        }
+        self.success_count() as f64 / num_pages as f64
+    }
+
     /// Get list of failed page indices
     pub fn failed_pages(&self) -> Vec<usize> {
         let mut result = Vec::new();
@@ -495,6 +520,13 @@ impl PageStatusBitmap {
 /// - Efficient reads via cached ioremap pages
 /// - Configurable memory type (cached/device/etc)
 /// - RAII fd management
+///
+/// # Thread safety
+///
+/// `Send + Sync`: the only state is the owned fd, which is never mutated after
+/// construction. Every read/write builds its command on the caller's stack and
+/// issues a single independent ioctl, so concurrent `&self` reads from rayon
+/// workers need no per-thread handle.
 pub struct BindProc {
     fd: OwnedFd,
 }
@@ -668,10 +700,23 @@ pub struct MemRegionsResult {
 }
 
 /// WuWa driver connection handle
+///
+/// # Thread safety
+///
+/// Same as [`BindProc`]: only an owned socket fd plus stack-local ioctl commands,
+/// so it is `Send + Sync` and safe to share behind `DRIVER_MANAGER.read()`.
 pub struct WuWaDriver {
     sock: OwnedFd,
 }
 
+// Searches read through these handles from many rayon workers at once; adding
+// shared mutable state (e.g. a cached buffer) must not silently break that.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BindProc>();
+    assert_send_sync::<WuWaDriver>();
+};
+
 impl WuWaDriver {
     /// Discover driver by probing address families
     fn driver_id() -> Result<OwnedFd, anyhow::Error> {